@@ -0,0 +1,518 @@
+use crate::import::*;
+
+/// What an outline entry or a completion stands for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolKind {
+    Function,
+    Struct,
+    Field,
+    Enum,
+    Variant,
+    Impl,
+    Method,
+    Variable,
+}
+
+/// An entry of the document outline; locations point at the item's name.
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub detail: String,
+    pub location: SourceLocation,
+    pub children: Vec<Symbol>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub label: String,
+    pub kind: SymbolKind,
+    pub detail: String,
+}
+
+/// One source file as the language server sees it: lexed, parsed with error recovery and
+/// type-checked, together with the local files it imports from.
+pub struct Analysis {
+    pub source: String,
+    pub diagnostics: Vec<Diagnostic>,
+    file: String,
+    tokens: Vec<Token>,
+    spans: Vec<SourceSpan>,
+    line_starts: Vec<usize>,
+    program: Program,
+    structs: Vec<StructDef>,
+    enums: Vec<EnumDef>,
+    impls: Vec<ImplBlock>,
+    /// Imported files with the names taken from each.
+    imports: Vec<(Module, Vec<String>)>,
+    checker: TypeChecker,
+}
+
+impl Analysis {
+    /// `open` holds the unsaved text of the files open in the editor, which wins over the disk.
+    pub fn new(path: &Path, source: String, open: &HashMap<PathBuf, String>) -> Self {
+        let file = path.display().to_string();
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let manifest = Manifest::find(dir).and_then(|manifest| Manifest::load(&manifest).ok());
+        let root = manifest.as_ref().map_or(dir.to_path_buf(), |manifest| manifest.root.clone());
+        let options = match &manifest {
+            Some(manifest) => manifest.diagnostic_options(DiagnosticFormat::Silent),
+            None => DiagnosticOptions { format: DiagnosticFormat::Silent, ..DiagnosticOptions::default() },
+        };
+
+        let mut lexer = Lexer::new(&source).with_file(file.clone());
+        let tokens = lexer.tokenize();
+        let spans = lexer.spans.clone();
+        let mut diagnostics = std::mem::take(&mut lexer.errors);
+
+        let parser = Parser::new(tokens.clone(), source.clone(), spans.clone()).with_file(file.clone());
+        let line_starts = parser.line_starts.clone();
+        let (program, structs, enums, externs, _, _, _, impls, traits, _, import_decls, syntax) = parser.parse();
+        diagnostics.extend(syntax);
+
+        let mut imports: Vec<(Module, Vec<String>)> = Vec::new();
+        let mut library_imports = Vec::new();
+        for import in import_decls {
+            let ImportDecl::FileImport { name, from } = &import else {
+                library_imports.push(import);
+                continue;
+            };
+            let target = [root.join(from), dir.join(from)].into_iter().find_map(|candidate| fs::canonicalize(candidate).ok());
+            let Some(target) = target else {
+                library_imports.push(import);
+                continue;
+            };
+            if let Some((_, names)) = imports.iter_mut().find(|(module, _)| module.path == target) {
+                names.push(name.clone());
+                continue;
+            }
+            let text = open.get(&target).cloned().or_else(|| fs::read_to_string(&target).ok());
+            if let Some(Ok((module, _))) = text.map(|text| Module::parse_source(from.clone(), &target, text)) {
+                imports.push((module, vec![name.clone()]));
+            }
+        }
+
+        let imported = |module: &Module, names: &[String]| {
+            let functions: Vec<Function> = module.program.functions.iter().filter(|f| names.contains(&f.name)).cloned().collect();
+            let structs: Vec<StructDef> = module.structs.iter().filter(|s| names.contains(&s.name)).cloned().collect();
+            let enums: Vec<EnumDef> = module.enums.iter().filter(|e| names.contains(&e.name)).cloned().collect();
+            let traits: Vec<TraitDef> = module.traits.iter().filter(|t| names.contains(&t.name)).cloned().collect();
+            let impls: Vec<ImplBlock> = module
+                .impls
+                .iter()
+                .filter(|block| block.trait_name.is_some() && names.contains(&block.struct_name))
+                .cloned()
+                .collect();
+            (functions, structs, enums, traits, impls)
+        };
+
+        let mut checker = TypeChecker::new(source.clone()).with_file(file.clone()).with_options(options.clone());
+        let mut codegen = Codegen::new(ArchConfig::x86_64(), source.clone(), file.clone());
+        codegen.diagnostics.options = options;
+        let (mut all_structs, mut all_enums, mut all_traits, mut all_impls) = (structs.clone(), enums.clone(), traits.clone(), impls.clone());
+        for (module, names) in &imports {
+            let (functions, structs, enums, traits, impls) = imported(module, names);
+            checker.register_module_imports(&functions, &structs, &enums, &traits, &impls);
+            for function in &functions {
+                let params = function.params.iter().map(|(name, ty, _)| (name.clone(), ty.clone())).collect();
+                codegen.user_functions.insert(function.name.clone(), (params, function.return_type.clone()));
+            }
+            codegen.diagnostics.add_source(module.path.display().to_string(), module.source.to_string());
+            all_structs.extend(structs);
+            all_enums.extend(enums);
+            all_traits.extend(traits);
+            all_impls.extend(impls);
+        }
+        checker.check_program(&program, &structs, &enums, &externs, &impls, &traits, &library_imports);
+
+        // Type errors in a file that does not parse are mostly noise from the recovery.
+        if !diagnostics.iter().any(Diagnostic::is_error) {
+            diagnostics.extend(checker.diagnostics().iter().cloned());
+        }
+        // Code generation reports what the type checker leaves to it, as in `vix check`.
+        if !diagnostics.iter().any(Diagnostic::is_error) {
+            codegen.user_functions.extend(checker.resolved_functions());
+            let _ = codegen.codegen_program_full(&program, &all_structs, &all_enums, &all_impls, &all_traits, &externs, &[], &[]);
+            let own = codegen.diagnostics.diagnostics().iter().filter(|d| d.context.primary_location.file == file);
+            diagnostics.extend(own.cloned());
+        }
+
+        Self {
+            source,
+            diagnostics,
+            file,
+            tokens,
+            spans,
+            line_starts,
+            program,
+            structs,
+            enums,
+            impls,
+            imports,
+            checker,
+        }
+    }
+
+    /// Where the name under the cursor is defined. Positions are 1-based and count characters.
+    pub fn definition(&self, line: usize, column: usize) -> Vec<SourceLocation> {
+        let offset = self.offset(line, column);
+        let Some(index) = self.token_at(offset) else {
+            return Vec::new();
+        };
+        let Token::Identifier(name) = &self.tokens[index] else {
+            return Vec::new();
+        };
+
+        if let Some(receiver) = self.receiver(index) {
+            let owner = self.receiver_struct(&receiver, offset);
+            let fields = self
+                .all_structs()
+                .filter(|(s, _)| owner.as_ref().is_none_or(|owner| &s.name == owner))
+                .flat_map(|(s, source)| s.fields.iter().filter(|f| &f.name == name).map(move |f| name_location(source, &f.location, &f.name)));
+            let methods = self
+                .all_impls()
+                .filter(|(block, _)| owner.as_ref().is_none_or(|owner| &block.struct_name == owner))
+                .flat_map(|(block, source)| block.methods.iter().filter(|m| &m.name == name).map(move |m| name_location(source, &m.location, &m.name)));
+            return fields.chain(methods).collect();
+        }
+
+        if let Some((_, _, location)) = self.binding(name, offset) {
+            return vec![location];
+        }
+        let functions = self.all_functions().filter(|(f, _)| &f.name == name).map(|(f, source)| name_location(source, &f.location, &f.name));
+        let structs = self.all_structs().filter(|(s, _)| &s.name == name).map(|(s, source)| name_location(source, &s.location, &s.name));
+        let enums = self.all_enums().filter(|(e, _)| &e.name == name).map(|(e, source)| name_location(source, &e.location, &e.name));
+        functions.chain(structs).chain(enums).collect()
+    }
+
+    /// Markdown for the name or expression under the cursor: an item's signature or a value's inferred type.
+    pub fn hover(&self, line: usize, column: usize) -> Option<String> {
+        let offset = self.offset(line, column);
+        let index = self.token_at(offset)?;
+
+        if let Token::Identifier(name) = &self.tokens[index] {
+            if let Some(receiver) = self.receiver(index) {
+                let owner = self.receiver_struct(&receiver, offset);
+                let field = self
+                    .all_structs()
+                    .filter(|(s, _)| owner.as_ref().is_none_or(|owner| &s.name == owner))
+                    .find_map(|(s, _)| s.fields.iter().find(|f| &f.name == name).map(|f| format!("{}.{}: {}", s.name, f.name, self.type_name(&f.ty))));
+                let method = self
+                    .all_impls()
+                    .filter(|(block, _)| owner.as_ref().is_none_or(|owner| &block.struct_name == owner))
+                    .find_map(|(block, _)| block.methods.iter().find(|m| &m.name == name).map(|m| self.method_signature(block, m)));
+                if let Some(text) = field.or(method) {
+                    return Some(code_block(&text));
+                }
+            } else if let Some((_, ty, _)) = self.binding(name, offset) {
+                return Some(code_block(&format!("{}: {}", name, self.type_name(&ty))));
+            } else if let Some((f, _)) = self.all_functions().find(|(f, _)| &f.name == name) {
+                return Some(code_block(&self.signature(&f.name, &f.params, None, &f.return_type)));
+            } else if let Some((s, _)) = self.all_structs().find(|(s, _)| &s.name == name) {
+                return Some(code_block(&self.struct_outline(s)));
+            } else if let Some((e, _)) = self.all_enums().find(|(e, _)| &e.name == name) {
+                return Some(code_block(&self.enum_outline(e)));
+            } else if let Some(signature) = self
+                .all_impls()
+                .find_map(|(block, _)| block.methods.iter().find(|m| &m.name == name).map(|m| self.method_signature(block, m)))
+            {
+                return Some(code_block(&signature));
+            }
+        }
+
+        // The innermost checked expression around the cursor.
+        self.checker
+            .expr_types()
+            .into_iter()
+            .filter(|(location, _)| location.file == self.file)
+            .filter(|(location, _)| {
+                let start = self.offset(location.line, location.column);
+                start <= offset && offset <= start + location.length
+            })
+            .min_by_key(|(location, _)| location.length)
+            .filter(|(_, ty)| *ty != Type::Any)
+            .map(|(_, ty)| code_block(&self.type_name(&ty)))
+    }
+
+    /// Functions, structs with their fields, enums with their variants and impl blocks with their methods.
+    pub fn symbols(&self) -> Vec<Symbol> {
+        let source = &self.source;
+        let mut symbols = Vec::new();
+
+        for f in &self.program.functions {
+            symbols.push(Symbol {
+                name: f.name.clone(),
+                kind: SymbolKind::Function,
+                detail: self.signature(&f.name, &f.params, None, &f.return_type),
+                location: name_location(source, &f.location, &f.name),
+                children: Vec::new(),
+            });
+        }
+        for s in &self.structs {
+            let children = s
+                .fields
+                .iter()
+                .map(|field| Symbol {
+                    name: field.name.clone(),
+                    kind: SymbolKind::Field,
+                    detail: self.type_name(&field.ty),
+                    location: name_location(source, &field.location, &field.name),
+                    children: Vec::new(),
+                })
+                .collect();
+            symbols.push(Symbol {
+                name: s.name.clone(),
+                kind: SymbolKind::Struct,
+                detail: String::new(),
+                location: name_location(source, &s.location, &s.name),
+                children,
+            });
+        }
+        for e in &self.enums {
+            let location = name_location(source, &e.location, &e.name);
+            let children = e
+                .variants
+                .iter()
+                .map(|variant| Symbol {
+                    name: variant_name(variant).to_string(),
+                    kind: SymbolKind::Variant,
+                    detail: String::new(),
+                    location: location.clone(),
+                    children: Vec::new(),
+                })
+                .collect();
+            symbols.push(Symbol { name: e.name.clone(), kind: SymbolKind::Enum, detail: String::new(), location, children });
+        }
+        for block in &self.impls {
+            let children: Vec<Symbol> = block
+                .methods
+                .iter()
+                .map(|method| Symbol {
+                    name: method.name.clone(),
+                    kind: SymbolKind::Method,
+                    detail: self.method_signature(block, method),
+                    location: name_location(source, &method.location, &method.name),
+                    children: Vec::new(),
+                })
+                .collect();
+            // An impl block has no location of its own; it is shown where its first method is.
+            let Some(location) = children.first().map(|method| method.location.clone()) else {
+                continue;
+            };
+            let name = match &block.trait_name {
+                Some(trait_name) => format!("impl {} for {}", trait_name, block.struct_name),
+                None => format!("impl {}", block.struct_name),
+            };
+            symbols.push(Symbol { name, kind: SymbolKind::Impl, detail: String::new(), location, children });
+        }
+        symbols
+    }
+
+    /// After `value.`, the fields and methods of the value's struct; after `Struct.`, its static methods.
+    /// Anywhere else, the variables in scope and the items of the file.
+    pub fn completions(&self, line: usize, column: usize) -> Vec<Completion> {
+        let offset = self.offset(line, column);
+        // The word being typed is not part of what is completed.
+        let mut last = self.spans.iter().rposition(|span| span.offset() + span.len() <= offset && !span.is_empty());
+        if let Some(index) = last.filter(|&index| matches!(self.tokens[index], Token::Identifier(_)) && self.spans[index].offset() + self.spans[index].len() == offset) {
+            last = index.checked_sub(1);
+        }
+
+        if let Some(dot) = last.filter(|&index| self.tokens[index] == Token::Dot) {
+            let Some(receiver) = self.receiver(dot + 1) else {
+                return Vec::new();
+            };
+            let is_type = self.all_structs().any(|(s, _)| s.name == receiver) && self.binding(&receiver, offset).is_none();
+            let Some(owner) = self.receiver_struct(&receiver, offset) else {
+                return Vec::new();
+            };
+
+            let mut items = Vec::new();
+            if !is_type {
+                for (s, _) in self.all_structs().filter(|(s, _)| s.name == owner) {
+                    items.extend(s.fields.iter().map(|f| Completion { label: f.name.clone(), kind: SymbolKind::Field, detail: self.type_name(&f.ty) }));
+                }
+            }
+            for (block, _) in self.all_impls().filter(|(block, _)| block.struct_name == owner) {
+                let methods = block.methods.iter().filter(|m| m.self_modifier.is_some() != is_type && m.name != block.struct_name);
+                items.extend(methods.map(|m| Completion { label: m.name.clone(), kind: SymbolKind::Method, detail: self.method_signature(block, m) }));
+            }
+            return items;
+        }
+
+        let mut items: Vec<Completion> = Vec::new();
+        for (name, ty, _) in self.bindings_before(offset) {
+            if !items.iter().any(|item| item.label == *name) {
+                items.push(Completion { label: name.clone(), kind: SymbolKind::Variable, detail: self.type_name(ty) });
+            }
+        }
+        items.extend(self.all_functions().map(|(f, _)| Completion {
+            label: f.name.clone(),
+            kind: SymbolKind::Function,
+            detail: self.signature(&f.name, &f.params, None, &f.return_type),
+        }));
+        items.extend(self.all_structs().map(|(s, _)| Completion { label: s.name.clone(), kind: SymbolKind::Struct, detail: String::new() }));
+        items.extend(self.all_enums().map(|(e, _)| Completion { label: e.name.clone(), kind: SymbolKind::Enum, detail: String::new() }));
+        items
+    }
+
+    fn offset(&self, line: usize, column: usize) -> usize {
+        let line_start = self.line_starts.get(line.saturating_sub(1)).copied().unwrap_or(self.source.chars().count());
+        line_start + column.saturating_sub(1)
+    }
+
+    /// The token under the cursor; a cursor right after a name still counts as on it.
+    fn token_at(&self, offset: usize) -> Option<usize> {
+        let covering = |index: &usize| {
+            let span = &self.spans[*index];
+            span.offset() <= offset && offset <= span.offset() + span.len() && !span.is_empty()
+        };
+        let mut found = (0..self.spans.len()).filter(covering);
+        let first = found.next()?;
+        // Between two tokens (`a.b` with the cursor before `b`), prefer the name.
+        Some(found.next().filter(|&second| matches!(self.tokens[second], Token::Identifier(_))).unwrap_or(first))
+    }
+
+    /// For the name at `index` in `receiver.name`, the receiver's spelling.
+    fn receiver(&self, index: usize) -> Option<String> {
+        if index < 2 || self.tokens[index - 1] != Token::Dot {
+            return None;
+        }
+        match &self.tokens[index - 2] {
+            Token::Identifier(name) => Some(name.clone()),
+            Token::Selfish => Some("self".to_string()),
+            _ => None,
+        }
+    }
+
+    /// The struct a receiver refers to: a variable of struct type, or the struct itself.
+    fn receiver_struct(&self, receiver: &str, offset: usize) -> Option<String> {
+        if let Some((_, ty, _)) = self.binding(receiver, offset) {
+            return struct_name(&ty);
+        }
+        self.all_structs().find(|(s, _)| s.name == receiver).map(|(s, _)| s.name.clone())
+    }
+
+    /// Variables declared before the cursor in the function or method around it, closest first.
+    fn bindings_before(&self, offset: usize) -> impl Iterator<Item = &(String, Type, SourceLocation)> {
+        let items = self.program.functions.iter().map(|f| &f.location);
+        let methods = self.impls.iter().flat_map(|block| block.methods.iter().map(|m| &m.location));
+        let scope_start = items
+            .chain(methods)
+            .map(|location| self.offset(location.line, location.column))
+            .filter(|&start| start <= offset)
+            .max()
+            .unwrap_or(0);
+
+        self.checker.bindings().iter().rev().filter(move |(_, _, location)| {
+            let start = self.offset(location.line, location.column);
+            location.file == self.file && scope_start <= start && start <= offset
+        })
+    }
+
+    /// The closest declaration of `name` before the cursor.
+    fn binding(&self, name: &str, offset: usize) -> Option<(String, Type, SourceLocation)> {
+        self.bindings_before(offset).find(|(binding, _, _)| binding == name).cloned()
+    }
+
+    fn all_functions(&self) -> impl Iterator<Item = (&Function, &str)> {
+        let own = self.program.functions.iter().map(|f| (f, self.source.as_str()));
+        let imported = self.imports.iter().flat_map(|(module, names)| {
+            module.program.functions.iter().filter(|f| names.contains(&f.name)).map(|f| (f, module.source.as_str()))
+        });
+        own.chain(imported)
+    }
+
+    fn all_structs(&self) -> impl Iterator<Item = (&StructDef, &str)> {
+        let own = self.structs.iter().map(|s| (s, self.source.as_str()));
+        let imported = self.imports.iter().flat_map(|(module, names)| {
+            module.structs.iter().filter(|s| names.contains(&s.name)).map(|s| (s, module.source.as_str()))
+        });
+        own.chain(imported)
+    }
+
+    fn all_enums(&self) -> impl Iterator<Item = (&EnumDef, &str)> {
+        let own = self.enums.iter().map(|e| (e, self.source.as_str()));
+        let imported = self.imports.iter().flat_map(|(module, names)| {
+            module.enums.iter().filter(|e| names.contains(&e.name)).map(|e| (e, module.source.as_str()))
+        });
+        own.chain(imported)
+    }
+
+    /// Impl blocks of this file and of the structs it imports.
+    fn all_impls(&self) -> impl Iterator<Item = (&ImplBlock, &str)> {
+        let own = self.impls.iter().map(|block| (block, self.source.as_str()));
+        let imported = self.imports.iter().flat_map(|(module, names)| {
+            module.impls.iter().filter(|block| names.contains(&block.struct_name)).map(|block| (block, module.source.as_str()))
+        });
+        own.chain(imported)
+    }
+
+    fn type_name(&self, ty: &Type) -> String {
+        self.checker.type_to_string(ty)
+    }
+
+    fn signature(&self, name: &str, params: &[(String, Type, ParamModifier)], self_modifier: Option<&SelfModifier>, return_type: &Type) -> String {
+        let receiver = self_modifier.map(|modifier| match modifier {
+            SelfModifier::Immutable => "self".to_string(),
+            SelfModifier::Mutable => "mut self".to_string(),
+            SelfModifier::Borrow | SelfModifier::Reference => "&self".to_string(),
+        });
+        let params: Vec<String> = receiver
+            .into_iter()
+            .chain(params.iter().map(|(name, ty, _)| format!("{}: {}", name, self.type_name(ty))))
+            .collect();
+        format!("func {}({}): {}", name, params.join(", "), self.type_name(return_type))
+    }
+
+    fn method_signature(&self, block: &ImplBlock, method: &ImplMethod) -> String {
+        let name = format!("{}.{}", block.struct_name, method.name);
+        self.signature(&name, &method.params, method.self_modifier.as_ref(), &method.return_type)
+    }
+
+    fn struct_outline(&self, s: &StructDef) -> String {
+        let fields: Vec<String> = s.fields.iter().map(|f| format!("    {} = {}", f.name, self.type_name(&f.ty))).collect();
+        format!("struct {}:\n{}\nend", s.name, fields.join("\n"))
+    }
+
+    fn enum_outline(&self, e: &EnumDef) -> String {
+        let variants: Vec<&str> = e.variants.iter().map(variant_name).collect();
+        format!("enum {}:\n    {}\nend", e.name, variants.join(", "))
+    }
+}
+
+fn variant_name(variant: &EnumVariant) -> &str {
+    match variant {
+        EnumVariant::Simple(name) | EnumVariant::Tuple(name, _) | EnumVariant::Struct(name, _) => name,
+    }
+}
+
+fn struct_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Struct { name } => Some(name.clone()),
+        Type::Ref(inner) | Type::MutRef(inner) | Type::Owned(inner) | Type::Ptr(inner) | Type::RawPtr(inner) => struct_name(inner),
+        _ => None,
+    }
+}
+
+fn code_block(text: &str) -> String {
+    format!("```vix\n{}\n```", text)
+}
+
+/// Items are located at their keyword (`func`, `struct`, ...); this narrows the location to the name
+/// that follows it on the same line.
+fn name_location(source: &str, location: &SourceLocation, name: &str) -> SourceLocation {
+    let Some(line) = source.lines().nth(location.line.saturating_sub(1)) else {
+        return location.clone();
+    };
+    let skip = location.column.saturating_sub(1);
+    let rest: String = line.chars().skip(skip).collect();
+    match rest.find(name) {
+        Some(byte) => SourceLocation {
+            column: location.column + rest[..byte].chars().count(),
+            length: name.chars().count(),
+            ..location.clone()
+        },
+        None => location.clone(),
+    }
+}
@@ -0,0 +1,143 @@
+use crate::import::*;
+use sha2::{Digest, Sha256};
+
+/// Content hash of everything a build output depends on.
+pub struct Fingerprint {
+    hasher: Sha256,
+}
+
+impl Fingerprint {
+    pub fn new() -> Self {
+        let mut fingerprint = Self { hasher: Sha256::new() };
+        fingerprint.add_str("vix", env!("CARGO_PKG_VERSION"));
+        fingerprint
+    }
+
+    pub fn add_str(&mut self, label: &str, value: &str) {
+        // Length prefixes keep ("ab", "c") and ("a", "bc") from hashing the same.
+        self.hasher.update((label.len() as u64).to_le_bytes());
+        self.hasher.update(label.as_bytes());
+        self.hasher.update((value.len() as u64).to_le_bytes());
+        self.hasher.update(value.as_bytes());
+    }
+
+    /// Missing files hash differently from empty ones, so deleting an input invalidates too.
+    pub fn add_file(&mut self, path: &Path) {
+        match fs::read(path) {
+            Ok(bytes) => {
+                self.add_str("file", &path.to_string_lossy());
+                self.hasher.update((bytes.len() as u64).to_le_bytes());
+                self.hasher.update(&bytes);
+            }
+            Err(_) => self.add_str("missing", &path.to_string_lossy()),
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+impl Default for Fingerprint {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Per-profile/target cache living next to the build outputs.
+pub struct BuildCache {
+    dir: PathBuf,
+}
+
+impl BuildCache {
+    pub fn new(layout: &BuildLayout) -> Self {
+        Self { dir: layout.dir.join("cache") }
+    }
+
+    fn record_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.fingerprint", name))
+    }
+
+    /// Extra inputs (library sources, package.json) recorded by the last successful build.
+    pub fn recorded_inputs(&self, name: &str) -> Vec<PathBuf> {
+        fs::read_to_string(self.record_path(name))
+            .map(|content| content.lines().skip(1).filter(|l| !l.is_empty()).map(PathBuf::from).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn is_fresh(&self, name: &str, key: &str, output: &Path) -> bool {
+        if !output.exists() {
+            return false;
+        }
+        fs::read_to_string(self.record_path(name))
+            .map(|content| content.lines().next() == Some(key))
+            .unwrap_or(false)
+    }
+
+    fn warnings_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.warnings", name))
+    }
+
+    /// Warnings the last successful build printed, rendered, so an up-to-date build repeats them.
+    pub fn recorded_warnings(&self, name: &str) -> Vec<String> {
+        read_warnings(&self.warnings_path(name))
+    }
+
+    pub fn record(&self, name: &str, key: &str, inputs: &[PathBuf], warnings: &[String]) -> std::io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let mut content = format!("{}\n", key);
+        for input in inputs {
+            content.push_str(&input.to_string_lossy());
+            content.push('\n');
+        }
+        write_warnings(&self.warnings_path(name), warnings)?;
+        fs::write(self.record_path(name), content)
+    }
+
+    /// The generated C of a module's translation unit, by the module's fingerprint.
+    pub fn unit_source(&self, key: &str) -> PathBuf {
+        self.dir.join("units").join(format!("{}.c", key))
+    }
+
+    pub fn unit_object(&self, key: &str, target: TargetOS) -> PathBuf {
+        self.dir.join("units").join(format!("{}{}", key, target.object_extension()))
+    }
+
+    /// Warnings from generating a unit, repeated when its cached C is reused.
+    pub fn unit_warnings(&self, key: &str) -> Vec<String> {
+        read_warnings(&self.dir.join("units").join(format!("{}.warnings", key)))
+    }
+
+    pub fn record_unit_warnings(&self, key: &str, warnings: &[String]) -> std::io::Result<()> {
+        fs::create_dir_all(self.dir.join("units"))?;
+        write_warnings(&self.dir.join("units").join(format!("{}.warnings", key)), warnings)
+    }
+
+    /// Where the object for a given C translation unit hash is kept.
+    pub fn object(&self, key: &str, target: TargetOS) -> PathBuf {
+        self.dir.join("objects").join(format!("{}{}", key, target.object_extension()))
+    }
+
+    /// Moves a freshly built file into the cache without exposing a partial write.
+    pub fn store(&self, built: &Path, cached: &Path) -> std::io::Result<()> {
+        if let Some(parent) = cached.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(built, cached)
+    }
+}
+
+fn read_warnings(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|content| content.split('\0').filter(|w| !w.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn write_warnings(path: &Path, warnings: &[String]) -> std::io::Result<()> {
+    if warnings.is_empty() {
+        let _ = fs::remove_file(path);
+        Ok(())
+    } else {
+        fs::write(path, warnings.join("\0"))
+    }
+}
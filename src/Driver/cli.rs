@@ -0,0 +1,186 @@
+use crate::import::*;
+use clap::{Args, Parser as ClapParser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+
+#[derive(Debug, ClapParser)]
+#[command(name = "vix", bin_name = "vix", version, about = "Vix Compiler", propagate_version = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
+    /// Print extra progress output (repeat for more detail)
+    #[arg(short, long, action = clap::ArgAction::Count, global = true, alias = "debug")]
+    pub verbose: u8,
+
+    /// Only print errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Path to Vix.toml (defaults to searching the current directory and its parents)
+    #[arg(long = "manifest-path", global = true)]
+    pub manifest_path: Option<PathBuf>,
+
+    /// How to print compiler diagnostics
+    #[arg(long = "message-format", value_enum, global = true, default_value = "human")]
+    pub message_format: MessageFormat,
+
+    /// Silence a lint or warning code (e.g. `-A unused_variables`)
+    #[arg(short = 'A', long = "allow", value_name = "LINT", global = true)]
+    pub allow: Vec<String>,
+
+    /// Report a lint or warning code as a warning, overriding Vix.toml
+    #[arg(short = 'W', long = "warn", value_name = "LINT", global = true)]
+    pub warn: Vec<String>,
+
+    /// Report a lint or warning code as an error
+    #[arg(short = 'D', long = "deny", value_name = "LINT", global = true)]
+    pub deny: Vec<String>,
+
+    /// Fail the build on any warning that is not allowed
+    #[arg(long = "deny-warnings", global = true)]
+    pub deny_warnings: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Compile and run the program
+    Run(BuildArgs),
+    /// Compile the program without running
+    Build(BuildArgs),
+    /// Parse and type-check without producing any output
+    Check(CheckArgs),
+    /// Build the `test func`s into a test harness and run them
+    Test(TestArgs),
+    /// Apply the machine-applicable suggestions from compiler diagnostics to the sources
+    Fix(CheckArgs),
+    /// Normalize the indentation and spacing of sources
+    Fmt(FmtArgs),
+    /// Remove the target directory
+    Clean(CleanArgs),
+    /// Show the Vix installation directory
+    Path,
+    /// Run the language server over stdin/stdout
+    Lsp,
+    /// Explain a diagnostic code or lint, e.g. `vix explain E0308` or `vix explain unused_variables`
+    Explain {
+        code: String,
+    },
+    /// Generate a shell completion script
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+}
+
+#[derive(Debug, Args, Clone, Default)]
+pub struct BuildArgs {
+    /// Target operating system (windows, linux, macos, freebsd)
+    #[arg(long, value_parser = parse_target)]
+    pub target: Option<TargetOS>,
+
+    /// Output executable name (overrides Vix.toml)
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// Build with the release profile (optimized, no debug info or runtime checks)
+    #[arg(long)]
+    pub release: bool,
+
+    /// Optimization level (overrides the profile)
+    #[arg(short = 'O', long = "opt-level", value_enum)]
+    pub opt_level: Option<OptLevel>,
+
+    /// Directory containing the program sources (overrides Vix.toml)
+    #[arg(long = "src-dir")]
+    pub src_dir: Option<PathBuf>,
+
+    /// Directory for build artifacts (default: target/ in the project root)
+    #[arg(long = "target-dir")]
+    pub target_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct CleanArgs {
+    /// Directory for build artifacts (default: target/ in the project root)
+    #[arg(long = "target-dir")]
+    pub target_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct CheckArgs {
+    /// Directory containing the program sources (overrides Vix.toml)
+    #[arg(long = "src-dir")]
+    pub src_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct TestArgs {
+    /// Only run tests whose name contains this text
+    pub filter: Option<String>,
+
+    /// Build with the release profile (optimized, no debug info or runtime checks)
+    #[arg(long)]
+    pub release: bool,
+
+    /// Directory containing the program sources (overrides Vix.toml)
+    #[arg(long = "src-dir")]
+    pub src_dir: Option<PathBuf>,
+
+    /// Directory for build artifacts (default: target/ in the project root)
+    #[arg(long = "target-dir")]
+    pub target_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct FmtArgs {
+    /// Files or directories to format (defaults to the project's sources)
+    pub paths: Vec<PathBuf>,
+
+    /// Only report files that are not formatted, exiting with 1 if there are any
+    #[arg(long)]
+    pub check: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum OptLevel {
+    #[value(name = "0")]
+    O0,
+    #[value(name = "1")]
+    O1,
+    #[value(name = "2")]
+    O2,
+    #[value(name = "3")]
+    O3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum MessageFormat {
+    Human,
+    Json,
+}
+
+impl From<MessageFormat> for DiagnosticFormat {
+    fn from(format: MessageFormat) -> Self {
+        match format {
+            MessageFormat::Human => DiagnosticFormat::Human,
+            MessageFormat::Json => DiagnosticFormat::Json,
+        }
+    }
+}
+
+impl From<OptLevel> for OptimizationLevel {
+    fn from(level: OptLevel) -> Self {
+        match level {
+            OptLevel::O0 => OptimizationLevel::None,
+            OptLevel::O1 => OptimizationLevel::O1,
+            OptLevel::O2 => OptimizationLevel::O2,
+            OptLevel::O3 => OptimizationLevel::O3,
+        }
+    }
+}
+
+fn parse_target(s: &str) -> std::result::Result<TargetOS, String> {
+    TargetOS::from_string(s).ok_or_else(|| {
+        format!("unknown target OS '{}'. Valid options: windows, linux, macos, freebsd", s)
+    })
+}
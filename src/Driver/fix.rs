@@ -0,0 +1,42 @@
+use crate::import::*;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// Applies every machine-applicable fix carried by `diagnostics` to the files they point into.
+/// Overlapping edits are skipped; the next `vix fix` run picks them up once the first one is applied.
+/// Returns the number of edits applied per file.
+pub fn apply_fixes<'a>(diagnostics: impl IntoIterator<Item = &'a Diagnostic>) -> Result<Vec<(String, usize)>, String> {
+    let mut by_file: BTreeMap<String, Vec<&Fix>> = BTreeMap::new();
+    for fix in diagnostics.into_iter().flat_map(|diagnostic| &diagnostic.fixes).filter(|fix| fix.applicable) {
+        by_file.entry(fix.location.file.clone()).or_default().push(fix);
+    }
+
+    let mut applied = Vec::new();
+    for (file, fixes) in by_file {
+        let mut source = fs::read_to_string(&file).map_err(|e| format!("Could not read {}: {}", file, e))?;
+
+        let mut edits: Vec<(Range<usize>, &str)> = fixes
+            .iter()
+            .filter_map(|fix| location_to_range(&source, &fix.location).map(|range| (range, fix.replacement.as_str())))
+            .collect();
+        edits.sort_by_key(|(range, _)| (range.start, range.end));
+        edits.dedup();
+
+        let mut count = 0;
+        let mut next_start = usize::MAX;
+        for (range, replacement) in edits.into_iter().rev() {
+            if range.end > next_start {
+                continue;
+            }
+            next_start = range.start;
+            source.replace_range(range, replacement);
+            count += 1;
+        }
+
+        if count > 0 {
+            fs::write(&file, source).map_err(|e| format!("Could not write {}: {}", file, e))?;
+            applied.push((file, count));
+        }
+    }
+    Ok(applied)
+}
@@ -0,0 +1,56 @@
+use crate::import::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub const DEFAULT_TARGET_DIR: &str = "target";
+
+static INTERMEDIATE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Where a build writes its files: `<target-dir>/<profile>/<target-os>/`.
+#[derive(Debug, Clone)]
+pub struct BuildLayout {
+    pub target_dir: PathBuf,
+    pub dir: PathBuf,
+    pub target: TargetOS,
+}
+
+impl BuildLayout {
+    pub fn new(target_dir: &Path, profile: &str, target: TargetOS) -> Self {
+        Self {
+            target_dir: target_dir.to_path_buf(),
+            dir: target_dir.join(profile).join(target.dir_name()),
+            target,
+        }
+    }
+
+    pub fn create(&self) -> std::io::Result<()> {
+        fs::create_dir_all(self.intermediate_dir())
+    }
+
+    pub fn intermediate_dir(&self) -> PathBuf {
+        self.dir.join("build")
+    }
+
+    /// A file name no other build running at the same time will pick.
+    pub fn intermediate(&self, stem: &str, extension: &str) -> PathBuf {
+        let n = INTERMEDIATE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let name = format!("{}-{}-{}.{}", stem, std::process::id(), n, extension.trim_start_matches('.'));
+        self.intermediate_dir().join(name)
+    }
+
+    pub fn object(&self, stem: &str) -> PathBuf {
+        self.intermediate(stem, self.target.object_extension())
+    }
+
+    pub fn executable(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}{}", name, self.target.executable_extension()))
+    }
+
+    /// Removes the whole target directory. Returns false if there was nothing to remove.
+    pub fn clean(target_dir: &Path) -> std::io::Result<bool> {
+        if !target_dir.exists() {
+            return Ok(false);
+        }
+        fs::remove_dir_all(target_dir)?;
+        Ok(true)
+    }
+}
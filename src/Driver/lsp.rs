@@ -0,0 +1,296 @@
+use crate::import::*;
+use crate::Driver::analysis::{Analysis, SymbolKind};
+use lsp_server::{Connection, ErrorCode as RpcError, Message, Notification, Request, Response};
+use lsp_types as lsp;
+use lsp_types::notification::{DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument, Notification as _, PublishDiagnostics};
+use lsp_types::request::{Completion, DocumentSymbolRequest, GotoDefinition, HoverRequest, Request as _};
+use serde::de::DeserializeOwned;
+use std::str::FromStr;
+
+/// `vix lsp`: a language server speaking JSON-RPC over stdin and stdout.
+pub fn run_server() -> Result<(), String> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = lsp::ServerCapabilities {
+        text_document_sync: Some(lsp::TextDocumentSyncCapability::Options(lsp::TextDocumentSyncOptions {
+            open_close: Some(true),
+            change: Some(lsp::TextDocumentSyncKind::FULL),
+            save: Some(lsp::TextDocumentSyncSaveOptions::SaveOptions(lsp::SaveOptions { include_text: Some(true) })),
+            ..Default::default()
+        })),
+        definition_provider: Some(lsp::OneOf::Left(true)),
+        hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+        document_symbol_provider: Some(lsp::OneOf::Left(true)),
+        completion_provider: Some(lsp::CompletionOptions {
+            trigger_characters: Some(vec![".".to_string()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let capabilities = serde_json::to_value(capabilities).map_err(|e| e.to_string())?;
+    connection.initialize(capabilities).map_err(|e| e.to_string())?;
+
+    Server::default().run(&connection)?;
+    drop(connection);
+    io_threads.join().map_err(|e| e.to_string())
+}
+
+#[derive(Default)]
+struct Server {
+    /// Text of the documents open in the editor, saved or not.
+    open: HashMap<PathBuf, String>,
+    analyses: HashMap<PathBuf, Analysis>,
+}
+
+impl Server {
+    fn run(&mut self, connection: &Connection) -> Result<(), String> {
+        for message in &connection.receiver {
+            let replies = match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request).map_err(|e| e.to_string())? {
+                        return Ok(());
+                    }
+                    vec![Message::Response(self.handle_request(request))]
+                }
+                Message::Notification(notification) => self.handle_notification(notification),
+                Message::Response(_) => Vec::new(),
+            };
+            for reply in replies {
+                connection.sender.send(reply).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            GotoDefinition::METHOD => respond(request, |params: lsp::GotoDefinitionParams| self.definition(params.text_document_position_params)),
+            HoverRequest::METHOD => respond(request, |params: lsp::HoverParams| self.hover(params.text_document_position_params)),
+            DocumentSymbolRequest::METHOD => respond(request, |params: lsp::DocumentSymbolParams| self.symbols(params.text_document)),
+            Completion::METHOD => respond(request, |params: lsp::CompletionParams| self.completions(params.text_document_position)),
+            method => Response::new_err(request.id, RpcError::MethodNotFound as i32, format!("Unsupported request '{}'", method)),
+        }
+    }
+
+    /// Every change re-checks all open documents, since they may import each other.
+    fn handle_notification(&mut self, notification: Notification) -> Vec<Message> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                if let Ok(params) = serde_json::from_value::<lsp::DidOpenTextDocumentParams>(notification.params) {
+                    self.open.insert(uri_to_path(&params.text_document.uri), params.text_document.text);
+                }
+            }
+            DidChangeTextDocument::METHOD => {
+                if let Ok(params) = serde_json::from_value::<lsp::DidChangeTextDocumentParams>(notification.params)
+                    && let Some(change) = params.content_changes.into_iter().last()
+                {
+                    self.open.insert(uri_to_path(&params.text_document.uri), change.text);
+                }
+            }
+            DidSaveTextDocument::METHOD => {
+                if let Ok(params) = serde_json::from_value::<lsp::DidSaveTextDocumentParams>(notification.params)
+                    && let Some(text) = params.text
+                {
+                    self.open.insert(uri_to_path(&params.text_document.uri), text);
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                if let Ok(params) = serde_json::from_value::<lsp::DidCloseTextDocumentParams>(notification.params) {
+                    let path = uri_to_path(&params.text_document.uri);
+                    self.open.remove(&path);
+                    self.analyses.remove(&path);
+                    return vec![publish(params.text_document.uri, Vec::new())];
+                }
+            }
+            _ => return Vec::new(),
+        }
+
+        let mut messages = Vec::new();
+        for (path, text) in &self.open {
+            let analysis = Analysis::new(path, text.clone(), &self.open);
+            let diagnostics = analysis
+                .diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.context.primary_location.file == path.display().to_string())
+                .map(|diagnostic| to_lsp_diagnostic(&analysis.source, diagnostic))
+                .collect();
+            messages.push(publish(path_to_uri(path), diagnostics));
+            self.analyses.insert(path.clone(), analysis);
+        }
+        messages
+    }
+
+    fn analysis(&self, document: &lsp::TextDocumentIdentifier) -> Option<&Analysis> {
+        self.analyses.get(&uri_to_path(&document.uri))
+    }
+
+    fn definition(&self, params: lsp::TextDocumentPositionParams) -> Option<lsp::GotoDefinitionResponse> {
+        let analysis = self.analysis(&params.text_document)?;
+        let (line, column) = from_position(&analysis.source, params.position);
+        let locations: Vec<lsp::Location> = analysis
+            .definition(line, column)
+            .iter()
+            .map(|location| {
+                let path = PathBuf::from(&location.file);
+                let source = self.open.get(&path).cloned().or_else(|| fs::read_to_string(&path).ok()).unwrap_or_default();
+                lsp::Location::new(path_to_uri(&path), to_range(&source, location))
+            })
+            .collect();
+        (!locations.is_empty()).then_some(lsp::GotoDefinitionResponse::Array(locations))
+    }
+
+    fn hover(&self, params: lsp::TextDocumentPositionParams) -> Option<lsp::Hover> {
+        let analysis = self.analysis(&params.text_document)?;
+        let (line, column) = from_position(&analysis.source, params.position);
+        analysis.hover(line, column).map(|value| lsp::Hover {
+            contents: lsp::HoverContents::Markup(lsp::MarkupContent { kind: lsp::MarkupKind::Markdown, value }),
+            range: None,
+        })
+    }
+
+    fn symbols(&self, document: lsp::TextDocumentIdentifier) -> Option<lsp::DocumentSymbolResponse> {
+        let analysis = self.analysis(&document)?;
+        let symbols = analysis.symbols().iter().map(|symbol| to_lsp_symbol(&analysis.source, symbol)).collect();
+        Some(lsp::DocumentSymbolResponse::Nested(symbols))
+    }
+
+    fn completions(&self, params: lsp::TextDocumentPositionParams) -> Option<lsp::CompletionResponse> {
+        let analysis = self.analysis(&params.text_document)?;
+        let (line, column) = from_position(&analysis.source, params.position);
+        let items = analysis
+            .completions(line, column)
+            .into_iter()
+            .map(|completion| lsp::CompletionItem {
+                label: completion.label,
+                kind: Some(completion_kind(completion.kind)),
+                detail: (!completion.detail.is_empty()).then_some(completion.detail),
+                ..Default::default()
+            })
+            .collect();
+        Some(lsp::CompletionResponse::Array(items))
+    }
+}
+
+fn respond<P: DeserializeOwned, R: Serialize>(request: Request, handler: impl FnOnce(P) -> R) -> Response {
+    match serde_json::from_value::<P>(request.params) {
+        Ok(params) => Response::new_ok(request.id, handler(params)),
+        Err(e) => Response::new_err(request.id, RpcError::InvalidParams as i32, e.to_string()),
+    }
+}
+
+fn publish(uri: lsp::Uri, diagnostics: Vec<lsp::Diagnostic>) -> Message {
+    let params = lsp::PublishDiagnosticsParams { uri, diagnostics, version: None };
+    Message::Notification(Notification::new(PublishDiagnostics::METHOD.to_string(), params))
+}
+
+fn to_lsp_diagnostic(source: &str, diagnostic: &Diagnostic) -> lsp::Diagnostic {
+    let mut message = diagnostic.message.clone();
+    if let Some(help) = &diagnostic.context.help_message {
+        message.push_str(&format!("\nhelp: {}", help));
+    }
+    let related = diagnostic
+        .context
+        .secondary_locations
+        .iter()
+        .filter(|(location, _)| location.file == diagnostic.context.primary_location.file)
+        .map(|(location, label)| lsp::DiagnosticRelatedInformation {
+            location: lsp::Location::new(path_to_uri(Path::new(&location.file)), to_range(source, location)),
+            message: label.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    lsp::Diagnostic {
+        range: to_range(source, &diagnostic.context.primary_location),
+        severity: Some(if diagnostic.is_error() { lsp::DiagnosticSeverity::ERROR } else { lsp::DiagnosticSeverity::WARNING }),
+        code: Some(lsp::NumberOrString::String(diagnostic.code.clone())),
+        source: Some("vix".to_string()),
+        message,
+        related_information: (!related.is_empty()).then_some(related),
+        ..Default::default()
+    }
+}
+
+#[allow(deprecated)]
+fn to_lsp_symbol(source: &str, symbol: &crate::Driver::analysis::Symbol) -> lsp::DocumentSymbol {
+    let range = to_range(source, &symbol.location);
+    lsp::DocumentSymbol {
+        name: symbol.name.clone(),
+        detail: (!symbol.detail.is_empty()).then(|| symbol.detail.clone()),
+        kind: match symbol.kind {
+            SymbolKind::Function => lsp::SymbolKind::FUNCTION,
+            SymbolKind::Struct => lsp::SymbolKind::STRUCT,
+            SymbolKind::Field => lsp::SymbolKind::FIELD,
+            SymbolKind::Enum => lsp::SymbolKind::ENUM,
+            SymbolKind::Variant => lsp::SymbolKind::ENUM_MEMBER,
+            SymbolKind::Impl => lsp::SymbolKind::OBJECT,
+            SymbolKind::Method => lsp::SymbolKind::METHOD,
+            SymbolKind::Variable => lsp::SymbolKind::VARIABLE,
+        },
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range: range,
+        children: (!symbol.children.is_empty()).then(|| symbol.children.iter().map(|child| to_lsp_symbol(source, child)).collect()),
+    }
+}
+
+fn completion_kind(kind: SymbolKind) -> lsp::CompletionItemKind {
+    match kind {
+        SymbolKind::Function => lsp::CompletionItemKind::FUNCTION,
+        SymbolKind::Struct => lsp::CompletionItemKind::STRUCT,
+        SymbolKind::Field => lsp::CompletionItemKind::FIELD,
+        SymbolKind::Enum => lsp::CompletionItemKind::ENUM,
+        SymbolKind::Variant => lsp::CompletionItemKind::ENUM_MEMBER,
+        SymbolKind::Impl => lsp::CompletionItemKind::MODULE,
+        SymbolKind::Method => lsp::CompletionItemKind::METHOD,
+        SymbolKind::Variable => lsp::CompletionItemKind::VARIABLE,
+    }
+}
+
+/// LSP positions are 0-based and count UTF-16 units; Vix locations are 1-based and count characters.
+fn to_position(source: &str, line: usize, column: usize) -> lsp::Position {
+    let text = source.lines().nth(line.saturating_sub(1)).unwrap_or("");
+    let character: usize = text.chars().take(column.saturating_sub(1)).map(char::len_utf16).sum();
+    lsp::Position::new(line.saturating_sub(1) as u32, character as u32)
+}
+
+fn from_position(source: &str, position: lsp::Position) -> (usize, usize) {
+    let text = source.lines().nth(position.line as usize).unwrap_or("");
+    let mut units = 0;
+    let column = text
+        .chars()
+        .take_while(|c| {
+            units += c.len_utf16();
+            units <= position.character as usize
+        })
+        .count();
+    (position.line as usize + 1, column + 1)
+}
+
+/// Ranges end on the line they start on, which is all a diagnostic or a name needs.
+fn to_range(source: &str, location: &SourceLocation) -> lsp::Range {
+    if location.line == 0 {
+        return lsp::Range::default();
+    }
+    let start = to_position(source, location.line, location.column);
+    let end = to_position(source, location.line, location.column + location.length);
+    lsp::Range::new(start, end)
+}
+
+fn uri_to_path(uri: &lsp::Uri) -> PathBuf {
+    let path = PathBuf::from(uri.path().as_estr().decode().into_string_lossy().as_ref());
+    // Imports are resolved to canonical paths, so open documents are keyed the same way.
+    fs::canonicalize(&path).unwrap_or(path)
+}
+
+fn path_to_uri(path: &Path) -> lsp::Uri {
+    let mut encoded = String::from("file://");
+    for byte in path.display().to_string().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    lsp::Uri::from_str(&encoded).unwrap_or_else(|_| lsp::Uri::from_str("file:///").expect("valid URI"))
+}
@@ -0,0 +1,315 @@
+use crate::import::*;
+
+pub const MANIFEST_FILE: &str = "Vix.toml";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Manifest {
+    #[serde(rename = "Information")]
+    pub information: ManifestInformation,
+    #[serde(default)]
+    pub build: ManifestBuild,
+    #[serde(default, rename = "package")]
+    pub dependencies: HashMap<String, String>,
+    #[serde(default)]
+    pub profile: HashMap<String, ManifestProfile>,
+    #[serde(default)]
+    pub diagnostics: ManifestDiagnostics,
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestInformation {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Version")]
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ManifestBuild {
+    pub entry: Option<PathBuf>,
+    pub sources: Vec<PathBuf>,
+    pub extensions: Vec<String>,
+    pub output: Option<String>,
+    pub target: Option<String>,
+    #[serde(rename = "target-dir")]
+    pub target_dir: Option<PathBuf>,
+}
+
+impl Default for ManifestBuild {
+    fn default() -> Self {
+        Self {
+            entry: None,
+            sources: vec![PathBuf::from("src")],
+            extensions: vec!["x".to_string(), "vix".to_string()],
+            output: None,
+            target: None,
+            target_dir: None,
+        }
+    }
+}
+
+/// `[profile.dev]` / `[profile.release]` overrides on top of the built-in defaults.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ManifestProfile {
+    #[serde(rename = "opt-level")]
+    pub opt_level: Option<u8>,
+    pub debug: Option<bool>,
+    #[serde(rename = "runtime-checks")]
+    pub runtime_checks: Option<bool>,
+}
+
+/// `[diagnostics]`: warnings, by code or lint name, to silence (`allow`), to report when they are
+/// off by default (`warn`) or to fail the build on (`deny`).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ManifestDiagnostics {
+    pub allow: Vec<String>,
+    pub warn: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+    FileReadError(PathBuf, String),
+    ParseError(PathBuf, String),
+    InvalidTarget(String),
+    MissingField(&'static str),
+    SourceRootNotFound(PathBuf),
+    EntryNotFound(PathBuf),
+    UnknownProfile(String),
+    InvalidOptLevel(String, u8),
+    UnknownDiagnosticCode(String),
+    CannotAllowError(String),
+}
+
+impl std::fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ManifestError::FileReadError(path, msg) => write!(f, "Could not read {}: {}", path.display(), msg),
+            ManifestError::ParseError(path, msg) => write!(f, "Invalid manifest {}: {}", path.display(), msg),
+            ManifestError::InvalidTarget(target) => write!(f, "Unknown target OS '{}'. Valid options: windows, linux, macos, freebsd", target),
+            ManifestError::MissingField(field) => write!(f, "Missing field: {}", field),
+            ManifestError::SourceRootNotFound(path) => write!(f, "Source directory not found: {}", path.display()),
+            ManifestError::EntryNotFound(path) => write!(f, "Entry file not found: {}", path.display()),
+            ManifestError::UnknownProfile(name) => write!(f, "Unknown profile '{}'. Valid options: dev, release", name),
+            ManifestError::InvalidOptLevel(profile, level) => {
+                write!(f, "Invalid opt-level {} in [profile.{}]. Valid options: 0, 1, 2, 3", level, profile)
+            }
+            ManifestError::UnknownDiagnosticCode(code) => {
+                let names: Vec<_> = codes::LINTS.iter().map(|lint| lint.name).collect();
+                write!(
+                    f,
+                    "Unknown diagnostic code or lint '{}' in [diagnostics]. Run `vix explain <CODE>` to look codes up; lints are {}",
+                    code,
+                    names.join(", ")
+                )
+            }
+            ManifestError::CannotAllowError(code) => {
+                write!(f, "{} is an error and cannot be allowed, warned or denied in [diagnostics]; only warnings can", code)
+            }
+        }
+    }
+}
+
+impl Manifest {
+    /// Walks up from `start` until a directory containing `Vix.toml` is found.
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(MANIFEST_FILE))
+            .find(|candidate| candidate.is_file())
+    }
+
+    pub fn load(path: &Path) -> Result<Manifest, ManifestError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| ManifestError::FileReadError(path.to_path_buf(), e.to_string()))?;
+        let mut manifest: Manifest = toml::from_str(&content)
+            .map_err(|e| ManifestError::ParseError(path.to_path_buf(), e.to_string()))?;
+
+        if manifest.information.name.trim().is_empty() {
+            return Err(ManifestError::MissingField("Name"));
+        }
+        if manifest.information.version.trim().is_empty() {
+            return Err(ManifestError::MissingField("Version"));
+        }
+        if let Some(target) = &manifest.build.target
+            && TargetOS::from_string(target).is_none()
+        {
+            return Err(ManifestError::InvalidTarget(target.clone()));
+        }
+        for (name, profile) in &manifest.profile {
+            if Profile::by_name(name).is_none() {
+                return Err(ManifestError::UnknownProfile(name.clone()));
+            }
+            if let Some(level) = profile.opt_level.filter(|level| *level > 3) {
+                return Err(ManifestError::InvalidOptLevel(name.clone(), level));
+            }
+        }
+        let diagnostics = &manifest.diagnostics;
+        for code in diagnostics.allow.iter().chain(&diagnostics.warn).chain(&diagnostics.deny) {
+            match codes::lookup(code) {
+                None => return Err(ManifestError::UnknownDiagnosticCode(code.clone())),
+                Some(entry) if !entry.is_warning() => return Err(ManifestError::CannotAllowError(entry.code.to_string())),
+                Some(_) => {}
+            }
+        }
+
+        let root = path.parent().unwrap_or(Path::new("."));
+        manifest.root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        Ok(manifest)
+    }
+
+    /// Projects without a `Vix.toml` keep the old layout: every `.x` file in `src_dir`.
+    pub fn legacy(src_dir: PathBuf) -> Manifest {
+        Manifest {
+            information: ManifestInformation {
+                name: "program".to_string(),
+                version: "0.0.0".to_string(),
+            },
+            build: ManifestBuild {
+                sources: vec![src_dir],
+                extensions: vec!["x".to_string()],
+                ..ManifestBuild::default()
+            },
+            dependencies: HashMap::new(),
+            profile: HashMap::new(),
+            diagnostics: ManifestDiagnostics::default(),
+            root: env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        }
+    }
+
+    /// The built-in `dev` or `release` profile with this manifest's overrides applied.
+    pub fn profile(&self, release: bool) -> Profile {
+        let mut profile = if release { Profile::release() } else { Profile::dev() };
+        if let Some(overrides) = self.profile.get(&profile.name) {
+            if let Some(level) = overrides.opt_level {
+                profile.opt_level = match level {
+                    0 => OptimizationLevel::None,
+                    1 => OptimizationLevel::O1,
+                    2 => OptimizationLevel::O2,
+                    _ => OptimizationLevel::O3,
+                };
+            }
+            if let Some(debug) = overrides.debug {
+                profile.debug = debug;
+            }
+            if let Some(runtime_checks) = overrides.runtime_checks {
+                profile.runtime_checks = runtime_checks;
+            }
+        }
+        profile
+    }
+
+    /// Diagnostic settings for this project: lint defaults, then `[diagnostics]`.
+    pub fn diagnostic_options(&self, format: DiagnosticFormat) -> DiagnosticOptions {
+        let mut options = DiagnosticOptions { format, ..DiagnosticOptions::default() };
+        let levels = [
+            (&self.diagnostics.allow, LintLevel::Allow),
+            (&self.diagnostics.warn, LintLevel::Warn),
+            (&self.diagnostics.deny, LintLevel::Deny),
+        ];
+        for (list, level) in levels {
+            for code in list.iter().filter_map(|name| codes::warning_code(name)) {
+                options.set_level(code, level);
+            }
+        }
+        options
+    }
+
+    pub fn target(&self) -> Option<TargetOS> {
+        self.build.target.as_deref().and_then(TargetOS::from_string)
+    }
+
+    /// `explicit` (from the command line) wins over the manifest; both are relative to the project root.
+    pub fn target_dir(&self, explicit: Option<&Path>) -> PathBuf {
+        let dir = explicit
+            .map(Path::to_path_buf)
+            .or_else(|| self.build.target_dir.clone())
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_TARGET_DIR));
+        self.root.join(dir)
+    }
+
+    pub fn output_name(&self) -> String {
+        self.build.output.clone().unwrap_or_else(|| self.information.name.clone())
+    }
+
+    /// Collects every source file under the source roots, with the entry file first.
+    pub fn source_files(&self) -> Result<Vec<PathBuf>, ManifestError> {
+        let mut files = Vec::new();
+
+        for source_root in &self.build.sources {
+            let dir = self.root.join(source_root);
+            if !dir.is_dir() {
+                return Err(ManifestError::SourceRootNotFound(dir));
+            }
+            self.collect_sources(&dir, &mut files)?;
+        }
+
+        files.sort();
+        files.dedup();
+
+        if let Some(entry) = &self.build.entry {
+            let entry_path = self.root.join(entry);
+            if !entry_path.is_file() {
+                return Err(ManifestError::EntryNotFound(entry_path));
+            }
+            files.retain(|f| f != &entry_path);
+            files.insert(0, entry_path);
+        }
+
+        Ok(files)
+    }
+
+    /// Module name for a source file: its path inside the source root, joined with `_`.
+    pub fn module_name(&self, path: &Path) -> String {
+        let relative = self
+            .build
+            .sources
+            .iter()
+            .map(|source_root| self.root.join(source_root))
+            .find_map(|dir| path.strip_prefix(&dir).ok())
+            .unwrap_or(path);
+
+        relative
+            .with_extension("")
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("_")
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect()
+    }
+
+    fn collect_sources(&self, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), ManifestError> {
+        let entries = fs::read_dir(dir)
+            .map_err(|e| ManifestError::FileReadError(dir.to_path_buf(), e.to_string()))?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                self.collect_sources(&path, files)?;
+            } else if let Some(ext) = path.extension().and_then(|s| s.to_str())
+                && self.build.extensions.iter().any(|allowed| allowed == ext)
+            {
+                files.push(path);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Declared dependencies are treated like `import name` so they are built and linked.
+    pub fn dependency_imports(&self) -> Vec<ImportDecl> {
+        let mut names: Vec<&String> = self.dependencies.keys().collect();
+        names.sort();
+        names
+            .into_iter()
+            .map(|name| ImportDecl::LibraryImport { name: name.clone() })
+            .collect()
+    }
+}
@@ -0,0 +1,9 @@
+pub mod analysis;
+pub mod cache;
+pub mod cli;
+pub mod fix;
+pub mod layout;
+pub mod lsp;
+pub mod manifest;
+pub mod module;
+pub mod profile;
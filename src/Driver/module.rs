@@ -0,0 +1,627 @@
+use crate::import::*;
+use crate::Token::Helper::visit::{walk_expr, walk_impl_block, walk_stmt, VisitMut};
+
+/// A function's parameters and return type.
+pub type Signature = (Vec<(String, Type)>, Type);
+
+/// One source file, parsed on its own.
+pub struct Module {
+    pub name: String,
+    pub path: PathBuf,
+    pub source: Arc<String>,
+    pub program: Program,
+    pub structs: Vec<StructDef>,
+    pub enums: Vec<EnumDef>,
+    pub externs: Vec<ExternDecl>,
+    pub impls: Vec<ImplBlock>,
+    pub traits: Vec<TraitDef>,
+    /// Imports that are not satisfied by another local module (libraries).
+    pub library_imports: Vec<ImportDecl>,
+    /// `import X from "file"` resolved to (module index, item name).
+    pub local_imports: Vec<(usize, String)>,
+    /// Source name -> emitted C-level name for the functions this module defines.
+    pub renames: HashMap<String, String>,
+    /// Source name -> emitted C-level name for the private structs, enums and traits.
+    pub type_renames: HashMap<String, String>,
+    /// Parser warnings, left for the driver to emit with the project's lint levels and message format.
+    pub warnings: Vec<Diagnostic>,
+}
+
+#[derive(Debug)]
+pub enum ModuleError {
+    FileReadError(PathBuf, String),
+    /// Lexer or parser diagnostics, already carrying their source text.
+    Syntax(Vec<Diagnostic>),
+    ItemNotFound { module: PathBuf, name: String, from: String },
+    PrivateItem { module: PathBuf, name: String, from: PathBuf },
+    DuplicateItem { name: String, first: PathBuf, second: PathBuf },
+}
+
+impl std::fmt::Display for ModuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ModuleError::FileReadError(path, msg) => write!(f, "Failed to read {}: {}", path.display(), msg),
+            ModuleError::Syntax(diags) => {
+                let rendered: Vec<String> = diags.iter().map(Diagnostic::render).collect();
+                write!(f, "{}", rendered.join("\n"))
+            }
+            ModuleError::ItemNotFound { module, name, from } => {
+                write!(f, "in {}: '{}' is not defined in '{}'", module.display(), name, from)
+            }
+            ModuleError::PrivateItem { module, name, from } => {
+                write!(f, "in {}: '{}' is private to {}; mark it `public` to import it", module.display(), name, from.display())
+            }
+            ModuleError::DuplicateItem { name, first, second } => {
+                write!(f, "'{}' is defined in both {} and {}", name, first.display(), second.display())
+            }
+        }
+    }
+}
+
+impl ModuleError {
+    /// The error as diagnostics pointing at the file they concern, for `--message-format=json`.
+    pub fn to_diagnostics(&self) -> Vec<Diagnostic> {
+        let (code, file) = match self {
+            ModuleError::Syntax(diags) => return diags.clone(),
+            ModuleError::FileReadError(path, _) => (&codes::E0583, path),
+            ModuleError::ItemNotFound { module, .. } => (&codes::E0432, module),
+            ModuleError::PrivateItem { module, .. } => (&codes::E0603, module),
+            ModuleError::DuplicateItem { second, .. } => (&codes::E0428, second),
+        };
+        vec![Diagnostic::error(
+            code,
+            &self.to_string(),
+            ErrorContext {
+                primary_location: SourceLocation { file: file.display().to_string(), line: 0, column: 0, length: 0 },
+                secondary_locations: vec![],
+                help_message: None,
+                suggestions: vec![],
+            },
+        )]
+    }
+}
+
+impl Module {
+    pub fn parse(name: String, path: &Path) -> Result<(Module, Vec<ImportDecl>), ModuleError> {
+        let source_code = fs::read_to_string(path)
+            .map_err(|e| ModuleError::FileReadError(path.to_path_buf(), e.to_string()))?;
+        Self::parse_source(name, path, source_code)
+    }
+
+    /// Parses text that may not have been saved to `path` yet.
+    pub fn parse_source(name: String, path: &Path, source_code: String) -> Result<(Module, Vec<ImportDecl>), ModuleError> {
+        let file = path.display().to_string();
+        let with_source = |diags: Vec<Diagnostic>| -> Vec<Diagnostic> {
+            diags.into_iter().map(|d| d.with_source(&source_code)).collect()
+        };
+
+        let mut lexer = Lexer::new(&source_code).with_file(file.clone());
+        let tokens = lexer.tokenize();
+
+        if !lexer.errors.is_empty() {
+            return Err(ModuleError::Syntax(with_source(std::mem::take(&mut lexer.errors))));
+        }
+
+        let parser = Parser::new(tokens, source_code.clone(), lexer.spans).with_file(file);
+        let (program, structs, enums, externs, _, _, _, impls, traits, _, import_decls, diags) = parser.parse();
+
+        let diags = with_source(diags);
+        if diags.iter().any(Diagnostic::is_error) {
+            return Err(ModuleError::Syntax(diags));
+        }
+
+        let module = Module {
+            name,
+            path: path.to_path_buf(),
+            source: Arc::new(source_code),
+            program,
+            structs,
+            enums,
+            externs,
+            impls,
+            traits,
+            library_imports: Vec::new(),
+            local_imports: Vec::new(),
+            renames: HashMap::new(),
+            type_renames: HashMap::new(),
+            warnings: diags,
+        };
+
+        Ok((module, import_decls))
+    }
+
+    pub fn named_source(&self) -> NamedSource<String> {
+        NamedSource::new(self.path.display().to_string(), self.source.to_string())
+    }
+
+    fn defines(&self, name: &str) -> Option<bool> {
+        if let Some(func) = self.program.functions.iter().find(|f| f.name == name) {
+            return Some(func.is_public);
+        }
+        if let Some(s) = self.structs.iter().find(|s| s.name == name) {
+            return Some(s.is_public);
+        }
+        if let Some(e) = self.enums.iter().find(|e| e.name == name) {
+            return Some(e.is_public);
+        }
+        if let Some(t) = self.traits.iter().find(|t| t.name == name) {
+            return Some(t.is_public);
+        }
+        None
+    }
+
+    /// The emitted name and signature of a function or method this module defines, as the
+    /// type checker resolved it; `None` for anything it only imported.
+    pub fn emitted_signature(&self, name: &str, (params, return_type): &Signature) -> Option<(String, Signature)> {
+        let emitted = self.renames.get(name).cloned().or_else(|| {
+            self.impls.iter().find_map(|block| {
+                let method = name.strip_prefix(&block.struct_name)?.strip_prefix('_')?;
+                block.methods.iter().any(|m| m.name == method).then(|| {
+                    format!("{}_{}", self.type_renames.get(&block.struct_name).unwrap_or(&block.struct_name), method)
+                })
+            })
+        })?;
+        let renamer = Renamer { functions: HashMap::new(), types: self.type_renames.clone() };
+        let params = params.iter().map(|(name, ty)| (name.clone(), renamer.rename_type(ty))).collect();
+        Some((emitted, (params, renamer.rename_type(return_type))))
+    }
+}
+
+/// Every module's items under their emitted names, ready for code generation.
+pub struct LinkedProgram {
+    pub program: Program,
+    pub structs: Vec<StructDef>,
+    pub enums: Vec<EnumDef>,
+    pub externs: Vec<ExternDecl>,
+    pub impls: Vec<ImplBlock>,
+    pub traits: Vec<TraitDef>,
+}
+
+pub struct ModuleGraph {
+    pub modules: Vec<Module>,
+    pub root: PathBuf,
+}
+
+impl ModuleGraph {
+    /// Parses every file as its own module. The first file is the entry module.
+    pub fn load(files: &[(String, PathBuf)], root: &Path) -> Result<ModuleGraph, Vec<ModuleError>> {
+        let mut modules = Vec::new();
+        let mut pending_imports = Vec::new();
+        let mut errors = Vec::new();
+
+        for (name, path) in files {
+            match Module::parse(name.clone(), path) {
+                Ok((module, imports)) => {
+                    modules.push(module);
+                    pending_imports.push(imports);
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut graph = ModuleGraph { modules, root: root.to_path_buf() };
+        graph.resolve_imports(pending_imports, &mut errors);
+        graph.check_duplicates(&mut errors);
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        graph.assign_names();
+        Ok(graph)
+    }
+
+    fn find_module(&self, importer: &Path, from: &str) -> Option<usize> {
+        let candidates = [
+            self.root.join(from),
+            importer.parent().unwrap_or(Path::new(".")).join(from),
+        ];
+
+        candidates.iter().find_map(|candidate| {
+            let candidate = fs::canonicalize(candidate).ok()?;
+            self.modules.iter().position(|m| fs::canonicalize(&m.path).ok().as_ref() == Some(&candidate))
+        })
+    }
+
+    fn resolve_imports(&mut self, pending: Vec<Vec<ImportDecl>>, errors: &mut Vec<ModuleError>) {
+        for (index, imports) in pending.into_iter().enumerate() {
+            for import in imports {
+                let (name, from) = match &import {
+                    ImportDecl::FileImport { name, from } => (name.clone(), from.clone()),
+                    ImportDecl::LibraryImport { .. } => {
+                        self.modules[index].library_imports.push(import);
+                        continue;
+                    }
+                };
+
+                let Some(target) = self.find_module(&self.modules[index].path, &from) else {
+                    self.modules[index].library_imports.push(import);
+                    continue;
+                };
+
+                match self.modules[target].defines(&name) {
+                    Some(true) => self.modules[index].local_imports.push((target, name)),
+                    Some(false) => errors.push(ModuleError::PrivateItem {
+                        module: self.modules[index].path.clone(),
+                        name,
+                        from: self.modules[target].path.clone(),
+                    }),
+                    None => errors.push(ModuleError::ItemNotFound {
+                        module: self.modules[index].path.clone(),
+                        name,
+                        from,
+                    }),
+                }
+            }
+        }
+    }
+
+    /// Public functions, structs, enums and traits share one namespace in the generated C.
+    fn check_duplicates(&self, errors: &mut Vec<ModuleError>) {
+        let mut seen: HashMap<String, &Path> = HashMap::new();
+
+        for module in &self.modules {
+            let public_functions = module.program.functions.iter().filter(|f| f.is_public || f.name == "main").map(|f| &f.name);
+            let types = module
+                .structs
+                .iter()
+                .filter(|s| s.is_public)
+                .map(|s| &s.name)
+                .chain(module.enums.iter().filter(|e| e.is_public).map(|e| &e.name))
+                .chain(module.traits.iter().filter(|t| t.is_public).map(|t| &t.name));
+
+            for name in public_functions.chain(types) {
+                if let Some(first) = seen.get(name) {
+                    errors.push(ModuleError::DuplicateItem {
+                        name: name.clone(),
+                        first: first.to_path_buf(),
+                        second: module.path.clone(),
+                    });
+                } else {
+                    seen.insert(name.clone(), &module.path);
+                }
+            }
+        }
+    }
+
+    /// Private functions and types are prefixed with their module name so that two files
+    /// can each have their own helper or `Point` with the same name.
+    fn assign_names(&mut self) {
+        for module in &mut self.modules {
+            for func in &module.program.functions {
+                let emitted = if func.is_public || func.name == "main" {
+                    func.name.clone()
+                } else {
+                    format!("{}__{}", module.name, func.name)
+                };
+                module.renames.insert(func.name.clone(), emitted);
+            }
+
+            let private_types = module
+                .structs
+                .iter()
+                .filter(|s| !s.is_public)
+                .map(|s| &s.name)
+                .chain(module.enums.iter().filter(|e| !e.is_public).map(|e| &e.name))
+                .chain(module.traits.iter().filter(|t| !t.is_public).map(|t| &t.name));
+            for name in private_types {
+                module.type_renames.insert(name.clone(), format!("{}__{}", module.name, name));
+            }
+        }
+    }
+
+    /// Functions visible inside `index`: its own definitions plus what it imported.
+    pub fn visible_functions(&self, index: usize) -> Vec<Function> {
+        self.modules[index]
+            .local_imports
+            .iter()
+            .filter_map(|(target, name)| self.modules[*target].program.functions.iter().find(|f| &f.name == name))
+            .cloned()
+            .collect()
+    }
+
+    pub fn visible_structs(&self, index: usize) -> Vec<StructDef> {
+        self.modules[index]
+            .local_imports
+            .iter()
+            .filter_map(|(target, name)| self.modules[*target].structs.iter().find(|s| &s.name == name))
+            .cloned()
+            .collect()
+    }
+
+    pub fn visible_enums(&self, index: usize) -> Vec<EnumDef> {
+        self.modules[index]
+            .local_imports
+            .iter()
+            .filter_map(|(target, name)| self.modules[*target].enums.iter().find(|e| &e.name == name))
+            .cloned()
+            .collect()
+    }
+
+    pub fn library_imports(&self) -> Vec<ImportDecl> {
+        self.modules.iter().flat_map(|m| m.library_imports.iter().cloned()).collect()
+    }
+
+    pub fn visible_traits(&self, index: usize) -> Vec<TraitDef> {
+        self.modules[index]
+            .local_imports
+            .iter()
+            .filter_map(|(target, name)| self.modules[*target].traits.iter().find(|t| &t.name == name))
+            .cloned()
+            .collect()
+    }
+
+    /// Trait impls written in other modules for the structs `index` imports.
+    pub fn visible_trait_impls(&self, index: usize) -> Vec<ImplBlock> {
+        let structs = self.visible_structs(index);
+        self.modules
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .flat_map(|(_, module)| module.impls.iter())
+            .filter(|block| block.trait_name.is_some() && structs.iter().any(|s| s.name == block.struct_name))
+            .cloned()
+            .collect()
+    }
+
+    /// The other modules whose source the code generated for `index` depends on: those it
+    /// imports from, directly or through another import, and those with trait impls for the
+    /// structs it can see.
+    pub fn dependencies(&self, index: usize) -> Vec<usize> {
+        let mut seen = HashSet::from([index]);
+        let mut pending = vec![index];
+        while let Some(current) = pending.pop() {
+            for (target, _) in &self.modules[current].local_imports {
+                if seen.insert(*target) {
+                    pending.push(*target);
+                }
+            }
+        }
+
+        let structs: HashSet<&String> = seen.iter().flat_map(|&m| self.modules[m].structs.iter().map(|s| &s.name)).collect();
+        for (other, module) in self.modules.iter().enumerate() {
+            if module.impls.iter().any(|block| block.trait_name.is_some() && structs.contains(&block.struct_name)) {
+                seen.insert(other);
+            }
+        }
+
+        seen.remove(&index);
+        let mut dependencies: Vec<usize> = seen.into_iter().collect();
+        dependencies.sort();
+        dependencies
+    }
+
+    /// Rewrites every module to its emitted names and merges them into a single
+    /// program for code generation.
+    pub fn link(self) -> LinkedProgram {
+        let mut functions = Vec::new();
+        let mut structs = Vec::new();
+        let mut enums = Vec::new();
+        let mut externs = Vec::new();
+        let mut impls = Vec::new();
+        let mut traits = Vec::new();
+
+        let imported_names: Vec<HashMap<String, String>> = self
+            .modules
+            .iter()
+            .map(|module| {
+                module
+                    .local_imports
+                    .iter()
+                    .filter_map(|(target, name)| {
+                        self.modules[*target].renames.get(name).map(|emitted| (name.clone(), emitted.clone()))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for (module, imported) in self.modules.into_iter().zip(imported_names) {
+            let mut renamer = Renamer { functions: imported, types: module.type_renames };
+            renamer.functions.extend(module.renames);
+
+            for mut func in module.program.functions {
+                renamer.rename_function(&mut func);
+                functions.push(func);
+            }
+
+            for mut impl_block in module.impls {
+                renamer.rename_impl_block(&mut impl_block);
+                impls.push(impl_block);
+            }
+
+            for mut struct_def in module.structs {
+                renamer.rename(&mut struct_def.name);
+                renamer.rename_bounds(&mut struct_def.type_params);
+                for field in &mut struct_def.fields {
+                    field.ty = renamer.rename_type(&field.ty);
+                }
+                structs.push(struct_def);
+            }
+            for mut enum_def in module.enums {
+                renamer.rename(&mut enum_def.name);
+                for variant in &mut enum_def.variants {
+                    match variant {
+                        EnumVariant::Simple(_) => {}
+                        EnumVariant::Tuple(_, types) => types.iter_mut().for_each(|ty| *ty = renamer.rename_type(ty)),
+                        EnumVariant::Struct(_, fields) => fields.iter_mut().for_each(|field| field.ty = renamer.rename_type(&field.ty)),
+                    }
+                }
+                enums.push(enum_def);
+            }
+            for mut trait_def in module.traits {
+                renamer.rename(&mut trait_def.name);
+                for method in &mut trait_def.methods {
+                    renamer.rename_params(&mut method.params);
+                    method.return_type = renamer.rename_type(&method.return_type);
+                }
+                traits.push(trait_def);
+            }
+            for mut extern_decl in module.externs {
+                renamer.rename_extern(&mut extern_decl);
+                externs.push(extern_decl);
+            }
+        }
+
+        LinkedProgram { program: Program { functions }, structs, enums, externs, impls, traits }
+    }
+}
+
+/// Rewrites a module's calls and types to their emitted names.
+struct Renamer {
+    functions: HashMap<String, String>,
+    types: HashMap<String, String>,
+}
+
+impl Renamer {
+    fn rename(&self, name: &mut String) {
+        if let Some(emitted) = self.types.get(name) {
+            *name = emitted.clone();
+        }
+    }
+
+    fn rename_type(&self, ty: &Type) -> Type {
+        ty.map(&mut |ty| match ty {
+            Type::Struct { name } => self.types.get(name).map(|emitted| Type::Struct { name: emitted.clone() }),
+            Type::Trait { name } => self.types.get(name).map(|emitted| Type::Trait { name: emitted.clone() }),
+            Type::Generic { name, args } => self.types.get(name).map(|emitted| Type::Generic {
+                name: emitted.clone(),
+                args: args.iter().map(|arg| self.rename_type(arg)).collect(),
+            }),
+            _ => None,
+        })
+    }
+
+    fn rename_params(&self, params: &mut [(String, Type, ParamModifier)]) {
+        for (_, ty, _) in params {
+            *ty = self.rename_type(ty);
+        }
+    }
+
+    fn rename_bounds(&self, type_params: &mut [(String, Option<String>)]) {
+        for bound in type_params.iter_mut().filter_map(|(_, bound)| bound.as_mut()) {
+            self.rename(bound);
+        }
+    }
+
+    fn rename_pattern(&self, pattern: &mut Pattern) {
+        match &mut pattern.kind {
+            PatternKind::Variant { enum_name, fields, .. } => {
+                if let Some(enum_name) = enum_name {
+                    self.rename(enum_name);
+                }
+                fields.iter_mut().for_each(|field| self.rename_pattern(field));
+            }
+            PatternKind::Tuple(patterns) | PatternKind::Or(patterns) => patterns.iter_mut().for_each(|p| self.rename_pattern(p)),
+            PatternKind::Wildcard | PatternKind::Binding(_) | PatternKind::Literal(_) | PatternKind::Range { .. } => {}
+        }
+    }
+
+    /// `Shape.Circle(1)` and `Shape.Empty` name the enum as if it were a variable.
+    fn rename_receiver(&self, obj: &mut Expr) {
+        if let ExprKind::Var(name) = &mut obj.kind {
+            self.rename(name);
+        }
+    }
+
+    fn rename_call(&self, name: &mut String) {
+        if let Some(emitted) = self.functions.get(name) {
+            *name = emitted.clone();
+        } else {
+            // A struct's constructor is called by the struct's name.
+            self.rename(name);
+        }
+    }
+
+    fn rename_function(&mut self, func: &mut Function) {
+        if let Some(emitted) = self.functions.get(&func.name) {
+            func.name = emitted.clone();
+        }
+        self.rename_params(&mut func.params);
+        func.return_type = self.rename_type(&func.return_type);
+        self.rename_bounds(&mut func.type_params);
+        self.visit_body(&mut func.body);
+    }
+
+    fn rename_impl_block(&mut self, impl_block: &mut ImplBlock) {
+        self.rename(&mut impl_block.struct_name);
+        if let Some(trait_name) = &mut impl_block.trait_name {
+            self.rename(trait_name);
+        }
+        for (_, ty) in &mut impl_block.constructor_params {
+            *ty = self.rename_type(ty);
+        }
+        for method in &mut impl_block.methods {
+            self.rename_params(&mut method.params);
+            method.return_type = self.rename_type(&method.return_type);
+        }
+        walk_impl_block(self, impl_block);
+    }
+
+    fn rename_extern(&mut self, extern_decl: &mut ExternDecl) {
+        let rename_signature = |params: &mut [(String, Type)], return_type: &mut Type| {
+            for (_, ty) in params {
+                *ty = self.rename_type(ty);
+            }
+            *return_type = self.rename_type(return_type);
+        };
+        match extern_decl {
+            ExternDecl::Single { func, .. } => rename_signature(&mut func.params, &mut func.return_type),
+            ExternDecl::Block { functions, .. } => {
+                for func in functions {
+                    rename_signature(&mut func.params, &mut func.return_type);
+                }
+            }
+            ExternDecl::SingleWithBody { func, .. } => {
+                rename_signature(&mut func.params, &mut func.return_type);
+                self.visit_body(&mut func.body);
+            }
+        }
+    }
+}
+
+impl VisitMut for Renamer {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::Call(name, _) => self.rename_call(name),
+            Stmt::TypedDeclaration { ty, .. } => *ty = self.rename_type(ty),
+            Stmt::StaticMethodCall(type_name, _, _) | Stmt::StaticMethodCallNamed(type_name, _, _) => self.rename(type_name),
+            Stmt::MethodCall(obj, _, _) | Stmt::MethodCallNamed(obj, _, _) => self.rename_receiver(obj),
+            Stmt::Match(_, cases, _) => cases.iter_mut().for_each(|case| self.rename_pattern(&mut case.pattern)),
+            Stmt::ImplBlock(impl_block) => {
+                self.rename_impl_block(impl_block);
+                return;
+            }
+            _ => {}
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        match &mut expr.kind {
+            ExprKind::Call(name, _) | ExprKind::CallNamed(name, _) | ExprKind::FuncAddr(name) => self.rename_call(name),
+            ExprKind::StructInit(name, _)
+            | ExprKind::StaticMethodCall(name, _, _)
+            | ExprKind::StaticMethodCallNamed(name, _, _)
+            | ExprKind::OffsetOf { struct_type: name, .. } => self.rename(name),
+            ExprKind::MethodCall(obj, _, _) | ExprKind::MethodCallNamed(obj, _, _) | ExprKind::MemberAccess(obj, _) => {
+                self.rename_receiver(obj)
+            }
+            ExprKind::Lambda { params, return_type, .. } => {
+                for (_, ty) in params {
+                    *ty = self.rename_type(ty);
+                }
+                *return_type = self.rename_type(return_type);
+            }
+            ExprKind::Cast(_, CastTarget::Type(ty)) | ExprKind::ReferenceTo(ty) | ExprKind::SizeOf(ty) | ExprKind::AlignOf(ty) | ExprKind::Type(ty) => {
+                *ty = self.rename_type(ty)
+            }
+            ExprKind::Cast(_, CastTarget::LibraryCallTyped(_, types)) => types.iter_mut().for_each(|ty| *ty = self.rename_type(ty)),
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::import::*;
+
+/// Settings shared by code generation and every clang invocation of one build.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub opt_level: OptimizationLevel,
+    pub debug: bool,
+    /// Emit the checks behind `unwrap` (None/Err/null) that abort with a message.
+    pub runtime_checks: bool,
+}
+
+impl Profile {
+    pub fn dev() -> Self {
+        Self {
+            name: "dev".to_string(),
+            opt_level: OptimizationLevel::None,
+            debug: true,
+            runtime_checks: true,
+        }
+    }
+
+    pub fn release() -> Self {
+        Self {
+            name: "release".to_string(),
+            opt_level: OptimizationLevel::O3,
+            debug: false,
+            runtime_checks: false,
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "dev" => Some(Self::dev()),
+            "release" => Some(Self::release()),
+            _ => None,
+        }
+    }
+
+    pub fn clang_args(&self) -> Vec<&'static str> {
+        let mut args = vec![match self.opt_level {
+            OptimizationLevel::None => "-O0",
+            OptimizationLevel::O1 => "-O1",
+            OptimizationLevel::O2 => "-O2",
+            OptimizationLevel::O3 => "-O3",
+        }];
+        if self.debug {
+            args.push("-g");
+        }
+        args
+    }
+
+    pub fn apply(&self, config: &mut CodegenConfig) {
+        config.optimization_level = self.opt_level;
+        config.debug_info = self.debug;
+        config.runtime_checks = self.runtime_checks;
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::dev()
+    }
+}
@@ -58,6 +58,16 @@ impl TargetOS {
         }
     }
 
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            TargetOS::Windows => "windows",
+            TargetOS::Linux => "linux",
+            TargetOS::MacOS => "macos",
+            TargetOS::FreeBSD => "freebsd",
+            TargetOS::Unknown => "unknown",
+        }
+    }
+
     pub fn display_name(&self) -> &'static str {
         match self {
             TargetOS::Windows => "Windows",
@@ -72,14 +82,45 @@ impl TargetOS {
 pub struct Clang;
 
 impl Clang {
-    fn create_cfg_stub() -> Result<PathBuf, String> {
+    /// Placeholder left by debug codegen where line numbers should return to the C file itself.
+    pub const GENERATED_LINE_MARKER: &'static str = "// vix: generated code";
+
+    /// Writes the C file, replacing each line marker with a `#line` naming the C file's own line.
+    pub fn write_c_source(c_code: &str, c_path: &Path) -> Result<(), String> {
+        let c_file = c_path.display().to_string().replace('\\', "\\\\").replace('"', "\\\"");
+        let source: String = c_code
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                if line == Self::GENERATED_LINE_MARKER {
+                    format!("#line {} \"{}\"\n", i + 2, c_file)
+                } else {
+                    format!("{}\n", line)
+                }
+            })
+            .collect();
+
+        fs::write(c_path, source).map_err(|e| format!("Failed to write C source: {}", e))
+    }
+
+    /// First line of `clang --version`, used to invalidate cached objects when the toolchain changes.
+    pub fn version() -> String {
+        Command::new("clang")
+            .arg("--version")
+            .output()
+            .ok()
+            .and_then(|output| String::from_utf8_lossy(&output.stdout).lines().next().map(str::to_string))
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    fn create_cfg_stub(dir: &Path, profile: &Profile) -> Result<PathBuf, String> {
         let stub_c = r#"
 unsigned int __guard_eh_cont_count = 0;
 void* __guard_eh_cont_table = 0;
 "#;
 
-        let stub_path = Path::new("cfg_stub.c");
-        let obj_path = PathBuf::from("cfg_stub.obj");
+        let stub_path = dir.join(format!("cfg_stub-{}.c", std::process::id()));
+        let obj_path = stub_path.with_extension("obj");
         let _ = fs::remove_file(&stub_path);
         let _ = fs::remove_file(&obj_path);
 
@@ -90,10 +131,12 @@ void* __guard_eh_cont_table = 0;
             .arg(&stub_path)
             .arg("-o")
             .arg(&obj_path)
-            .arg("-O2");
+            .args(profile.clang_args());
 
         let output = cmd.output().map_err(|e| format!("Failed to execute clang for CFG stub: {}", e))?;
 
+        let _ = fs::remove_file(&stub_path);
+
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
             let stdout = String::from_utf8_lossy(&output.stdout);
@@ -110,23 +153,28 @@ void* __guard_eh_cont_table = 0;
         c_code: &str,
         output_path: &Path,
         target_os: Option<TargetOS>,
+        profile: &Profile,
     ) -> Result<(), String> {
-        let mut cmd = Command::new("clang");
         let target = target_os.unwrap_or_else(TargetOS::current);
         let obj_path = if output_path.extension().is_none() {
             output_path.with_extension(target.object_extension().trim_start_matches('.'))
         } else {
             output_path.to_path_buf()
         };
-        let c_path = Path::new("output.c");
+        let c_path = obj_path.with_extension("c");
 
-        fs::write(&c_path, c_code).map_err(|e| format!("Failed to write C source: {}", e))?;
+        Self::write_c_source(c_code, &c_path)?;
+        Self::compile_c_file(&c_path, &obj_path, target, profile)
+    }
 
+    /// Compiles a C file written by `write_c_source`, leaving it where it is.
+    pub fn compile_c_file(c_path: &Path, obj_path: &Path, target: TargetOS, profile: &Profile) -> Result<(), String> {
+        let mut cmd = Command::new("clang");
         cmd.arg("-c")
-            .arg(&c_path)
+            .arg(c_path)
             .arg("-o")
-            .arg(&obj_path)
-            .arg("-O2")
+            .arg(obj_path)
+            .args(profile.clang_args())
             .arg("-std=c17")
             .arg("-Wall")
             .arg("-Wextra");
@@ -148,16 +196,23 @@ void* __guard_eh_cont_table = 0;
 
     pub fn link_executable(
         object_files: &[&Path],
-        output_name: &str,
+        exe_path: &Path,
         extra_libs: &[String],
         target_os: Option<TargetOS>,
+        profile: &Profile,
     ) -> Result<(), String> {
         let mut cmd = Command::new("clang");
         let target = target_os.unwrap_or_else(TargetOS::current);
-        let exe_path = format!("{}{}", output_name, target.executable_extension());
+        let out_dir = exe_path.parent().unwrap_or(Path::new("."));
+        // Link to a private name first so a concurrent build never sees a half-written executable.
+        let partial_path = out_dir.join(format!(
+            ".{}-{}.partial",
+            exe_path.file_name().and_then(|n| n.to_str()).unwrap_or("program"),
+            std::process::id()
+        ));
 
         let cfg_stub = if target == TargetOS::Windows {
-            Some(Self::create_cfg_stub()?)
+            Some(Self::create_cfg_stub(out_dir, profile)?)
         } else {
             None
         };
@@ -170,7 +225,7 @@ void* __guard_eh_cont_table = 0;
             cmd.arg(obj);
         }
 
-        cmd.arg("-o").arg(&exe_path);
+        cmd.arg("-o").arg(&partial_path);
 
         Self::add_platform_specific_args(&mut cmd, target);
 
@@ -185,31 +240,33 @@ void* __guard_eh_cont_table = 0;
         }
 
         if !output.status.success() {
+            let _ = fs::remove_file(&partial_path);
             let stderr = String::from_utf8_lossy(&output.stderr);
             return Err(format!("Linking failed:\n{}", stderr));
         }
 
-        println!("   {} Executable linked: {}", "success:".green(), exe_path);
+        fs::rename(&partial_path, exe_path).map_err(|e| format!("Failed to move executable into place: {}", e))?;
         Ok(())
     }
 
     pub fn generate_and_compile(
         c_code: &str,
-        output_name: &str,
+        exe_path: &Path,
         extra_libs: &[String],
         target_os: Option<TargetOS>,
+        profile: &Profile,
     ) -> Result<(), String> {
         let target = target_os.unwrap_or_else(TargetOS::current);
-        let exe_path = format!("{}{}", output_name, target.executable_extension());
-        let c_path = Path::new("output.c");
+        let out_dir = exe_path.parent().unwrap_or(Path::new("."));
+        let c_path = out_dir.join(format!("output-{}.c", std::process::id()));
 
         let cfg_stub = if target == TargetOS::Windows {
-            Some(Self::create_cfg_stub()?)
+            Some(Self::create_cfg_stub(out_dir, profile)?)
         } else {
             None
         };
 
-        fs::write(&c_path, c_code).map_err(|e| format!("Failed to write C source: {}", e))?;
+        Self::write_c_source(c_code, &c_path)?;
 
         let mut cmd = Command::new("clang");
 
@@ -220,7 +277,7 @@ void* __guard_eh_cont_table = 0;
         cmd.arg(&c_path)
             .arg("-o")
             .arg(&exe_path)
-            .arg("-O2")
+            .args(profile.clang_args())
             .arg("-std=c17")
             .arg("-Wall")
             .arg("-Wextra");
@@ -247,7 +304,7 @@ void* __guard_eh_cont_table = 0;
             return Err(format!("Compilation failed:\nSTDOUT:\n{}\nSTDERR:\n{}\n", stdout, stderr));
         }
 
-        println!("   {} Executable built: {}", "✓".green(), exe_path);
+        println!("   {} Executable built: {}", "✓".green(), exe_path.display());
         Ok(())
     }
 
@@ -284,10 +341,8 @@ void* __guard_eh_cont_table = 0;
         vec![]
     }
 
-    pub fn run_executable(exe_name: &str, target_os: Option<TargetOS>) -> Result<(), String> {
-        let target = target_os.unwrap_or_else(TargetOS::current);
-        let exe_path = format!("{}{}{}", target.executable_prefix(), exe_name,target.executable_extension());
-        let output = Command::new(&exe_path).output().map_err(|e| format!("Failed to run {}: {}", exe_path, e))?;
+    pub fn run_executable(exe_path: &Path) -> Result<(), String> {
+        let output = Command::new(exe_path).output().map_err(|e| format!("Failed to run {}: {}", exe_path.display(), e))?;
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
 
@@ -0,0 +1,552 @@
+use super::error::DiagnosticLevel;
+
+/// An entry in the catalogue of diagnostic codes. Codes never change meaning once published;
+/// `E` codes are errors and `W` codes are warnings.
+#[derive(Debug)]
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub level: DiagnosticLevel,
+    pub title: &'static str,
+    pub explanation: &'static str,
+    pub wrong: &'static str,
+    pub fixed: &'static str,
+}
+
+impl ErrorCode {
+    pub fn is_warning(&self) -> bool {
+        matches!(self.level, DiagnosticLevel::Warning)
+    }
+
+    /// Long-form text shown by `vix explain`.
+    pub fn explain(&self) -> String {
+        let indent = |code: &str| code.lines().map(|line| format!("    {}", line)).collect::<Vec<_>>().join("\n");
+        let lint = LINTS.iter().find(|lint| lint.code.code == self.code).map_or(String::new(), |lint| format!(" ({})", lint.name));
+        format!(
+            "{}{}: {}\n\n{}\n\nErroneous code example:\n\n{}\n\nFixed:\n\n{}\n",
+            self.code,
+            lint,
+            self.title,
+            self.explanation,
+            indent(self.wrong),
+            indent(self.fixed)
+        )
+    }
+}
+
+pub const E0001: ErrorCode = ErrorCode {
+    code: "E0001",
+    level: DiagnosticLevel::Error,
+    title: "void used as a value type",
+    explanation: "`void` means \"no value\", so it cannot be the type of a variable, parameter, struct field, \
+array element, tuple field or union variant. Use the type of the value you want to store, or \
+`Option[T]` if it may be absent.",
+    wrong: "func log(message: void)\n    print(message)\nend",
+    fixed: "func log(message: str)\n    print(message)\nend",
+};
+
+pub const E0004: ErrorCode = ErrorCode {
+    code: "E0004",
+    level: DiagnosticLevel::Error,
+    title: "non-exhaustive match",
+    explanation: "A `match` without a `default:` arm must have a pattern for every value the matched \
+expression can take. Arms with an `if` guard do not count, since the guard may fail. Add the missing \
+patterns, or a `_` or `default:` arm.",
+    wrong: "enum Light:\n    Red,\n    Green,\nend\n\nmatch light:\n    case Light.Red:\n        stop()\nend",
+    fixed: "enum Light:\n    Red,\n    Green,\nend\n\nmatch light:\n    case Light.Red:\n        stop()\n    case Light.Green:\n        go()\nend",
+};
+
+pub const E0023: ErrorCode = ErrorCode {
+    code: "E0023",
+    level: DiagnosticLevel::Error,
+    title: "wrong number of fields in pattern",
+    explanation: "A variant pattern must list one sub-pattern for each field of the variant, in declaration \
+order. Use `_` for fields that do not matter.",
+    wrong: "enum Shape:\n    Rect(float32, float32),\nend\n\nmatch shape:\n    case Shape.Rect(w):\n        print(w)\nend",
+    fixed: "enum Shape:\n    Rect(float32, float32),\nend\n\nmatch shape:\n    case Shape.Rect(w, _):\n        print(w)\nend",
+};
+
+pub const E0046: ErrorCode = ErrorCode {
+    code: "E0046",
+    level: DiagnosticLevel::Error,
+    title: "missing trait method",
+    explanation: "An `impl Trait for Struct` block must define every method the trait declares.",
+    wrong: "trait Shape:\n    func area(self): float32\n    func name(self): str\nend\n\nimpl Shape for Square:\n    func area(self): float32\n        return self.side * self.side\n    end\nend",
+    fixed: "trait Shape:\n    func area(self): float32\n    func name(self): str\nend\n\nimpl Shape for Square:\n    func area(self): float32\n        return self.side * self.side\n    end\n    func name(self): str\n        return \"square\"\n    end\nend",
+};
+
+pub const E0053: ErrorCode = ErrorCode {
+    code: "E0053",
+    level: DiagnosticLevel::Error,
+    title: "method signature does not match the trait",
+    explanation: "A method in an `impl Trait for Struct` block must take `self` if the trait's does, and have \
+the same parameter types and return type as the trait declares.",
+    wrong: "trait Shape:\n    func area(self): float32\nend\n\nimpl Shape for Square:\n    func area(self): int32\n        return 4\n    end\nend",
+    fixed: "trait Shape:\n    func area(self): float32\nend\n\nimpl Shape for Square:\n    func area(self): float32\n        return self.side * self.side\n    end\nend",
+};
+
+pub const E0061: ErrorCode = ErrorCode {
+    code: "E0061",
+    level: DiagnosticLevel::Error,
+    title: "wrong number of arguments",
+    explanation: "A function was called with a different number of arguments than it declares parameters, \
+or an enum variant was built with a different number of fields than its payload has.",
+    wrong: "func add(a: int32, b: int32): int32\n    return a + b\nend\n\nx: int32 = add(1)",
+    fixed: "func add(a: int32, b: int32): int32\n    return a + b\nend\n\nx: int32 = add(1, 2)",
+};
+
+pub const E0062: ErrorCode = ErrorCode {
+    code: "E0062",
+    level: DiagnosticLevel::Error,
+    title: "field specified more than once",
+    explanation: "A named field was given twice when building an enum variant.",
+    wrong: "enum Action:\n    Join(game: str, seat: int32)\nend\n\na: Action = Action.Join(game = \"go\", game = \"chess\")",
+    fixed: "enum Action:\n    Join(game: str, seat: int32)\nend\n\na: Action = Action.Join(game = \"go\", seat = 1)",
+};
+
+pub const E0100: ErrorCode = ErrorCode {
+    code: "E0100",
+    level: DiagnosticLevel::Error,
+    title: "unexpected character",
+    explanation: "The source contains a character that is not part of any Vix token. It is often a stray \
+symbol or a quote character pasted from a word processor.",
+    wrong: "x: int32 = 1 $",
+    fixed: "x: int32 = 1",
+};
+
+pub const E0101: ErrorCode = ErrorCode {
+    code: "E0101",
+    level: DiagnosticLevel::Error,
+    title: "expected an item",
+    explanation: "Only declarations (func, struct, enum, impl, trait, type, extern and import) may appear at \
+the top level of a file. Statements have to be inside a function.",
+    wrong: "print(\"hello\")",
+    fixed: "func main(): int32\n    print(\"hello\")\n    return 0\nend",
+};
+
+pub const E0102: ErrorCode = ErrorCode {
+    code: "E0102",
+    level: DiagnosticLevel::Error,
+    title: "import after other items",
+    explanation: "Imports are resolved before the rest of the file is parsed, so they must come first.",
+    wrong: "func main(): int32\n    return add(1, 2)\nend\n\nimport add from \"src/math.x\"",
+    fixed: "import add from \"src/math.x\"\n\nfunc main(): int32\n    return add(1, 2)\nend",
+};
+
+pub const E0103: ErrorCode = ErrorCode {
+    code: "E0103",
+    level: DiagnosticLevel::Error,
+    title: "missing `end`",
+    explanation: "A function ran into the next item or the end of the file before it was closed with `end`. \
+The missing `end` is often that of an inner `if`, `while` or `for` block, which then takes the function's \
+`end` for its own.",
+    wrong: "func main(): int32\n    if true then\n        print(\"yes\")\n    return 0\nend",
+    fixed: "func main(): int32\n    if true then\n        print(\"yes\")\n    end\n    return 0\nend",
+};
+
+pub const E0104: ErrorCode = ErrorCode {
+    code: "E0104",
+    level: DiagnosticLevel::Error,
+    title: "unexpected token",
+    explanation: "The parser expected one thing, such as a name, a type, an expression or a particular \
+symbol, and found another. The error points at the token it found; the mistake is often just before it.",
+    wrong: "func add(a: int32, b int32): int32\n    return a + b\nend",
+    fixed: "func add(a: int32, b: int32): int32\n    return a + b\nend",
+};
+
+pub const E0277: ErrorCode = ErrorCode {
+    code: "E0277",
+    level: DiagnosticLevel::Error,
+    title: "type does not support this operation",
+    explanation: "The operation is not defined for the operand's type: arithmetic or assignment on `void`, \
+dereferencing something that is not a pointer, indexing something that is not an array, \
+`not` on a non-boolean, or a `for` loop over something that cannot be iterated.",
+    wrong: "x: int32 = 5\nfor i in x do\n    print(i)\nend",
+    fixed: "xs = [1, 2, 3, 4, 5]\nfor i in xs do\n    print(i)\nend",
+};
+
+pub const E0282: ErrorCode = ErrorCode {
+    code: "E0282",
+    level: DiagnosticLevel::Error,
+    title: "cannot infer type parameter",
+    explanation: "Type arguments of a generic function are inferred from the arguments of each call. \
+A type parameter that appears in no parameter type, such as one used only in the return type, \
+can never be inferred.",
+    wrong: "func zero[T](): T
+    return 0
+end",
+    fixed: "func zero[T](like: T): T
+    return like - like
+end",
+};
+
+pub const E0283: ErrorCode = ErrorCode {
+    code: "E0283",
+    level: DiagnosticLevel::Error,
+    title: "type argument does not satisfy its bound",
+    explanation: "A type parameter declared as `T: Trait` only accepts types that implement the trait, \
+because the generic code may call the trait's methods on it.",
+    wrong: "func describe[T: Shape](shape: T): float32
+    return shape.area()
+end
+
+func main(): int32
+    describe(5)
+    return 0
+end",
+    fixed: "func describe[T: Shape](shape: T): float32
+    return shape.area()
+end
+
+func main(): int32
+    describe(Square(side = 5.0))
+    return 0
+end",
+};
+
+pub const E0308: ErrorCode = ErrorCode {
+    code: "E0308",
+    level: DiagnosticLevel::Error,
+    title: "mismatched types",
+    explanation: "A value's type does not match the type expected at that position: a declaration, assignment, \
+argument, return value, condition or match arm.",
+    wrong: "flag: bool = \"yes\"",
+    fixed: "flag: bool = true",
+};
+
+pub const E0369: ErrorCode = ErrorCode {
+    code: "E0369",
+    level: DiagnosticLevel::Error,
+    title: "binary operator cannot be applied to these types",
+    explanation: "The operator is not defined for this combination of operand types. Convert one operand so \
+both sides have compatible types.",
+    wrong: "x: int32 = 1\ny: int32 = x + true",
+    fixed: "x: int32 = 1\ny: int32 = x + 1",
+};
+
+pub const E0373: ErrorCode = ErrorCode {
+    code: "E0373",
+    level: DiagnosticLevel::Error,
+    title: "closure may outlive captured variables",
+    explanation: "A closure keeps its captured variables in the block that creates it, so it cannot be \
+returned from its function or stored in a variable declared outside that block. Pass the values it needs \
+as parameters instead.",
+    wrong: "func adder(n: int32): func(int32): int32\n    return func(x: int32): int32 => x + n\nend",
+    fixed: "func add(x: int32, n: int32): int32\n    return x + n\nend",
+};
+
+pub const E0405: ErrorCode = ErrorCode {
+    code: "E0405",
+    level: DiagnosticLevel::Error,
+    title: "unknown trait",
+    explanation: "An `impl` block, a `dyn` type or a type parameter bound names a trait that is not defined in this file or imported.",
+    wrong: "impl Shape for Square:\n    func area(self): float32\n        return self.side * self.side\n    end\nend",
+    fixed: "trait Shape:\n    func area(self): float32\nend\n\nimpl Shape for Square:\n    func area(self): float32\n        return self.side * self.side\n    end\nend",
+};
+
+pub const E0407: ErrorCode = ErrorCode {
+    code: "E0407",
+    level: DiagnosticLevel::Error,
+    title: "method is not a member of the trait",
+    explanation: "An `impl Trait for Struct` block may only define the methods the trait declares. Move other \
+methods to a plain `impl Struct` block.",
+    wrong: "impl Shape for Square:\n    func area(self): float32\n        return self.side * self.side\n    end\n    func grow(self)\n    end\nend",
+    fixed: "impl Shape for Square:\n    func area(self): float32\n        return self.side * self.side\n    end\nend\n\nimpl Square:\n    func grow(self)\n    end\nend",
+};
+
+pub const E0384: ErrorCode = ErrorCode {
+    code: "E0384",
+    level: DiagnosticLevel::Error,
+    title: "assignment to immutable variable",
+    explanation: "Local variables and parameters are immutable unless declared with `mut`.",
+    wrong: "count: int32 = 0\ncount = count + 1",
+    fixed: "mut count: int32 = 0\ncount = count + 1",
+};
+
+pub const E0408: ErrorCode = ErrorCode {
+    code: "E0408",
+    level: DiagnosticLevel::Error,
+    title: "variable not bound in all alternatives",
+    explanation: "Every alternative of an or-pattern (`a | b`) must bind the same names with the same types, \
+since the arm's body may use them whichever alternative matched.",
+    wrong: "match pair:\n    case (x, 0) | (0, y):\n        print(x)\nend",
+    fixed: "match pair:\n    case (x, 0) | (0, x):\n        print(x)\nend",
+};
+
+pub const E0412: ErrorCode = ErrorCode {
+    code: "E0412",
+    level: DiagnosticLevel::Error,
+    title: "unknown struct",
+    explanation: "An impl block or type refers to a struct that is not defined or imported.",
+    wrong: "impl Player:\n    func heal(&mut self)\n    end\nend",
+    fixed: "struct Player:\n    health = int\nend\n\nimpl Player:\n    func heal(&mut self)\n    end\nend",
+};
+
+pub const E0425: ErrorCode = ErrorCode {
+    code: "E0425",
+    level: DiagnosticLevel::Error,
+    title: "unresolved name",
+    explanation: "A variable or function is used but is not declared in this scope. Check the spelling, \
+declare it first, or import it from the module that defines it.",
+    wrong: "func main(): int32\n    total = count + 1\n    return 0\nend",
+    fixed: "func main(): int32\n    count: int32 = 0\n    total = count + 1\n    return 0\nend",
+};
+
+pub const E0428: ErrorCode = ErrorCode {
+    code: "E0428",
+    level: DiagnosticLevel::Error,
+    title: "duplicate definition",
+    explanation: "Two items with the same name are visible in the same place, for example a function that \
+shadows a built-in or an imported function, or a struct defined twice.",
+    wrong: "struct Point:\n    x = int\nend\n\nstruct Point:\n    y = int\nend",
+    fixed: "struct Point:\n    x = int\n    y = int\nend",
+};
+
+pub const E0432: ErrorCode = ErrorCode {
+    code: "E0432",
+    level: DiagnosticLevel::Error,
+    title: "unresolved import",
+    explanation: "An `import ... from \"file\"` names an item that the file does not define.",
+    wrong: "import sub from \"src/math.x\"   // math.x only defines add",
+    fixed: "import add from \"src/math.x\"",
+};
+
+pub const E0434: ErrorCode = ErrorCode {
+    code: "E0434",
+    level: DiagnosticLevel::Error,
+    title: "capturing closure used as a function pointer",
+    explanation: "A `^func` type is a bare C function pointer with no room for captured variables, so only a \
+lambda that captures nothing can be passed where one is expected. Pass the value as a parameter, or \
+take a `func` type that carries its environment.",
+    wrong: "limit: int32 = 10\nset_handler(func(x: int32): bool => x > limit)   // handler: ^func(int32): bool",
+    fixed: "set_handler(func(x: int32): bool => x > 10)",
+};
+
+pub const E0502: ErrorCode = ErrorCode {
+    code: "E0502",
+    level: DiagnosticLevel::Error,
+    title: "conflicting borrows",
+    explanation: "A value may have any number of immutable borrows or a single mutable one, never both at once. \
+Declaring the same mutable variable twice in one scope, or taking a `mutable reference` parameter \
+to a name that is already borrowed, creates a second borrow.",
+    wrong: "mut total: int32 = 0\nmut total: int32 = 1",
+    fixed: "mut total: int32 = 0\ntotal = 1",
+};
+
+pub const E0580: ErrorCode = ErrorCode {
+    code: "E0580",
+    level: DiagnosticLevel::Error,
+    title: "test function with parameters or a return type",
+    explanation: "The `vix test` harness calls each `test func` with no arguments and has nowhere to put a \
+return value, so a test takes no parameters and returns nothing. Check results with `assert` instead.",
+    wrong: "test func adds(a: int32): bool\n    return a + 1 == 2\nend",
+    fixed: "test func adds()\n    assert(1 + 1 == 2)\nend",
+};
+
+pub const E0583: ErrorCode = ErrorCode {
+    code: "E0583",
+    level: DiagnosticLevel::Error,
+    title: "source file could not be read",
+    explanation: "A source file listed in the project, or named by an import, does not exist or is not readable.",
+    wrong: "import add from \"src/maths.x\"",
+    fixed: "import add from \"src/math.x\"",
+};
+
+pub const E0594: ErrorCode = ErrorCode {
+    code: "E0594",
+    level: DiagnosticLevel::Error,
+    title: "assignment to immutable field",
+    explanation: "Struct fields are immutable unless declared with `mut`.",
+    wrong: "struct Counter:\n    count = int\nend\n\nc.count = 1",
+    fixed: "struct Counter:\n    mut count = int\nend\n\nc.count = 1",
+};
+
+pub const E0599: ErrorCode = ErrorCode {
+    code: "E0599",
+    level: DiagnosticLevel::Error,
+    title: "no such method or variant",
+    explanation: "A method is called on a type whose impl block does not define it, or an enum variant is \
+named that the enum does not declare.",
+    wrong: "p = Player::spawn()",
+    fixed: "impl Player:\n    func spawn(): Player\n        return Player(\"Hero\", 100, 0)\n    end\nend\n\np = Player::spawn()",
+};
+
+pub const E0603: ErrorCode = ErrorCode {
+    code: "E0603",
+    level: DiagnosticLevel::Error,
+    title: "private item imported",
+    explanation: "Items are private to their file unless marked `public`.",
+    wrong: "// src/math.x\nfunc add(a: int32, b: int32): int32\n    return a + b\nend",
+    fixed: "// src/math.x\npublic func add(a: int32, b: int32): int32\n    return a + b\nend",
+};
+
+pub const E0609: ErrorCode = ErrorCode {
+    code: "E0609",
+    level: DiagnosticLevel::Error,
+    title: "no such field",
+    explanation: "A field is accessed or assigned on a struct, or named in an enum variant constructor, \
+that does not declare it.",
+    wrong: "struct Point:\n    x = int\nend\n\np.y = 2",
+    fixed: "struct Point:\n    x = int\n    y = int\nend\n\np.y = 2",
+};
+
+pub const E0800: ErrorCode = ErrorCode {
+    code: "E0800",
+    level: DiagnosticLevel::Error,
+    title: "expression not supported by code generation",
+    explanation: "The expression parsed, but the C backend cannot generate code for it yet. Rewrite it with \
+simpler expressions, and please report it.",
+    wrong: "offset = offsetof(Point, y)",
+    fixed: "// Compute the offset from the field types until offsetof is supported.\noffset: uint64 = 8",
+};
+
+pub const W0001: ErrorCode = ErrorCode {
+    code: "W0001",
+    level: DiagnosticLevel::Warning,
+    title: "pointer cast to a non-pointer type",
+    explanation: "Casting a pointer to an integer or other non-pointer type discards provenance and may \
+truncate on some targets.",
+    wrong: "addr = p as int32",
+    fixed: "addr = p as uint64",
+};
+
+pub const W0002: ErrorCode = ErrorCode {
+    code: "W0002",
+    level: DiagnosticLevel::Warning,
+    title: "statement skipped by code generation",
+    explanation: "The statement parsed, but the C backend does not support it yet and leaves it out of the \
+program. The compiled program will behave as if the statement were not there.",
+    wrong: "unsafe:\n    counter += 1\nend",
+    fixed: "counter += 1",
+};
+
+pub const W0100: ErrorCode = ErrorCode {
+    code: "W0100",
+    level: DiagnosticLevel::Warning,
+    title: "unused variable",
+    explanation: "A local variable is declared but its value is never read. Remove it, or start its name with \
+an underscore if it exists on purpose.",
+    wrong: "func main(): int32\n    count: int32 = 0\n    return 0\nend",
+    fixed: "func main(): int32\n    _count: int32 = 0\n    return 0\nend",
+};
+
+pub const W0101: ErrorCode = ErrorCode {
+    code: "W0101",
+    level: DiagnosticLevel::Warning,
+    title: "unused import",
+    explanation: "An item is imported from another file but never used in this one.",
+    wrong: "import add from \"src/math.x\"\n\nfunc main(): int32\n    return 0\nend",
+    fixed: "func main(): int32\n    return 0\nend",
+};
+
+pub const W0102: ErrorCode = ErrorCode {
+    code: "W0102",
+    level: DiagnosticLevel::Warning,
+    title: "unused function",
+    explanation: "A private function is never called. Other files cannot call it either, since it is not \
+`public`, so it is dead code.",
+    wrong: "func helper(): int32\n    return 1\nend\n\nfunc main(): int32\n    return 0\nend",
+    fixed: "func main(): int32\n    return 0\nend",
+};
+
+pub const W0103: ErrorCode = ErrorCode {
+    code: "W0103",
+    level: DiagnosticLevel::Warning,
+    title: "unreachable code",
+    explanation: "A statement follows a `return`, `break` or `continue` in the same block, so it never runs.",
+    wrong: "func main(): int32\n    return 0\n    print(\"done\")\nend",
+    fixed: "func main(): int32\n    print(\"done\")\n    return 0\nend",
+};
+
+pub const W0104: ErrorCode = ErrorCode {
+    code: "W0104",
+    level: DiagnosticLevel::Warning,
+    title: "variable does not need to be mutable",
+    explanation: "A variable is declared `mut` but is never assigned to or passed where it could be modified.",
+    wrong: "mut limit: int32 = 10\nprint(limit)",
+    fixed: "limit: int32 = 10\nprint(limit)",
+};
+
+pub const W0105: ErrorCode = ErrorCode {
+    code: "W0105",
+    level: DiagnosticLevel::Warning,
+    title: "shadowed variable",
+    explanation: "A declaration reuses the name of a variable that is still in scope, hiding the earlier one. \
+This lint is allowed by default.",
+    wrong: "total: int32 = 1\nif ready then\n    total: int32 = 2\nend",
+    fixed: "mut total: int32 = 1\nif ready then\n    total = 2\nend",
+};
+
+pub const W0106: ErrorCode = ErrorCode {
+    code: "W0106",
+    level: DiagnosticLevel::Warning,
+    title: "comparison is always true or always false",
+    explanation: "Both sides of the comparison are constants, or the same variable, so its result is known \
+before the program runs. It usually means the wrong variable was compared.",
+    wrong: "if count == count then\n    print(\"equal\")\nend",
+    fixed: "if count == limit then\n    print(\"equal\")\nend",
+};
+
+pub const W0107: ErrorCode = ErrorCode {
+    code: "W0107",
+    level: DiagnosticLevel::Warning,
+    title: "empty unsafe block",
+    explanation: "An `unsafe:` block contains no statements and can be removed.",
+    wrong: "func main(): int32\n    unsafe:\n    end\n    return 0\nend",
+    fixed: "func main(): int32\n    return 0\nend",
+};
+
+pub const W0108: ErrorCode = ErrorCode {
+    code: "W0108",
+    level: DiagnosticLevel::Warning,
+    title: "unreachable pattern",
+    explanation: "Every value a `match` arm's pattern accepts is already handled by earlier arms without a \
+guard, so the arm never runs. This is often a catch-all arm placed before more specific ones.",
+    wrong: "match n:\n    case _:\n        print(\"any\")\n    case 0:\n        print(\"zero\")\nend",
+    fixed: "match n:\n    case 0:\n        print(\"zero\")\n    case _:\n        print(\"any\")\nend",
+};
+
+pub const ALL: &[ErrorCode] = &[
+    E0001, E0004, E0023, E0046, E0053, E0061, E0062, E0100, E0101, E0102, E0103, E0104, E0277, E0282, E0283, E0308, E0369, E0373, E0384, E0405,
+    E0407, E0408, E0412, E0425, E0428, E0432, E0434, E0502, E0580, E0583, E0594, E0599, E0603, E0609, E0800, W0001, W0002, W0100, W0101, W0102,
+    W0103, W0104, W0105, W0106, W0107, W0108,
+];
+
+/// A diagnostic by its code, or a lint's warning by the lint's name.
+pub fn lookup(code: &str) -> Option<&'static ErrorCode> {
+    if let Some(lint) = LINTS.iter().find(|lint| lint.name == code) {
+        return Some(lint.code);
+    }
+    ALL.iter().find(|entry| entry.code.eq_ignore_ascii_case(code))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+/// A named warning reported by the lint pass, configurable by name from `[diagnostics]` and `-A`/`-W`/`-D`.
+#[derive(Debug)]
+pub struct Lint {
+    pub name: &'static str,
+    pub code: &'static ErrorCode,
+    pub default: LintLevel,
+}
+
+pub const LINTS: &[Lint] = &[
+    Lint { name: "unused_variables", code: &W0100, default: LintLevel::Warn },
+    Lint { name: "unused_imports", code: &W0101, default: LintLevel::Warn },
+    Lint { name: "unused_functions", code: &W0102, default: LintLevel::Warn },
+    Lint { name: "unreachable_code", code: &W0103, default: LintLevel::Warn },
+    Lint { name: "unused_mut", code: &W0104, default: LintLevel::Warn },
+    Lint { name: "shadowing", code: &W0105, default: LintLevel::Allow },
+    Lint { name: "constant_comparisons", code: &W0106, default: LintLevel::Warn },
+    Lint { name: "empty_unsafe", code: &W0107, default: LintLevel::Warn },
+    Lint { name: "unreachable_patterns", code: &W0108, default: LintLevel::Warn },
+];
+
+/// Resolves a lint name or any warning code to the code it configures.
+pub fn warning_code(name: &str) -> Option<&'static str> {
+    lookup(name).filter(|entry| entry.is_warning()).map(|entry| entry.code)
+}
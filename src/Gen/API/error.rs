@@ -1,3 +1,9 @@
+use miette::{GraphicalReportHandler, LabeledSpan, NamedSource, Severity, SourceCode, SourceSpan};
+use super::codes::{ErrorCode, LintLevel, LINTS};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct SourceLocation {
     pub file: String,
@@ -14,107 +20,319 @@ pub struct ErrorContext {
     pub suggestions: Vec<String>,
 }
 
+/// An edit: the text at `location` (nothing, when `length` is 0) is replaced by `replacement`.
+/// `vix fix` applies the machine-applicable ones to the source files; the rest are only shown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fix {
+    pub message: String,
+    pub location: SourceLocation,
+    pub replacement: String,
+    pub applicable: bool,
+}
+
+impl Fix {
+    pub fn insert(message: impl Into<String>, location: SourceLocation, text: impl Into<String>) -> Self {
+        Self::replace(message, SourceLocation { length: 0, ..location }, text)
+    }
+
+    pub fn replace(message: impl Into<String>, location: SourceLocation, text: impl Into<String>) -> Self {
+        Self { message: message.into(), location, replacement: text.into(), applicable: true }
+    }
+
+    /// A likely but unverified edit, which `vix fix` leaves for the user to apply.
+    pub fn maybe_incorrect(self) -> Self {
+        Self { applicable: false, ..self }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum DiagnosticLevel {
     Error,
     Warning,
 }
 
+/// How diagnostics are written to stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DiagnosticFormat {
+    #[default]
+    Human,
+    /// One JSON object per line, for editors and CI annotators.
+    Json,
+    /// Collected but not printed; the language server publishes them itself.
+    Silent,
+}
+
+/// Output format plus the warning levels from `Vix.toml` and the command line.
+#[derive(Debug, Clone)]
+pub struct DiagnosticOptions {
+    pub format: DiagnosticFormat,
+    /// Warning codes that are silently dropped.
+    pub allow: HashSet<String>,
+    /// Warning codes that are reported as errors.
+    pub deny: HashSet<String>,
+    /// Report every warning that is not allowed as an error (`--deny-warnings`).
+    pub deny_warnings: bool,
+}
+
+impl Default for DiagnosticOptions {
+    fn default() -> Self {
+        let mut options = Self {
+            format: DiagnosticFormat::Human,
+            allow: HashSet::new(),
+            deny: HashSet::new(),
+            deny_warnings: false,
+        };
+        for lint in LINTS {
+            options.set_level(lint.code.code, lint.default);
+        }
+        options
+    }
+}
+
+impl DiagnosticOptions {
+    pub fn set_level(&mut self, code: &str, level: LintLevel) {
+        self.allow.remove(code);
+        self.deny.remove(code);
+        match level {
+            LintLevel::Allow => {
+                self.allow.insert(code.to_string());
+            }
+            LintLevel::Warn => {}
+            LintLevel::Deny => {
+                self.deny.insert(code.to_string());
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Diagnostic {
     pub level: DiagnosticLevel,
     pub code: String,
     pub message: String,
     pub context: ErrorContext,
+    pub fixes: Vec<Fix>,
+    source: Option<NamedSource<String>>,
+    labels: Vec<LabeledSpan>,
+    notes: Vec<String>,
 }
 
 impl Diagnostic {
-    pub fn error(code: &str, message: &str, context: ErrorContext) -> Self {
+    pub fn error(code: &ErrorCode, message: &str, context: ErrorContext) -> Self {
+        Self::new(DiagnosticLevel::Error, code, message, context)
+    }
+
+    pub fn warning(code: &ErrorCode, message: &str, context: ErrorContext) -> Self {
+        Self::new(DiagnosticLevel::Warning, code, message, context)
+    }
+
+    fn new(level: DiagnosticLevel, code: &ErrorCode, message: &str, context: ErrorContext) -> Self {
         Self {
-            level: DiagnosticLevel::Error,
-            code: code.to_string(),
+            level,
+            code: code.code.to_string(),
             message: message.to_string(),
             context,
+            fixes: Vec::new(),
+            source: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
         }
     }
 
-    pub fn warning(code: &str, message: &str, context: ErrorContext) -> Self {
-        Self {
-            level: DiagnosticLevel::Warning,
-            code: code.to_string(),
-            message: message.to_string(),
-            context,
+    pub fn with_fixes(mut self, fixes: impl IntoIterator<Item = Fix>) -> Self {
+        self.fixes.extend(fixes);
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self.level, DiagnosticLevel::Error)
+    }
+
+    /// Attaches the text of the primary location's file so the labels can be rendered.
+    /// Secondary locations in other files become notes.
+    pub fn with_source(mut self, source_code: &str) -> Self {
+        let primary = self.context.primary_location.clone();
+        self.labels.clear();
+        self.notes.clear();
+
+        for (loc, msg) in &self.context.secondary_locations {
+            match location_to_span(source_code, loc).filter(|_| loc.file == primary.file) {
+                Some(span) => self.labels.push(LabeledSpan::new_with_span(Some(msg.clone()), span)),
+                None => self.notes.push(format!("{}:{}:{}: {}", loc.file, loc.line, loc.column, msg)),
+            }
         }
+        // The renderer can't draw labels nested inside an unlabelled primary span, so the
+        // primary underline is only added when it doesn't overlap a labelled one.
+        if let Some(span) = location_to_span(source_code, &primary) {
+            let overlaps = |other: &LabeledSpan| {
+                other.offset() < span.offset() + span.len().max(1) && span.offset() < other.offset() + other.len().max(1)
+            };
+            if self.labels.iter().any(overlaps) {
+                self.labels[0] = LabeledSpan::new_primary_with_span(self.labels[0].label().map(str::to_string), *self.labels[0].inner());
+            } else {
+                self.labels.insert(0, LabeledSpan::new_primary_with_span(None, span));
+            }
+        }
+
+        self.source = Some(NamedSource::new(&primary.file, source_code.to_string()));
+        self
     }
 
-    pub fn display(&self, source_code: &str) -> String {
+    pub fn render(&self) -> String {
         let mut output = String::new();
-        
-        match self.level {
-            DiagnosticLevel::Error => output.push_str(&format!("[Error]: {} Error:\n", self.code)),
-            DiagnosticLevel::Warning => output.push_str(&format!("[Warning]: {} Warning:\n", self.code)),
+        if GraphicalReportHandler::new().render_report(&mut output, self).is_err() {
+            output = format!("{}[{}]: {}", self.level_name(), self.code, self.message);
         }
-        
-        let primary = &self.context.primary_location;
-        output.push_str(&format!("   | {}:{}\n", primary.file, primary.line));
-        output.push_str("   |\n");
-        
-        let lines: Vec<&str> = source_code.lines().collect();
-        
-        let start_line = primary.line.saturating_sub(3);
-        for i in start_line..primary.line.saturating_sub(1) {
-            if i < lines.len() {
-                output.push_str(&format!("{:2} | {}\n", i + 1, lines[i]));
-            }
+        output
+    }
+
+    pub fn format(&self, format: DiagnosticFormat) -> String {
+        match format {
+            DiagnosticFormat::Human | DiagnosticFormat::Silent => self.render(),
+            DiagnosticFormat::Json => self.to_json(),
         }
-        
-        if primary.line > 0 && primary.line <= lines.len() {
-            let line_content = lines[primary.line - 1];
-            output.push_str(&format!("{:2} |> {}\n", primary.line, line_content));
-            
-            output.push_str(&format!("   | {}{}^ {}\n", 
-                " ".repeat(primary.column.saturating_sub(1)),
-                "^".repeat(primary.length.saturating_sub(1).max(0)),
-                self.message
-            ));
+    }
+
+    /// Single-line JSON; spans are byte offsets into the file and are null when unknown.
+    pub fn to_json(&self) -> String {
+        let source = self.source.as_ref().map(|s| s.inner().as_str());
+        let location_json = |loc: &SourceLocation| {
+            let span = source
+                .filter(|_| loc.file == self.context.primary_location.file)
+                .and_then(|source| location_to_span(source, loc))
+                .map(|span| serde_json::json!({ "start": span.offset(), "end": span.offset() + span.len() }));
+            let known = loc.line > 0;
+            serde_json::json!({
+                "file": loc.file,
+                "span": span,
+                "line": known.then_some(loc.line),
+                "column": known.then_some(loc.column),
+            })
+        };
+
+        let mut value = location_json(&self.context.primary_location);
+        let labels: Vec<_> = self
+            .context
+            .secondary_locations
+            .iter()
+            .map(|(loc, msg)| {
+                let mut label = location_json(loc);
+                label["message"] = serde_json::json!(msg);
+                label
+            })
+            .collect();
+        value["severity"] = serde_json::json!(self.level_name());
+        value["code"] = serde_json::json!(self.code);
+        value["message"] = serde_json::json!(self.message);
+        value["help"] = serde_json::json!(self.context.help_message);
+        value["suggestions"] = serde_json::json!(self.context.suggestions);
+        value["labels"] = serde_json::json!(labels);
+        value["fixes"] = serde_json::json!(self
+            .fixes
+            .iter()
+            .map(|fix| {
+                let mut edit = location_json(&fix.location);
+                if let Some(range) = source.filter(|_| fix.location.file == self.context.primary_location.file).and_then(|source| location_to_range(source, &fix.location)) {
+                    edit["span"] = serde_json::json!({ "start": range.start, "end": range.end });
+                }
+                edit["message"] = serde_json::json!(fix.message);
+                edit["replacement"] = serde_json::json!(fix.replacement);
+                edit["applicable"] = serde_json::json!(fix.applicable);
+                edit
+            })
+            .collect::<Vec<_>>());
+        value.to_string()
+    }
+
+    fn level_name(&self) -> &'static str {
+        match self.level {
+            DiagnosticLevel::Error => "error",
+            DiagnosticLevel::Warning => "warning",
         }
-        
-        for (sec_loc, sec_msg) in &self.context.secondary_locations {
-            output.push_str("   |\n");
-            if sec_loc.line > 0 && sec_loc.line <= lines.len() {
-                let line_content = lines[sec_loc.line - 1];
-                output.push_str(&format!("{:2} |> {}\n", sec_loc.line, line_content));
-                output.push_str(&format!("   | {}{} {}\n", 
-                    " ".repeat(sec_loc.column.saturating_sub(1)),
-                    "^".repeat(sec_loc.length.max(1)),
-                    sec_msg
-                ));
-            }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+impl miette::Diagnostic for Diagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(&self.code))
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        Some(match self.level {
+            DiagnosticLevel::Error => Severity::Error,
+            DiagnosticLevel::Warning => Severity::Warning,
+        })
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        let mut help = Vec::new();
+        help.extend(self.context.help_message.clone());
+        help.extend(self.notes.iter().map(|note| format!("note: {}", note)));
+        if !self.context.suggestions.is_empty() || !self.fixes.is_empty() {
+            help.push("suggestions:".to_string());
+            help.extend(self.fixes.iter().map(|fix| match fix.applicable {
+                true => format!("  - {} (run `vix fix` to apply)", fix.message),
+                false => format!("  - {}", fix.message),
+            }));
+            help.extend(self.context.suggestions.iter().map(|s| format!("  - {}", s)));
         }
-        
-        if let Some(help) = &self.context.help_message {
-            output.push_str("   |\n");
-            output.push_str(&format!("   {}\n", "-".repeat(72)));
-            output.push_str("   |-> help:\n");
-            for line in help.lines() {
-                output.push_str(&format!("    {}\n", line));
-            }
+        if help.is_empty() {
+            None
+        } else {
+            Some(Box::new(help.join("\n")))
         }
-        
-        if !self.context.suggestions.is_empty() {
-            output.push_str("   |\n");
-            output.push_str("   |-> suggestions:\n");
-            for suggestion in &self.context.suggestions {
-                output.push_str(&format!("    - {}\n", suggestion));
-            }
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.source.as_ref().map(|source| source as &dyn SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        if self.labels.is_empty() {
+            None
+        } else {
+            Some(Box::new(self.labels.iter().cloned()))
         }
-        
-        output
     }
 }
 
+/// Byte span of a location for rendering; empty locations still get a one-character underline.
+fn location_to_span(source_code: &str, loc: &SourceLocation) -> Option<SourceSpan> {
+    let loc = SourceLocation { length: loc.length.max(1), ..loc.clone() };
+    location_to_range(source_code, &loc).map(SourceSpan::from)
+}
+
+/// Byte range of a 1-based line/column location (columns and lengths count characters).
+pub fn location_to_range(source_code: &str, loc: &SourceLocation) -> Option<Range<usize>> {
+    if loc.line == 0 {
+        return None;
+    }
+    let line_start: usize = source_code.split_inclusive('\n').take(loc.line - 1).map(str::len).sum();
+    if loc.line > 1 && line_start >= source_code.len() {
+        return None;
+    }
+    let line = source_code[line_start..].split('\n').next()?;
+
+    let byte_at = |chars: usize| line.char_indices().nth(chars).map(|(i, _)| i).unwrap_or(line.len());
+    let start = byte_at(loc.column.saturating_sub(1));
+    let end = byte_at(loc.column.saturating_sub(1) + loc.length).max(start);
+    Some(line_start + start..line_start + end)
+}
+
 pub struct DiagnosticHandler {
     diagnostics: Vec<Diagnostic>,
     source_code: String,
+    /// Sources of every file diagnostics may point into, keyed by `SourceLocation::file`.
+    sources: HashMap<String, String>,
+    pub options: DiagnosticOptions,
     max_errors: usize,
     pub error_count: usize,
     pub warning_count: usize,
@@ -125,28 +343,66 @@ impl DiagnosticHandler {
         Self {
             diagnostics: Vec::new(),
             source_code,
+            sources: HashMap::new(),
+            options: DiagnosticOptions::default(),
             max_errors: 100,
             error_count: 0,
             warning_count: 0,
         }
     }
 
-    pub fn error(&mut self, code: &str, message: &str, context: ErrorContext) {
-        let diagnostic = Diagnostic::error(code, message, context);
-        eprintln!("{}", diagnostic.display(&self.source_code));
-        self.diagnostics.push(diagnostic);
-        self.error_count += 1;
-        
-        if self.error_count >= self.max_errors {
-            eprintln!("[Warning]: Reached {} errors, but continuing to generate code...", self.error_count);
-        }
+    pub fn add_source(&mut self, file: impl Into<String>, source_code: impl Into<String>) {
+        self.sources.insert(file.into(), source_code.into());
+    }
+
+    /// The text of the file this handler was created for.
+    pub fn source(&self) -> &str {
+        &self.source_code
     }
 
-    pub fn warning(&mut self, code: &str, message: &str, context: ErrorContext) {
-        let diagnostic = Diagnostic::warning(code, message, context);
-        eprintln!("{}", diagnostic.display(&self.source_code));
+    fn source_for(&self, file: &str) -> &str {
+        self.sources.get(file).unwrap_or(&self.source_code)
+    }
+
+    pub fn error(&mut self, code: &ErrorCode, message: &str, context: ErrorContext) {
+        self.emit(Diagnostic::error(code, message, context));
+    }
+
+    pub fn warning(&mut self, code: &ErrorCode, message: &str, context: ErrorContext) {
+        self.emit(Diagnostic::warning(code, message, context));
+    }
+
+    /// Renders a diagnostic from any stage against the source of the file it points into.
+    /// Allowed warnings are dropped and denied ones are promoted to errors.
+    pub fn emit(&mut self, mut diagnostic: Diagnostic) {
+        if !diagnostic.is_error() {
+            if self.options.allow.contains(&diagnostic.code) {
+                return;
+            }
+            if self.options.deny_warnings || self.options.deny.contains(&diagnostic.code) {
+                diagnostic.level = DiagnosticLevel::Error;
+            }
+        }
+        let file = diagnostic.context.primary_location.file.clone();
+        let diagnostic = diagnostic.with_source(self.source_for(&file));
+        let silent = self.options.format == DiagnosticFormat::Silent;
+        if !silent {
+            eprintln!("{}", diagnostic.format(self.options.format));
+        }
+
+        if diagnostic.is_error() {
+            self.error_count += 1;
+            if self.error_count >= self.max_errors && self.options.format == DiagnosticFormat::Human {
+                eprintln!("[Warning]: Reached {} errors, but continuing to generate code...", self.error_count);
+            }
+        } else {
+            self.warning_count += 1;
+        }
         self.diagnostics.push(diagnostic);
-        self.warning_count += 1;
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
     }
 
     pub fn has_errors(&self) -> bool {
@@ -154,7 +410,7 @@ impl DiagnosticHandler {
     }
 
     pub fn print_summary(&self) {
-        if self.error_count > 0 || self.warning_count > 0 {
+        if self.options.format == DiagnosticFormat::Human && (self.error_count > 0 || self.warning_count > 0) {
             eprintln!("\nCompilation finished with {} error(s) and {} warning(s).", 
                 self.error_count, self.warning_count);
         }
@@ -1,3 +1,4 @@
 pub mod Clang;
 pub mod IRGen;
+pub mod codes;
 pub mod error;
\ No newline at end of file
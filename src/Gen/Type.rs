@@ -14,10 +14,16 @@ pub struct StructDefinition {
 
 pub struct EnumDefinition {
     pub name: String,
-    pub variants: Vec<(String, Option<Type>)>,
+    pub variants: Vec<VariantDefinition>,
     pub is_public: bool,
 }
 
+pub struct VariantDefinition {
+    pub name: String,
+    /// The payload. Fields of a tuple variant are named `field_0`, `field_1`, ... like a tuple's.
+    pub fields: Vec<(String, Type)>,
+}
+
 impl TypeRegistry {
     pub fn new() -> Self {
         Self {
@@ -27,7 +33,7 @@ impl TypeRegistry {
         }
     }
     
-    pub fn register_enum(&mut self, name: String, variants: Vec<(String, Option<Type>)>, is_public: bool) {
+    pub fn register_enum(&mut self, name: String, variants: Vec<VariantDefinition>, is_public: bool) {
         self.enum_definitions.insert(name.clone(), EnumDefinition { 
             name, 
             variants,
@@ -35,28 +41,48 @@ impl TypeRegistry {
         });
     }
     
+    pub fn enum_variants(&self, name: &str) -> Option<&[VariantDefinition]> {
+        self.enum_definitions.get(name).map(|def| def.variants.as_slice())
+    }
+
+    pub fn enum_variant(&self, name: &str, variant: &str) -> Option<&VariantDefinition> {
+        self.enum_variants(name)?.iter().find(|v| v.name == variant)
+    }
+
     pub fn generate_enum_definition(&mut self, name: &str, arch: &ArchConfig) -> Option<String> {
         let enum_def = self.enum_definitions.get(name)?;
         
         let mut def = format!("typedef enum {{\n");
         
-        for (i, (variant_name, _)) in enum_def.variants.iter().enumerate() {
-            def.push_str(&format!("    {}__{} = {},\n", name, variant_name, i));
+        for (i, variant) in enum_def.variants.iter().enumerate() {
+            def.push_str(&format!("    {}__{} = {},\n", name, variant.name, i));
         }
         
         def.push_str(&format!("}} {}_Tag;\n\n", name));
         def.push_str(&format!("typedef struct {{\n"));
-        def.push_str(&format!("    {}_Tag tag;\n", name));
-        def.push_str("    union {\n");
-        
-        for (variant_name, variant_type) in &enum_def.variants {
-            if let Some(ty) = variant_type {
-                let c_type = ty.to_c_type(&arch);
-                def.push_str(&format!("        {} {};\n", c_type, variant_name));
+
+        // The tag is the smallest integer that numbers every variant, and the union of the
+        // payloads is only as large as the largest one.
+        let tag_type = match arch.tag_bits_for_variants(enum_def.variants.len()) {
+            8 => "uint8_t",
+            16 => "uint16_t",
+            _ => "uint32_t",
+        };
+        def.push_str(&format!("    {} tag;\n", tag_type));
+
+        let payloads: Vec<&VariantDefinition> = enum_def.variants.iter().filter(|v| !v.fields.is_empty()).collect();
+        if !payloads.is_empty() {
+            def.push_str("    union {\n");
+            for variant in payloads {
+                def.push_str("        struct {\n");
+                for (field, ty) in &variant.fields {
+                    def.push_str(&format!("            {} {};\n", ty.to_c_type(arch), field));
+                }
+                def.push_str(&format!("        }} {};\n", variant.name));
             }
+            def.push_str("    } data;\n");
         }
-        
-        def.push_str(&format!("    }} data;\n}} {};\n", name));
+        def.push_str(&format!("}} {};\n", name));
         
         Some(def)
     }
@@ -139,6 +165,36 @@ impl TypeRegistry {
             Type::Option { inner } => self.generate_option_definition(inner, arch),
             Type::Result { ok, err } => self.generate_result_definition(ok, err, arch),
             Type::Tuple { fields } => self.generate_tuple_definition(fields, arch),
+            Type::FnPtr { params, return_type } | Type::Closure { params, return_type } => {
+                let type_id = ty.to_c_type(arch);
+                if self.generated_types.contains_key(&type_id) {
+                    return None;
+                }
+                let c_params: Vec<String> = params.iter().map(|p| p.to_c_type(arch)).collect();
+                let c_return = return_type.to_c_type(arch);
+                // A closure is its function, which takes the captured environment first, and
+                // that environment. `FnPtr_..._call` lets a plain function pointer stand in as a
+                // closure's function, with the pointer as its environment.
+                let def = if matches!(ty, Type::FnPtr { .. }) {
+                    let args: Vec<String> = (0..params.len()).map(|i| format!("a{}", i)).collect();
+                    let typed_args: Vec<String> = c_params.iter().zip(&args).map(|(ty, arg)| format!(", {} {}", ty, arg)).collect();
+                    let ret = if matches!(**return_type, Type::Void) { "" } else { "return " };
+                    format!(
+                        "typedef {0} (*{1})({2});\nstatic {0} {1}_call(void* f{3}) {{ {4}(({1})f)({5}); }}\n",
+                        c_return,
+                        type_id,
+                        if c_params.is_empty() { "void".to_string() } else { c_params.join(", ") },
+                        typed_args.concat(),
+                        ret,
+                        args.join(", ")
+                    )
+                } else {
+                    let c_params: Vec<String> = std::iter::once("void*".to_string()).chain(c_params).collect();
+                    format!("typedef struct {{\n    {} (*call)({});\n    void* env;\n}} {};\n", c_return, c_params.join(", "), type_id)
+                };
+                self.generated_types.insert(type_id, def.clone());
+                Some(def)
+            }
             
             Type::Union { variants } => {
                 let type_id = self.get_union_type_id(variants);
@@ -310,12 +366,12 @@ impl Type {
             Type::MultiArray { element, dimensions: _ } => {element.to_c_type(arch)}
             Type::Variadic => "...".to_string(),
             Type::Any => "void*".to_string(),
-            Type::Trait => "void*".to_string(),
+            Type::Trait { name } => format!("{}_dyn", name),
+            Type::Param { name } => name.clone(),
+            Type::Generic { name, args } => Type::mangle(name, args),
             Type::Owned(inner) | Type::Ref(inner) | Type::MutRef(inner) => format!("{}*", inner.to_c_type(arch)),
-            Type::FnPtr { params, return_type } => {
-                let param_types: Vec<String> = params.iter().map(|p| p.to_c_type(arch)).collect();
-                format!("{} (*)({})", return_type.to_c_type(arch), param_types.join(", "))
-            }
+            Type::FnPtr { params, return_type } => Type::signature_id("FnPtr", params, return_type),
+            Type::Closure { params, return_type } => Type::signature_id("Closure", params, return_type),
 
             Type::Tuple { fields } => {
                 let names: Vec<String> = fields.iter().map(|f| TypeRegistry::sanitize_type_name(&f.name())).collect();
@@ -354,7 +410,9 @@ impl Type {
             Type::Tuple { fields } => fields.iter().map(|f| f.size_bits(arch)).sum(),
             Type::Option { inner } => 8 + inner.size_bits(arch),
             Type::SelfType => 10,
-            Type::Any | Type::Trait => arch.pointer_bits,
+            Type::Any => arch.pointer_bits,
+            Type::Trait { .. } | Type::Closure { .. } => arch.pointer_bits * 2,
+            Type::Param { .. } | Type::Generic { .. } => arch.pointer_bits,
             Type::Owned(_) | Type::Ref(_) | Type::MutRef(_) => arch.pointer_bits,
             Type::Union { variants } => {
                 let tag_bits = arch.tag_bits_for_variants(variants.len());
@@ -388,7 +446,7 @@ impl Type {
             Type::Union { variants } => {variants.iter().map(|v| v.alignment(arch)).max().unwrap_or(1)}
             Type::Option { inner } | Type::Result { ok: inner, .. } => {inner.alignment(arch).max(1)}
             Type::SelfType => 10,
-            Type::Any | Type::Trait => arch.pointer_align,
+            Type::Any | Type::Trait { .. } | Type::Closure { .. } | Type::Param { .. } | Type::Generic { .. } => arch.pointer_align,
             Type::Owned(_) | Type::Ref(_) | Type::MutRef(_) => arch.pointer_align,
         }
     }
@@ -418,7 +476,12 @@ impl Type {
             Type::Result { ok, err } => format!("Result<{}, {}>", ok.name(), err.name()),
             Type::Variadic => "...".to_string(),
             Type::Any => "any".to_string(),
-            Type::Trait => "trait".to_string(),
+            Type::Trait { name } => format!("dyn {}", name),
+            Type::Param { name } => name.clone(),
+            Type::Generic { name, args } => {
+                let names: Vec<String> = args.iter().map(|a| a.name()).collect();
+                format!("{}[{}]", name, names.join(", "))
+            }
             Type::Owned(inner) => format!("~{}", inner.name()),
             Type::Ref(inner) => format!("&{}", inner.name()),
             Type::MutRef(inner) => format!("&mut {}", inner.name()),
@@ -440,8 +503,107 @@ impl Type {
             }
             Type::FnPtr { params, return_type } => {
                 let param_names: Vec<String> = params.iter().map(|p| p.name()).collect();
-                format!("fn({}) -> {}", param_names.join(", "), return_type.name())
+                format!("^func({}): {}", param_names.join(", "), return_type.name())
+            }
+            Type::Closure { params, return_type } => {
+                let param_names: Vec<String> = params.iter().map(|p| p.name()).collect();
+                format!("func({}): {}", param_names.join(", "), return_type.name())
             }
         }
     }
-}
\ No newline at end of file
+}
+/// Generic functions and structs are monomorphized: each set of type arguments, found by
+/// unifying the declared types with the concrete ones, gets its own copy under a mangled name.
+impl Type {
+    /// `Pair[int32, str]` becomes `Pair_int32_str`.
+    pub fn mangle(name: &str, args: &[Type]) -> String {
+        let names: Vec<String> = args.iter().map(|a| TypeRegistry::sanitize_type_name(&a.name())).collect();
+        format!("{}_{}", name, names.join("_"))
+    }
+
+    /// The C name of a function type, `FnPtr_int32_int32` for `^func(int32): int32`.
+    pub fn signature_id(prefix: &str, params: &[Type], return_type: &Type) -> String {
+        let parts: Vec<Type> = params.iter().chain(std::iter::once(return_type)).cloned().collect();
+        Type::mangle(prefix, &parts)
+    }
+
+    /// Rebuilds the type, replacing every part for which `f` returns a type.
+    pub fn map(&self, f: &mut dyn FnMut(&Type) -> Option<Type>) -> Type {
+        if let Some(mapped) = f(self) {
+            return mapped;
+        }
+        match self {
+            Type::Const(inner) => Type::Const(Box::new(inner.map(f))),
+            Type::Ptr(inner) => Type::Ptr(Box::new(inner.map(f))),
+            Type::RawPtr(inner) => Type::RawPtr(Box::new(inner.map(f))),
+            Type::Owned(inner) => Type::Owned(Box::new(inner.map(f))),
+            Type::Ref(inner) => Type::Ref(Box::new(inner.map(f))),
+            Type::MutRef(inner) => Type::MutRef(Box::new(inner.map(f))),
+            Type::Array { element, size } => Type::Array { element: Box::new(element.map(f)), size: *size },
+            Type::MultiArray { element, dimensions } => {
+                Type::MultiArray { element: Box::new(element.map(f)), dimensions: dimensions.clone() }
+            }
+            Type::Option { inner } => Type::Option { inner: Box::new(inner.map(f)) },
+            Type::Result { ok, err } => Type::Result { ok: Box::new(ok.map(f)), err: Box::new(err.map(f)) },
+            Type::Tuple { fields } => Type::Tuple { fields: fields.iter().map(|t| t.map(f)).collect() },
+            Type::Union { variants } => Type::Union { variants: variants.iter().map(|t| t.map(f)).collect() },
+            Type::Intersection { types } => Type::Intersection { types: types.iter().map(|t| t.map(f)).collect() },
+            Type::FnPtr { params, return_type } => Type::FnPtr {
+                params: params.iter().map(|t| t.map(f)).collect(),
+                return_type: Box::new(return_type.map(f)),
+            },
+            Type::Closure { params, return_type } => Type::Closure {
+                params: params.iter().map(|t| t.map(f)).collect(),
+                return_type: Box::new(return_type.map(f)),
+            },
+            Type::Generic { name, args } => Type::Generic { name: name.clone(), args: args.iter().map(|t| t.map(f)).collect() },
+            other => other.clone(),
+        }
+    }
+
+    /// Replaces the type parameters bound in `bindings`.
+    pub fn substitute(&self, bindings: &HashMap<String, Type>) -> Type {
+        self.map(&mut |ty| match ty {
+            Type::Param { name } => bindings.get(name).cloned(),
+            _ => None,
+        })
+    }
+
+    /// Binds the type parameters in `self` to the matching parts of `concrete`. Fails with the
+    /// name of a parameter that is already bound to a different type; mismatches elsewhere are
+    /// left to the usual compatibility check.
+    pub fn unify(&self, concrete: &Type, bindings: &mut HashMap<String, Type>) -> Result<(), String> {
+        match (self, concrete) {
+            (Type::Param { .. }, Type::Any) => Ok(()),
+            (Type::Param { name }, _) => match bindings.get(name) {
+                Some(bound) if bound != concrete => Err(name.clone()),
+                Some(_) => Ok(()),
+                None => {
+                    bindings.insert(name.clone(), concrete.clone());
+                    Ok(())
+                }
+            },
+            (Type::Const(a), Type::Const(b))
+            | (Type::Ptr(a), Type::Ptr(b))
+            | (Type::RawPtr(a), Type::RawPtr(b))
+            | (Type::Owned(a), Type::Owned(b))
+            | (Type::Ref(a), Type::Ref(b))
+            | (Type::MutRef(a), Type::MutRef(b))
+            | (Type::Array { element: a, .. }, Type::Array { element: b, .. })
+            | (Type::Option { inner: a }, Type::Option { inner: b }) => a.unify(b, bindings),
+            (Type::Result { ok: a_ok, err: a_err }, Type::Result { ok: b_ok, err: b_err }) => {
+                a_ok.unify(b_ok, bindings)?;
+                a_err.unify(b_err, bindings)
+            }
+            (Type::Tuple { fields: a }, Type::Tuple { fields: b }) if a.len() == b.len() => {
+                a.iter().zip(b).try_for_each(|(a, b)| a.unify(b, bindings))
+            }
+            (Type::Generic { name: a_name, args: a }, Type::Generic { name: b_name, args: b })
+                if a_name == b_name && a.len() == b.len() =>
+            {
+                a.iter().zip(b).try_for_each(|(a, b)| a.unify(b, bindings))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::import::*;
+
+/// A lambda is lifted to a `static` function `vix_lambda_N`. One that captures nothing is a plain
+/// `FnPtr_...`; one that does gets a `vix_lambda_N_env` struct built where it is written, and the
+/// `Closure_...` value pairs the lifted function with a pointer to that environment. `mut`
+/// variables are captured by reference, so the lambda sees and makes changes to them; every
+/// other variable is copied in.
+impl Codegen {
+    pub(crate) fn codegen_lambda(&mut self, params: &[(String, Type)], return_type: &Type, lambda_body: &Expr, body: &mut String) -> Result<(String, Type), ()> {
+        let name = format!("vix_lambda_{}", self.lambda_count);
+        self.lambda_count += 1;
+
+        // (name, C name, type, by reference) of every variable the lambda captures.
+        let captures: Vec<(String, String, Type, bool)> = free_variables(params, lambda_body)
+            .into_iter()
+            .filter_map(|var| {
+                let (c_name, ty) = self.vars.get(&var)?.clone();
+                let by_ref = self.mutable_vars.contains(&c_name);
+                Some((var, c_name, ty, by_ref))
+            })
+            .collect();
+        let fn_type = Type::FnPtr { params: params.iter().map(|(_, ty)| ty.clone()).collect(), return_type: Box::new(return_type.clone()) };
+        self.ensure_type_defined(&fn_type);
+
+        let saved_vars = self.vars.clone();
+        let saved_mutable_vars = self.mutable_vars.clone();
+        let saved_return_type = self.current_return_type.replace(return_type.clone());
+        let saved_dyn_temps = std::mem::take(&mut self.dyn_temps);
+        let mut c_params = Vec::new();
+        if !captures.is_empty() {
+            c_params.push("void* vix_env_ptr".to_string());
+        }
+        for (param, ty) in params {
+            let c_name = self.local_c_name(param);
+            c_params.push(format!("{} {}", ty.to_c_type(&self.arch), c_name));
+            self.vars.insert(param.clone(), (c_name, ty.clone()));
+        }
+        let mut fields = String::new();
+        let mut inits = Vec::new();
+        for (var, c_name, ty, by_ref) in &captures {
+            let field = self.local_c_name(var);
+            let c_type = ty.to_c_type(&self.arch);
+            if *by_ref {
+                fields.push_str(&format!("    {}* {};\n", c_type, field));
+                inits.push(format!(".{} = &{}", field, c_name));
+                let access = format!("(*vix_env->{})", field);
+                self.mutable_vars.insert(access.clone());
+                self.vars.insert(var.clone(), (access, ty.clone()));
+            } else {
+                fields.push_str(&format!("    {} {};\n", c_type, field));
+                inits.push(format!(".{} = {}", field, c_name));
+                self.vars.insert(var.clone(), (format!("vix_env->{}", field), ty.clone()));
+            }
+        }
+
+        let mut lifted_body = String::new();
+        if !captures.is_empty() {
+            lifted_body.push_str(&format!("{0}_env* vix_env = vix_env_ptr;\n", name));
+        }
+        let result = self.codegen_expr(lambda_body, &mut lifted_body);
+        let value = match (&result, matches!(return_type, Type::Void)) {
+            (Ok((var, ty)), false) => Some(self.coerce_to_owned(var.clone(), ty, return_type, &mut lifted_body)),
+            _ => None,
+        };
+        self.free_dyn_temps(0, &mut lifted_body);
+        if let Some(value) = value {
+            lifted_body.push_str(&format!("return {};\n", value));
+        }
+        self.dyn_temps = saved_dyn_temps;
+        self.vars = saved_vars;
+        self.mutable_vars = saved_mutable_vars;
+        self.current_return_type = saved_return_type;
+        result?;
+
+        let c_params = if c_params.is_empty() { "void".to_string() } else { c_params.join(", ") };
+        self.ir.functions.push_str(&format!("static {} {}({}) {{\n{}}}\n\n", return_type.to_c_type(&self.arch), name, c_params, lifted_body));
+        if captures.is_empty() {
+            return Ok((name, fn_type));
+        }
+
+        let Type::FnPtr { params, return_type } = fn_type else { unreachable!() };
+        let closure_type = Type::Closure { params, return_type };
+        self.ensure_type_defined(&closure_type);
+        self.ir.forward_decls.push_str(&format!("typedef struct {{\n{}}} {}_env;\n\n", fields, name));
+        let env = self.fresh_var();
+        let tmp = self.fresh_var();
+        body.push_str(&format!("{}_env {} = {{ {} }};\n", name, env, inits.join(", ")));
+        body.push_str(&format!("{} {} = {{ {}, &{} }};\n", closure_type.to_c_type(&self.arch), tmp, name, env));
+        Ok((tmp, closure_type))
+    }
+
+    /// `&func` is the function itself, as a pointer.
+    pub(crate) fn codegen_func_addr(&mut self, name: &str, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let Some((params, return_type)) = self.user_functions.get(name).cloned() else {
+            self.diagnostics.error(
+                &codes::E0425,
+                &format!("Cannot find function '{}' in this scope", name),
+                undefined_function_error(name, 0, loc),
+            );
+            return Err(());
+        };
+        let fn_type = Type::FnPtr { params: params.into_iter().map(|(_, ty)| ty).collect(), return_type: Box::new(return_type) };
+        self.ensure_type_defined(&fn_type);
+        let c_name = if name == "main" { "vix_main".to_string() } else { name.to_string() };
+        Ok((c_name, fn_type))
+    }
+
+    /// A call through a variable holding a function: a closure passes its environment first.
+    pub(crate) fn codegen_indirect_call(&mut self, callee: &str, ty: &Type, args: &[Expr], body: &mut String) -> Result<(String, Type), ()> {
+        let (Type::FnPtr { params, return_type } | Type::Closure { params, return_type }) = ty else {
+            return Err(());
+        };
+        let mut arg_vars = Vec::new();
+        if matches!(ty, Type::Closure { .. }) {
+            arg_vars.push(format!("{}.env", callee));
+        }
+        for (arg, param_ty) in args.iter().zip(params) {
+            let (var, arg_ty) = self.codegen_expr(arg, body).check_error();
+            arg_vars.push(self.coerce_to_trait_local(var, &arg_ty, param_ty, body));
+        }
+        let function = if matches!(ty, Type::Closure { .. }) { format!("{}.call", callee) } else { callee.to_string() };
+        let call = format!("{}({})", function, arg_vars.join(", "));
+        if matches!(**return_type, Type::Void) {
+            body.push_str(&format!("{};\n", call));
+            return Ok((String::new(), Type::Void));
+        }
+        let tmp = self.fresh_var();
+        body.push_str(&format!("{} {} = {};\n", return_type.to_c_type(&self.arch), tmp, call));
+        Ok((tmp, (**return_type).clone()))
+    }
+
+    /// The C name and type of `name` when it is a variable holding a function.
+    pub fn function_var(&self, name: &str) -> Option<(String, Type)> {
+        self.vars.get(name).filter(|(_, ty)| matches!(ty, Type::FnPtr { .. } | Type::Closure { .. })).cloned()
+    }
+}
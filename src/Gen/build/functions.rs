@@ -309,7 +309,7 @@ impl Codegen {
         if let Type::Result { ok, .. } = &result_type {
             if !self.types_compatible(ok, &val_ty) {
                 self.diagnostics.error(
-                    "TypeMismatch",
+                    &codes::E0308,
                     &format!("Cannot return {} in Result with OK type {}", val_ty.name(), ok.name()),
                     type_mismatch_error(&ok.name(), &val_ty.name(), value.location(), value.location())
                 );
@@ -347,7 +347,7 @@ impl Codegen {
         if let Type::Result { err, .. } = &result_type {
             if !self.types_compatible(err, &val_ty) {
                 self.diagnostics.error(
-                    "TypeMismatch",
+                    &codes::E0308,
                     &format!("Cannot return {} in Result with ERR type {}", val_ty.name(), err.name()),
                     type_mismatch_error(&err.name(), &val_ty.name(), value.location(), value.location())
                 );
@@ -366,29 +366,35 @@ impl Codegen {
             Type::Option { inner } => {
                 let c_type = inner.to_c_type(&self.arch);
  
-                body.push_str(&format!("if ({}.tag == 0) {{\n", var));
-                body.push_str("    fprintf(stderr, \"unwrap called on None\\n\");\n");
-                body.push_str("    exit(1);\n");
-                body.push_str("}\n");
+                if self.config.runtime_checks {
+                    body.push_str(&format!("if ({}.tag == 0) {{\n", var));
+                    body.push_str("    fprintf(stderr, \"unwrap called on None\\n\");\n");
+                    body.push_str("    exit(1);\n");
+                    body.push_str("}\n");
+                }
                 body.push_str(&format!("{} {} = {}.value;\n", c_type, tmp, var));
                 Ok((tmp, *inner.clone()))
             }
             Type::Result { ok, .. } => {
                 let c_type = ok.to_c_type(&self.arch);
  
-                body.push_str(&format!("if ({}.tag != 0) {{\n", var));
-                body.push_str("    fprintf(stderr, \"unwrap called on Err\\n\");\n");
-                body.push_str("    exit(1);\n");
-                body.push_str("}\n");
+                if self.config.runtime_checks {
+                    body.push_str(&format!("if ({}.tag != 0) {{\n", var));
+                    body.push_str("    fprintf(stderr, \"unwrap called on Err\\n\");\n");
+                    body.push_str("    exit(1);\n");
+                    body.push_str("}\n");
+                }
                 body.push_str(&format!("{} {} = {}.data.ok;\n", c_type, tmp, var));
                 Ok((tmp, *ok.clone()))
             }
             Type::Ptr(inner) => {
                 let c_type = inner.to_c_type(&self.arch);
-                body.push_str(&format!("if ({} == NULL) {{\n", var));
-                body.push_str("    fprintf(stderr, \"unwrap called on null pointer\\n\");\n");
-                body.push_str("    exit(1);\n");
-                body.push_str("}\n");
+                if self.config.runtime_checks {
+                    body.push_str(&format!("if ({} == NULL) {{\n", var));
+                    body.push_str("    fprintf(stderr, \"unwrap called on null pointer\\n\");\n");
+                    body.push_str("    exit(1);\n");
+                    body.push_str("}\n");
+                }
                 body.push_str(&format!("{} {} = *{};\n", c_type, tmp, var));
                 Ok((tmp, *inner.clone()))
             }
@@ -545,8 +551,16 @@ impl Codegen {
         body: &mut String,
         loc: SourceLocation,
     ) -> Result<(String, Type), ()> {
+        if let Some(enum_name) = self.enum_name_of(obj) {
+            let args: Vec<(Option<&str>, &Expr)> = args.iter().map(|arg| (None, arg)).collect();
+            return self.codegen_enum_new(enum_name, method, &args, body);
+        }
         let (obj_var, obj_ty) = self.codegen_expr(obj, body).check_error();
 
+        if let Type::Trait { name } = &obj_ty {
+            return self.codegen_dyn_method_call(&obj_var, name, method, args, body, loc);
+        }
+
         let struct_name = match &obj_ty {
             Type::Struct { name } => name.clone(),
             Type::Ref(inner) | Type::MutRef(inner) => {
@@ -561,15 +575,15 @@ impl Codegen {
 
         let method_full_name = format!("{}_{}", struct_name, method);
 
-        let (return_type, is_instance) = if let Some((_, ret_ty, is_inst)) = self.impl_methods.get(&(struct_name.clone(), method.to_string())) {
-            (ret_ty.clone(), *is_inst)
+        let (params, return_type, is_instance) = if let Some((params, ret_ty, is_inst)) = self.impl_methods.get(&(struct_name.clone(), method.to_string())) {
+            (params.clone(), ret_ty.clone(), *is_inst)
         } else {
              
             let prefixed_name = format!("{}_{}", struct_name, method);
-            if let Some((_, ret_ty)) = self.user_functions.get(&prefixed_name) {
-                (ret_ty.clone(), false)
+            if let Some((params, ret_ty)) = self.user_functions.get(&prefixed_name) {
+                (params.clone(), ret_ty.clone(), false)
             } else {
-                (Type::Void, false)
+                (Vec::new(), Type::Void, false)
             }
         };
 
@@ -582,9 +596,12 @@ impl Codegen {
             }
         }
 
-        for arg in args {
-            let (var, _ty) = self.codegen_expr(arg, body).check_error();
-            arg_vars.push(var);
+        for (i, arg) in args.iter().enumerate() {
+            let (var, ty) = self.codegen_expr(arg, body).check_error();
+            match params.get(i) {
+                Some((_, param_ty)) => arg_vars.push(self.coerce_to_trait_local(var, &ty, param_ty, body)),
+                None => arg_vars.push(var),
+            }
         }
 
         let tmp = self.fresh_var();
@@ -660,8 +677,9 @@ impl Codegen {
     }
 
     pub fn codegen_panic(&mut self, expr: &Expr, body: &mut String) -> Result<(String, Type), ()> {
-        let (msg_var, _) = self.codegen_expr(expr, body) .check_error();
+        let (msg_var, msg_ty) = self.codegen_expr(expr, body) .check_error();
         let tmp = self.fresh_var();
+        let msg_var = if matches!(msg_ty, Type::Str { .. }) { format!("{}.ptr", msg_var) } else { msg_var };
         
         body.push_str(&format!("fprintf(stderr, \"panic: %s\\n\", {});\n", msg_var));
         body.push_str("exit(1);\n");
@@ -669,4 +687,19 @@ impl Codegen {
         
         Ok((tmp, Type::Void))
     }
+
+    /// A failed `assert` ends the program, and under `vix test` only the current test, with its file and line.
+    pub(crate) fn codegen_assert(&mut self, cond: &Expr, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let (cond_var, _) = self.codegen_expr(cond, body) .check_error();
+        let tmp = self.fresh_var();
+        let file = loc.file.replace('\\', "\\\\").replace('"', "\\\"");
+
+        body.push_str(&format!("if (!({})) {{\n", cond_var));
+        body.push_str(&format!("    fprintf(stderr, \"assertion failed at %s:%d\\n\", \"{}\", {});\n", file, loc.line));
+        body.push_str("    exit(1);\n");
+        body.push_str("}\n");
+        body.push_str(&format!("int {} = 0;\n", tmp));
+
+        Ok((tmp, Type::Void))
+    }
 }
\ No newline at end of file
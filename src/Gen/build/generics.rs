@@ -0,0 +1,211 @@
+use crate::import::*;
+use crate::Token::Helper::visit::{walk_expr, walk_stmt, VisitMut};
+
+/// Generics are monomorphized on demand: the first use of a generic function or struct with a
+/// set of type arguments emits a copy of it under the mangled name, e.g. `max_int32` or
+/// `Pair_int32_str`, with every type parameter replaced by its argument. Instances are `static`,
+/// so each translation unit that uses one has its own copy.
+impl Codegen {
+    /// `ty` with each generic struct it mentions replaced by that struct's instance.
+    pub fn concrete_type(&mut self, ty: &Type) -> Type {
+        let mut generics = Vec::new();
+        ty.map(&mut |part| {
+            if let Type::Generic { name, args } = part {
+                generics.push((name.clone(), args.clone()));
+            }
+            None
+        });
+        for (name, args) in generics.into_iter().rev() {
+            self.instantiate_struct(&name, &args);
+        }
+
+        ty.map(&mut |part| match part {
+            Type::Generic { name, args } => Some(Type::Struct { name: Type::mangle(name, args) }),
+            _ => None,
+        })
+    }
+
+    /// The inverse of `concrete_type`, so type arguments can be read back off a value's type.
+    fn generic_form(&self, ty: &Type) -> Type {
+        ty.map(&mut |part| match part {
+            Type::Struct { name } => self.struct_instances.get(name).map(|generic| self.generic_form(generic)),
+            _ => None,
+        })
+    }
+
+    fn instantiate_struct(&mut self, name: &str, args: &[Type]) {
+        let mangled = Type::mangle(name, args);
+        if self.structs.contains_key(&mangled) {
+            return;
+        }
+        let Some(template) = self.generic_structs.get(name).cloned() else {
+            let loc = self.default_location();
+            self.diagnostics.error(
+                &codes::E0412,
+                &format!("Struct '{}' is not generic", name),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some(format!("Only a struct declared as `struct {}[T]:` takes type arguments.", name)),
+                    suggestions: vec![],
+                },
+            );
+            return;
+        };
+
+        self.struct_instances.insert(mangled.clone(), Type::Generic { name: name.to_string(), args: args.to_vec() });
+        let bindings = template.type_params.iter().map(|(param, _)| param.clone()).zip(args.iter().cloned()).collect();
+        let mut instance = template;
+        instance.name = mangled;
+        instance.type_params.clear();
+        for field in &mut instance.fields {
+            field.ty = self.concrete_type(&field.ty.substitute(&bindings));
+        }
+        let _ = self.codegen_struct_definition(&instance);
+    }
+
+    /// A copy of `func` with `bindings` applied to the types of its signature and body.
+    pub fn monomorphize(&mut self, func: &Function, bindings: &HashMap<String, Type>) -> Function {
+        let mut func = func.clone();
+        for (_, ty, _) in &mut func.params {
+            *ty = self.concrete_type(&ty.substitute(bindings));
+        }
+        func.return_type = self.concrete_type(&func.return_type.substitute(bindings));
+        Monomorphizer { codegen: self, bindings }.visit_body(&mut func.body);
+        func
+    }
+
+    /// An impl block whose types may mention generic structs, with those instantiated.
+    pub fn monomorphize_impl(&mut self, impl_block: &ImplBlock) -> ImplBlock {
+        let bindings = HashMap::new();
+        let mut impl_block = impl_block.clone();
+        for method in &mut impl_block.methods {
+            for (_, ty, _) in &mut method.params {
+                *ty = self.concrete_type(ty);
+            }
+            method.return_type = self.concrete_type(&method.return_type);
+            Monomorphizer { codegen: self, bindings: &bindings }.visit_body(&mut method.body);
+        }
+        impl_block
+    }
+
+    /// A call to a generic function. Its type arguments come from the argument types; the
+    /// instance for them is declared now and its body emitted after the other functions.
+    pub(crate) fn codegen_generic_call(&mut self, template: &Function, args: &[Expr], body: &mut String) -> Result<(String, Type), ()> {
+        let mut values = Vec::new();
+        let mut bindings = HashMap::new();
+        for (arg, (_, param_ty, _)) in args.iter().zip(&template.params) {
+            let (var, ty) = self.codegen_expr(arg, body).check_error();
+            let _ = param_ty.unify(&self.generic_form(&ty), &mut bindings);
+            values.push((var, ty));
+        }
+
+        let type_args: Vec<Type> = template
+            .type_params
+            .iter()
+            .map(|(param, _)| bindings.get(param).cloned().unwrap_or(Type::Any))
+            .collect();
+        let name = Type::mangle(&template.name, &type_args);
+        if !self.user_functions.contains_key(&name) {
+            let mut instance = self.monomorphize(template, &bindings);
+            instance.name = name.clone();
+            instance.type_params.clear();
+
+            // Declaring the instance resets the local state of the function being generated.
+            let vars = std::mem::take(&mut self.vars);
+            let var_count = self.var_count;
+            self.generic_instances.insert(name.clone(), instance.clone());
+            self.codegen_function(&instance, true);
+            self.vars = vars;
+            self.var_count = var_count;
+        }
+        if self.unit_instances.insert(name.clone()) {
+            self.pending_instances.push(self.generic_instances[&name].clone());
+        }
+
+        let (params, return_type) = self.user_functions[&name].clone();
+        let mut arg_vars = Vec::new();
+        for ((var, ty), (_, param_ty)) in values.into_iter().zip(&params) {
+            arg_vars.push(self.coerce_to_trait_local(var, &ty, param_ty, body));
+        }
+        let call = format!("{}({})", name, arg_vars.join(", "));
+        if matches!(return_type, Type::Void) {
+            body.push_str(&format!("{};\n", call));
+            return Ok((String::new(), Type::Void));
+        }
+        let tmp = self.fresh_var();
+        body.push_str(&format!("{} {} = {};\n", return_type.to_c_type(&self.arch), tmp, call));
+        Ok((tmp, return_type))
+    }
+
+    /// A generic struct constructor: the instance follows from the types of the fields given.
+    /// Arguments without a field name are matched to the fields in order.
+    pub(crate) fn codegen_generic_struct_new(&mut self, name: &str, args: &[(Option<&str>, &Expr)], body: &mut String) -> Result<(String, Type), ()> {
+        let template = self.generic_structs[name].clone();
+        let mut arg_vars = Vec::new();
+        let mut bindings = HashMap::new();
+        for (i, (field, arg)) in args.iter().enumerate() {
+            let (var, ty) = self.codegen_expr(arg, body).check_error();
+            let declared = match field {
+                Some(field) => template.fields.iter().find(|f| f.name == *field),
+                None => template.fields.get(i),
+            };
+            if let Some(declared) = declared {
+                let _ = declared.ty.unify(&self.generic_form(&ty), &mut bindings);
+            }
+            arg_vars.push((*field, var, ty));
+        }
+
+        let type_args = template
+            .type_params
+            .iter()
+            .map(|(param, _)| bindings.get(param).cloned().unwrap_or(Type::Any))
+            .collect();
+        let ty = self.concrete_type(&Type::Generic { name: name.to_string(), args: type_args });
+        let c_type = ty.to_c_type(&self.arch);
+        let arg_vars = self.constructor_args(&c_type, arg_vars, body);
+        let tmp = self.fresh_var();
+        body.push_str(&format!("{} {} = {}_new({});\n", c_type, tmp, c_type, arg_vars.join(", ")));
+        Ok((tmp, ty))
+    }
+}
+
+/// Rewrites the types written inside a function body: type parameters become their arguments
+/// and generic structs their instances.
+struct Monomorphizer<'a> {
+    codegen: &'a mut Codegen,
+    bindings: &'a HashMap<String, Type>,
+}
+
+impl Monomorphizer<'_> {
+    fn rewrite(&mut self, ty: &mut Type) {
+        *ty = self.codegen.concrete_type(&ty.substitute(self.bindings));
+    }
+}
+
+impl VisitMut for Monomorphizer<'_> {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        if let Stmt::TypedDeclaration { ty, .. } = stmt {
+            self.rewrite(ty);
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        match &mut expr.kind {
+            ExprKind::Cast(_, CastTarget::Type(ty))
+            | ExprKind::ReferenceTo(ty)
+            | ExprKind::SizeOf(ty)
+            | ExprKind::AlignOf(ty)
+            | ExprKind::Type(ty) => self.rewrite(ty),
+            ExprKind::Lambda { params, return_type, .. } => {
+                for (_, ty) in params {
+                    self.rewrite(ty);
+                }
+                self.rewrite(return_type);
+            }
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+}
@@ -1,7 +1,10 @@
+pub mod closures;
 pub mod functions;
 pub mod generate_type;
+pub mod generics;
 pub mod operation;
 pub mod stmt;
+pub mod traits;
 pub mod unknow;
 pub mod var;
 pub mod std;
\ No newline at end of file
@@ -15,7 +15,7 @@ impl Codegen {
  
         if matches!(l_ty, Type::Void) {
             self.diagnostics.error(
-                "VoidOperand",
+                &codes::E0277,
                 &format!("Left operand of '{}' cannot be void", op),
                 ErrorContext {
                     primary_location: loc.clone(),
@@ -29,7 +29,7 @@ impl Codegen {
 
         if matches!(r_ty, Type::Void) {
             self.diagnostics.error(
-                "VoidOperand",
+                &codes::E0277,
                 &format!("Right operand of '{}' cannot be void", op),
                 ErrorContext {
                     primary_location: loc.clone(),
@@ -47,7 +47,7 @@ impl Codegen {
             let right_loc = right.location();
 
             self.diagnostics.error(
-                "IncompatibleTypes",
+                &codes::E0369,
                 &format!("Cannot apply '{}' to types {} and {}", op, l_ty.name(), r_ty.name()),
                 ErrorContext {
                     primary_location: loc,
@@ -111,7 +111,7 @@ impl Codegen {
 
             _ => {
                 self.diagnostics.error(
-                    "UnsupportedBinOp",
+                    &codes::E0369,
                     &format!("Binary operator '{}' is not supported", op),
                     ErrorContext {
                         primary_location: loc,
@@ -138,7 +138,7 @@ impl Codegen {
             "&" => {
                 if matches!(ty, Type::Void) {
                     self.diagnostics.error(
-                        "VoidAddressOf",
+                        &codes::E0277,
                         "Cannot take address of void expression",
                         void_operation_error("address-of (&)", loc)
                     );
@@ -154,7 +154,7 @@ impl Codegen {
                 if let Type::Ptr(inner) = ty {
                     if matches!(*inner, Type::Void) {
                         self.diagnostics.error(
-                            "VoidDereference",
+                            &codes::E0277,
                             "Cannot dereference void pointer without cast",
                             dereference_void_error(loc)
                         );
@@ -168,7 +168,7 @@ impl Codegen {
                     let operand_loc = operand.location();
                     
                     self.diagnostics.error(
-                        "InvalidDereference",
+                        &codes::E0277,
                         "Cannot dereference non-pointer type",
                         ErrorContext {
                             primary_location: loc,
@@ -192,7 +192,7 @@ impl Codegen {
             _ => {
                 if matches!(ty, Type::Void) {
                     self.diagnostics.error(
-                        "VoidUnaryOp",
+                        &codes::E0277,
                         &format!("Cannot apply '{}' to void type", op),
                         void_operation_error(op, loc)
                     );
@@ -208,13 +208,13 @@ impl Codegen {
 
     pub fn codegen_typed_declaration(&mut self, name: &str, ty: &Type, value: &Expr, body: &mut String, loc: SourceLocation) -> Result<(), ()> {
         let (val_var, val_ty) = self.codegen_expr(value, body) .check_error();
-        let c_name = format!("var_{}", name);
+        let c_name = self.local_c_name(name);
         
  
         match ty {
             Type::Void => {
                 self.diagnostics.error(
-                    "VoidVariable",
+                    &codes::E0001,
                     &format!("Variable '{}' cannot have void type", name),
                     void_variable_error(name, loc)
                 );
@@ -224,7 +224,7 @@ impl Codegen {
             Type::Array { element, size: Some(size) } => {
                 if matches!(**element, Type::Void) {
                     self.diagnostics.error(
-                        "VoidArrayElement",
+                        &codes::E0001,
                         &format!("Array '{}' cannot have void elements", name),
                         void_array_error(loc)
                     );
@@ -240,7 +240,7 @@ impl Codegen {
             Type::Array { element, size: None } => {
                 if matches!(**element, Type::Void) {
                     self.diagnostics.error(
-                        "VoidArrayElement",
+                        &codes::E0001,
                         &format!("Array '{}' cannot have void elements", name),
                         void_array_error(loc)
                     );
@@ -254,7 +254,7 @@ impl Codegen {
             Type::MultiArray { element, dimensions } => {
                 if matches!(**element, Type::Void) {
                     self.diagnostics.error(
-                        "VoidArrayElement",
+                        &codes::E0001,
                         &format!("Multi-array '{}' cannot have void elements", name),
                         void_array_error(loc)
                     );
@@ -274,7 +274,7 @@ impl Codegen {
                 for (i, field) in fields.iter().enumerate() {
                     if matches!(field, Type::Void) {
                         self.diagnostics.error(
-                            "VoidTupleField",
+                            &codes::E0001,
                             &format!("Tuple field {} in variable '{}' cannot be void", i, name),
                             ErrorContext {
                                 primary_location: loc.clone(),
@@ -298,7 +298,7 @@ impl Codegen {
                 for (i, variant) in variants.iter().enumerate() {
                     if matches!(variant, Type::Void) {
                         self.diagnostics.error(
-                            "VoidUnionVariant",
+                            &codes::E0001,
                             &format!("Union variant {} in variable '{}' cannot be void", i, name),
                             ErrorContext {
                                 primary_location: loc.clone(),
@@ -330,7 +330,7 @@ impl Codegen {
                     let value_loc = value.location();
                     
                     self.diagnostics.error(
-                        "TypeMismatch",
+                        &codes::E0308,
                         &format!("Cannot initialize variable '{}' of type {} with value of type {}", 
                                 name, ty.name(), val_ty.name()),
                         type_mismatch_error(
@@ -373,7 +373,7 @@ impl Codegen {
                 
                 for (i, name) in names.iter().enumerate() {
                     let field_ty = &fields[i];
-                    let c_name = format!("var_{}", name);
+                    let c_name = self.local_c_name(name);
                     body.push_str(&format!("{} {} = {}.field_{};\n", field_ty.to_c_type(&self.arch), c_name, val_var, i));
                     self.vars.insert(name.clone(), (c_name, field_ty.clone()));
                 }
@@ -3,7 +3,6 @@ use crate::import::*;
 impl Codegen {
     pub fn codegen_std_call(&mut self, func: &str, args: &[Expr], body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
         self.ensure_runtime_functions();
-        println!("[DEBUG] codegen_std_call: func_name={}", func);
         match func {
             "array" | "Array" => self.codegen_array_init(args, body),
             "vector" | "Vector" => self.codegen_vector_init(args, body),
@@ -20,7 +19,7 @@ impl Codegen {
     fn ensure_runtime_functions(&mut self) {
         if !self.ir.functions.contains("void* x_array_init") {
             self.ir.functions.push_str(r#"
-void* x_array_init() {
+static void* x_array_init() {
     struct { void* ptr; size_t len; size_t capacity; }* arr = malloc(sizeof(*arr));
     arr->ptr = malloc(16 * sizeof(void*));
     arr->len = 0;
@@ -28,11 +27,11 @@ void* x_array_init() {
     return arr;
 }
 
-void* x_hashmap_init() { return malloc(1024); }
-void* x_vector_init() { return malloc(1024); }
-void* x_buffer_init() { return malloc(1024); }
+static void* x_hashmap_init() { return malloc(1024); }
+static void* x_vector_init() { return malloc(1024); }
+static void* x_buffer_init() { return malloc(1024); }
 
-int x_random(int max) {
+static int x_random(int max) {
     if (max <= 0) return 0;
     static int seeded = 0;
     if (!seeded) { srand((unsigned)time(NULL)); seeded = 1; }
@@ -104,15 +103,21 @@ int x_random(int max) {
                     if matches!(param_ty, Type::ConstStr) && matches!(ty, Type::Str { .. }) {
                         var = format!("{}.ptr", var);
                     }
+                    var = self.coerce_to_trait_local(var, &ty, param_ty, body);
                 }
             }
 
             arg_vars.push(var);
         }
 
-        let tmp = self.fresh_var();
         let args_str = arg_vars.join(", ");
-        body.push_str(&format!("int32_t {} = {}({});\n", tmp, func, args_str));
-        Ok((tmp, Type::i32()))
+        let return_type = self.user_functions.get(func).map_or(Type::i32(), |(_, ret)| ret.clone());
+        if matches!(return_type, Type::Void) {
+            body.push_str(&format!("{}({});\n", func, args_str));
+            return Ok((String::new(), Type::Void));
+        }
+        let tmp = self.fresh_var();
+        body.push_str(&format!("{} {} = {}({});\n", return_type.to_c_type(&self.arch), tmp, func, args_str));
+        Ok((tmp, return_type))
     }
 }
@@ -61,7 +61,7 @@ impl Codegen {
             
             if matches!(underlying_type, Type::Void) {
                 self.diagnostics.error(
-                    "VoidParameter",
+                    &codes::E0001,
                     &format!("Parameter '{}' in extern function '{}' cannot be void", param_name, func.name),
                     ErrorContext {
                         primary_location: loc.clone(),
@@ -76,6 +76,7 @@ impl Codegen {
                 return Err(());
             }
             
+            self.ensure_type_defined(param_type);
             let c_type = param_type.to_c_type(&self.arch);
             params_str.push(format!("{} {}", c_type, param_name));
         }
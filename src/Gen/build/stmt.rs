@@ -8,7 +8,7 @@ impl Codegen {
 
         if self.structs.contains_key(&struct_def.name) {
             self.diagnostics.error(
-                "DuplicateStruct",
+                &codes::E0428,
                 &format!("Struct '{}' is already defined", struct_def.name),
                 ErrorContext {
                     primary_location: loc.clone(),
@@ -28,7 +28,7 @@ impl Codegen {
         for field in &struct_def.fields {
             if matches!(field.ty, Type::Void) {
                 self.diagnostics.error(
-                    "VoidField",
+                    &codes::E0001,
                     &format!("Field '{}' in struct '{}' cannot be void", field.name, struct_def.name),
                     ErrorContext {
                         primary_location: loc.clone(),
@@ -78,8 +78,14 @@ impl Codegen {
             param_strs.push(field.name.clone());
         }
         
-        let signature = format!("{} {}({})", struct_def.name, constructor_name, params_c.join(", "));
-        self.ir.forward_decls.push_str(&format!("{};\n", signature));
+        // An instance of a generic struct may first be used in any translation unit, so each
+        // gets its own copy of the constructor.
+        let instance = self.struct_instances.contains_key(&struct_def.name);
+        let linkage = if instance { "static inline " } else { "" };
+        let signature = format!("{}{} {}({})", linkage, struct_def.name, constructor_name, params_c.join(", "));
+        if !instance {
+            self.ir.forward_decls.push_str(&format!("{};\n", signature));
+        }
         
         // Generate body
         let mut func_code = String::new();
@@ -93,7 +99,11 @@ impl Codegen {
         func_code.push_str("    return instance;\n");
         func_code.push_str("}\n\n");
         
-        self.ir.functions.push_str(&func_code);
+        if instance {
+            self.ir.forward_decls.push_str(&func_code);
+        } else {
+            self.ir.functions.push_str(&func_code);
+        }
 
         Ok(())
     }
@@ -103,7 +113,7 @@ impl Codegen {
         
         if !self.structs.contains_key(&impl_block.struct_name) {
             self.diagnostics.error(
-                "UndefinedStruct",
+                &codes::E0412,
                 &format!("Struct '{}' is not defined", impl_block.struct_name),
                 ErrorContext {
                     primary_location: loc.clone(),
@@ -124,6 +134,10 @@ impl Codegen {
         for method in &impl_block.methods {
             self.codegen_impl_method(method, &impl_block.struct_name, only_signatures);
         }
+
+        if let Some(trait_name) = &impl_block.trait_name {
+            self.codegen_trait_impl(impl_block, trait_name, only_signatures);
+        }
         Ok(())
     }
     
@@ -216,7 +230,7 @@ impl Codegen {
     for (param_name, param_type, _) in &method.params {
         if matches!(param_type, Type::Void) {
             self.diagnostics.error(
-                "VoidParameter",
+                &codes::E0001,
                 &format!("Parameter '{}' in method '{}' cannot be void", param_name, method.name),
                 ErrorContext {
                     primary_location: self.default_location(),
@@ -330,6 +344,9 @@ impl Codegen {
      
     let return_c_type = method.return_type.to_c_type(&self.arch);
 
+    if self.config.debug_info {
+        self.emit_line_directive(&method.location, &mut func_code);
+    }
     func_code.push_str(&format!("{} {}(", return_c_type, method_name));
     
      
@@ -382,6 +399,7 @@ impl Codegen {
     for stmt in &method.body {
         let _ = self.codegen_stmt(stmt, &mut body_code);
     }
+    let body_code = self.finish_dyn_body(body_code);
     func_code.push_str(&body_code);
     
      
@@ -391,7 +409,9 @@ impl Codegen {
         }
     }
     
-    func_code.push_str("}\n\n");
+    func_code.push_str("}\n");
+    self.end_line_mapping(&mut func_code);
+    func_code.push('\n');
     
      
     let params_for_registry: Vec<(String, Type)> = method.params.iter()
@@ -425,18 +445,24 @@ impl Codegen {
             .collect();
         self.user_functions.insert(func.name.clone(), (param_types, func.return_type.clone()));
 
+        self.ensure_type_defined(&func.return_type);
+        for (_, p_ty, _) in &func.params {
+            self.ensure_type_defined(p_ty);
+        }
         let c_return_type = func.return_type.to_c_type(&self.arch);
         let c_func_name = if func.name == "main" { "vix_main".to_string() } else { func.name.clone() };
+        let linkage = if self.generic_instances.contains_key(&func.name) { "static " } else { "" };
         let mut params_str = Vec::new();
         for (p_name, p_ty, _) in &func.params {
             let c_p_type = p_ty.to_c_type(&self.arch);
-            params_str.push(format!("{} var_{}", c_p_type, p_name));
+            let c_p_name = self.local_c_name(p_name);
+            params_str.push(format!("{} {}", c_p_type, c_p_name));
              
-            self.vars.insert(p_name.clone(), (format!("var_{}", p_name), p_ty.clone()));
+            self.vars.insert(p_name.clone(), (c_p_name, p_ty.clone()));
         }
 
         if only_signatures {
-            let sig = format!("{} {}({});\n", c_return_type, c_func_name, params_str.join(", "));
+            let sig = format!("{}{} {}({});\n", linkage, c_return_type, c_func_name, params_str.join(", "));
             if !self.ir.forward_decls.contains(&sig) {
                 self.ir.forward_decls.push_str(&sig);
             }
@@ -445,8 +471,11 @@ impl Codegen {
 
          
         let mut func_code = String::new();
+        if self.config.debug_info {
+            self.emit_line_directive(&func.location, &mut func_code);
+        }
         self.current_return_type = Some(func.return_type.clone());
-        func_code.push_str(&format!("{} {}(", c_return_type, c_func_name));
+        func_code.push_str(&format!("{}{} {}(", linkage, c_return_type, c_func_name));
         func_code.push_str(&params_str.join(", "));
         func_code.push_str(") {\n");
 
@@ -454,6 +483,7 @@ impl Codegen {
         for stmt in &func.body {
             self.codegen_stmt(stmt, &mut body_code);
         }
+        let body_code = self.finish_dyn_body(body_code);
 
         self.current_return_type = None;
         func_code.push_str(&body_code);
@@ -464,7 +494,9 @@ impl Codegen {
             }
         }
 
-        func_code.push_str("}\n\n");
+        func_code.push_str("}\n");
+        self.end_line_mapping(&mut func_code);
+        func_code.push('\n');
         self.ir.functions.push_str(&func_code);
     }
 
@@ -472,8 +504,8 @@ impl Codegen {
     pub fn codegen_if(
         &mut self,
         cond: &Expr,
-        then_body: &[Stmt],
-        else_body: &Option<Vec<Stmt>>,
+        then_body: &[SpannedStmt],
+        else_body: &Option<Vec<SpannedStmt>>,
         body: &mut String,
     ) -> Result<(), ()> {
         let (cond_var, _cond_ty) = self.codegen_expr(cond, body) .check_error();
@@ -499,27 +531,15 @@ impl Codegen {
     pub fn codegen_enum_definition(&mut self, enum_def: &EnumDef) -> Result<(), ()> {
         let mut variants = Vec::new();
         for variant in &enum_def.variants {
-            match variant {
-                EnumVariant::Simple(name) => variants.push((name.clone(), None)),
-                EnumVariant::Tuple(name, types) => {
-                    variants.push((name.clone(), Some(Type::Tuple { fields: types.clone() })));
-                }
-                EnumVariant::Struct(name, fields) => {
-                     let payload_name = format!("{}_{}_Payload", enum_def.name, name);
-                     let struct_def = StructDef {
-                        name: payload_name.clone(),
-                        fields: fields.clone(),
-                        is_public: enum_def.is_public,
-                     };
-                     
-                     
-                    let reg_fields = fields.iter().map(|f| (f.name.clone(), f.ty.clone())).collect();
-                    self.type_registry.register_struct(payload_name.clone(), reg_fields);
-                    self.codegen_struct_definition(&struct_def);
-
-                     variants.push((name.clone(), Some(Type::Struct { name: payload_name })));
-                }
+            let (name, fields) = match variant {
+                EnumVariant::Simple(name) => (name, vec![]),
+                EnumVariant::Tuple(name, types) => (name, types.iter().enumerate().map(|(i, ty)| (format!("field_{}", i), ty.clone())).collect()),
+                EnumVariant::Struct(name, fields) => (name, fields.iter().map(|f| (f.name.clone(), f.ty.clone())).collect()),
+            };
+            for (_, ty) in &fields {
+                self.ensure_type_defined(ty);
             }
+            variants.push(VariantDefinition { name: name.clone(), fields });
         }
         self.type_registry.register_enum(enum_def.name.clone(), variants, enum_def.is_public);
         
@@ -531,7 +551,7 @@ impl Codegen {
     }
 
     
-    pub fn codegen_scope(&mut self, stmts: &[Stmt], body: &mut String) -> Result<(), ()>{
+    pub fn codegen_scope(&mut self, stmts: &[SpannedStmt], body: &mut String) -> Result<(), ()>{
         self.scope_depth += 1;
         let prev_owned_vars = self.owned_vars.clone();
         
@@ -556,14 +576,21 @@ impl Codegen {
         Ok(())
     }
 
-    pub fn codegen_while(&mut self, cond: &Expr, loop_body: &[Stmt], body: &mut String, _loc: SourceLocation)  -> Result<(), ()> {
+    pub fn codegen_while(&mut self, cond: &Expr, loop_body: &[SpannedStmt], body: &mut String, _loc: SourceLocation)  -> Result<(), ()> {
         let loop_label = self.fresh_label();
         let end_label = self.fresh_label();
         
         body.push_str(&format!("{}:\n", loop_label));
         
+        let temps = self.dyn_temps.len();
         let (cond_var, _) = self.codegen_expr(cond, body).check_error();
-        body.push_str(&format!("if (!{}) goto {};\n", cond_var, end_label));
+        let mut frees = String::new();
+        self.free_dyn_temps(temps, &mut frees);
+        if frees.is_empty() {
+            body.push_str(&format!("if (!{}) goto {};\n", cond_var, end_label));
+        } else {
+            body.push_str(&format!("if (!{}) {{\n{}goto {};\n}}\n{}", cond_var, frees, end_label, frees));
+        }
         
         for stmt in loop_body {
             self.codegen_stmt(stmt, body);
@@ -575,13 +602,8 @@ impl Codegen {
         Ok(()) 
     }
 
-    pub fn codegen_for(&mut self, var: &str, iter: &Expr, loop_body: &[Stmt], body: &mut String, _loc: SourceLocation) -> Result<(), ()> {
+    pub fn codegen_for(&mut self, var: &str, iter: &Expr, loop_body: &[SpannedStmt], body: &mut String, _loc: SourceLocation) -> Result<(), ()> {
         let (iter_var, iter_ty) = self.codegen_expr(iter, body) .check_error();
-        println!("[DEBUG] codegen_for: var={}, iter_ty={:?}", var, iter_ty);
-        println!("[DEBUG] loop_body length: {}", loop_body.len());
-        for (i, stmt) in loop_body.iter().enumerate() {
-            println!("[DEBUG] stmt[{}]: {:?}", i, stmt);
-        }
         let loop_label = self.fresh_label();
         let end_label = self.fresh_label();
         let idx_var = self.fresh_var();
@@ -614,7 +636,7 @@ impl Codegen {
             }
         };
         
-        let c_name = format!("var_{}", var);
+        let c_name = self.local_c_name(var);
         let c_type = elem_type.to_c_type(&self.arch);
         
         body.push_str(&format!("size_t {} = 0;\n", idx_var));
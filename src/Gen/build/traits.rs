@@ -0,0 +1,269 @@
+use crate::import::*;
+
+/// A `dyn Trait` value is a `Trait_dyn`: a pointer to the boxed struct plus a pointer to the
+/// `Trait_vtable` of its impl. Each `impl Trait for Struct` gets a `Struct_Trait_vtable` filled
+/// with thunks that cast `self` back to the struct and call its method.
+///
+/// A struct passed or declared as a `dyn Trait` is boxed on the stack, since the value does not
+/// outlive its block. A `dyn` that is returned, assigned, or stored in a struct field is boxed on
+/// the heap, and each box has exactly one owner: the variable it ends up in (directly or through
+/// a field) frees it when it is overwritten or the function returns. A call result that nothing
+/// takes is freed at the end of its statement, and a `dyn` read out of something else is cloned
+/// before it is stored.
+impl Codegen {
+    /// `Trait_dyn` only points at its vtable, so it can be declared before the structs that hold one.
+    pub fn codegen_trait_object(&mut self, trait_def: &TraitDef) {
+        self.traits.insert(trait_def.name.clone(), trait_def.clone());
+        self.ir.forward_decls.push_str(&format!(
+            "typedef struct {name}_vtable {name}_vtable;\ntypedef struct {name}_dyn {{\n    void* self;\n    const {name}_vtable* vtable;\n}} {name}_dyn;\n\n",
+            name = trait_def.name
+        ));
+    }
+
+    /// The vtable layout, once the struct types its methods take are known.
+    pub fn codegen_trait_vtable(&mut self, trait_def: &TraitDef) {
+        self.ensure_string_typedef();
+
+        let mut entries = String::from("    size_t vix_size;\n");
+        for method in trait_def.methods.iter().filter(|m| m.self_modifier.is_some()) {
+            self.ensure_type_defined(&method.return_type);
+            let mut params = vec!["void* self".to_string()];
+            for (_, ty, _) in &method.params {
+                self.ensure_type_defined(ty);
+                params.push(ty.to_c_type(&self.arch));
+            }
+            entries.push_str(&format!(
+                "    {} (*{})({});\n",
+                method.return_type.to_c_type(&self.arch),
+                method.name,
+                params.join(", ")
+            ));
+        }
+        self.ir.forward_decls.push_str(&format!("struct {}_vtable {{\n{}}};\n\n", trait_def.name, entries));
+        self.ir.forward_decls.push_str(&format!(
+            "static {name}_dyn {name}_clone({name}_dyn value) {{\n    void* boxed = malloc(value.vtable->vix_size);\n    memcpy(boxed, value.self, value.vtable->vix_size);\n    return ({name}_dyn){{ boxed, value.vtable }};\n}}\n\n",
+            name = trait_def.name
+        ));
+    }
+
+    /// Declares the vtable and `Struct_as_Trait`, which boxes a struct on the heap, and with
+    /// `only_signatures` unset emits them along with the thunks.
+    pub fn codegen_trait_impl(&mut self, impl_block: &ImplBlock, trait_name: &str, only_signatures: bool) {
+        let Some(trait_def) = self.traits.get(trait_name).cloned() else {
+            return;
+        };
+        let struct_name = &impl_block.struct_name;
+        let boxing = format!("{}_dyn {}_as_{}({} value)", trait_name, struct_name, trait_name, struct_name);
+        let vtable = format!("const {}_vtable {}_{}_vtable", trait_name, struct_name, trait_name);
+
+        if only_signatures {
+            self.trait_impls.insert((struct_name.clone(), trait_name.to_string()));
+            self.ir.forward_decls.push_str(&format!("extern {};\n{};\n", vtable, boxing));
+            return;
+        }
+
+        let mut code = String::new();
+        let mut entries = vec![format!("    .vix_size = sizeof({}),\n", struct_name)];
+        for method in trait_def.methods.iter().filter(|m| m.self_modifier.is_some()) {
+            let thunk = format!("{}_{}_{}", struct_name, trait_name, method.name);
+            let mut params = vec!["void* self".to_string()];
+            let mut args = vec![format!("({}*)self", struct_name)];
+            for (name, ty, _) in &method.params {
+                params.push(format!("{} {}", ty.to_c_type(&self.arch), name));
+                args.push(name.clone());
+            }
+
+            let call = format!("{}_{}({})", struct_name, method.name, args.join(", "));
+            let statement = if matches!(method.return_type, Type::Void) { call } else { format!("return {}", call) };
+            code.push_str(&format!(
+                "static {} {}({}) {{\n    {};\n}}\n\n",
+                method.return_type.to_c_type(&self.arch),
+                thunk,
+                params.join(", "),
+                statement
+            ));
+            entries.push(format!("    .{} = {},\n", method.name, thunk));
+        }
+
+        code.push_str(&format!("{} = {{\n{}}};\n\n", vtable, entries.concat()));
+        code.push_str(&format!(
+            "{} {{\n    {}* boxed = malloc(sizeof({}));\n    *boxed = value;\n    return ({}_dyn){{ boxed, &{}_{}_vtable }};\n}}\n\n",
+            boxing, struct_name, struct_name, trait_name, struct_name, trait_name
+        ));
+        self.ir.functions.push_str(&code);
+    }
+
+    /// `var` as a value of type `to`: a struct passed where a `dyn Trait` is expected is boxed, and a
+    /// function pointer passed where a closure is expected goes through its `_call` trampoline.
+    pub fn coerce_to_trait(var: String, from: &Type, to: &Type) -> String {
+        match (from, to) {
+            (Type::Struct { name }, Type::Trait { name: trait_name }) => format!("{}_as_{}({})", name, trait_name, var),
+            (Type::FnPtr { params, return_type }, Type::Closure { .. }) => {
+                format!("({}){{ {}_call, (void*){} }}", Type::signature_id("Closure", params, return_type), Type::signature_id("FnPtr", params, return_type), var)
+            }
+            _ => var,
+        }
+    }
+
+    /// `var` as an argument or a local of type `to`. A struct is boxed in a temporary of the
+    /// current block rather than on the heap.
+    pub(crate) fn coerce_to_trait_local(&mut self, var: String, from: &Type, to: &Type, body: &mut String) -> String {
+        match (from, to) {
+            (Type::Struct { name }, Type::Trait { name: trait_name }) => {
+                let boxed = self.fresh_var();
+                body.push_str(&format!("{} {} = {};\n", name, boxed, var));
+                format!("({}_dyn){{ &{}, &{}_{}_vtable }}", trait_name, boxed, name, trait_name)
+            }
+            _ => Self::coerce_to_trait(var, from, to),
+        }
+    }
+
+    /// Where the `dyn` values inside a value of type `ty` are, as a field path (empty for a `dyn`
+    /// itself) and the trait of each.
+    pub(crate) fn dyn_paths(&self, ty: &Type) -> Vec<(String, String)> {
+        match ty {
+            Type::Trait { name } => vec![(String::new(), name.clone())],
+            Type::Struct { name } => match self.structs.get(name) {
+                Some(info) => info
+                    .fields
+                    .iter()
+                    .flat_map(|(field, field_ty, _)| {
+                        self.dyn_paths(field_ty).into_iter().map(move |(path, trait_name)| (format!(".{}{}", field, path), trait_name))
+                    })
+                    .collect(),
+                None => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// `var` as a value of type `to` that owns its boxes, for returning or storing: the result of
+    /// a call is taken over, a struct is boxed on the heap, and a borrowed `dyn` (or a struct
+    /// holding one) is copied there.
+    pub(crate) fn coerce_to_owned(&mut self, var: String, from: &Type, to: &Type, body: &mut String) -> String {
+        if let Some(i) = self.dyn_temps.iter().position(|(temp, _)| *temp == var) {
+            self.dyn_temps.remove(i);
+            return Self::coerce_to_trait(var, from, to);
+        }
+        match (from, to) {
+            (Type::Trait { name }, Type::Trait { .. }) => format!("{}_clone({})", name, var),
+            (Type::Struct { .. }, Type::Struct { .. }) if !self.dyn_paths(from).is_empty() => {
+                let copy = self.fresh_var();
+                body.push_str(&format!("{} {} = {};\n", from.to_c_type(&self.arch), copy, var));
+                for (path, trait_name) in self.dyn_paths(from) {
+                    body.push_str(&format!("{copy}{path} = {trait_name}_clone({copy}{path});\n"));
+                }
+                copy
+            }
+            _ => Self::coerce_to_trait(var, from, to),
+        }
+    }
+
+    /// Hands the boxes of the `dyn` values in `var` to the current function. Each place that does
+    /// so has its own pointers, so running it again frees the boxes it took the last time.
+    pub(crate) fn own_dyn(&mut self, var: &str, ty: &Type, body: &mut String) {
+        for (path, _) in self.dyn_paths(ty) {
+            let slot = self.fresh_var();
+            body.push_str(&format!("free({});\n{} = {}{}.self;\n", slot, slot, var, path));
+            self.dyn_slots.push(slot);
+        }
+    }
+
+    /// The arguments of a `Struct_new` call in field order, each owned by the new struct so that
+    /// whoever owns it frees them. Named arguments are matched by field, positional ones by order.
+    pub(crate) fn constructor_args(&mut self, struct_name: &str, args: Vec<(Option<&str>, String, Type)>, body: &mut String) -> Vec<String> {
+        let fields = self.structs.get(struct_name).map(|info| info.fields.clone()).unwrap_or_default();
+        let mut vars: Vec<(usize, String)> = Vec::new();
+        for (i, (field, var, ty)) in args.into_iter().enumerate() {
+            let index = match field {
+                Some(field) => fields.iter().position(|(name, _, _)| name == field).unwrap_or(i),
+                None => i,
+            };
+            let var = match fields.get(index) {
+                Some((_, field_ty, _)) => self.coerce_to_owned(var, &ty, field_ty, body),
+                None => var,
+            };
+            vars.push((index, var));
+        }
+        vars.sort_by_key(|(index, _)| *index);
+        vars.into_iter().map(|(_, var)| var).collect()
+    }
+
+    /// Frees the `dyn` values in `var`, a value of type `ty` that nothing owns.
+    pub(crate) fn free_dyn_value(&self, var: &str, ty: &Type) -> String {
+        self.dyn_paths(ty).iter().map(|(path, _)| format!("free({}{}.self);\n", var, path)).collect()
+    }
+
+    /// Frees the `dyn` values in the results of calls made since `mark`.
+    pub(crate) fn free_dyn_temps(&mut self, mark: usize, body: &mut String) {
+        let temps: Vec<_> = self.dyn_temps.drain(mark.min(self.dyn_temps.len())..).collect();
+        for (temp, ty) in temps {
+            body.push_str(&self.free_dyn_value(&temp, &ty));
+        }
+    }
+
+    /// What a function returning has to free: every `dyn` it still owns.
+    pub(crate) fn dyn_cleanup(&self) -> String {
+        let temps = self.dyn_temps.iter().map(|(temp, ty)| self.free_dyn_value(temp, ty));
+        let slots = self.dyn_slots.iter().map(|slot| format!("free({});\n", slot));
+        temps.chain(slots).collect()
+    }
+
+    /// Declares the pointers of the boxes a function owns ahead of `body`, and frees them if it
+    /// falls off its end.
+    pub(crate) fn finish_dyn_body(&mut self, body: String) -> String {
+        let slots = std::mem::take(&mut self.dyn_slots);
+        self.dyn_temps.clear();
+        if slots.is_empty() {
+            return body;
+        }
+        let declarations: String = slots.iter().map(|slot| format!("void* {} = NULL;\n", slot)).collect();
+        let frees: String = slots.iter().map(|slot| format!("free({});\n", slot)).collect();
+        format!("{}{}{}", declarations, body, frees)
+    }
+
+    /// A method called on a `dyn Trait` goes through the vtable.
+    pub(crate) fn codegen_dyn_method_call(
+        &mut self,
+        obj_var: &str,
+        trait_name: &str,
+        method: &str,
+        args: &[Expr],
+        body: &mut String,
+        loc: SourceLocation,
+    ) -> Result<(String, Type), ()> {
+        let declared = self
+            .traits
+            .get(trait_name)
+            .and_then(|trait_def| trait_def.methods.iter().find(|m| m.name == method && m.self_modifier.is_some()))
+            .cloned();
+        let Some(declared) = declared else {
+            self.diagnostics.error(
+                &codes::E0599,
+                &format!("No method '{}' on 'dyn {}'", method, trait_name),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some(format!("Only the methods declared in trait '{}' that take `self` can be called on it.", trait_name)),
+                    suggestions: vec![],
+                },
+            );
+            return Err(());
+        };
+
+        let mut arg_vars = vec![format!("{}.self", obj_var)];
+        for (arg, (_, param_ty, _)) in args.iter().zip(&declared.params) {
+            let (var, ty) = self.codegen_expr(arg, body).check_error();
+            arg_vars.push(self.coerce_to_trait_local(var, &ty, param_ty, body));
+        }
+
+        let call = format!("{}.vtable->{}({})", obj_var, method, arg_vars.join(", "));
+        if matches!(declared.return_type, Type::Void) {
+            body.push_str(&format!("{};\n", call));
+            return Ok((String::new(), Type::Void));
+        }
+        let tmp = self.fresh_var();
+        body.push_str(&format!("{} {} = {};\n", declared.return_type.to_c_type(&self.arch), tmp, call));
+        Ok((tmp, declared.return_type))
+    }
+}
@@ -16,7 +16,7 @@ impl Codegen {
             (c.clone(), t.clone())
         } else {
             self.diagnostics.error(
-                "UndefinedVariable",
+                &codes::E0425,
                 &format!("Cannot assign to undefined variable '{}'.", name),
                 ErrorContext {
                     primary_location: loc.clone(),
@@ -42,6 +42,12 @@ impl Codegen {
                  
                 body.push_str(&format!("{} = {}.ptr;\n", c_name, val_var));
             }
+            _ if !self.dyn_paths(&var_ty).is_empty() => {
+                // The variable may outlive the block the value comes from, so it gets its own boxes.
+                let value = self.coerce_to_owned(val_var, &val_ty, &var_ty, body);
+                body.push_str(&format!("{} = {};\n", c_name, value));
+                self.own_dyn(&c_name, &var_ty, body);
+            }
             _ => {
                  
                 body.push_str(&format!("{} = {};\n", c_name, val_var));
@@ -54,7 +60,11 @@ impl Codegen {
 
     pub fn codegen_typed_declaration_impl(&mut self, name: &str, ty: &Type, value: &Expr, body: &mut String, loc: SourceLocation, is_mutable: bool) -> Result<(), ()> {
         let (val_var, val_ty) = self.codegen_expr(value, body) .check_error();
-        let c_name = format!("var_{}", name);
+        let c_name = self.local_c_name(name);
+        if is_mutable {
+            self.mutable_vars.insert(c_name.clone());
+        }
+        self.ensure_type_defined(ty);
         
         let base_c_type = ty.to_c_type(&self.arch);
         // Apply const if immutable, except where initialization via memcpy requires mutability
@@ -71,7 +81,7 @@ impl Codegen {
         match ty {
             Type::Void => {
                 self.diagnostics.error(
-                    "VoidVariable",
+                    &codes::E0001,
                     &format!("Variable '{}' cannot have void type", name),
                     void_variable_error(name, loc)
                 );
@@ -81,7 +91,7 @@ impl Codegen {
             Type::Array { element, size: Some(size) } => {
                 if matches!(**element, Type::Void) {
                     self.diagnostics.error(
-                        "VoidArrayElement",
+                        &codes::E0001,
                         &format!("Array '{}' cannot have void elements", name),
                         void_array_error(loc)
                     );
@@ -97,7 +107,7 @@ impl Codegen {
             Type::Array { element, size: None } => {
                 if matches!(**element, Type::Void) {
                     self.diagnostics.error(
-                        "VoidArrayElement",
+                        &codes::E0001,
                         &format!("Array '{}' cannot have void elements", name),
                         void_array_error(loc)
                     );
@@ -112,7 +122,7 @@ impl Codegen {
             Type::MultiArray { element, dimensions } => {
                 if matches!(**element, Type::Void) {
                     self.diagnostics.error(
-                        "VoidArrayElement",
+                        &codes::E0001,
                         &format!("Multi-array '{}' cannot have void elements", name),
                         void_array_error(loc)
                     );
@@ -132,7 +142,7 @@ impl Codegen {
                 for (i, field) in fields.iter().enumerate() {
                     if matches!(field, Type::Void) {
                         self.diagnostics.error(
-                            "VoidTupleField",
+                            &codes::E0001,
                             &format!("Tuple field {} in variable '{}' cannot be void", i, name),
                             ErrorContext {
                                 primary_location: loc.clone(),
@@ -155,7 +165,7 @@ impl Codegen {
                 for (i, variant) in variants.iter().enumerate() {
                     if matches!(variant, Type::Void) {
                         self.diagnostics.error(
-                            "VoidUnionVariant",
+                            &codes::E0001,
                             &format!("Union variant {} in variable '{}' cannot be void", i, name),
                             ErrorContext {
                                 primary_location: loc.clone(),
@@ -183,7 +193,7 @@ impl Codegen {
                     let value_loc = value.location();
                     
                     self.diagnostics.error(
-                        "TypeMismatch",
+                        &codes::E0308,
                         &format!("Cannot initialize variable '{}' of type {} with value of type {}", 
                                 name, ty.name(), val_ty.name()),
                         type_mismatch_error(
@@ -200,6 +210,15 @@ impl Codegen {
                     (Type::ConstStr, Type::Str { .. }) => {
                         body.push_str(&format!("{} {} = {}.ptr;\n", decl_type, c_name, val_var));
                     }
+                    (Type::Trait { .. }, Type::Struct { .. }) | (Type::Closure { .. }, Type::FnPtr { .. }) => {
+                        let value = self.coerce_to_trait_local(val_var, &val_ty, ty, body);
+                        body.push_str(&format!("{} {} = {};\n", decl_type, c_name, value));
+                    }
+                    _ if !self.dyn_paths(ty).is_empty() => {
+                        let value = self.coerce_to_owned(val_var, &val_ty, ty, body);
+                        body.push_str(&format!("{} {} = {};\n", decl_type, c_name, value));
+                        self.own_dyn(&c_name, ty, body);
+                    }
                     _ => {
                         body.push_str(&format!("{} {} = {};\n", decl_type, c_name, val_var));
                     }
@@ -221,7 +240,7 @@ impl Codegen {
         if let Some((c_name, var_ty)) = self.vars.get(name) {
             if matches!(var_ty, Type::Void) || matches!(val_ty, Type::Void) {
                 self.diagnostics.error(
-                    "VoidOperation",
+                    &codes::E0277,
                     "Cannot perform compound assignment on void type",
                     void_operation_error(op, loc)
                 );
@@ -233,7 +252,7 @@ impl Codegen {
             Ok(())
         } else {
             self.diagnostics.error(
-                "UndefinedVariable",
+                &codes::E0425,
                 &format!("Variable '{}' is not defined", name),
                 ErrorContext {
                     primary_location: loc,
@@ -246,6 +265,9 @@ impl Codegen {
         }
     }
     pub fn codegen_call_expr(&mut self, func: &str, args: &[Expr], body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        if let Some(template) = self.generic_functions.get(func).cloned() {
+            return self.codegen_generic_call(&template, args, body);
+        }
         match func {
             "print" => {
                 let mut format_str = String::new();
@@ -265,12 +287,12 @@ impl Codegen {
                         Type::Str { .. } => {
                             format_str.push_str("%s");
                             arg_list.push(format!("{}.ptr", var));
-                            if let Expr::String(s) = arg { if s.contains('\r') { has_r = true; } }
+                            if let ExprKind::String(s) = &arg.kind { if s.contains('\r') { has_r = true; } }
                         }
                         Type::ConstStr => {
                             format_str.push_str("%s");
                             arg_list.push(var);
-                            if let Expr::String(s) = arg { if s.contains('\r') { has_r = true; } }
+                            if let ExprKind::String(s) = &arg.kind { if s.contains('\r') { has_r = true; } }
                         }
                         Type::Bool => {
                             format_str.push_str("%s");
@@ -339,7 +361,7 @@ impl Codegen {
             }
             "contain_all" => {
                 if args.len() != 2 { return Err(()); }
-                if let Expr::Array(items) = &args[1] {
+                if let ExprKind::Array(items) = &args[1].kind {
                     self.codegen_contain_all(&args[0], items, body)
                 } else {
                     Err(())
@@ -409,13 +431,55 @@ impl Codegen {
                 if args.len() != 1 { return Err(()); }
                 self.codegen_panic(&args[0], body)
             }
+            "assert" => {
+                if args.len() != 1 { return Err(()); }
+                self.codegen_assert(&args[0], body, loc)
+            }
             _ => {
                 self.codegen_std_call(func, args, body, loc)
             }
         }
     }
 
+    /// `Enum.Variant(args)` builds a payload variant: the tag plus the variant's struct in the
+    /// union, its fields given in order or by name.
+    pub fn codegen_enum_new(&mut self, enum_name: &str, variant: &str, args: &[(Option<&str>, &Expr)], body: &mut String) -> Result<(String, Type), ()> {
+        let fields = self.type_registry.enum_variant(enum_name, variant).map(|v| v.fields.clone()).ok_or(())?;
+        let mut inits = Vec::new();
+        for (i, (field, arg)) in args.iter().enumerate() {
+            let (var, _) = self.codegen_expr(arg, body).check_error();
+            let field = match field {
+                Some(field) => field.to_string(),
+                None => fields.get(i).map(|(name, _)| name.clone()).ok_or(())?,
+            };
+            inits.push(format!(".{} = {}", field, var));
+        }
+        let tmp = self.fresh_var();
+        body.push_str(&format!(
+            "{0} {1} = ({0}){{ .tag = {0}__{2}, .data.{2} = {{ {3} }} }};\n",
+            enum_name,
+            tmp,
+            variant,
+            inits.join(", ")
+        ));
+        Ok((tmp, Type::Struct { name: enum_name.to_string() }))
+    }
+
+    /// The enum named by the object of `Enum.Variant`, unless a variable shadows it.
+    pub fn enum_name_of<'a>(&self, obj: &'a Expr) -> Option<&'a String> {
+        match &obj.kind {
+            ExprKind::Var(name) if !self.vars.contains_key(name) && self.type_registry.enum_variants(name).is_some() => Some(name),
+            _ => None,
+        }
+    }
+
     pub fn codegen_member_access(&mut self, obj: &Expr, field: &str, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        // `Enum.Variant` names a variant without a payload.
+        if let Some(enum_name) = self.enum_name_of(obj) {
+            let tmp = self.fresh_var();
+            body.push_str(&format!("{0} {1} = ({0}){{ .tag = {0}__{2} }};\n", enum_name, tmp, field));
+            return Ok((tmp, Type::Struct { name: enum_name.clone() }));
+        }
         let (obj_var, obj_ty) = self.codegen_expr(obj, body) .check_error();
         
         if let Type::Str { .. } = &obj_ty {
@@ -488,19 +552,41 @@ impl Codegen {
 
     pub fn codegen_member_assign(&mut self, obj: &Expr, field: &str, value: &Expr, body: &mut String, _loc: SourceLocation) -> Result<(), ()> {
         let (obj_var, obj_ty) = self.codegen_expr(obj, body).check_error();
-        let (val_var, _val_ty) = self.codegen_expr(value, body).check_error();
+        let (val_var, val_ty) = self.codegen_expr(value, body).check_error();
 
         let op = if matches!(obj_ty, Type::Ref(_) | Type::MutRef(_) | Type::Ptr(_)) { "->" } else { "." };
-        body.push_str(&format!("{}{}{} = {};\n", obj_var, op, field, val_var));
+        let target = format!("{}{}{}", obj_var, op, field);
+        let struct_ty = match &obj_ty {
+            Type::Ref(inner) | Type::MutRef(inner) | Type::Ptr(inner) => inner.as_ref(),
+            ty => ty,
+        };
+        let field_ty = match struct_ty {
+            Type::Struct { name } => self.structs.get(name).and_then(|info| info.fields.iter().find(|(f, _, _)| f == field).map(|(_, ty, _)| ty.clone())),
+            _ => None,
+        };
+        match field_ty {
+            Some(field_ty) if !self.dyn_paths(&field_ty).is_empty() => {
+                let value = self.coerce_to_owned(val_var, &val_ty, &field_ty, body);
+                body.push_str(&format!("{} = {};\n", target, value));
+                self.own_dyn(&target, &field_ty, body);
+            }
+            _ => body.push_str(&format!("{} = {};\n", target, val_var)),
+        }
 
         Ok(())
     }
 
     pub fn codegen_call_stmt(&mut self, func: &str, args: &[Expr], body: &mut String, loc: SourceLocation) -> Result<(), ()> {
-        if func == "print" {
+        if matches!(func, "print" | "panic" | "assert") {
             self.codegen_call_expr(func, args, body, loc).check_error();
             return Ok(());
         }
+        if let Some(template) = self.generic_functions.get(func).cloned() {
+            return self.codegen_generic_call(&template, args, body).map(|_| ());
+        }
+        if let Some((c_name, ty)) = self.function_var(func) {
+            return self.codegen_indirect_call(&c_name, &ty, args, body).map(|_| ());
+        }
 
         let mut arg_vars = Vec::new();
         
@@ -522,6 +608,7 @@ impl Codegen {
                     if (matches!(param_ty, Type::ConstStr) || matches!(param_ty, Type::Ptr(_) | Type::RawPtr(_) | Type::Ref(_) | Type::MutRef(_))) && matches!(ty, Type::Str { .. }) {
                         var = format!("{}.ptr", var);
                     }
+                    var = self.coerce_to_trait_local(var, &ty, param_ty, body);
                 }
             }
             
@@ -529,7 +616,14 @@ impl Codegen {
         }
 
         let args_str = arg_vars.join(", ");
-        body.push_str(&format!("{}({});\n", func, args_str));
+        let return_type = self.user_functions.get(func).map(|(_, ty)| ty.clone()).unwrap_or(Type::Void);
+        if !self.dyn_paths(&return_type).is_empty() {
+            let result = self.fresh_var();
+            body.push_str(&format!("{} {} = {}({});\n", return_type.to_c_type(&self.arch), result, func, args_str));
+            body.push_str(&self.free_dyn_value(&result, &return_type));
+        } else {
+            body.push_str(&format!("{}({});\n", func, args_str));
+        }
 
         Ok(())
     }
@@ -582,7 +676,7 @@ impl Codegen {
         } else {
              
             self.diagnostics.error(
-                "UndefinedMethod",
+                &codes::E0599,
                 &format!("Static method '{}::{}' is not defined", type_name, method),
                 ErrorContext {
                     primary_location: loc,
@@ -611,7 +705,7 @@ impl Codegen {
 
         if matches!(source_ty, Type::Ptr(_)) && !matches!(target_ty, Type::Ptr(_) | Type::RawPtr(_)) {
             self.diagnostics.warning(
-                "UnsafeCast",
+                &codes::W0001,
                 "Casting pointer to non-pointer type may be unsafe",
                 ErrorContext {
                     primary_location: loc.clone(),
@@ -641,6 +735,10 @@ impl Codegen {
             (Type::Str { .. }, Type::ConstStr) => true,
             (Type::Ptr(inner1), Type::Ptr(inner2)) => self.types_compatible(inner1, inner2),
             (Type::Struct { name: n1 }, Type::Struct { name: n2 }) => n1 == n2,
+            (Type::Trait { name: n1 }, Type::Trait { name: n2 }) => n1 == n2,
+            (Type::Trait { name: trait_name }, Type::Struct { name: struct_name }) => {
+                self.trait_impls.contains(&(struct_name.clone(), trait_name.clone()))
+            }
             (Type::Array { element: e1, size: s1 }, Type::Array { element: e2, size: s2 }) => {
                 self.types_compatible(e1, e2) && (s1 == s2)
             },
@@ -652,6 +750,8 @@ impl Codegen {
             (Type::Result { ok: o1, err: e1 }, Type::Result { ok: o2, err: e2 }) => {
                 self.types_compatible(o1, o2) && self.types_compatible(e1, e2)
             },
+            (Type::FnPtr { .. }, Type::FnPtr { .. }) | (Type::Closure { .. }, Type::Closure { .. }) => ty1 == ty2,
+            (Type::Closure { params: p1, return_type: r1 }, Type::FnPtr { params: p2, return_type: r2 }) => p1 == p2 && r1 == r2,
             (Type::Const(i1), Type::Const(i2)) => self.types_compatible(i1, i2),
             (Type::Const(i1), other) => self.types_compatible(i1, other),
             (other, Type::Const(i2)) => self.types_compatible(other, i2),
@@ -675,7 +775,7 @@ impl Codegen {
 
         if matches!(obj_ty, Type::Void) {
             self.diagnostics.error(
-                "VoidMemberAssign",
+                &codes::E0277,
                 "Cannot perform compound assignment on member of void type",
                 ErrorContext {
                     primary_location: loc,
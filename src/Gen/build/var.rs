@@ -8,6 +8,21 @@ impl Codegen {
         varaable
     }
 
+    /// C name for a Vix local. Debug builds keep the Vix name so a debugger shows `x`
+    /// rather than `var_x`, unless that would clash with something the C code declares.
+    pub fn local_c_name(&self, name: &str) -> String {
+        let clashes = is_reserved_c_name(name)
+            || self.user_functions.contains_key(name)
+            || self.extern_functions.contains_key(name)
+            || self.structs.contains_key(name);
+
+        if self.config.debug_info && !clashes {
+            name.to_string()
+        } else {
+            format!("var_{}", name)
+        }
+    }
+
     pub fn codegen_var(&mut self, name: &str, loc: SourceLocation) -> Result<(String, Type), ()> {
         if let Some((c_name, ty)) = self.vars.get(name) {
             return Ok((c_name.clone(), ty.clone()));
@@ -20,7 +35,7 @@ impl Codegen {
         }
         
         self.diagnostics.error(
-            "UndefinedVariable",
+            &codes::E0425,
             &format!("Variable '{}' has not been declared in this scope.", name),
             ErrorContext {
                 primary_location: loc.clone(),
@@ -51,22 +66,39 @@ impl Codegen {
         Ok(())
     }
 
-    pub fn codegen_match(&mut self, expr: &Expr, cases: &[MatchCase], default: &Option<Vec<Stmt>>, body: &mut String){
-        let (match_var, _match_ty) = self.codegen_expr(expr, body).check_error();
+    /// A match on an enum switches on its tag, and each `case` of the switch tries, in order, the
+    /// arms that can match that variant. Other matches try every arm in turn. An arm that matches
+    /// jumps past the rest; falling off the end runs the `default:` body.
+    pub fn codegen_match(&mut self, expr: &Expr, cases: &[MatchCase], default: &Option<Vec<SpannedStmt>>, body: &mut String){
+        let (match_var, match_ty) = self.codegen_expr(expr, body).check_error();
 
         let end_label = self.fresh_label();
 
-        for case in cases {
-            let (case_val, _) = self.codegen_expr(&case.value, body).check_error();
-            
-            let _case_label = self.fresh_label();
-            body.push_str(&format!("if ({} == {}) {{\n", match_var, case_val));
+        let variants = match &match_ty {
+            Type::Struct { name } => self
+                .type_registry
+                .enum_variants(name)
+                .map(|variants| (name.clone(), variants.iter().map(|variant| variant.name.clone()).collect::<Vec<_>>())),
+            _ => None,
+        };
 
-            for stmt in &case.body {
-                self.codegen_stmt(stmt, body) ;
+        match variants {
+            Some((enum_name, variants)) => {
+                body.push_str(&format!("switch ({}.tag) {{\n", match_var));
+                for variant in &variants {
+                    body.push_str(&format!("case {}__{}: {{\n", enum_name, variant));
+                    for case in cases.iter().filter(|case| may_match_variant(&case.pattern, variant)) {
+                        self.codegen_match_arm(case, &match_var, &match_ty, &end_label, body);
+                    }
+                    body.push_str("break;\n}\n");
+                }
+                body.push_str("}\n");
+            }
+            None => {
+                for case in cases {
+                    self.codegen_match_arm(case, &match_var, &match_ty, &end_label, body);
+                }
             }
-
-            body.push_str(&format!("goto {};\n}}\n", end_label));
         }
 
         if let Some(default_body) = default {
@@ -75,7 +107,116 @@ impl Codegen {
             }
         }
 
-        body.push_str(&format!("{}:\n", end_label));
+        body.push_str(&format!("{}:;\n", end_label));
+    }
+
+    fn codegen_match_arm(&mut self, case: &MatchCase, match_var: &str, match_ty: &Type, end_label: &str, body: &mut String) {
+        let saved_vars = self.vars.clone();
+        let (condition, bindings) = self.pattern_test(&case.pattern, match_var, match_ty, body);
+        body.push_str(&format!("if ({}) {{\n", condition));
+        for (name, value, ty) in bindings {
+            let c_name = self.local_c_name(&name);
+            body.push_str(&format!("{} {} = {};\n", ty.to_c_type(&self.arch), c_name, value));
+            self.vars.insert(name, (c_name, ty));
+        }
+        if let Some(guard) = &case.guard {
+            let (guard_var, _) = self.codegen_expr(guard, body).check_error();
+            body.push_str(&format!("if ({}) {{\n", guard_var));
+        }
+
+        for stmt in &case.body {
+            self.codegen_stmt(stmt, body);
+        }
+
+        body.push_str(&format!("goto {};\n", end_label));
+        if case.guard.is_some() {
+            body.push_str("}\n");
+        }
+        body.push_str("}\n");
+        self.vars = saved_vars;
+    }
+
+    /// The C condition under which `pattern` matches the value at `access`, and what it binds
+    /// as (name, C expression, type).
+    fn pattern_test(&mut self, pattern: &Pattern, access: &str, ty: &Type, body: &mut String) -> (String, Vec<(String, String, Type)>) {
+        match &pattern.kind {
+            PatternKind::Wildcard => ("1".to_string(), vec![]),
+            PatternKind::Binding(name) => ("1".to_string(), vec![(name.clone(), access.to_string(), ty.clone())]),
+            PatternKind::Literal(literal) => {
+                let (value, _) = self.codegen_expr(literal, body).check_error();
+                let condition = match ty {
+                    Type::Str { .. } => format!("({0}.len == {1}.len && memcmp({0}.ptr, {1}.ptr, {0}.len) == 0)", access, value),
+                    _ => format!("{} == {}", access, value),
+                };
+                (condition, vec![])
+            }
+            PatternKind::Range { start, end, inclusive } => {
+                let (low, _) = self.codegen_expr(start, body).check_error();
+                let (high, _) = self.codegen_expr(end, body).check_error();
+                let op = if *inclusive { "<=" } else { "<" };
+                (format!("{0} >= {1} && {0} {2} {3}", access, low, op, high), vec![])
+            }
+            PatternKind::Tuple(elements) => {
+                let fields = match ty {
+                    Type::Tuple { fields } => fields.clone(),
+                    _ => vec![Type::Any; elements.len()],
+                };
+                let parts = fields.into_iter().enumerate().map(|(i, field_ty)| (format!("{}.field_{}", access, i), field_ty)).collect();
+                self.all_pattern_tests(elements, parts, vec![], body)
+            }
+            PatternKind::Variant { variant, fields, .. } => {
+                let (tag_test, payload) = self.variant_layout(access, ty, variant);
+                self.all_pattern_tests(fields, payload, vec![tag_test], body)
+            }
+            PatternKind::Or(alternatives) => {
+                let tests: Vec<_> = alternatives.iter().map(|alternative| self.pattern_test(alternative, access, ty, body)).collect();
+                let condition = tests.iter().map(|(condition, _)| format!("({})", condition)).collect::<Vec<_>>().join(" || ");
+                // Each name takes its value from whichever alternative matched.
+                let mut bindings = tests.last().map(|(_, bindings)| bindings.clone()).unwrap_or_default();
+                for (alternative_condition, alternative_bindings) in tests.iter().rev().skip(1) {
+                    for (name, value, _) in &mut bindings {
+                        if let Some((_, alternative_value, _)) = alternative_bindings.iter().find(|(other, _, _)| other == name) {
+                            *value = format!("({}) ? {} : {}", alternative_condition, alternative_value, value);
+                        }
+                    }
+                }
+                (condition, bindings)
+            }
+        }
+    }
+
+    fn all_pattern_tests(&mut self, patterns: &[Pattern], parts: Vec<(String, Type)>, mut conditions: Vec<String>, body: &mut String) -> (String, Vec<(String, String, Type)>) {
+        let mut bindings = Vec::new();
+        for (pattern, (access, ty)) in patterns.iter().zip(parts) {
+            let (condition, pattern_bindings) = self.pattern_test(pattern, &access, &ty, body);
+            if condition != "1" {
+                conditions.push(format!("({})", condition));
+            }
+            bindings.extend(pattern_bindings);
+        }
+        let condition = if conditions.is_empty() { "1".to_string() } else { conditions.join(" && ") };
+        (condition, bindings)
+    }
+
+    /// The tag test for `variant` of the value at `access`, and where each of its fields lives.
+    fn variant_layout(&self, access: &str, ty: &Type, variant: &str) -> (String, Vec<(String, Type)>) {
+        match (ty, variant) {
+            (Type::Option { inner }, "Some") => (format!("{}.tag == 1", access), vec![(format!("{}.value", access), (**inner).clone())]),
+            (Type::Option { .. }, _) => (format!("{}.tag == 0", access), vec![]),
+            (Type::Result { ok, .. }, "Ok") => (format!("{}.tag == 0", access), vec![(format!("{}.data.ok", access), (**ok).clone())]),
+            (Type::Result { err, .. }, _) => (format!("{}.tag == 1", access), vec![(format!("{}.data.err", access), (**err).clone())]),
+            (Type::Struct { name }, _) => {
+                let fields = self
+                    .type_registry
+                    .enum_variant(name, variant)
+                    .map(|definition| {
+                        definition.fields.iter().map(|(field, field_ty)| (format!("{}.data.{}.{}", access, variant, field), field_ty.clone())).collect()
+                    })
+                    .unwrap_or_default();
+                (format!("{}.tag == {}__{}", access, name, variant), fields)
+            }
+            _ => ("0".to_string(), vec![]),
+        }
     }
 
 
@@ -90,7 +231,7 @@ impl Codegen {
             }
             _ => {
                 self.diagnostics.error(
-                    "InvalidNot",
+                    &codes::E0277,
                     &format!("Cannot apply NOT operator to type {}", ty.name()),
                     ErrorContext {
                         primary_location: self.default_location(),
@@ -112,15 +253,61 @@ impl Codegen {
     pub fn codegen_return(&mut self, expr: &Option<Expr>, body: &mut String) -> Result<(), ()> {
         if let Some(e) = expr {
             let (var, ty) = self.codegen_expr(e, body).check_error();
+            let var = match self.current_return_type.clone() {
+                Some(return_type) => self.coerce_to_owned(var, &ty, &return_type, body),
+                None => var,
+            };
+            let cleanup = self.dyn_cleanup();
             
-            if !matches!(ty, Type::Void) {
+            if matches!(ty, Type::Void) {
+                body.push_str(&format!("{}return;\n", cleanup));
+            } else if cleanup.is_empty() {
                 body.push_str(&format!("return {};\n", var));
-                    } else {
-                body.push_str("return;\n");
+            } else {
+                // The value may read what is about to be freed.
+                let return_type = self.current_return_type.clone().unwrap_or(ty);
+                let result = self.fresh_var();
+                body.push_str(&format!("{} {} = {};\n{}return {};\n", return_type.to_c_type(&self.arch), result, var, cleanup, result));
             }
         } else {
-            body.push_str("return;\n");
+            body.push_str(&format!("{}return;\n", self.dyn_cleanup()));
         }
         Ok(())
     }
 }
+
+const C_KEYWORDS: &[&str] = &[
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else", "enum",
+    "extern", "float", "for", "goto", "if", "inline", "int", "long", "register", "restrict", "return",
+    "short", "signed", "sizeof", "static", "struct", "switch", "typedef", "union", "unsigned", "void",
+    "volatile", "while", "bool", "true", "false", "NULL", "main",
+];
+
+/// Library functions and globals the generated runtime code refers to.
+const C_RUNTIME_NAMES: &[&str] = &[
+    "malloc", "free", "memcpy", "strlen", "strcpy", "strcat", "printf", "fprintf", "snprintf",
+    "exit", "rand", "srand", "time", "stderr", "stdout", "String",
+];
+
+fn is_reserved_c_name(name: &str) -> bool {
+    let numbered = |prefix: &str| {
+        name.strip_prefix(prefix).is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit()))
+    };
+
+    C_KEYWORDS.contains(&name)
+        || C_RUNTIME_NAMES.contains(&name)
+        || name.starts_with('_')
+        || ["var_", "param_", "vix_", "x_"].iter().any(|prefix| name.starts_with(prefix))
+        || numbered("t")
+        || numbered("label_")
+}
+
+/// Whether an arm can match a value of enum variant `variant`, looking only at the outermost pattern.
+fn may_match_variant(pattern: &Pattern, variant: &str) -> bool {
+    match &pattern.kind {
+        PatternKind::Wildcard | PatternKind::Binding(_) => true,
+        PatternKind::Variant { variant: other, .. } => other == variant,
+        PatternKind::Or(alternatives) => alternatives.iter().any(|alternative| may_match_variant(alternative, variant)),
+        _ => false,
+    }
+}
@@ -65,9 +65,16 @@ impl Codegen {
                 arch: arch.clone(),
                 optimization_level: OptimizationLevel::default(),
                 debug_info: false,
+                runtime_checks: true,
             },
             type_registry: TypeRegistry::new(),
             impl_methods: HashMap::new(),
+            traits: HashMap::new(),
+            trait_impls: HashSet::new(),
+            generic_functions: HashMap::new(),
+            generic_structs: HashMap::new(),
+            struct_instances: HashMap::new(),
+            pending_instances: Vec::new(),
             c_code: String::new(),
             globals: String::new(),
             var_count: 0,
@@ -92,6 +99,13 @@ impl Codegen {
             source_code,
             current_file: filename,
             current_return_type: None,
+            lambda_count: 0,
+            mutable_vars: HashSet::new(),
+            dyn_temps: Vec::new(),
+            dyn_slots: Vec::new(),
+            units: Vec::new(),
+            generic_instances: HashMap::new(),
+            unit_instances: HashSet::new(),
         }
     }
 
@@ -117,6 +131,16 @@ impl Codegen {
             Type::Array { element, .. } => {
                 self.ensure_type_defined(element);
             }
+            Type::FnPtr { params, return_type } => {
+                for param in params {
+                    self.ensure_type_defined(param);
+                }
+                self.ensure_type_defined(return_type);
+            }
+            // A function pointer may be converted to the closure type.
+            Type::Closure { params, return_type } => {
+                self.ensure_type_defined(&Type::FnPtr { params: params.clone(), return_type: return_type.clone() });
+            }
             Type::Ptr(inner) | Type::RawPtr(inner) | Type::Owned(inner) | Type::Ref(inner) | Type::MutRef(inner) | Type::Const(inner) => {
                 self.ensure_type_defined(inner);
             }
@@ -155,6 +179,15 @@ impl Codegen {
             length,
         }
     }
+    /// `location` if it came from the parser, otherwise the current file with no line.
+    pub fn located(&self, location: &SourceLocation) -> SourceLocation {
+        if location.line == 0 {
+            self.default_location()
+        } else {
+            location.clone()
+        }
+    }
+
     pub fn default_location(&self) -> SourceLocation {
         SourceLocation {
             file: self.current_file.clone(),
@@ -164,16 +197,71 @@ impl Codegen {
         }
     }
 
+    /// A progress line on stdout, left out when diagnostics are printed as JSON.
+    fn progress(&self, line: String) {
+        if self.diagnostics.options.format == DiagnosticFormat::Human {
+            println!("{}", line);
+        }
+    }
+
     pub fn fresh_label(&mut self) -> String {
         let label = format!("label_{}", self.label_count);
         self.label_count += 1;
         label
     }
 
-    pub fn codegen_stmt(&mut self, stmt: &Stmt, body: &mut String) -> Result<(), ()> {
-        let loc = self.default_location();
-        
-        match stmt {
+    /// Points the following C back at the Vix source so debuggers step through `.x` lines.
+    pub fn emit_line_directive(&self, location: &SourceLocation, body: &mut String) {
+        if location.line == 0 {
+            return;
+        }
+        if !body.is_empty() && !body.ends_with('\n') {
+            body.push('\n');
+        }
+        let file = location.file.replace('\\', "\\\\").replace('"', "\\\"");
+        body.push_str(&format!("#line {} \"{}\"\n", location.line, file));
+    }
+
+    /// Adds the C lines of a statement to `body`, each pointed back at the statement's line: after
+    /// `#line N` the C compiler numbers the following lines N+1, N+2, ... Lines that a nested
+    /// statement already pointed at its own line keep it.
+    fn emit_mapped_lines(&self, location: &SourceLocation, code: &str, body: &mut String) {
+        let mut mapped = false;
+        for line in code.lines() {
+            if line.starts_with("#line ") {
+                mapped = true;
+            } else if !std::mem::take(&mut mapped) {
+                self.emit_line_directive(location, body);
+            }
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+
+    /// Hands the lines after a function back to the generated C file.
+    pub fn end_line_mapping(&self, code: &mut String) {
+        if self.config.debug_info {
+            code.push_str(Clang::GENERATED_LINE_MARKER);
+            code.push('\n');
+        }
+    }
+
+    pub fn codegen_stmt(&mut self, spanned: &SpannedStmt, body: &mut String) -> Result<(), ()> {
+        if !self.config.debug_info {
+            return self.codegen_stmt_kind(spanned, body);
+        }
+        let mut code = String::new();
+        let result = self.codegen_stmt_kind(spanned, &mut code);
+        self.emit_mapped_lines(&spanned.location, &code, body);
+        result
+    }
+
+    fn codegen_stmt_kind(&mut self, spanned: &SpannedStmt, body: &mut String) -> Result<(), ()> {
+        let stmt = &spanned.stmt;
+        let loc = self.located(&spanned.location);
+
+        let temps = self.dyn_temps.len();
+        let result = match stmt {
             Stmt::TypedDeclaration { name, ty, value, is_mutable, .. } => {
                 self.codegen_typed_declaration_impl(name, ty, value, body, loc, *is_mutable)
             }
@@ -191,6 +279,8 @@ impl Codegen {
             Stmt::Break => self.codegen_break(body).map_err(|_| ()),
             Stmt::Continue => self.codegen_continue(body).map_err(|_| ()),
             Stmt::Scope(stmts) => self.codegen_scope(stmts, body),
+            // Nothing to emit, and the lint pass already reports it (W0107).
+            Stmt::Unsafe(stmts) if stmts.is_empty() => Ok(()),
             Stmt::StructDef(s) => self.codegen_struct_definition(s),
             Stmt::EnumDef(e) => self.codegen_enum_definition(e),
             Stmt::MemberCompoundAssign(obj, field, op, value) => {self.codegen_member_compound_assign(obj, field, op, value, body, loc)}
@@ -200,7 +290,7 @@ impl Codegen {
             }
             _ => {
                 self.diagnostics.warning(
-                    "UnsupportedStatement",
+                    &codes::W0002,
                     &format!("Skipping unsupported statement type: {:?}", stmt),
                     ErrorContext {
                         primary_location: loc,
@@ -211,31 +301,61 @@ impl Codegen {
                 );
                 Ok(())
             }
-        }
+        };
+        self.free_dyn_temps(temps, body);
+        result
     }
 
+/// A call whose result holds a `dyn Trait` hands over its boxes, which are freed after the
+/// statement unless a declaration, assignment, constructor or return takes them.
 pub fn codegen_expr(&mut self, expr: &Expr, body: &mut String) -> Result<(String, Type), ()> {
-    let loc = self.default_location();
-    match expr {
-        Expr::Number(n) => Ok(self.codegen_number(*n, body)),
-        Expr::Float(f) => Ok(self.codegen_float(*f, body)),
-        Expr::Bool(b) => Ok(self.codegen_bool(*b, body)),
-        Expr::Char(c) => Ok(self.codegen_char(*c, body)),
-        Expr::HexNumber(n) => Ok(self.codegen_hex_number(*n, body)),
-        Expr::BinaryNumber(n) => Ok(self.codegen_binary_number(*n, body)),
-        Expr::OctalNumber(n) => Ok(self.codegen_octal_number(*n, body)),
-        Expr::String(s) => Ok(self.codegen_string(s, body)),
-        Expr::Var(name) => self.codegen_var(name, loc),
-        Expr::BinOp(op, left, right) => self.codegen_binop(op, left, right, body, loc),
-        Expr::UnOp(op, operand) => self.codegen_unop(op, operand, body, loc),
-        Expr::Call(name, args) if self.structs.contains_key(name) => {
+    let result = self.codegen_expr_kind(expr, body);
+    let is_call = matches!(
+        expr.kind,
+        ExprKind::Call(..) | ExprKind::CallNamed(..) | ExprKind::MethodCall(..) | ExprKind::ModuleCall(..) | ExprKind::StaticMethodCall(..)
+    );
+    if let (Ok((var, ty)), true) = (&result, is_call) {
+        let paths = self.dyn_paths(ty);
+        if !paths.is_empty() {
+            self.dyn_temps.push((var.clone(), ty.clone()));
+        }
+    }
+    result
+}
+
+fn codegen_expr_kind(&mut self, expr: &Expr, body: &mut String) -> Result<(String, Type), ()> {
+    let loc = self.located(&expr.location);
+    match &expr.kind {
+        ExprKind::Number(n) => Ok(self.codegen_number(*n, body)),
+        ExprKind::Float(f) => Ok(self.codegen_float(*f, body)),
+        ExprKind::Bool(b) => Ok(self.codegen_bool(*b, body)),
+        ExprKind::Char(c) => Ok(self.codegen_char(*c, body)),
+        ExprKind::HexNumber(n) => Ok(self.codegen_hex_number(*n, body)),
+        ExprKind::BinaryNumber(n) => Ok(self.codegen_binary_number(*n, body)),
+        ExprKind::OctalNumber(n) => Ok(self.codegen_octal_number(*n, body)),
+        ExprKind::String(s) => Ok(self.codegen_string(s, body)),
+        ExprKind::Var(name) => self.codegen_var(name, loc),
+        ExprKind::BinOp(op, left, right) => self.codegen_binop(op, left, right, body, loc),
+        ExprKind::UnOp(op, operand) => self.codegen_unop(op, operand, body, loc),
+        ExprKind::Lambda { params, return_type, body: lambda_body } => self.codegen_lambda(params, return_type, lambda_body, body),
+        ExprKind::FuncAddr(name) => self.codegen_func_addr(name, loc),
+        ExprKind::Call(name, args) if self.function_var(name).is_some() => {
+            let (c_name, ty) = self.function_var(name).unwrap();
+            self.codegen_indirect_call(&c_name, &ty, args, body)
+        }
+        ExprKind::Call(name, args) if self.generic_structs.contains_key(name) => {
+            let args: Vec<(Option<&str>, &Expr)> = args.iter().map(|arg| (None, arg)).collect();
+            self.codegen_generic_struct_new(name, &args, body)
+        }
+        ExprKind::Call(name, args) if self.structs.contains_key(name) => {
             let constructor_name = format!("{}_new", name);
             
             let mut arg_vars = Vec::new();
             for arg in args {
-                let (var, _ty) = self.codegen_expr(arg, body).check_error();
-                arg_vars.push(var);
+                let (var, ty) = self.codegen_expr(arg, body).check_error();
+                arg_vars.push((None, var, ty));
             }
+            let arg_vars = self.constructor_args(name, arg_vars, body);
             
             let tmp = self.fresh_var();
             let args_str = arg_vars.join(", ");
@@ -244,29 +364,38 @@ pub fn codegen_expr(&mut self, expr: &Expr, body: &mut String) -> Result<(String
             return Ok((tmp, Type::Struct { name: name.clone() }));
         }
         
-        Expr::Call(func, args) => self.codegen_call_expr(func, args, body, loc),
-        Expr::Array(elements) => self.codegen_array(elements, body),
-        Expr::Index(arr, indices) => self.codegen_index(arr, indices, body),
-        Expr::MemberAccess(obj, field) => self.codegen_member_access(obj, field, body, loc),
-        Expr::ResultOk(inner) => {self.codegen_result_ok(inner, body)}
-        Expr::ResultErr(inner) => {self.codegen_result_err(inner, body)}
-        Expr::Not(expr) => self.codegen_not(expr, body).map_err(|_| ()),
-        Expr::Tuple(elements) => self.codegen_tuple(elements, body),
-        Expr::MethodCall(obj, method, args) => self.codegen_method_call(obj, method, args, body, loc),
-        Expr::ModuleCall(module, func, args) => self.codegen_module_call(module, func, args, body, loc),
-        Expr::Cast(expr, target) => self.codegen_cast_target(expr, target, body, loc),
-        Expr::StaticMethodCall(type_name, method, args) => {
+        ExprKind::Call(func, args) => self.codegen_call_expr(func, args, body, loc),
+        ExprKind::Array(elements) => self.codegen_array(elements, body),
+        ExprKind::Index(arr, indices) => self.codegen_index(arr, indices, body),
+        ExprKind::MemberAccess(obj, field) => self.codegen_member_access(obj, field, body, loc),
+        ExprKind::ResultOk(inner) => {self.codegen_result_ok(inner, body)}
+        ExprKind::ResultErr(inner) => {self.codegen_result_err(inner, body)}
+        ExprKind::Not(expr) => self.codegen_not(expr, body).map_err(|_| ()),
+        ExprKind::Tuple(elements) => self.codegen_tuple(elements, body),
+        ExprKind::MethodCall(obj, method, args) => self.codegen_method_call(obj, method, args, body, loc),
+        ExprKind::MethodCallNamed(obj, method, args) if self.enum_name_of(obj).is_some() => {
+            let args: Vec<(Option<&str>, &Expr)> = args.iter().map(|(field, arg)| (Some(field.as_str()), arg)).collect();
+            self.codegen_enum_new(self.enum_name_of(obj).unwrap(), method, &args, body)
+        }
+        ExprKind::ModuleCall(module, func, args) => self.codegen_module_call(module, func, args, body, loc),
+        ExprKind::Cast(expr, target) => self.codegen_cast_target(expr, target, body, loc),
+        ExprKind::StaticMethodCall(type_name, method, args) => {
             self.codegen_static_method(type_name, method, args, body, loc)
         }
-        Expr::CallNamed(name, named_args) => {
+        ExprKind::CallNamed(name, named_args) => {
+            if self.generic_structs.contains_key(name) {
+                let args: Vec<(Option<&str>, &Expr)> = named_args.iter().map(|(field, arg)| (Some(field.as_str()), arg)).collect();
+                return self.codegen_generic_struct_new(name, &args, body);
+            }
             if self.structs.contains_key(name) {
                 let constructor_name = format!("{}_new", name);
                 
                 let mut arg_vars = Vec::new();
-                for (_arg_name, arg_expr) in named_args {
-                    let (var, _ty) = self.codegen_expr(arg_expr, body).check_error();
-                    arg_vars.push(var);
+                for (arg_name, arg_expr) in named_args {
+                    let (var, ty) = self.codegen_expr(arg_expr, body).check_error();
+                    arg_vars.push((Some(arg_name.as_str()), var, ty));
                 }
+                let arg_vars = self.constructor_args(name, arg_vars, body);
                 
                 let tmp = self.fresh_var();
                 let args_str = arg_vars.join(", ");
@@ -288,36 +417,35 @@ pub fn codegen_expr(&mut self, expr: &Expr, body: &mut String) -> Result<(String
             Ok((tmp, Type::i32()))
         }
 
-        Expr::SizeOf(ty) => {
+        ExprKind::SizeOf(ty) => {
             let tmp = self.fresh_var();
             let c_type = ty.to_c_type(&self.arch);
             body.push_str(&format!("size_t {} = sizeof({});\n", tmp, c_type));
             Ok((tmp, Type::i64()))
         }
-        Expr::AlignOf(ty) => {
+        ExprKind::AlignOf(ty) => {
             let tmp = self.fresh_var();
             let c_type = ty.to_c_type(&self.arch);
             body.push_str(&format!("size_t {} = _Alignof({});\n", tmp, c_type));
             Ok((tmp, Type::i64()))
         }
-        Expr::TypeOf(expr) => {
+        ExprKind::TypeOf(expr) => {
             let (var, ty) = self.codegen_expr(expr, body)?;
             Ok((var, ty))
         }
 
-        Expr::None => {
-            let tmp = self.fresh_var();
-            body.push_str(&format!("void* {} = NULL;\n", tmp));
-            Ok((tmp, Type::Ptr(Box::new(Type::Void))))
+        ExprKind::None => {
+            let expected = self.current_return_type.clone();
+            self.codegen_none(expected.as_ref(), body)
         }
-        Expr::Some(inner) => {
+        ExprKind::Some(inner) => {
             self.codegen_some(inner, body)
         }
 
-        Expr::Pipe(left, right) => {
+        ExprKind::Pipe(left, right) => {
             let _ = self.codegen_expr(left, body)?;
-            match right.as_ref() {
-                Expr::Call(func, args) => {
+            match &right.kind {
+                ExprKind::Call(func, args) => {
                     let mut new_args = vec![*left.clone()];
                     new_args.extend(args.clone());
                     self.codegen_call_expr(func, &new_args, body, loc)
@@ -328,7 +456,7 @@ pub fn codegen_expr(&mut self, expr: &Expr, body: &mut String) -> Result<(String
 
         _ => {
             self.diagnostics.error(
-                "UnsupportedExpression",
+                &codes::E0800,
                 &format!("Unsupported expression type: {:?}", expr),
                 ErrorContext {
                     primary_location: loc,
@@ -351,6 +479,7 @@ pub fn codegen_expr(&mut self, expr: &Expr, body: &mut String) -> Result<(String
         structs: &[StructDef],
         enums: &[EnumDef],
         impls: &[ImplBlock],
+        traits: &[TraitDef],
         externs: &[ExternDecl],
         library_includes: &[String],  
         library_functions: &[FunctionSignature],  
@@ -376,21 +505,40 @@ pub fn codegen_expr(&mut self, expr: &Expr, body: &mut String) -> Result<(String
             self.ir.forward_decls.push_str("\n");
         }
         
-        println!("   {} Generating struct definitions...", "processing:".bright_black());
+        self.progress(format!("   {} Generating struct definitions...", "processing:".bright_black()));
         
-        for struct_def in structs {
-            self.codegen_struct_definition(struct_def);
+        // A struct may hold a `dyn Trait`, and a vtable may take a struct.
+        for trait_def in traits {
+            self.codegen_trait_object(trait_def);
         }
 
-        for enum_def in enums {
-            self.codegen_enum_definition(enum_def);
+        // Generic structs are only emitted once instantiated, which a later struct may do.
+        for struct_def in structs.iter().filter(|s| !s.type_params.is_empty()) {
+            self.generic_structs.insert(struct_def.name.clone(), struct_def.clone());
         }
+        self.codegen_type_definitions(structs, enums);
 
-        println!("   {} Registering function/method signatures...", "processing:".bright_black());
-        for func in &program.functions {
+        for trait_def in traits {
+            self.codegen_trait_vtable(trait_def);
+        }
+
+        // `test func`s only exist in the test harness.
+        let with_tests = matches!(self.compilation_mode, CompilationMode::Test { .. });
+        let mut functions = Vec::new();
+        for func in program.functions.iter().filter(|func| with_tests || !func.is_test) {
+            if func.type_params.is_empty() {
+                functions.push(self.monomorphize(func, &HashMap::new()));
+            } else {
+                self.generic_functions.insert(func.name.clone(), func.clone());
+            }
+        }
+        let impls: Vec<ImplBlock> = impls.iter().map(|impl_block| self.monomorphize_impl(impl_block)).collect();
+
+        self.progress(format!("   {} Registering function/method signatures...", "processing:".bright_black()));
+        for func in &functions {
             self.codegen_function(func, true);
         }
-        for impl_block in impls {
+        for impl_block in &impls {
             let _ = self.codegen_impl_block(impl_block, true);
         }
 
@@ -398,31 +546,157 @@ pub fn codegen_expr(&mut self, expr: &Expr, body: &mut String) -> Result<(String
            
         }
 
-        println!("   {} Generating function code...", "processing:".bright_black());
-        for func in &program.functions {
-            self.codegen_function(func, false);
-        }
+        if !self.units.is_empty() {
+            self.codegen_units(&functions, &impls);
+        } else {
+            self.progress(format!("   {} Generating function code...", "processing:".bright_black()));
+            for func in &functions {
+                self.codegen_function(func, false);
+            }
+
+            self.progress(format!("   {} Generating impl block code...", "processing:".bright_black()));
+            for impl_block in &impls {
+                if let Err(_) = self.codegen_impl_block(impl_block, false) {
+                   
+                }
+            }
 
-        println!("   {} Generating impl block code...", "processing:".bright_black());
-        for impl_block in impls {
-            if let Err(_) = self.codegen_impl_block(impl_block, false) {
-               
+            // Emitting an instance may call for further instances.
+            while let Some(instance) = self.pending_instances.pop() {
+                self.codegen_function(&instance, false);
             }
         }
          
         if self.diagnostics.has_errors() {
-            println!();
             self.diagnostics.print_summary();
-            println!("Code generation failed due to errors");
+            self.progress("Code generation failed due to errors".to_string());
         }
-        
+
         if self.diagnostics.warning_count > 0 {
-            println!("   {} {} warning(s) generated", "Warning:".yellow(), self.diagnostics.warning_count);
+            self.progress(format!("   {} {} warning(s) generated", "Warning:".yellow(), self.diagnostics.warning_count));
         }
 
          
-        self.ir.functions.push_str("\nint main() {\n    vix_main();\n    return 0;\n}\n");
+        let entry = match &self.compilation_mode {
+            CompilationMode::Test { tests } => Self::test_runner(tests),
+            _ => "\nint main() {\n    vix_main();\n    return 0;\n}\n".to_string(),
+        };
+        self.ir.functions.push_str(&entry);
         
         Ok(self.ir.clone().finalize())
     }
+
+    /// Emits non-generic structs and enums so that each comes after the types it holds by value.
+    fn codegen_type_definitions(&mut self, structs: &[StructDef], enums: &[EnumDef]) {
+        let structs: Vec<&StructDef> = structs.iter().filter(|s| s.type_params.is_empty()).collect();
+        let mut emitted = vec![false; structs.len() + enums.len()];
+        for index in 0..emitted.len() {
+            self.codegen_type_definition(index, &structs, enums, &mut emitted);
+        }
+    }
+
+    /// `index` counts the structs first, then the enums.
+    fn codegen_type_definition(&mut self, index: usize, structs: &[&StructDef], enums: &[EnumDef], emitted: &mut [bool]) {
+        if emitted[index] {
+            return;
+        }
+        emitted[index] = true;
+        let (name, held): (&str, Vec<&Type>) = match structs.get(index) {
+            Some(struct_def) => (&struct_def.name, struct_def.fields.iter().map(|f| &f.ty).collect()),
+            None => {
+                let enum_def = &enums[index - structs.len()];
+                let held = enum_def.variants.iter().flat_map(|variant| match variant {
+                    EnumVariant::Simple(_) => vec![],
+                    EnumVariant::Tuple(_, types) => types.iter().collect(),
+                    EnumVariant::Struct(_, fields) => fields.iter().map(|f| &f.ty).collect(),
+                });
+                (&enum_def.name, held.collect())
+            }
+        };
+
+        // Every struct or enum named in a field's type, pointers included, since C needs those declared too.
+        let mut names = Vec::new();
+        for ty in held {
+            ty.map(&mut |ty| {
+                if let Type::Struct { name } | Type::Generic { name, .. } = ty {
+                    names.push(name.clone());
+                }
+                None
+            });
+        }
+        let positions = structs.iter().map(|s| &s.name).chain(enums.iter().map(|e| &e.name));
+        let dependencies: Vec<usize> = positions.enumerate().filter(|(_, other)| *other != name && names.contains(other)).map(|(i, _)| i).collect();
+        for dependency in dependencies {
+            self.codegen_type_definition(dependency, structs, enums, emitted);
+        }
+
+        let _ = match structs.get(index) {
+            Some(struct_def) => {
+                let mut struct_def = (*struct_def).clone();
+                for field in &mut struct_def.fields {
+                    field.ty = self.concrete_type(&field.ty);
+                }
+                self.codegen_struct_definition(&struct_def)
+            }
+            None => self.codegen_enum_definition(&enums[index - structs.len()]),
+        };
+    }
+
+    /// Emits the functions and impl blocks of each unit that is not cached apart from the rest,
+    /// which stays in `ir.functions` for the translation unit they all share.
+    fn codegen_units(&mut self, functions: &[Function], impls: &[ImplBlock]) {
+        self.progress(format!("   {} Generating code for {} module(s)...", "processing:".bright_black(), self.units.iter().filter(|unit| !unit.cached).count()));
+        let shared = std::mem::take(&mut self.ir.functions);
+        let mut units = std::mem::take(&mut self.units);
+        for unit in units.iter_mut().filter(|unit| !unit.cached) {
+            self.unit_instances.clear();
+            for func in functions.iter().filter(|func| unit.functions.contains(&func.name)) {
+                self.codegen_function(func, false);
+            }
+            for impl_block in &impls[unit.impls.clone()] {
+                let _ = self.codegen_impl_block(impl_block, false);
+            }
+            while let Some(instance) = self.pending_instances.pop() {
+                self.codegen_function(&instance, false);
+            }
+            unit.code = std::mem::take(&mut self.ir.functions);
+        }
+        self.units = units;
+        self.ir.functions = shared;
+    }
+
+    /// The C of a unit: its functions after the declarations every unit shares.
+    pub fn unit_source(&self, unit: &CodegenUnit) -> String {
+        IR { functions: unit.code.clone(), ..self.ir.clone() }.finalize()
+    }
+
+    /// The `main` of the test harness: without arguments it lists the tests one per line,
+    /// and given a test's name it runs only that test.
+    fn test_runner(tests: &[(String, String)]) -> String {
+        let names: String = tests.iter().map(|(name, _)| format!("\"{}\", ", name)).collect();
+        let functions: String = tests.iter().map(|(_, function)| format!("{}, ", function)).collect();
+        format!(
+            r#"
+static const char* vix_test_names[] = {{ {}NULL }};
+static void (*const vix_test_functions[])(void) = {{ {}NULL }};
+
+int main(int argc, char** argv) {{
+    for (int i = 0; vix_test_names[i] != NULL; i++) {{
+        if (argc < 2) {{
+            printf("%s\n", vix_test_names[i]);
+        }} else if (strcmp(argv[1], vix_test_names[i]) == 0) {{
+            vix_test_functions[i]();
+            return 0;
+        }}
+    }}
+    if (argc < 2) {{
+        return 0;
+    }}
+    fprintf(stderr, "no test named %s\n", argv[1]);
+    return 2;
+}}
+"#,
+            names, functions
+        )
+    }
 }
@@ -19,6 +19,8 @@ pub struct FootprintPack {
     pub includes: Vec<String>,
     pub functions: Vec<String>,
     pub source_library: String,
+    #[serde(default)]
+    pub inputs: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -135,15 +137,21 @@ impl LibraryManager {
         &self,
         lib_metadata: &LibraryMetadata,
         target_os: Option<TargetOS>,
+        profile: &Profile,
     ) -> Result<PathBuf, LibraryError> {
         let vix_path = Self::get_vix_path()?;
         let binary_dir = vix_path.join("library").join("binary");
-        let output_name = format!("{}-{}", lib_metadata.name, lib_metadata.version);
+        // Each profile keeps its own binary so switching between dev and release doesn't rebuild.
+        let output_name = format!("{}-{}-{}", lib_metadata.name, lib_metadata.version, profile.name);
         let binary_path = binary_dir.join(format!("{}.bin", output_name));
 
+        let hash_path = binary_dir.join(format!("{}.hash", output_name));
+
         fs::create_dir_all(&binary_dir).map_err(|e| LibraryError::FileReadError(binary_dir.clone(), e.to_string()))?;
 
-        if binary_path.exists() {
+        let fingerprint = Self::library_fingerprint(lib_metadata, target_os, profile);
+        let cached_hash = fs::read_to_string(&hash_path).unwrap_or_default();
+        if binary_path.exists() && cached_hash.trim() == fingerprint {
             return Ok(binary_path);
         }
 
@@ -160,48 +168,80 @@ impl LibraryManager {
                     all_source.push_str("\n\n");
                 }
                 "c" | "cpp" => {
-                    return Self::compile_c_cpp_library(script_path, &binary_path, ext, target_os);
+                    let built = Self::compile_c_cpp_library(script_path, &binary_path, ext, target_os, profile)?;
+                    let _ = fs::write(&hash_path, &fingerprint);
+                    return Ok(built);
                 }
                 "ll" => {
-                    return Self::compile_llvm_library(script_path, &binary_path, target_os);
+                    let built = Self::compile_llvm_library(script_path, &binary_path, target_os)?;
+                    let _ = fs::write(&hash_path, &fingerprint);
+                    return Ok(built);
                 }
                 _ => {}
             }
         }
 
         if !all_source.is_empty() {
-            Self::compile_vix_library(&all_source, &binary_path, target_os, &lib_metadata.includes)?;
+            Self::compile_vix_library(&all_source, &binary_path, target_os, profile, &lib_metadata.includes)?;
         }
 
+        let _ = fs::write(&hash_path, &fingerprint);
         println!("   {} Library compiled successfully", "✓".green());
         Ok(binary_path)
     }
 
+    /// Files a compiled library depends on: its package.json and every source script.
+    pub fn library_inputs(lib_metadata: &LibraryMetadata) -> Vec<PathBuf> {
+        let mut inputs = vec![lib_metadata.path.join("package.json")];
+        inputs.extend(lib_metadata.verified_scripts.iter().cloned());
+        inputs
+    }
+
+    fn library_fingerprint(lib_metadata: &LibraryMetadata, target_os: Option<TargetOS>, profile: &Profile) -> String {
+        let mut fingerprint = Fingerprint::new();
+        fingerprint.add_str("target", target_os.unwrap_or_else(TargetOS::current).dir_name());
+        fingerprint.add_str("profile", &format!("{:?}", profile));
+        fingerprint.add_str("clang", &Clang::version());
+        for input in Self::library_inputs(lib_metadata) {
+            fingerprint.add_file(&input);
+        }
+        fingerprint.finish()
+    }
+
     fn compile_vix_library(
         source: &str,
         output_path: &PathBuf,
         target_os: Option<TargetOS>,
+        profile: &Profile,
         library_includes: &[String],  
     ) -> Result<(), LibraryError> {
         let mut lexer = Lexer::new(source);
         let tokens = lexer.tokenize();
 
         if !lexer.errors.is_empty() {
-            eprintln!("   {} Lexer errors:", "Error:".red());
             for error in &lexer.errors {
-                eprintln!("      {}", error.message);
+                eprintln!("{}", error.clone().with_source(source).render());
             }
             return Err(LibraryError::ParseError("Lexer failed".to_string()));
         }
 
         let parser = Parser::new(tokens, source.to_string(), lexer.spans.clone());
-        let (program, structs, enums, externs, _, _, _, impls, _, _, _) = parser.parse();
+        let (program, structs, enums, externs, _, _, _, impls, traits, _, _, diags) = parser.parse();
+        for diag in &diags {
+            eprintln!("{}", diag.clone().with_source(source).render());
+        }
+        if diags.iter().any(Diagnostic::is_error) {
+            return Err(LibraryError::ParseError("Parser failed".to_string()));
+        }
         let arch = ArchConfig::x86_64();
         let mut codegen = Codegen::new(arch, source.to_string(), "library".to_string());
-        let c_code = codegen.codegen_program_full(&program, &structs, &enums, &impls, &externs, library_includes, &[])  .map_err(|_| LibraryError::ParseError("Codegen failed".to_string()))?;
+        profile.apply(&mut codegen.config);
+        // Library scripts are concatenated, so their lines can't be mapped back to one file.
+        codegen.config.debug_info = false;
+        let c_code = codegen.codegen_program_full(&program, &structs, &enums, &impls, &traits, &externs, library_includes, &[])  .map_err(|_| LibraryError::ParseError("Codegen failed".to_string()))?;
 
          
-        Clang::compile_to_object(&c_code, output_path, target_os).map_err(|e| LibraryError::ParseError(e))?;
+        Clang::compile_to_object(&c_code, output_path, target_os, profile).map_err(|e| LibraryError::ParseError(e))?;
 
         Ok(())
     }
@@ -214,6 +254,7 @@ impl LibraryManager {
         output_path: &PathBuf,
         lang: &str,
         _target_os: Option<TargetOS>,
+        profile: &Profile,
     ) -> Result<PathBuf, LibraryError> {
         let mut cmd = Command::new("clang");
         if lang == "cpp" {
@@ -225,7 +266,7 @@ impl LibraryManager {
            .arg(source_path)
            .arg("-o")
            .arg(output_path)
-           .arg("-O3")
+           .args(profile.clang_args())
            .arg("-std=c17");
          
 
@@ -272,6 +313,7 @@ impl LibraryManager {
     pub fn process_all_imports(
         source_files: &Vec<PathBuf>,
         target_os: Option<TargetOS>,
+        profile: &Profile,
     ) -> Result<Vec<FootprintPack>, LibraryError> {
         let mut required_libs = HashSet::new();
 
@@ -331,7 +373,7 @@ impl LibraryManager {
                 includes: package_json.include.clang.clone(),
             };
 
-            let binary_path = Self.compile_library(&lib_metadata, target_os)?;
+            let binary_path = Self.compile_library(&lib_metadata, target_os, profile)?;
 
              
             let (functions, classes) = Self::extract_library_symbols(&lib_metadata)?;
@@ -346,6 +388,7 @@ impl LibraryManager {
                 functions,
                 includes: package_json.include.clang.clone(),
                 source_library: binary_path.to_string_lossy().to_string(),
+                inputs: Self::library_inputs(&lib_metadata).iter().map(|p| p.to_string_lossy().to_string()).collect(),
             });
         }
 
@@ -374,6 +417,7 @@ impl LibraryManager {
     pub fn process_imports_from_decls(
         import_decls: &[ImportDecl],
         target_os: Option<TargetOS>,
+        profile: &Profile,
     ) -> Result<Vec<FootprintPack>, LibraryError> {
         let imports = Self::extract_imports_from_decls(import_decls);
         let mut required_libs = HashSet::new();
@@ -466,7 +510,7 @@ impl LibraryManager {
                 includes: package_json.include.clang.clone(),
             };
 
-            let binary_path = Self.compile_library(&lib_metadata, target_os)?;
+            let binary_path = Self.compile_library(&lib_metadata, target_os, profile)?;
 
             println!("   {} Binary created: {}", "success:".green(), binary_path.display());
 
@@ -483,6 +527,7 @@ impl LibraryManager {
                 functions,
                 includes: package_json.include.clang.clone(),
                 source_library: binary_path.to_string_lossy().to_string(),
+                inputs: Self::library_inputs(&lib_metadata).iter().map(|p| p.to_string_lossy().to_string()).collect(),
             });
         }
 
@@ -559,7 +604,7 @@ impl LibraryManager {
             let mut lexer = Lexer::new(&source);
             let tokens = lexer.tokenize();
             let parser = Parser::new(tokens, source.clone(), lexer.spans.clone());
-            let (program, _, _, _, _, _, _, _, _, _, _) = parser.parse();
+            let (program, _, _, _, _, _, _, _, _, _, _, _) = parser.parse();
 
              
             println!("      {} Found {} total functions in library", "→".bright_black(), program.functions.len());
@@ -1 +1,2 @@
 pub mod operations;
+pub mod visit;
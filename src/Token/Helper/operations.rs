@@ -17,13 +17,18 @@ impl Parser {
         self.spans.get(self.pos).cloned().unwrap_or(SourceSpan::from(0..0))
     }
 
+    /// Consumes `expected`. Otherwise reports it and skips ahead to `expected`, a token in `sync`,
+    /// a closing bracket, or the `end`/`func`/`struct`/`impl` that starts or closes the
+    /// surrounding block.
     pub fn expect(&mut self, expected: Token, sync: Vec<Token>) {
-        let _start_pos = self.pos;
         let current = self.current();
         if current == expected {
             self.advance();
+            self.recovering = false;
             return;
         }
+        self.unexpected(&expected.to_string());
+
         let sync_set: std::collections::HashSet<Token> = sync.into_iter().collect();
         let mut skipped = 0;
         let max_skip = 50;
@@ -33,14 +38,70 @@ impl Parser {
                 self.advance();
                 return;
             }
-            if sync_set.contains(&current_token) {
-                return;
-            }
-            if current_token == Token::EOF {
+            if sync_set.contains(&current_token) || Self::is_sync_token(&current_token) || self.at_list_end() {
                 return;
             }
             self.advance();
             skipped += 1;
         }
     }
+
+    /// Consumes an identifier, or reports `what` as missing and returns a placeholder name.
+    pub fn expect_identifier(&mut self, what: &str) -> String {
+        if let Token::Identifier(name) = self.current() {
+            self.advance();
+            return name;
+        }
+        self.unexpected(what);
+        self.advance();
+        "error".to_string()
+    }
+
+    /// Records "expected X, found Y" at the current token, unless this is fallout from an
+    /// error that has not been recovered from yet.
+    pub fn unexpected(&mut self, expected: &str) {
+        if self.recovering {
+            return;
+        }
+        self.recovering = true;
+        let found = self.current();
+        self.error_at(self.pos, &codes::E0104, &format!("Expected {}, found {}", expected, found), None);
+    }
+
+    pub fn is_sync_token(token: &Token) -> bool {
+        matches!(token, Token::End | Token::Func | Token::Struct | Token::Impl)
+    }
+
+    /// Whether the current token closes the block being parsed or starts the next item, which
+    /// recovery inside the block must leave alone. A `func` followed by `(` is a lambda.
+    pub fn at_block_end(&self) -> bool {
+        match self.current() {
+            Token::End | Token::EOF | Token::Struct | Token::Impl | Token::Trait => true,
+            Token::Func => self.peek(1) != Token::LeftParen,
+            _ => self.at_test_func(),
+        }
+    }
+
+    /// Whether the current token closes the argument or element list being parsed, or one
+    /// around it.
+    pub fn at_list_end(&self) -> bool {
+        matches!(self.current(), Token::RightParen | Token::RightBracket) || self.at_block_end()
+    }
+
+    /// `test` is only a keyword right before `func`, so it stays usable as a name elsewhere.
+    pub fn at_test_func(&self) -> bool {
+        matches!(self.current(), Token::Identifier(name) if name == "test") && self.peek(1) == Token::Func
+    }
+
+    /// Skips to the next top-level item after a syntax error.
+    pub fn skip_to_item(&mut self) {
+        while !matches!(
+            self.current(),
+            Token::Func | Token::Struct | Token::Impl | Token::Enum | Token::Pub | Token::Extern | Token::Module | Token::Type | Token::Trait | Token::Use | Token::Mod | Token::Import | Token::EOF
+        ) && !self.at_test_func()
+        {
+            self.advance();
+        }
+        self.recovering = false;
+    }
 }
\ No newline at end of file
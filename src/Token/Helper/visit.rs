@@ -0,0 +1,252 @@
+use crate::import::*;
+
+/// Mutable walk over statements and expressions. Override `visit_stmt`/`visit_expr`
+/// and call the matching `walk_*` function to keep descending.
+pub trait VisitMut {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr);
+    }
+
+    fn visit_body(&mut self, body: &mut Vec<SpannedStmt>) {
+        for spanned in body {
+            self.visit_stmt(&mut spanned.stmt);
+        }
+    }
+}
+
+pub fn walk_stmt<V: VisitMut + ?Sized>(v: &mut V, stmt: &mut Stmt) {
+    match stmt {
+        Stmt::TypedDeclaration { value, .. } | Stmt::TupleUnpack { value, .. } => v.visit_expr(value),
+        Stmt::Assign(_, value)
+        | Stmt::CompoundAssign(_, _, value)
+        | Stmt::ModuleAssign(_, _, value)
+        | Stmt::ModuleCompoundAssign(_, _, _, value) => v.visit_expr(value),
+        Stmt::IndexAssign(obj, indices, value) => {
+            v.visit_expr(obj);
+            for index in indices {
+                v.visit_expr(index);
+            }
+            v.visit_expr(value);
+        }
+        Stmt::MemberAssign(obj, _, value) => {
+            v.visit_expr(obj);
+            v.visit_expr(value);
+        }
+        Stmt::MemberCompoundAssign(obj, _, _, value) => {
+            v.visit_expr(obj);
+            v.visit_expr(value);
+        }
+        Stmt::If(cond, then_body, else_body) => {
+            v.visit_expr(cond);
+            v.visit_body(then_body);
+            if let Some(else_body) = else_body {
+                v.visit_body(else_body);
+            }
+        }
+        Stmt::While(cond, body) => {
+            v.visit_expr(cond);
+            v.visit_body(body);
+        }
+        Stmt::For(_, iterable, body) => {
+            v.visit_expr(iterable);
+            v.visit_body(body);
+        }
+        Stmt::Loop(body) | Stmt::Unsafe(body) | Stmt::Scope(body) => v.visit_body(body),
+        Stmt::Match(expr, cases, default) => {
+            v.visit_expr(expr);
+            for case in cases {
+                if let Some(guard) = &mut case.guard {
+                    v.visit_expr(guard);
+                }
+                v.visit_body(&mut case.body);
+            }
+            if let Some(default) = default {
+                v.visit_body(default);
+            }
+        }
+        Stmt::Call(_, args) | Stmt::ModuleCall(_, _, args) | Stmt::StaticMethodCall(_, _, args) => {
+            for arg in args {
+                v.visit_expr(arg);
+            }
+        }
+        Stmt::StaticMethodCallNamed(_, _, args) => {
+            for (_, arg) in args {
+                v.visit_expr(arg);
+            }
+        }
+        Stmt::MethodCall(obj, _, args) => {
+            v.visit_expr(obj);
+            for arg in args {
+                v.visit_expr(arg);
+            }
+        }
+        Stmt::MethodCallNamed(obj, _, args) => {
+            v.visit_expr(obj);
+            for (_, arg) in args {
+                v.visit_expr(arg);
+            }
+        }
+        Stmt::Return(value) => {
+            if let Some(value) = value {
+                v.visit_expr(value);
+            }
+        }
+        Stmt::ImplBlock(impl_block) => walk_impl_block(v, impl_block),
+        Stmt::Expr(expr) => v.visit_expr(expr),
+        Stmt::Break
+        | Stmt::Continue
+        | Stmt::StructDef(_)
+        | Stmt::EnumDef(_)
+        | Stmt::TraitDef(_)
+        | Stmt::ExternDecl(_)
+        | Stmt::ModuleImport(_)
+        | Stmt::ModuleUse(_) => {}
+    }
+}
+
+pub fn walk_impl_block<V: VisitMut + ?Sized>(v: &mut V, impl_block: &mut ImplBlock) {
+    if let Some(fields) = &mut impl_block.constructor_body {
+        for (_, value) in fields {
+            v.visit_expr(value);
+        }
+    }
+    for method in &mut impl_block.methods {
+        v.visit_body(&mut method.body);
+    }
+}
+
+pub fn walk_expr<V: VisitMut + ?Sized>(v: &mut V, expr: &mut Expr) {
+    match &mut expr.kind {
+        ExprKind::ResultOk(inner)
+        | ExprKind::ResultErr(inner)
+        | ExprKind::Not(inner)
+        | ExprKind::Wait(inner)
+        | ExprKind::Unwrap(inner)
+        | ExprKind::Chars(inner)
+        | ExprKind::IsNotEmpty(inner)
+        | ExprKind::Collect(inner)
+        | ExprKind::IsEmpty(inner)
+        | ExprKind::Some(inner)
+        | ExprKind::UnOp(_, inner)
+        | ExprKind::TupleAccess(inner, _)
+        | ExprKind::MemberAccess(inner, _)
+        | ExprKind::TypeOf(inner)
+        | ExprKind::Panic(inner) => v.visit_expr(inner),
+        ExprKind::ArrayGet { obj, reference } | ExprKind::Filter { obj, reference } => {
+            v.visit_expr(obj);
+            v.visit_expr(reference);
+        }
+        ExprKind::UnwrapOr(a, b) | ExprKind::BinOp(_, a, b) | ExprKind::Pipe(a, b) => {
+            v.visit_expr(a);
+            v.visit_expr(b);
+        }
+        ExprKind::Have { obj, item } | ExprKind::Contain { obj, item } | ExprKind::IndexOf { obj, item } => {
+            v.visit_expr(obj);
+            v.visit_expr(item);
+        }
+        ExprKind::ContainAll { obj, items } => {
+            v.visit_expr(obj);
+            for item in items {
+                v.visit_expr(item);
+            }
+        }
+        ExprKind::Call(_, args)
+        | ExprKind::StaticMethodCall(_, _, args)
+        | ExprKind::ModuleCall(_, _, args)
+        | ExprKind::Tuple(args)
+        | ExprKind::Array(args)
+        | ExprKind::OneOf(args) => {
+            for arg in args {
+                v.visit_expr(arg);
+            }
+        }
+        ExprKind::CallNamed(_, args)
+        | ExprKind::StaticMethodCallNamed(_, _, args)
+        | ExprKind::ModuleCallNamed(_, _, args)
+        | ExprKind::StructInit(_, args) => {
+            for (_, arg) in args {
+                v.visit_expr(arg);
+            }
+        }
+        ExprKind::Index(obj, indices) => {
+            v.visit_expr(obj);
+            for index in indices {
+                v.visit_expr(index);
+            }
+        }
+        ExprKind::MethodCall(obj, _, args)
+        | ExprKind::ArrayMethod { obj, args, .. }
+        | ExprKind::OptionMethod { obj, args, .. } => {
+            v.visit_expr(obj);
+            for arg in args {
+                v.visit_expr(arg);
+            }
+        }
+        ExprKind::MethodCallNamed(obj, _, args) => {
+            v.visit_expr(obj);
+            for (_, arg) in args {
+                v.visit_expr(arg);
+            }
+        }
+        ExprKind::Lambda { body, .. } => v.visit_expr(body),
+        ExprKind::Cast(inner, target) => {
+            v.visit_expr(inner);
+            match target {
+                CastTarget::LibraryCall(_, args) | CastTarget::LibraryModuleCall(_, _, args) => {
+                    for arg in args {
+                        v.visit_expr(arg);
+                    }
+                }
+                CastTarget::Type(_) | CastTarget::LibraryCallTyped(_, _) => {}
+            }
+        }
+        ExprKind::Number(_)
+        | ExprKind::Float(_)
+        | ExprKind::String(_)
+        | ExprKind::Bool(_)
+        | ExprKind::HexNumber(_)
+        | ExprKind::BinaryNumber(_)
+        | ExprKind::OctalNumber(_)
+        | ExprKind::Char(_)
+        | ExprKind::None
+        | ExprKind::Var(_)
+        | ExprKind::FuncAddr(_)
+        | ExprKind::ModuleAccess(_, _)
+        | ExprKind::ReferenceTo(_)
+        | ExprKind::SizeOf(_)
+        | ExprKind::AlignOf(_)
+        | ExprKind::OffsetOf { .. }
+        | ExprKind::Type(_) => {}
+    }
+}
+
+/// The variables a lambda body uses other than its parameters, in the order they first appear.
+/// Those in scope where the lambda is written are the ones it captures.
+pub fn free_variables(params: &[(String, Type)], body: &Expr) -> Vec<String> {
+    let mut free = FreeVariables { used: Vec::new(), params: params.iter().map(|(name, _)| name.clone()).collect() };
+    free.visit_expr(&mut body.clone());
+    free.used
+}
+
+struct FreeVariables {
+    used: Vec<String>,
+    /// Parameters of the lambda and of the lambdas nested in it.
+    params: HashSet<String>,
+}
+
+impl VisitMut for FreeVariables {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        match &expr.kind {
+            ExprKind::Var(name) | ExprKind::Call(name, _) if !self.params.contains(name) && !self.used.contains(name) => {
+                self.used.push(name.clone());
+            }
+            ExprKind::Lambda { params, .. } => self.params.extend(params.iter().map(|(name, _)| name.clone())),
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+}
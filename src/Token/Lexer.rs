@@ -1,17 +1,14 @@
 use crate::import::*;
 
-#[derive(Debug, Clone)]
-pub struct LexError {
-    pub message: String,
-    pub span: SourceSpan,
-}
-
 pub struct Lexer {
     source: String,
     chars: Vec<char>,
     pos: usize,
-    pub errors: Vec<LexError>,
+    file: String,
+    pub errors: Vec<Diagnostic>,
     pub spans: Vec<SourceSpan>,
+    /// `//` comments with their text, for the formatter; the parser never sees them.
+    pub comments: Vec<(SourceSpan, String)>,
 }
 
 impl Lexer {
@@ -20,8 +17,26 @@ impl Lexer {
             source: source.to_string(),
             chars: source.chars().collect(),
             pos: 0,
+            file: "input".to_string(),
             errors: Vec::new(),
             spans: Vec::new(),
+            comments: Vec::new(),
+        }
+    }
+
+    pub fn with_file(mut self, file: impl Into<String>) -> Self {
+        self.file = file.into();
+        self
+    }
+
+    fn location(&self, pos: usize, length: usize) -> SourceLocation {
+        let before = &self.chars[..pos.min(self.chars.len())];
+        let line_start = before.iter().rposition(|&c| c == '\n').map_or(0, |i| i + 1);
+        SourceLocation {
+            file: self.file.clone(),
+            line: before.iter().filter(|&&c| c == '\n').count() + 1,
+            column: pos - line_start + 1,
+            length,
         }
     }
 
@@ -51,9 +66,12 @@ impl Lexer {
 
     fn skip_comment(&mut self) {
         if self.current() == Some('/') && self.peek(1) == Some('/') {
+            let start = self.pos;
             while self.current().is_some() && self.current() != Some('\n') {
                 self.advance();
             }
+            let text: String = self.chars[start..self.pos].iter().collect();
+            self.comments.push((SourceSpan::from(start..self.pos), text.trim_end().to_string()));
         }
     }
 
@@ -250,7 +268,7 @@ impl Lexer {
             "not" => Token::Not,
             "or" => Token::Or,
             "reference_to" => Token::ReferenceTo,
-            "some" => Token::Some,
+            "some" | "Some" => Token::Some,
             "result" => Token::Result,
             "option" => Token::Option,
             "wait" => Token::Wait,
@@ -286,13 +304,14 @@ impl Lexer {
         let mut tokens = Vec::new();
 
         while self.pos < self.chars.len() {
-            let start = self.pos;
             self.skip_whitespace();
 
             if self.pos >= self.chars.len() {
                 break;
             }
 
+            let start = self.pos;
+
  
             if self.current() == Some('/') && self.peek(1) == Some('/') {
                 self.skip_comment();
@@ -332,6 +351,13 @@ impl Lexer {
                         self.advance();
                         self.advance();
                         Token::TripleDot
+                    } else if self.current() == Some('.') && self.peek(1) == Some('=') {
+                        self.advance();
+                        self.advance();
+                        Token::DotDotEquals
+                    } else if self.current() == Some('.') {
+                        self.advance();
+                        Token::DotDot
                     } else {
                         Token::Dot
                     }
@@ -389,6 +415,9 @@ impl Lexer {
                     if self.current() == Some('=') {
                         self.advance();
                         Token::EqualsEquals
+                    } else if self.current() == Some('>') {
+                        self.advance();
+                        Token::FatArrow
                     } else {
                         Token::Equals
                     }
@@ -465,10 +494,16 @@ impl Lexer {
                 Some(ch) if ch.is_ascii_digit() => self.read_number(),
                 Some(ch) if ch.is_alphabetic() || ch == '_' => self.read_identifier(),
                 Some(ch) => {
-                    self.errors.push(LexError {
-                        message: format!("Unexpected character: '{}'", ch),
-                        span: SourceSpan::from(start..self.pos + 1),
-                    });
+                    self.errors.push(Diagnostic::error(
+                        &codes::E0100,
+                        &format!("Unexpected character: '{}'", ch),
+                        ErrorContext {
+                            primary_location: self.location(start, 1),
+                            secondary_locations: vec![],
+                            help_message: Some("This character is not part of Vix syntax.".to_string()),
+                            suggestions: vec![],
+                        },
+                    ));
                     self.advance();
                     continue;
                 }
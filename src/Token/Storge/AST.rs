@@ -1,15 +1,10 @@
 use crate::import::*;
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct SpannedExpr {
-    pub expr: Expr,
-    pub span: SourceSpan,
-}
-
 #[derive(Debug, Clone, PartialEq)]
 pub struct SpannedStmt {
     pub stmt: Stmt,
     pub span: SourceSpan,
+    pub location: SourceLocation,
 }
 
 
@@ -29,7 +24,10 @@ pub enum Type {
     MultiArray { element: Box<Type>, dimensions: Vec<usize> },
     Tuple { fields: Vec<Type> },
     Union { variants: Vec<Type> },
+    /// A bare C function pointer, the kind extern callbacks take: `^func(int32): int32`.
     FnPtr { params: Vec<Type>, return_type: Box<Type> },
+    /// A function value that may carry the variables it captured: `func(int32): int32`.
+    Closure { params: Vec<Type>, return_type: Box<Type> },
     Option { inner: Box<Type> },
     Result { ok: Box<Type>, err: Box<Type> },
     Intersection { types: Vec<Type> },
@@ -37,7 +35,12 @@ pub enum Type {
     Variadic,
     SelfType,
     Any,
-    Trait,
+    /// A value of some struct that implements the trait, called through its vtable: `dyn Name`.
+    Trait { name: String },
+    /// A type parameter of a generic function or struct, bound at each use: `T`.
+    Param { name: String },
+    /// A generic struct applied to type arguments: `Pair[int32, str]`.
+    Generic { name: String, args: Vec<Type> },
     Owned(Box<Type>),
     Ref(Box<Type>),
     MutRef(Box<Type>),
@@ -57,6 +60,7 @@ pub struct EnumDef {
     pub name: String,
     pub variants: Vec<EnumVariant>,
     pub is_public: bool,
+    pub location: SourceLocation,
 }
 
 #[derive(Debug, Clone)]
@@ -77,38 +81,6 @@ pub enum ErrorType {
     UndefinedReference,
 }
 
-#[derive(Debug, Clone)]
-pub enum ParseError {
-    UnexpectedToken {
-        expected: String,
-        found: String,
-        span: SourceSpan,
-    },
-    MissingToken {
-        token: String,
-        span: SourceSpan,
-    },
-    InvalidSyntax {
-        message: String,
-        span: SourceSpan,
-    },
-}
-
-#[derive(Debug, Clone)]
-pub struct ParseDiagnostic {
-    pub message: String,
-    pub span: SourceSpan,
-    pub severity: DiagnosticSeverity,
-    pub help: Option<String>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum DiagnosticSeverity {
-    Error,
-    Warning,
-    Info,
-}
-
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ParamModifier {
     Immutable,
@@ -130,8 +102,13 @@ pub struct Function {
     pub name: String,
     pub params: Vec<(String, Type, ParamModifier)>,
     pub return_type: Type,
-    pub body: Vec<Stmt>,
+    pub body: Vec<SpannedStmt>,
+    /// `[T, U: Trait]`: each type parameter and its optional trait bound.
+    pub type_params: Vec<(String, Option<String>)>,
     pub is_public: bool,
+    /// Declared with `test func`: compiled only into the `vix test` harness.
+    pub is_test: bool,
+    pub location: SourceLocation,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -148,7 +125,7 @@ pub struct ExternFunctionWithBody {
     pub params: Vec<(String, Type)>,
     pub return_type: Type,
     pub is_public: bool,
-    pub body: Vec<Stmt>,
+    pub body: Vec<SpannedStmt>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -174,13 +151,17 @@ pub struct StructField {
     pub ty: Type,
     pub is_public: bool,
     pub is_mutable: bool,
+    /// Where the field's name (or its `mut`/`pub` prefix) starts.
+    pub location: SourceLocation,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct StructDef {
     pub name: String,
     pub fields: Vec<StructField>,
+    pub type_params: Vec<(String, Option<String>)>,
     pub is_public: bool,
+    pub location: SourceLocation,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -189,12 +170,15 @@ pub struct TraitMethod {
     pub params: Vec<(String, Type, ParamModifier)>,
     pub return_type: Type,
     pub self_modifier: Option<SelfModifier>,
+    pub location: SourceLocation,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct TraitDef {
     pub name: String,
     pub methods: Vec<TraitMethod>,
+    pub is_public: bool,
+    pub location: SourceLocation,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -202,9 +186,10 @@ pub struct ImplMethod {
     pub name: String,
     pub params: Vec<(String, Type, ParamModifier)>,
     pub return_type: Type,
-    pub body: Vec<Stmt>,
+    pub body: Vec<SpannedStmt>,
     pub self_modifier: Option<SelfModifier>,
     pub is_public: bool,
+    pub location: SourceLocation,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -214,12 +199,52 @@ pub struct ImplBlock {
     pub constructor_params: Vec<(String, Type)>,
     pub constructor_body: Option<Vec<(String, Expr)>>,
     pub methods: Vec<ImplMethod>,
+    pub location: SourceLocation,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct MatchCase {
-    pub value: Expr,
-    pub body: Vec<Stmt>,
+    pub pattern: Pattern,
+    /// The `if` condition after the pattern; the arm only runs when it holds.
+    pub guard: Option<Expr>,
+    pub body: Vec<SpannedStmt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub location: SourceLocation,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternKind {
+    /// `_`
+    Wildcard,
+    /// A bare name, which matches anything and binds it for the arm.
+    Binding(String),
+    Literal(Expr),
+    /// `1..5` or `1..=5`.
+    Range { start: Expr, end: Expr, inclusive: bool },
+    /// `Shape.Circle(r)`; `enum_name` is `None` for `Some`, `None`, `Ok` and `Err`.
+    Variant { enum_name: Option<String>, variant: String, fields: Vec<Pattern> },
+    Tuple(Vec<Pattern>),
+    /// `a | b`
+    Or(Vec<Pattern>),
+}
+
+impl Pattern {
+    /// The names the pattern binds and where, in order. Alternatives of an or-pattern bind the
+    /// same names, so only the first one is walked.
+    pub fn bindings(&self) -> Vec<(&str, &SourceLocation)> {
+        match &self.kind {
+            PatternKind::Binding(name) => vec![(name.as_str(), &self.location)],
+            PatternKind::Variant { fields: patterns, .. } | PatternKind::Tuple(patterns) => {
+                patterns.iter().flat_map(Pattern::bindings).collect()
+            }
+            PatternKind::Or(alternatives) => alternatives.first().map(Pattern::bindings).unwrap_or_default(),
+            PatternKind::Wildcard | PatternKind::Literal(_) | PatternKind::Range { .. } => vec![],
+        }
+    }
 }
 
 
@@ -250,12 +275,12 @@ pub enum Stmt {
     MemberAssign(Box<Expr>, String, Expr),
     ModuleAssign(String, String, Expr),
     ModuleCompoundAssign(String, String, String, Expr),
-    If(Expr, Vec<Stmt>, Option<Vec<Stmt>>),
-    While(Expr, Vec<Stmt>),
-    For(String, Expr, Vec<Stmt>),
+    If(Expr, Vec<SpannedStmt>, Option<Vec<SpannedStmt>>),
+    While(Expr, Vec<SpannedStmt>),
+    For(String, Expr, Vec<SpannedStmt>),
     MemberCompoundAssign(Box<Expr>, String, String, Box<Expr>),
-    Loop(Vec<Stmt>),
-    Match(Expr, Vec<MatchCase>, Option<Vec<Stmt>>),
+    Loop(Vec<SpannedStmt>),
+    Match(Expr, Vec<MatchCase>, Option<Vec<SpannedStmt>>),
     Call(String, Vec<Expr>),
     ModuleCall(String, String, Vec<Expr>),
     MethodCall(Box<Expr>, String, Vec<Expr>),
@@ -265,8 +290,8 @@ pub enum Stmt {
     Return(Option<Expr>),
     Break,
     Continue,
-    Unsafe(Vec<Stmt>),
-    Scope(Vec<Stmt>),
+    Unsafe(Vec<SpannedStmt>),
+    Scope(Vec<SpannedStmt>),
     StructDef(StructDef),
     EnumDef(EnumDef),
     ImplBlock(ImplBlock),
@@ -388,6 +413,16 @@ pub struct Codegen {
     pub config: CodegenConfig,
     pub type_registry: TypeRegistry,
     pub impl_methods: HashMap<(String, String), (Vec<(String, Type)>, Type, bool)>,
+    pub traits: HashMap<String, TraitDef>,
+    /// (struct, trait) for every `impl Trait for Struct`.
+    pub trait_impls: HashSet<(String, String)>,
+    /// Generic functions and structs by name; only their instances are emitted.
+    pub generic_functions: HashMap<String, Function>,
+    pub generic_structs: HashMap<String, StructDef>,
+    /// The generic type each emitted struct instance was made for, by mangled name.
+    pub struct_instances: HashMap<String, Type>,
+    /// Instances of generic functions that are declared but whose bodies are not emitted yet.
+    pub pending_instances: Vec<Function>,
     pub c_code: String,
     pub globals: String,
     pub var_count: usize,
@@ -412,12 +447,40 @@ pub struct Codegen {
     pub current_file: String,
     pub linked_libraries: Vec<String>,
     pub current_return_type: Option<Type>,
+    /// Lambdas lifted so far, which numbers the next one.
+    pub lambda_count: usize,
+    /// C names of the `mut` variables in scope, which lambdas capture by reference.
+    pub mutable_vars: HashSet<String>,
+    /// Results of calls that hold a `dyn Trait`, freed at the end of the statement that made them.
+    pub dyn_temps: Vec<(String, Type)>,
+    /// Pointers to the `dyn` boxes the current function keeps in variables.
+    pub dyn_slots: Vec<String>,
+    /// Modules emitted as translation units of their own; empty to emit a single one.
+    pub units: Vec<CodegenUnit>,
+    /// Instances of generic functions by mangled name.
+    pub generic_instances: HashMap<String, Function>,
+    /// Generic instances emitted in the current translation unit, each of which has its own
+    /// `static` copy.
+    pub unit_instances: HashSet<String>,
+}
+
+/// A module compiled as its own translation unit, so that an unchanged one can be reused.
+pub struct CodegenUnit {
+    /// Emitted names of the module's functions.
+    pub functions: HashSet<String>,
+    /// Where the module's blocks are in the linked list of impls.
+    pub impls: std::ops::Range<usize>,
+    /// The unit's C is already cached, so it is not generated again.
+    pub cached: bool,
+    /// The unit's functions, filled in by code generation.
+    pub code: String,
 }
 
 pub struct CodegenConfig {
     pub arch: ArchConfig,
     pub optimization_level: OptimizationLevel,
     pub debug_info: bool,
+    pub runtime_checks: bool,
 }
 
 impl Default for OptimizationLevel {
@@ -430,6 +493,8 @@ impl Default for OptimizationLevel {
 pub enum CompilationMode {
     Executable,
     Library,
+    /// The `vix test` harness; each test is (name shown to the user, C function).
+    Test { tests: Vec<(String, String)> },
 }
 
 impl Default for CompilationMode {
@@ -451,7 +516,14 @@ pub struct Parser {
     pub spans: Vec<SourceSpan>,
     pub pos: usize,
     pub source: Arc<String>,
-    pub diags: Vec<ParseDiagnostic>,
+    pub diags: Vec<Diagnostic>,
+    pub file: String,
+    /// Char offset where each source line starts, for turning spans into line/column.
+    pub line_starts: Vec<usize>,
+    /// Set after a syntax error until the parser gets back in step, so one mistake is reported once.
+    pub recovering: bool,
+    /// Type parameters of the generic function or struct being parsed, which type names may refer to.
+    pub type_params: Vec<String>,
 }
 
 pub struct Lexer {
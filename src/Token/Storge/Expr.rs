@@ -1,13 +1,34 @@
 use crate::import::*;
 
+/// An expression together with where it was written.
+#[derive(Debug, Clone)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: SourceSpan,
+    pub location: SourceLocation,
+}
+
 impl Expr {
+    pub fn new(kind: ExprKind, span: SourceSpan, location: SourceLocation) -> Self {
+        Self { kind, span, location }
+    }
+
     pub fn location(&self) -> SourceLocation {
-        SourceLocation {
-            file: "<unknown>".to_string(),
-            line: 0,
-            column: 0,
-            length: 1,
-        }
+        self.location.clone()
+    }
+}
+
+/// Expressions the compiler makes up itself have no source position.
+impl From<ExprKind> for Expr {
+    fn from(kind: ExprKind) -> Self {
+        Self::new(kind, SourceSpan::from(0..0), default_location())
+    }
+}
+
+/// Two expressions are equal when they have the same shape, wherever they were written.
+impl PartialEq for Expr {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
     }
 }
 
@@ -64,13 +85,16 @@ impl Default for Function {
             params: Vec::new(),
             return_type: Type::Void,
             body: Vec::new(),
+            type_params: Vec::new(),
             is_public: false,
+            is_test: false,
+            location: default_location(),
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum Expr {
+pub enum ExprKind {
     ResultOk(Box<Expr>),
     ResultErr(Box<Expr>),
     ArrayGet {obj: Box<Expr>, reference: Box<Expr>,},
@@ -101,6 +125,9 @@ pub enum Expr {
     Call(String, Vec<Expr>),
     CallNamed(String, Vec<(String, Expr)>),
     FuncAddr(String),
+    /// An anonymous function, `func(x: int32): int32 => x * k`. The variables it uses from the
+    /// scope it is written in are captured.
+    Lambda { params: Vec<(String, Type)>, return_type: Type, body: Box<Expr> },
     BinOp(String, Box<Expr>, Box<Expr>),
     UnOp(String, Box<Expr>),
     Tuple(Vec<Expr>),
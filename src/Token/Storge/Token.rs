@@ -6,6 +6,7 @@ pub enum Token {
     If, Else, While, For, Break, Continue, Module,
     True, False, Extern, From, Mut, Struct, Enum, Any,
     TripleDot,  
+    DotDot, DotDotEquals,
     Mod, To, BitwiseOr,     
     BitwiseAnd, 
     Use,  
@@ -61,7 +62,7 @@ pub enum Token {
     And, Or,
     Ampersand, Pipe,
     
-    Semicolon, Comma, Colon, Arrow, Dot,
+    Semicolon, Comma, Colon, Arrow, FatArrow, Dot,
     DoubleColon,
 
     LeftShift, RightShift,
@@ -73,10 +74,148 @@ pub enum Token {
     LessLessEquals, GreaterGreaterEquals,
 }
 
+/// Source spelling of a token, for diagnostics.
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let text = match self {
+            Token::Identifier(name) | Token::TypeIdentifier(name) => return write!(f, "`{}`", name),
+            Token::Number(n) => return write!(f, "`{}`", n),
+            Token::Float(n) => return write!(f, "`{}`", n),
+            Token::String(s) => return write!(f, "`\"{}\"`", s),
+            Token::HexNumber(n) => return write!(f, "`{:#x}`", n),
+            Token::BinaryNumber(n) => return write!(f, "`{:#b}`", n),
+            Token::OctalNumber(n) => return write!(f, "`{:#o}`", n),
+            Token::Create => "create",
+            Token::Pub => "public",
+            Token::Func => "func",
+            Token::End => "end",
+            Token::As => "as",
+            Token::Return => "return",
+            Token::If => "if",
+            Token::Else => "else",
+            Token::While => "while",
+            Token::For => "for",
+            Token::Break => "break",
+            Token::Continue => "continue",
+            Token::Module => "module",
+            Token::True => "true",
+            Token::False => "false",
+            Token::Extern => "extern",
+            Token::From => "from",
+            Token::Mut => "mut",
+            Token::Struct => "struct",
+            Token::Enum => "enum",
+            Token::Any => "any",
+            Token::TripleDot => "...",
+            Token::DotDot => "..",
+            Token::DotDotEquals => "..=",
+            Token::Mod => "mod",
+            Token::To => "to",
+            Token::BitwiseOr => "|",
+            Token::BitwiseAnd => "&",
+            Token::Use => "use",
+            Token::Match => "match",
+            Token::Case => "case",
+            Token::Default => "default",
+            Token::Unsafe => "unsafe",
+            Token::Then => "then",
+            Token::Do => "do",
+            Token::In => "in",
+            Token::Refrence => "refrence",
+            Token::Scope => "scope",
+            Token::Const => "const",
+            Token::Mutable => "mutable",
+            Token::Reference => "reference",
+            Token::None => "None",
+            Token::Null => "null",
+            Token::Impl => "impl",
+            Token::Selfish => "self",
+            Token::Trait => "trait",
+            Token::Import => "import",
+            Token::Array => "array",
+            Token::Slots => "slots",
+            Token::Lists => "lists",
+            Token::Char => "char",
+            Token::Panic => "panic",
+            Token::Have => "have",
+            Token::Get => "get",
+            Token::Filter => "filter",
+            Token::IsEmpty => "is_empty",
+            Token::Unwrap => "unwrap",
+            Token::UnwrapOr => "unwrap_or",
+            Token::IsSome => "is_some",
+            Token::IsNone => "is_none",
+            Token::Ok => "ok",
+            Token::Err => "err",
+            Token::Not => "not",
+            Token::ReferenceTo => "reference_to",
+            Token::Some => "some",
+            Token::Result => "result",
+            Token::Option => "option",
+            Token::Wait => "wait",
+            Token::NullPtr => "nullptr",
+            Token::SizeOf => "sizeof",
+            Token::TypeOf => "typeof",
+            Token::AlignOf => "alignof",
+            Token::OffsetOf => "offsetof",
+            Token::OneOf => "oneof",
+            Token::Caret => "^",
+            Token::Tilde => "~",
+            Token::Type => "type",
+            Token::Bool => "bool",
+            Token::Void => "void",
+            Token::Str => "str",
+            Token::LeftParen => "(",
+            Token::RightParen => ")",
+            Token::LeftBrace => "{",
+            Token::RightBrace => "}",
+            Token::LeftBracket => "[",
+            Token::RightBracket => "]",
+            Token::Equals => "=",
+            Token::EqualsEquals => "==",
+            Token::NotEquals => "!=",
+            Token::Plus => "+",
+            Token::Minus => "-",
+            Token::Star => "*",
+            Token::Slash => "/",
+            Token::Percent => "%",
+            Token::Less => "<",
+            Token::Greater => ">",
+            Token::LessEquals => "<=",
+            Token::GreaterEquals => ">=",
+            Token::And => "&&",
+            Token::Or => "||",
+            Token::Ampersand => "&",
+            Token::Pipe => "|",
+            Token::Semicolon => ";",
+            Token::Comma => ",",
+            Token::Colon => ":",
+            Token::Arrow => "->",
+            Token::FatArrow => "=>",
+            Token::Dot => ".",
+            Token::DoubleColon => "::",
+            Token::LeftShift => "<<",
+            Token::RightShift => ">>",
+            Token::EOF => return write!(f, "end of file"),
+            Token::PlusEquals => "+=",
+            Token::MinusEquals => "-=",
+            Token::StarEquals => "*=",
+            Token::SlashEquals => "/=",
+            Token::PercentEquals => "%=",
+            Token::AmpersandEquals => "&=",
+            Token::PipeEquals => "|=",
+            Token::CaretEquals => "^=",
+            Token::LessLessEquals => "<<=",
+            Token::GreaterGreaterEquals => ">>=",
+        };
+        write!(f, "`{}`", text)
+    }
+}
+
 pub struct Lexer {
     source: String,
     chars: Vec<char>,
     pos: usize,
-    pub errors: Vec<LexError>,
+    pub errors: Vec<Diagnostic>,
     pub spans: Vec<SourceSpan>,
 }
\ No newline at end of file
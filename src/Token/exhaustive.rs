@@ -0,0 +1,133 @@
+use crate::import::*;
+
+/// A `match` pattern reduced to what exhaustiveness checking looks at.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pat {
+    /// `_`, or a binding.
+    Any,
+    /// A constructor applied to sub-patterns: `Shape.Rect(_, _)`, `Some(_)`, `true`. Tuples use the
+    /// empty name, and literals and ranges are constructors without fields.
+    Ctor(String, Vec<Pat>),
+    Or(Vec<Pat>),
+}
+
+/// Lists the constructors of a type, named the way `Pat::Ctor` names them.
+pub trait Constructors {
+    /// Every constructor of `ty` with its field types, or `None` when its values cannot be
+    /// listed (numbers, strings) and only a catch-all pattern covers them all.
+    fn constructors(&self, ty: &Type) -> Option<Vec<(String, Vec<Type>)>>;
+}
+
+/// Whether a row of patterns `v` matches some value that none of `rows` do, with such a value
+/// as the witness. An arm is unreachable when its pattern is not useful after the arms before it,
+/// and a match is exhaustive when `_` is not useful after all of them.
+pub fn useful(cx: &dyn Constructors, rows: &[Vec<Pat>], v: &[Pat], tys: &[Type]) -> Option<Vec<Pat>> {
+    let Some((head, rest)) = v.split_first() else {
+        return rows.is_empty().then(Vec::new);
+    };
+    let rows = expand_or(rows);
+    match head {
+        Pat::Or(alternatives) => alternatives.iter().find_map(|alternative| {
+            let v: Vec<Pat> = std::iter::once(alternative.clone()).chain(rest.iter().cloned()).collect();
+            useful(cx, &rows, &v, tys)
+        }),
+        Pat::Ctor(name, fields) => {
+            let field_tys = field_types(cx, &tys[0], name, fields.len());
+            let v: Vec<Pat> = fields.iter().chain(rest).cloned().collect();
+            specialized(cx, &rows, name, &field_tys, &v, tys)
+        }
+        Pat::Any => {
+            let used: Vec<&String> = rows
+                .iter()
+                .filter_map(|row| match &row[0] {
+                    Pat::Ctor(name, _) => Some(name),
+                    _ => None,
+                })
+                .collect();
+            let all = cx.constructors(&tys[0]);
+            if let Some(all) = all.as_ref().filter(|all| !all.is_empty() && all.iter().all(|(name, _)| used.contains(&name))) {
+                return all.iter().find_map(|(name, field_tys)| {
+                    let v: Vec<Pat> = vec![Pat::Any; field_tys.len()].into_iter().chain(rest.iter().cloned()).collect();
+                    specialized(cx, &rows, name, field_tys, &v, tys)
+                });
+            }
+            // Some constructor is missing from the first column: only the rows that accept
+            // anything there can cover the rest of the value.
+            let defaults: Vec<Vec<Pat>> = rows.iter().filter(|row| row[0] == Pat::Any).map(|row| row[1..].to_vec()).collect();
+            let witness = useful(cx, &defaults, rest, &tys[1..])?;
+            let missing = all
+                .and_then(|all| all.into_iter().find(|(name, _)| !used.contains(&name)))
+                .map_or(Pat::Any, |(name, field_tys)| Pat::Ctor(name, vec![Pat::Any; field_tys.len()]));
+            Some(std::iter::once(missing).chain(witness).collect())
+        }
+    }
+}
+
+/// `useful` on the rows starting with constructor `name` (or `_`), with its fields spread out.
+fn specialized(cx: &dyn Constructors, rows: &[Vec<Pat>], name: &str, field_tys: &[Type], v: &[Pat], tys: &[Type]) -> Option<Vec<Pat>> {
+    let arity = field_tys.len();
+    let rows: Vec<Vec<Pat>> = rows
+        .iter()
+        .filter_map(|row| match &row[0] {
+            Pat::Ctor(row_name, fields) if row_name == name => Some(fields.iter().chain(&row[1..]).cloned().collect()),
+            Pat::Any => Some(vec![Pat::Any; arity].into_iter().chain(row[1..].iter().cloned()).collect()),
+            _ => None,
+        })
+        .collect();
+    let tys: Vec<Type> = field_tys.iter().chain(&tys[1..]).cloned().collect();
+    let mut witness = useful(cx, &rows, v, &tys)?;
+    let rest = witness.split_off(arity);
+    Some(std::iter::once(Pat::Ctor(name.to_string(), witness)).chain(rest).collect())
+}
+
+fn field_types(cx: &dyn Constructors, ty: &Type, name: &str, arity: usize) -> Vec<Type> {
+    cx.constructors(ty)
+        .and_then(|all| all.into_iter().find(|(ctor, _)| ctor == name))
+        .map(|(_, field_tys)| field_tys)
+        .filter(|field_tys| field_tys.len() == arity)
+        .unwrap_or_else(|| vec![Type::Any; arity])
+}
+
+/// Replaces each row starting with an or-pattern by one row per alternative.
+fn expand_or(rows: &[Vec<Pat>]) -> Vec<Vec<Pat>> {
+    let mut expanded = Vec::new();
+    for row in rows {
+        match row.first() {
+            Some(Pat::Or(alternatives)) => {
+                let alternatives: Vec<Vec<Pat>> = alternatives
+                    .iter()
+                    .map(|alternative| std::iter::once(alternative.clone()).chain(row[1..].iter().cloned()).collect())
+                    .collect();
+                expanded.extend(expand_or(&alternatives));
+            }
+            _ => expanded.push(row.clone()),
+        }
+    }
+    expanded
+}
+
+impl std::fmt::Display for Pat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let list = |patterns: &[Pat], separator: &str| patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(separator);
+        match self {
+            Pat::Any => write!(f, "_"),
+            Pat::Ctor(name, fields) if name.is_empty() => write!(f, "({})", list(fields, ", ")),
+            Pat::Ctor(name, fields) if fields.is_empty() => write!(f, "{}", name),
+            Pat::Ctor(name, fields) => write!(f, "{}({})", name, list(fields, ", ")),
+            Pat::Or(alternatives) => write!(f, "{}", list(alternatives, " | ")),
+        }
+    }
+}
+
+/// Identifies a literal in a pattern, so that two arms matching the same literal are recognised.
+pub fn literal_key(expr: &Expr) -> String {
+    match &expr.kind {
+        ExprKind::Number(n) => n.to_string(),
+        ExprKind::HexNumber(n) | ExprKind::BinaryNumber(n) | ExprKind::OctalNumber(n) => n.to_string(),
+        ExprKind::Float(f) => f.to_string(),
+        ExprKind::String(s) => format!("{:?}", s),
+        ExprKind::Bool(b) => b.to_string(),
+        ExprKind::UnOp(op, inner) => format!("{}{}", op, literal_key(inner)),
+        other => format!("{:?}", other),
+    }
+}
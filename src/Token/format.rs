@@ -0,0 +1,290 @@
+use crate::import::*;
+use std::collections::BTreeMap;
+
+const INDENT: &str = "    ";
+
+/// Keywords after which `(` or `[` starts a new expression rather than a call or an index.
+const CONTROL_KEYWORDS: &[Token] = &[
+    Token::If, Token::Else, Token::While, Token::For, Token::In, Token::Return, Token::Match, Token::Case,
+    Token::Default, Token::Then, Token::Do, Token::From, Token::As, Token::To, Token::Import, Token::Use,
+    Token::Pub, Token::Not,
+];
+
+/// A token as written in the source, or one the formatter adds.
+#[derive(Debug, Clone)]
+struct Piece {
+    token: Token,
+    text: String,
+    line: usize,
+    end_line: usize,
+    /// Whether the source had whitespace before it.
+    gap_before: bool,
+}
+
+/// What a line that ends with `end` closes, as far as indentation is concerned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Block {
+    Plain,
+    If,
+    /// Inside a `match`, `case x:` bodies sit one level deeper than the `case`.
+    Match { in_case: bool },
+    /// `extern ... from` blocks and traits, whose `func`s are signatures without an `end`.
+    Signatures,
+}
+
+/// Normalizes the whitespace and indentation of Vix source: four-space indentation between a
+/// block's opener and its `end`, single spaces around operators, at most one blank line in a row,
+/// `then`/`do` after `if`/`while` conditions and no trailing commas. Line breaks and comments stay
+/// where the author put them; code is not re-flowed. Source that does not parse is refused with its
+/// errors. Files written with `\r\n` line endings keep them.
+pub fn format_source(source: &str, file: &str) -> Result<String, Vec<Diagnostic>> {
+    let formatted = format_lines(source, file)?;
+    Ok(if source.contains("\r\n") { formatted.replace('\n', "\r\n") } else { formatted })
+}
+
+fn format_lines(source: &str, file: &str) -> Result<String, Vec<Diagnostic>> {
+    let mut lexer = Lexer::new(source).with_file(file);
+    let tokens = lexer.tokenize();
+    if !lexer.errors.is_empty() {
+        return Err(lexer.errors);
+    }
+
+    let chars: Vec<char> = source.chars().collect();
+    let line_of = line_index(&chars);
+    let mut pieces = Vec::new();
+    let mut previous_end = 0;
+    for (token, span) in tokens.iter().zip(&lexer.spans) {
+        if *token == Token::EOF {
+            break;
+        }
+        let (start, end) = (span.offset(), span.offset() + span.len());
+        pieces.push(Piece {
+            token: token.clone(),
+            text: chars[start..end].iter().collect(),
+            line: line_of(start),
+            end_line: line_of(end.saturating_sub(1).max(start)),
+            gap_before: chars[previous_end..start].iter().any(|c| c.is_whitespace()) || lexer.comments.iter().any(|(comment, _)| comment.offset() >= previous_end && comment.offset() < start),
+        });
+        previous_end = end;
+    }
+    let comments: Vec<(usize, String)> = lexer.comments.iter().map(|(span, text)| (line_of(span.offset()), text.clone())).collect();
+
+    let errors = parse_errors(source, file);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    // Adding `then`/`do` can still trip the parser on unusual layouts; keep the spacing-only result then.
+    let normalized = layout(&normalize(pieces.clone()), &comments);
+    Ok(if parse_errors(&normalized, file).is_empty() { normalized } else { layout(&pieces, &comments) })
+}
+
+fn parse_errors(source: &str, file: &str) -> Vec<Diagnostic> {
+    let mut lexer = Lexer::new(source).with_file(file);
+    let tokens = lexer.tokenize();
+    if !lexer.errors.is_empty() {
+        return lexer.errors;
+    }
+    let parser = Parser::new(tokens, source.to_string(), lexer.spans).with_file(file);
+    let (.., diagnostics) = parser.parse();
+    diagnostics.into_iter().filter(Diagnostic::is_error).collect()
+}
+
+/// Maps a character offset to its 1-based line.
+fn line_index(chars: &[char]) -> impl Fn(usize) -> usize {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(chars.iter().enumerate().filter(|(_, c)| **c == '\n').map(|(i, _)| i + 1))
+        .collect();
+    move |offset| line_starts.partition_point(|&start| start <= offset)
+}
+
+/// Adds `then`/`do` where a condition ends its line without one and drops commas right before a closer.
+fn normalize(pieces: Vec<Piece>) -> Vec<Piece> {
+    let mut result: Vec<Piece> = Vec::new();
+    let mut iter = pieces.into_iter().peekable();
+    while let Some(piece) = iter.next() {
+        let next = iter.peek();
+        if piece.token == Token::Comma
+            && next.is_some_and(|next| matches!(next.token, Token::RightParen | Token::RightBracket | Token::RightBrace | Token::End))
+        {
+            continue;
+        }
+        let line_ends = next.is_none_or(|next| next.line > piece.end_line);
+        result.push(piece);
+
+        if line_ends {
+            let start = result.iter().rposition(|p| p.line < result[result.len() - 1].line).map_or(0, |i| i + 1);
+            let line = &result[start..];
+            let keyword = match line[0].token {
+                Token::If => Some((Token::Then, "then")),
+                Token::While => Some((Token::Do, "do")),
+                _ => None,
+            };
+            let balanced = line.iter().filter(|p| is_opening(&p.token)).count() == line.iter().filter(|p| is_closing(&p.token)).count();
+            let complete = line.iter().any(|p| matches!(p.token, Token::Then | Token::Do | Token::End | Token::Colon));
+            if let Some((token, text)) = keyword.filter(|_| balanced && !complete && line.len() > 1) {
+                let last = &result[result.len() - 1];
+                let (line, end_line) = (last.end_line, last.end_line);
+                result.push(Piece { token, text: text.to_string(), line, end_line, gap_before: true });
+            }
+        }
+    }
+    result
+}
+
+fn is_opening(token: &Token) -> bool {
+    matches!(token, Token::LeftParen | Token::LeftBracket | Token::LeftBrace)
+}
+
+fn is_closing(token: &Token) -> bool {
+    matches!(token, Token::RightParen | Token::RightBracket | Token::RightBrace)
+}
+
+fn is_word(piece: &Piece) -> bool {
+    piece.text.starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '"' || c == '\'')
+}
+
+/// Whether `piece` can end an operand, so that a following `(` is a call and a `-` is binary.
+fn ends_operand(piece: &Piece) -> bool {
+    is_closing(&piece.token) || (is_word(piece) && !CONTROL_KEYWORDS.contains(&piece.token))
+}
+
+fn is_unary(piece: &Piece, previous: Option<&Piece>) -> bool {
+    let prefix = matches!(piece.token, Token::Minus | Token::Star | Token::Ampersand | Token::Caret | Token::Tilde)
+        || (piece.token == Token::Not && piece.text == "!");
+    prefix && previous.is_none_or(|previous| !ends_operand(previous))
+}
+
+fn needs_space(previous: &Piece, previous_unary: bool, next: &Piece) -> bool {
+    if previous_unary {
+        return false;
+    }
+    match (&previous.token, &next.token) {
+        (_, Token::RightParen | Token::RightBracket | Token::Comma | Token::Semicolon | Token::Dot | Token::DoubleColon | Token::Colon) => false,
+        (Token::LeftParen | Token::LeftBracket | Token::Dot | Token::DoubleColon, _) => false,
+        // Range patterns: `1..5`, `1..=9`.
+        (_, Token::DotDot | Token::DotDotEquals) | (Token::DotDot | Token::DotDotEquals, _) => false,
+        (_, Token::LeftParen | Token::LeftBracket) => !ends_operand(previous),
+        // `<` and `>` are both comparisons and generic brackets; keep them as written.
+        (Token::Less | Token::Greater, _) | (_, Token::Less | Token::Greater) => next.gap_before,
+        _ => true,
+    }
+}
+
+/// Whether the token at `index` of a line opens a block that a later `end` closes.
+fn opens_block(line: &[&Piece], index: usize, top: Option<Block>) -> Option<Block> {
+    let is_modifier = |i: usize| line[i].token == Token::Pub || (line[i].text == "test" && line.get(i + 1).is_some_and(|p| p.token == Token::Func));
+    let head = (0..line.len()).find(|&i| !is_modifier(i)).unwrap_or(0);
+    let at_head = index == head;
+    match line[index].token {
+        Token::Func if at_head && top != Some(Block::Signatures) && line[0].token != Token::Extern => Some(Block::Plain),
+        Token::Struct | Token::Enum | Token::Impl if at_head => Some(Block::Plain),
+        Token::Trait if at_head => Some(Block::Signatures),
+        Token::While | Token::For | Token::Scope if at_head => Some(Block::Plain),
+        Token::Unsafe if at_head && line.get(index + 1).is_some_and(|p| p.token == Token::Colon) => Some(Block::Plain),
+        Token::If if at_head => Some(Block::If),
+        Token::Match if at_head => Some(Block::Match { in_case: false }),
+        Token::Extern if at_head && line.iter().any(|p| p.token == Token::From) => Some(Block::Signatures),
+        // `case x do ... end`: a `do` that does not belong to a loop opens its own block.
+        Token::Do if !matches!(line[head].token, Token::While | Token::For) => Some(Block::Plain),
+        _ => None,
+    }
+}
+
+fn depth(stack: &[Block]) -> usize {
+    stack.iter().map(|block| if matches!(block, Block::Match { in_case: true }) { 2 } else { 1 }).sum()
+}
+
+fn layout(pieces: &[Piece], comments: &[(usize, String)]) -> String {
+    // Source line -> the tokens starting on it and a trailing or standalone comment.
+    let mut lines: BTreeMap<usize, (Vec<&Piece>, Option<&str>)> = BTreeMap::new();
+    for piece in pieces {
+        lines.entry(piece.line).or_default().0.push(piece);
+    }
+    for (line, text) in comments {
+        lines.entry(*line).or_default().1 = Some(text);
+    }
+
+    let mut output: Vec<String> = Vec::new();
+    let mut stack: Vec<Block> = Vec::new();
+    let mut brackets = 0usize;
+    let mut last_line = 0;
+    let mut opened_block = false;
+
+    for (&number, (line, comment)) in &lines {
+        let blank = !output.is_empty() && number > last_line + 1;
+        last_line = number.max(line.last().map_or(number, |p| p.end_line));
+
+        let Some(first) = line.first() else {
+            // A comment on a line of its own sits at the depth of the code around it.
+            if blank && !opened_block {
+                output.push(String::new());
+            }
+            output.push(format!("{}{}", INDENT.repeat(depth(&stack) + brackets), comment.unwrap_or_default()));
+            opened_block = false;
+            continue;
+        };
+
+        let mut skip_first = false;
+        if first.token == Token::End && !stack.is_empty() && brackets == 0 {
+            stack.pop();
+            skip_first = true;
+        }
+        let mut indent = depth(&stack);
+        let top = stack.last().copied();
+        match (&first.token, top) {
+            (Token::Else, Some(Block::If)) => indent -= 1,
+            (Token::Case | Token::Default, Some(Block::Match { in_case: true })) => indent -= 1,
+            _ => {}
+        }
+        indent += if is_closing(&first.token) { brackets.saturating_sub(1) } else { brackets };
+
+        let closes = first.token == Token::End || first.token == Token::Else || matches!(first.token, Token::Case | Token::Default);
+        if blank && !opened_block && !closes {
+            output.push(String::new());
+        }
+
+        let starts_case = matches!(first.token, Token::Case | Token::Default) && matches!(top, Some(Block::Match { .. }));
+        let depth_before = stack.len();
+        let mut text = INDENT.repeat(indent);
+        let mut previous: Option<&Piece> = None;
+        let mut previous_unary = false;
+        for (index, piece) in line.iter().enumerate() {
+            if let Some(previous) = previous
+                && needs_space(previous, previous_unary, piece)
+            {
+                text.push(' ');
+            }
+            text.push_str(&piece.text);
+            previous_unary = is_unary(piece, previous);
+            previous = Some(piece);
+
+            if is_opening(&piece.token) {
+                brackets += 1;
+            } else if is_closing(&piece.token) {
+                brackets = brackets.saturating_sub(1);
+            } else if piece.token == Token::End && !(index == 0 && skip_first) {
+                stack.pop();
+            } else if let Some(block) = opens_block(line, index, stack.last().copied()) {
+                stack.push(block);
+            }
+        }
+        opened_block = stack.len() > depth_before;
+        // A case without a block of its own has its body on the lines that follow.
+        if starts_case && let Some(Block::Match { in_case }) = stack.get_mut(depth_before - 1) {
+            *in_case = !opened_block;
+        }
+
+        if let Some(comment) = comment {
+            text.push(' ');
+            text.push_str(comment);
+        }
+        output.push(text);
+    }
+
+    let mut formatted = output.join("\n");
+    formatted.push('\n');
+    if formatted.trim().is_empty() {
+        return String::new();
+    }
+    formatted
+}
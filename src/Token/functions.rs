@@ -29,40 +29,40 @@ impl Parser {
     }
 
     pub fn infer_type(expr: &Expr) -> Type {
-        match expr {
-            Expr::Number(_) => Type::i32(),
-            Expr::HexNumber(_) => Type::i32(),
-            Expr::BinaryNumber(_) => Type::i32(),
-            Expr::OctalNumber(_) => Type::i32(),
-            Expr::Float(_) => Type::f32(),
-            Expr::String(_) => Type::Str { len_type: Box::new(Type::i32()) },
-            Expr::Bool(_) => Type::Bool,
-            Expr::Char(_) => Type::char32(),
-            Expr::Some(inner) => {
+        match &expr.kind {
+            ExprKind::Number(_) => Type::i32(),
+            ExprKind::HexNumber(_) => Type::i32(),
+            ExprKind::BinaryNumber(_) => Type::i32(),
+            ExprKind::OctalNumber(_) => Type::i32(),
+            ExprKind::Float(_) => Type::f32(),
+            ExprKind::String(_) => Type::Str { len_type: Box::new(Type::i32()) },
+            ExprKind::Bool(_) => Type::Bool,
+            ExprKind::Char(_) => Type::char32(),
+            ExprKind::Some(inner) => {
                 Type::Option { 
                     inner: Box::new(Parser::infer_type(inner)) 
                 }
             }
-            Expr::None => {
+            ExprKind::None => {
                 Type::Option { 
                     inner: Box::new(Type::Any) 
                 }
             }
             
-            Expr::ResultOk(inner) => {
+            ExprKind::ResultOk(inner) => {
                 Type::Result { 
                     ok: Box::new(Parser::infer_type(inner)), 
                     err: Box::new(Type::Any)
                 }
             }
-            Expr::ResultErr(inner) => {
+            ExprKind::ResultErr(inner) => {
                 Type::Result { 
                     ok: Box::new(Type::Any),
                     err: Box::new(Parser::infer_type(inner)) 
                 }
             }
             
-            Expr::Unwrap(inner) => {
+            ExprKind::Unwrap(inner) => {
                 let inner_type = Parser::infer_type(inner);
                 match inner_type {
                     Type::Option { inner: boxed } => *boxed,
@@ -70,7 +70,7 @@ impl Parser {
                     _ => inner_type,
                 }
             }
-            Expr::UnwrapOr(inner, default) => {
+            ExprKind::UnwrapOr(inner, default) => {
                 let inner_type = Parser::infer_type(inner);
                 let default_type = Parser::infer_type(default);
                 match inner_type {
@@ -80,14 +80,14 @@ impl Parser {
                 }
             }
             
-            Expr::OptionMethod { obj, method, .. } => {
+            ExprKind::OptionMethod { obj, method, .. } => {
                 match method.as_str() {
                     "is_some" | "is_none" => Type::Bool,
                     _ => Parser::infer_type(obj),
                 }
             }
             
-            Expr::Cast(_, cast_target) => {
+            ExprKind::Cast(_, cast_target) => {
                 match cast_target {
                     CastTarget::Type(ty) => ty.clone(),
                     CastTarget::LibraryCall(_, _) => Type::i32(),
@@ -98,12 +98,12 @@ impl Parser {
                 }
             }
             
-            Expr::Tuple(elements) => {
+            ExprKind::Tuple(elements) => {
                 let types = elements.iter().map(Parser::infer_type).collect();
                 Type::Tuple { fields: types }
             }
             
-            Expr::Array(elements) => {
+            ExprKind::Array(elements) => {
                 if elements.is_empty() {
                     Type::Array {
                         element: Box::new(Type::i32()),
@@ -128,11 +128,11 @@ impl Parser {
                 }
             }
             
-            Expr::CallNamed(name, _) => Type::Struct { name: name.clone() },
-            Expr::ModuleCallNamed(_, name, _) => Type::Struct { name: name.clone() },
-            Expr::IsEmpty(_) | Expr::IsNotEmpty(_) => Type::Bool,
-            Expr::Have { .. } | Expr::Contain { .. } | Expr::ContainAll { .. } => Type::Bool,
-            Expr::Index(arr, _) => {
+            ExprKind::CallNamed(name, _) => Type::Struct { name: name.clone() },
+            ExprKind::ModuleCallNamed(_, name, _) => Type::Struct { name: name.clone() },
+            ExprKind::IsEmpty(_) | ExprKind::IsNotEmpty(_) => Type::Bool,
+            ExprKind::Have { .. } | ExprKind::Contain { .. } | ExprKind::ContainAll { .. } => Type::Bool,
+            ExprKind::Index(arr, _) => {
                 let arr_type = Parser::infer_type(arr);
                 match arr_type {
                     Type::Array { element, .. } => *element,
@@ -141,19 +141,19 @@ impl Parser {
                 }
             }
 
-            Expr::IndexOf { .. } => Type::i32(),
-            Expr::SizeOf(_) | Expr::AlignOf(_) => Type::u64(),
-            Expr::TypeOf(_) => Type::Str { len_type: Box::new(Type::i32()) },
-            Expr::Panic(_) => Type::Void,
-            Expr::ReferenceTo(_) => Type::u32(),
-            Expr::Collect(_) => Type::Ptr(Box::new(Type::Void)),
-            Expr::Filter { obj, .. } => Parser::infer_type(obj),
-            Expr::Wait(inner) => {
+            ExprKind::IndexOf { .. } => Type::i32(),
+            ExprKind::SizeOf(_) | ExprKind::AlignOf(_) => Type::u64(),
+            ExprKind::TypeOf(_) => Type::Str { len_type: Box::new(Type::i32()) },
+            ExprKind::Panic(_) => Type::Void,
+            ExprKind::ReferenceTo(_) => Type::u32(),
+            ExprKind::Collect(_) => Type::Ptr(Box::new(Type::Void)),
+            ExprKind::Filter { obj, .. } => Parser::infer_type(obj),
+            ExprKind::Wait(inner) => {
                 let inner_type = Parser::infer_type(inner);
                 Type::Ptr(Box::new(inner_type))
             }
 
-            Expr::Chars(_) => Type::Array { 
+            ExprKind::Chars(_) => Type::Array { 
                 element: Box::new(Type::char8()), 
                 size: None 
             },
@@ -214,6 +214,7 @@ impl Parser {
         }
 
         self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
-        Expr::MethodCall(Box::new(obj), method_name, args)
+        let (span, location) = (obj.span, obj.location());
+        Expr::new(ExprKind::MethodCall(Box::new(obj), method_name, args), span, location)
     }
 }
\ No newline at end of file
@@ -0,0 +1,561 @@
+use crate::import::*;
+
+/// A local variable as the lint pass sees it.
+struct Binding {
+    name: String,
+    location: SourceLocation,
+    is_mutable: bool,
+    /// Parameters and `self` are never reported as unused.
+    reportable: bool,
+    read: bool,
+    mutated: bool,
+}
+
+/// Lints over a type-checked module. Findings go through the module's `DiagnosticHandler`,
+/// which applies the configured allow/warn/deny levels.
+pub struct Linter<'a> {
+    handler: &'a mut DiagnosticHandler,
+    scopes: Vec<Vec<Binding>>,
+    /// Every function, struct, module and import name mentioned outside a local binding.
+    referenced: HashSet<String>,
+    /// Function name -> whether each parameter may be modified by the callee.
+    mutable_params: HashMap<String, Vec<bool>>,
+}
+
+impl<'a> Linter<'a> {
+    pub fn new(handler: &'a mut DiagnosticHandler) -> Self {
+        Self {
+            handler,
+            scopes: Vec::new(),
+            referenced: HashSet::new(),
+            mutable_params: HashMap::new(),
+        }
+    }
+
+    /// `imports` are the names this module imports from other local files.
+    pub fn run(&mut self, program: &Program, impls: &[ImplBlock], imports: &[String], file: &str) {
+        for func in &program.functions {
+            let flags = func
+                .params
+                .iter()
+                .map(|(_, _, modifier)| matches!(modifier, ParamModifier::Mutable | ParamModifier::MutableReference))
+                .collect();
+            self.mutable_params.insert(func.name.clone(), flags);
+        }
+
+        for func in &program.functions {
+            self.check_body(&func.params, None, &func.body);
+            self.reference_type(&func.return_type);
+        }
+        for impl_block in impls {
+            self.referenced.insert(impl_block.struct_name.clone());
+            if let Some(body) = &impl_block.constructor_body {
+                self.scopes.push(Vec::new());
+                for (name, ty) in &impl_block.constructor_params {
+                    self.declare_param(name, ty, &default_location());
+                }
+                for (_, expr) in body {
+                    self.visit_expr(expr);
+                }
+                self.scopes.pop();
+            }
+            for method in &impl_block.methods {
+                self.check_body(&method.params, method.self_modifier.as_ref().map(|_| &method.location), &method.body);
+                self.reference_type(&method.return_type);
+            }
+        }
+
+        for func in &program.functions {
+            if !func.is_public && !func.is_test && func.name != "main" && !self.referenced.contains(&func.name) {
+                self.report(
+                    &codes::W0102,
+                    &format!("Function '{}' is never used", func.name),
+                    func.location.clone(),
+                    vec![],
+                    "Remove it, or mark it `public` if other files should call it.",
+                );
+            }
+        }
+
+        for name in imports {
+            if !self.referenced.contains(name) {
+                let location = import_location(self.handler.source(), name, file);
+                self.report(
+                    &codes::W0101,
+                    &format!("Unused import '{}'", name),
+                    location,
+                    vec![],
+                    "Remove the import.",
+                );
+            }
+        }
+    }
+
+    fn check_body(&mut self, params: &[(String, Type, ParamModifier)], self_location: Option<&SourceLocation>, body: &[SpannedStmt]) {
+        self.scopes.push(Vec::new());
+        if let Some(location) = self_location {
+            self.declare_param("self", &Type::SelfType, location);
+        }
+        for (name, ty, _) in params {
+            self.declare_param(name, ty, &default_location());
+        }
+        self.check_block(body);
+        self.exit_scope();
+    }
+
+    fn check_block(&mut self, body: &[SpannedStmt]) {
+        let mut diverged: Option<&SpannedStmt> = None;
+        for spanned in body {
+            if let Some(exit) = diverged.take() {
+                self.report(
+                    &codes::W0103,
+                    "Unreachable statement",
+                    spanned.location.clone(),
+                    vec![(exit.location.clone(), "any code after this is unreachable".to_string())],
+                    "Remove the statement or move it before the jump.",
+                );
+            }
+            self.visit_stmt(spanned);
+            if matches!(spanned.stmt, Stmt::Return(_) | Stmt::Break | Stmt::Continue) && diverged.is_none() {
+                diverged = Some(spanned);
+            }
+        }
+    }
+
+    fn check_scoped_block(&mut self, body: &[SpannedStmt]) {
+        self.scopes.push(Vec::new());
+        self.check_block(body);
+        self.exit_scope();
+    }
+
+    fn visit_stmt(&mut self, spanned: &SpannedStmt) {
+        let location = &spanned.location;
+        match &spanned.stmt {
+            Stmt::TypedDeclaration { name, ty, value, is_mutable } => {
+                self.visit_expr(value);
+                self.reference_type(ty);
+                self.declare(name, location, *is_mutable);
+            }
+            Stmt::TupleUnpack { names, value } => {
+                self.visit_expr(value);
+                for name in names {
+                    self.declare(name, location, false);
+                }
+            }
+            Stmt::Assign(name, value) | Stmt::CompoundAssign(name, _, value) => {
+                self.visit_expr(value);
+                self.mutate(name, false);
+            }
+            Stmt::IndexAssign(obj, indices, value) => {
+                self.visit_target(obj);
+                for index in indices {
+                    self.visit_expr(index);
+                }
+                self.visit_expr(value);
+            }
+            Stmt::MemberAssign(obj, _, value) => {
+                self.visit_target(obj);
+                self.visit_expr(value);
+            }
+            Stmt::MemberCompoundAssign(obj, _, _, value) => {
+                self.visit_target(obj);
+                self.visit_expr(value);
+            }
+            Stmt::ModuleAssign(module, _, value) | Stmt::ModuleCompoundAssign(module, _, _, value) => {
+                self.referenced.insert(module.clone());
+                self.visit_expr(value);
+            }
+            Stmt::If(cond, then_body, else_body) => {
+                self.visit_expr(cond);
+                self.check_scoped_block(then_body);
+                if let Some(else_body) = else_body {
+                    self.check_scoped_block(else_body);
+                }
+            }
+            Stmt::While(cond, body) => {
+                self.visit_expr(cond);
+                self.check_scoped_block(body);
+            }
+            Stmt::For(var, iterable, body) => {
+                self.visit_expr(iterable);
+                self.scopes.push(Vec::new());
+                self.declare(var, location, false);
+                self.check_block(body);
+                self.exit_scope();
+            }
+            Stmt::Loop(body) | Stmt::Scope(body) => self.check_scoped_block(body),
+            Stmt::Unsafe(body) => {
+                if body.is_empty() {
+                    self.report(&codes::W0107, "Empty `unsafe` block", location.clone(), vec![], "Remove the block.");
+                }
+                self.check_scoped_block(body);
+            }
+            Stmt::Match(expr, cases, default) => {
+                self.visit_expr(expr);
+                for case in cases {
+                    self.scopes.push(Vec::new());
+                    for (name, location) in case.pattern.bindings() {
+                        self.declare(name, location, false);
+                    }
+                    if let Some(guard) = &case.guard {
+                        self.visit_expr(guard);
+                    }
+                    self.check_block(&case.body);
+                    self.exit_scope();
+                }
+                if let Some(default) = default {
+                    self.check_scoped_block(default);
+                }
+            }
+            Stmt::Call(name, args) => self.visit_call(name, args.iter()),
+            Stmt::ModuleCall(module, _, args) => {
+                self.referenced.insert(module.clone());
+                self.visit_exprs(args);
+            }
+            Stmt::MethodCall(obj, _, args) => {
+                self.visit_target(obj);
+                self.visit_exprs(args);
+            }
+            Stmt::MethodCallNamed(obj, _, args) => {
+                self.visit_target(obj);
+                self.visit_exprs(args.iter().map(|(_, arg)| arg));
+            }
+            Stmt::StaticMethodCall(ty, _, args) => {
+                self.referenced.insert(ty.clone());
+                self.visit_exprs(args);
+            }
+            Stmt::StaticMethodCallNamed(ty, _, args) => {
+                self.referenced.insert(ty.clone());
+                self.visit_exprs(args.iter().map(|(_, arg)| arg));
+            }
+            Stmt::Return(value) => {
+                if let Some(value) = value {
+                    self.visit_expr(value);
+                }
+            }
+            Stmt::Expr(expr) => self.visit_expr(expr),
+            Stmt::Break
+            | Stmt::Continue
+            | Stmt::StructDef(_)
+            | Stmt::EnumDef(_)
+            | Stmt::ImplBlock(_)
+            | Stmt::TraitDef(_)
+            | Stmt::ExternDecl(_)
+            | Stmt::ModuleImport(_)
+            | Stmt::ModuleUse(_) => {}
+        }
+    }
+
+    /// The object of an assignment or method call: a variable there may be modified.
+    fn visit_target(&mut self, obj: &Expr) {
+        match &obj.kind {
+            ExprKind::Var(name) => self.mutate(name, true),
+            _ => self.visit_expr(obj),
+        }
+    }
+
+    fn visit_call<'e>(&mut self, name: &str, args: impl Iterator<Item = &'e Expr>) {
+        // The callee may be a variable holding a function.
+        if !self.read(name) {
+            self.referenced.insert(name.to_string());
+        }
+        let flags = self.mutable_params.get(name).cloned().unwrap_or_default();
+        for (index, arg) in args.enumerate() {
+            match &arg.kind {
+                ExprKind::Var(var) if flags.get(index).copied().unwrap_or(false) => self.mutate(var, true),
+                _ => self.visit_expr(arg),
+            }
+        }
+    }
+
+    fn visit_exprs<'e>(&mut self, exprs: impl IntoIterator<Item = &'e Expr>) {
+        for expr in exprs {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Var(name) => {
+                if !self.read(name) {
+                    self.referenced.insert(name.clone());
+                }
+            }
+            ExprKind::Call(name, args) => self.visit_call(name, args.iter()),
+            ExprKind::CallNamed(name, args) => self.visit_call(name, args.iter().map(|(_, arg)| arg)),
+            ExprKind::FuncAddr(name) => {
+                self.referenced.insert(name.clone());
+            }
+            ExprKind::Lambda { params, return_type, body } => {
+                self.reference_type(return_type);
+                self.scopes.push(Vec::new());
+                for (name, ty) in params {
+                    self.declare_param(name, ty, &expr.location);
+                }
+                self.visit_expr(body);
+                self.exit_scope();
+            }
+            ExprKind::StructInit(name, fields) => {
+                self.referenced.insert(name.clone());
+                self.visit_exprs(fields.iter().map(|(_, value)| value));
+            }
+            ExprKind::OffsetOf { struct_type, .. } => {
+                self.referenced.insert(struct_type.clone());
+            }
+            ExprKind::MethodCall(obj, _, args) | ExprKind::ArrayMethod { obj, args, .. } | ExprKind::OptionMethod { obj, args, .. } => {
+                self.visit_target(obj);
+                self.visit_exprs(args);
+            }
+            ExprKind::MethodCallNamed(obj, _, args) => {
+                self.visit_target(obj);
+                self.visit_exprs(args.iter().map(|(_, arg)| arg));
+            }
+            ExprKind::StaticMethodCall(ty, _, args) | ExprKind::ModuleCall(ty, _, args) => {
+                self.referenced.insert(ty.clone());
+                self.visit_exprs(args);
+            }
+            ExprKind::StaticMethodCallNamed(ty, _, args) | ExprKind::ModuleCallNamed(ty, _, args) => {
+                self.referenced.insert(ty.clone());
+                self.visit_exprs(args.iter().map(|(_, arg)| arg));
+            }
+            ExprKind::ModuleAccess(module, _) => {
+                self.referenced.insert(module.clone());
+            }
+            ExprKind::BinOp(op, left, right) => {
+                if let Some(result) = constant_comparison(op, left, right) {
+                    self.report(
+                        &codes::W0106,
+                        &format!("This comparison is always {}", result),
+                        expr.location.clone(),
+                        vec![],
+                        "Check that the intended variables are compared.",
+                    );
+                }
+                self.visit_expr(left);
+                self.visit_expr(right);
+            }
+            ExprKind::Cast(inner, target) => {
+                self.visit_expr(inner);
+                match target {
+                    CastTarget::Type(ty) => self.reference_type(ty),
+                    CastTarget::LibraryCall(name, args) => self.visit_call(name, args.iter()),
+                    CastTarget::LibraryCallTyped(name, types) => {
+                        self.referenced.insert(name.clone());
+                        for ty in types {
+                            self.reference_type(ty);
+                        }
+                    }
+                    CastTarget::LibraryModuleCall(module, _, args) => {
+                        self.referenced.insert(module.clone());
+                        self.visit_exprs(args);
+                    }
+                }
+            }
+            ExprKind::ReferenceTo(ty) | ExprKind::SizeOf(ty) | ExprKind::AlignOf(ty) | ExprKind::Type(ty) => self.reference_type(ty),
+            ExprKind::ResultOk(inner)
+            | ExprKind::ResultErr(inner)
+            | ExprKind::Not(inner)
+            | ExprKind::Wait(inner)
+            | ExprKind::Unwrap(inner)
+            | ExprKind::Chars(inner)
+            | ExprKind::IsNotEmpty(inner)
+            | ExprKind::Collect(inner)
+            | ExprKind::IsEmpty(inner)
+            | ExprKind::Some(inner)
+            | ExprKind::UnOp(_, inner)
+            | ExprKind::TupleAccess(inner, _)
+            | ExprKind::MemberAccess(inner, _)
+            | ExprKind::TypeOf(inner)
+            | ExprKind::Panic(inner) => self.visit_expr(inner),
+            ExprKind::ArrayGet { obj, reference: other }
+            | ExprKind::Filter { obj, reference: other }
+            | ExprKind::Have { obj, item: other }
+            | ExprKind::Contain { obj, item: other }
+            | ExprKind::IndexOf { obj, item: other }
+            | ExprKind::UnwrapOr(obj, other)
+            | ExprKind::Pipe(obj, other) => {
+                self.visit_expr(obj);
+                self.visit_expr(other);
+            }
+            ExprKind::ContainAll { obj, items } | ExprKind::Index(obj, items) => {
+                self.visit_expr(obj);
+                self.visit_exprs(items);
+            }
+            ExprKind::Tuple(items) | ExprKind::Array(items) | ExprKind::OneOf(items) => self.visit_exprs(items),
+            ExprKind::Number(_)
+            | ExprKind::Float(_)
+            | ExprKind::String(_)
+            | ExprKind::Bool(_)
+            | ExprKind::HexNumber(_)
+            | ExprKind::BinaryNumber(_)
+            | ExprKind::OctalNumber(_)
+            | ExprKind::Char(_)
+            | ExprKind::None => {}
+        }
+    }
+
+    fn reference_type(&mut self, ty: &Type) {
+        match ty {
+            Type::Struct { name } => {
+                self.referenced.insert(name.clone());
+            }
+            Type::Ptr(inner)
+            | Type::RawPtr(inner)
+            | Type::Owned(inner)
+            | Type::Ref(inner)
+            | Type::MutRef(inner)
+            | Type::Const(inner)
+            | Type::Array { element: inner, .. }
+            | Type::MultiArray { element: inner, .. }
+            | Type::Option { inner } => self.reference_type(inner),
+            Type::Tuple { fields: types } | Type::Union { variants: types } | Type::Intersection { types } => {
+                for ty in types {
+                    self.reference_type(ty);
+                }
+            }
+            Type::Result { ok, err } => {
+                self.reference_type(ok);
+                self.reference_type(err);
+            }
+            Type::FnPtr { params, return_type } | Type::Closure { params, return_type } => {
+                for ty in params {
+                    self.reference_type(ty);
+                }
+                self.reference_type(return_type);
+            }
+            _ => {}
+        }
+    }
+
+    fn declare(&mut self, name: &str, location: &SourceLocation, is_mutable: bool) {
+        if let Some(previous) = self.scopes.iter().flatten().rev().find(|binding| binding.name == name) {
+            let previous = previous.location.clone();
+            let mut secondary = vec![];
+            if previous.line > 0 {
+                secondary.push((previous, "previous declaration here".to_string()));
+            }
+            self.report(
+                &codes::W0105,
+                &format!("Declaration of '{}' shadows an earlier variable", name),
+                location.clone(),
+                secondary,
+                "Rename one of the variables, or assign to the existing one.",
+            );
+        }
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(Binding {
+                name: name.to_string(),
+                location: location.clone(),
+                is_mutable,
+                reportable: !name.starts_with('_'),
+                read: false,
+                mutated: false,
+            });
+        }
+    }
+
+    fn declare_param(&mut self, name: &str, ty: &Type, location: &SourceLocation) {
+        self.reference_type(ty);
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(Binding {
+                name: name.to_string(),
+                location: location.clone(),
+                is_mutable: false,
+                reportable: false,
+                read: false,
+                mutated: false,
+            });
+        }
+    }
+
+    fn binding_mut(&mut self, name: &str) -> Option<&mut Binding> {
+        self.scopes.iter_mut().flatten().rev().find(|binding| binding.name == name)
+    }
+
+    fn read(&mut self, name: &str) -> bool {
+        self.binding_mut(name).map(|binding| binding.read = true).is_some()
+    }
+
+    fn mutate(&mut self, name: &str, also_read: bool) {
+        match self.binding_mut(name) {
+            Some(binding) => {
+                binding.mutated = true;
+                binding.read |= also_read;
+            }
+            None => {
+                self.referenced.insert(name.to_string());
+            }
+        }
+    }
+
+    fn exit_scope(&mut self) {
+        for binding in self.scopes.pop().unwrap_or_default() {
+            if !binding.reportable {
+                continue;
+            }
+            if !binding.read {
+                self.report(
+                    &codes::W0100,
+                    &format!("Unused variable '{}'", binding.name),
+                    binding.location.clone(),
+                    vec![],
+                    &format!("If this is intentional, name it '_{}'.", binding.name),
+                );
+            } else if binding.is_mutable && !binding.mutated {
+                self.report(
+                    &codes::W0104,
+                    &format!("Variable '{}' does not need to be mutable", binding.name),
+                    binding.location,
+                    vec![],
+                    "Remove `mut` from the declaration.",
+                );
+            }
+        }
+    }
+
+    fn report(&mut self, code: &ErrorCode, message: &str, location: SourceLocation, secondary: Vec<(SourceLocation, String)>, help: &str) {
+        self.handler.warning(
+            code,
+            message,
+            ErrorContext {
+                primary_location: location,
+                secondary_locations: secondary,
+                help_message: Some(help.to_string()),
+                suggestions: vec![],
+            },
+        );
+    }
+}
+
+/// `Some(result)` when a comparison's outcome is fixed: two literals, or a variable compared with itself.
+fn constant_comparison(op: &str, left: &Expr, right: &Expr) -> Option<bool> {
+    let ordering = match (&left.kind, &right.kind) {
+        (ExprKind::Var(a), ExprKind::Var(b)) if a == b => std::cmp::Ordering::Equal,
+        (ExprKind::Number(a), ExprKind::Number(b)) => a.cmp(b),
+        (ExprKind::Bool(a), ExprKind::Bool(b)) => a.cmp(b),
+        (ExprKind::String(a), ExprKind::String(b)) => a.cmp(b),
+        (ExprKind::Char(a), ExprKind::Char(b)) => a.cmp(b),
+        _ => return None,
+    };
+    match op {
+        "==" => Some(ordering.is_eq()),
+        "!=" => Some(ordering.is_ne()),
+        "<" => Some(ordering.is_lt()),
+        "<=" => Some(ordering.is_le()),
+        ">" => Some(ordering.is_gt()),
+        ">=" => Some(ordering.is_ge()),
+        _ => None,
+    }
+}
+
+/// Imports carry no location in the AST, so the line is found in the source.
+fn import_location(source: &str, name: &str, file: &str) -> SourceLocation {
+    for (index, line) in source.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        if words.next() == Some("import") && words.next() == Some(name) {
+            let column = line.len() - line.trim_start().len() + 1;
+            return SourceLocation { file: file.to_string(), line: index + 1, column, length: line.trim().len() };
+        }
+    }
+    SourceLocation { file: file.to_string(), line: 0, column: 0, length: 0 }
+}
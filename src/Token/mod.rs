@@ -3,3 +3,7 @@ pub mod Storge;
 pub mod Lexer;
 pub mod functions;
 pub mod Helper;
+pub mod typechecker;
+pub mod lint;
+pub mod exhaustive;
+pub mod format;
@@ -30,15 +30,28 @@ impl ImportContext {
 
 impl Parser {
     pub fn new(tokens: Vec<Token>, source: String, spans: Vec<SourceSpan>) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.chars().enumerate().filter(|(_, c)| *c == '\n').map(|(i, _)| i + 1))
+            .collect();
+
         Parser { 
             tokens, 
             spans, 
             pos: 0, 
             source: Arc::new(source), 
-            diags: Vec::new() 
+            diags: Vec::new(),
+            file: "input".to_string(),
+            line_starts,
+            recovering: false,
+            type_params: Vec::new(),
         }
     }
 
+    pub fn with_file(mut self, file: impl Into<String>) -> Self {
+        self.file = file.into();
+        self
+    }
+
     pub fn build_import_context(import_decls: &[ImportDecl]) -> ImportContext {
         let mut context = ImportContext::new();
         
@@ -59,30 +72,88 @@ impl Parser {
     }
 
     pub fn get_location(&self, pos: usize) -> SourceLocation {
-        let lines: Vec<&str> = self.source.lines().collect();
-        let mut current_pos = 0;
-        
-        for (line_num, line) in lines.iter().enumerate() {
-            let line_len = line.len() + 1; 
-            if current_pos + line_len > pos {
-                return SourceLocation {
-                    file: "input".to_string(),
-                    line: line_num + 1,
-                    column: pos - current_pos + 1,
-                    length: 1,
-                };
-            }
-            current_pos += line_len;
-        }
-        
+        let line = self.line_starts.partition_point(|&start| start <= pos).max(1);
+
         SourceLocation {
-            file: "input".to_string(),
-            line: lines.len(),
-            column: 1,
+            file: self.file.clone(),
+            line,
+            column: pos - self.line_starts[line - 1] + 1,
             length: 1,
         }
     }
 
+    /// Wraps `kind` with the span of the tokens consumed since `start`.
+    pub fn expr_at(&self, start: usize, kind: ExprKind) -> Expr {
+        let (span, location) = self.span_from(start);
+        Expr::new(kind, span, location)
+    }
+
+    /// Records a syntax error at the token at `pos`; parsing continues after it.
+    pub fn error_at(&mut self, pos: usize, code: &ErrorCode, message: &str, help: Option<String>) {
+        let (_, primary_location) = self.span_from_to(pos, pos + 1);
+        self.diags.push(Diagnostic::error(
+            code,
+            message,
+            ErrorContext {
+                primary_location,
+                secondary_locations: vec![],
+                help_message: help,
+                suggestions: vec![],
+            },
+        ));
+    }
+
+    /// Span and location covering the tokens from `start` up to the current position.
+    fn span_from(&self, start: usize) -> (SourceSpan, SourceLocation) {
+        self.span_from_to(start, self.pos)
+    }
+
+    /// Reports a block that runs into the next item or the end of the file, with a fix that closes it.
+    fn missing_end(&mut self, what: &str, opened: SourceLocation) {
+        // An earlier error may have swallowed the `end`; that one has been reported already.
+        if self.recovering {
+            return;
+        }
+        let (location, text) = if self.current() == Token::EOF {
+            (self.get_location(self.source.trim_end().chars().count()), "\nend")
+        } else {
+            (self.span_from(self.pos).1, "end\n\n")
+        };
+        // At end of file there is no telling which open block the `end` belongs to, so the
+        // insertion is only suggested.
+        let fix = Fix::insert("Insert the missing `end`", location.clone(), text);
+        let fix = if self.current() == Token::EOF { fix.maybe_incorrect() } else { fix };
+        self.diags.push(
+            Diagnostic::error(
+                &codes::E0103,
+                &format!("Missing `end` to close {}", what),
+                ErrorContext {
+                    primary_location: location,
+                    secondary_locations: vec![(opened, "opened here".to_string())],
+                    help_message: Some("Every `func` is closed with `end`.".to_string()),
+                    suggestions: vec![],
+                },
+            )
+            .with_fixes(Some(fix)),
+        );
+    }
+
+    fn span_from_to(&self, start: usize, end: usize) -> (SourceSpan, SourceLocation) {
+        // Tokens past the last span (EOF) sit at the end of the source.
+        let eof = self.source.trim_end().chars().count();
+        let first = self.spans.get(start).cloned().unwrap_or(SourceSpan::from(eof..eof));
+        let end = self
+            .spans
+            .get(end.saturating_sub(1).max(start))
+            .map(|span| span.offset() + span.len())
+            .unwrap_or(first.offset());
+        let span = SourceSpan::from(first.offset()..end.max(first.offset()));
+
+        let mut location = self.get_location(first.offset());
+        location.length = span.len().max(1);
+        (span, location)
+    }
+
     
     pub fn parse_type(&mut self) -> Type {
         let mut is_union = false;
@@ -152,9 +223,9 @@ impl Parser {
                 self.advance();
                 Type::SelfType
             }
-            Token::Trait => {
+            Token::Identifier(ref word) if word == "dyn" && matches!(self.peek(1), Token::Identifier(_)) => {
                 self.advance();
-                Type::Trait
+                Type::Trait { name: self.expect_identifier("a trait name") }
             }
             Token::LeftBracket => {
                 self.advance();
@@ -241,8 +312,31 @@ impl Parser {
             }
             Token::Caret => {
                 self.advance();
-                let inner = self.parse_base_type();
-                Type::RawPtr(Box::new(inner))
+                match self.parse_base_type() {
+                    Type::Closure { params, return_type } => Type::FnPtr { params, return_type },
+                    inner => Type::RawPtr(Box::new(inner)),
+                }
+            }
+            Token::Func => {
+                self.advance();
+                self.expect(Token::LeftParen, vec![Token::RightParen]);
+                let mut params = Vec::new();
+                while !matches!(self.current(), Token::RightParen | Token::EOF) {
+                    params.push(self.parse_type());
+                    if self.current() == Token::Comma {
+                        self.advance();
+                    } else {
+                        break;
+                    }
+                }
+                self.expect(Token::RightParen, vec![Token::Colon]);
+                let return_type = if self.current() == Token::Colon {
+                    self.advance();
+                    self.parse_type()
+                } else {
+                    Type::Void
+                };
+                Type::Closure { params, return_type: Box::new(return_type) }
             }
             Token::TripleDot => { self.advance(); Type::TripleDot }
             Token::Any => { self.advance(); Type::Any }
@@ -259,6 +353,29 @@ impl Parser {
                     Type::Ref(Box::new(self.parse_base_type()))
                 }
             }
+            Token::Identifier(type_name) if self.type_params.contains(&type_name) => {
+                self.advance();
+                Type::Param { name: type_name }
+            }
+            Token::Identifier(type_name) if self.peek(1) == Token::LeftBracket && !matches!(self.peek(2), Token::RightBracket | Token::Number(_)) => {
+                self.advance();
+                self.advance();
+                let mut args = vec![self.parse_type()];
+                while self.current() == Token::Comma {
+                    self.advance();
+                    args.push(self.parse_type());
+                }
+                self.expect(Token::RightBracket, vec![Token::Comma, Token::Colon]);
+                // `Option[T]` and `Result[T, E]` spelled with capitals are the built-in types.
+                match (type_name.as_str(), args.len()) {
+                    ("Option", 1) => Type::option(args.remove(0)),
+                    ("Result", 2) => {
+                        let ok = args.remove(0);
+                        Type::result(ok, args.remove(0))
+                    }
+                    _ => Type::Generic { name: type_name, args },
+                }
+            }
             Token::Identifier(type_name) => {
                 self.advance();
                 if self.current() == Token::LeftBracket {
@@ -393,6 +510,7 @@ impl Parser {
                 }
             }
             _ => {
+                self.unexpected("a type");
                 self.advance();
                 Type::Void
             }
@@ -406,41 +524,43 @@ impl Parser {
     }
 
     fn parse_primary(&mut self) -> Expr {
+        let start = self.pos;
         let current = self.current();
-        let expr = match current {
+        let kind = match current {
             Token::OneOf => {
                 self.advance();
                 self.expect(Token::LeftParen, vec![Token::RightParen]);
                 let mut exprs = Vec::new();
-                while !matches!(self.current(), Token::RightParen | Token::EOF) {
+                while !self.at_list_end() {
                     exprs.push(self.parse_expr());
                     if self.current() == Token::Comma {
                         self.advance();
                     }
                 }
                 self.expect(Token::RightParen, vec![Token::Semicolon]);
-                Expr::OneOf(exprs)
+                ExprKind::OneOf(exprs)
+            }
+            Token::Panic => {
+                self.advance();
+                self.expect(Token::LeftParen, vec![Token::RightParen]);
+                let mut args = Vec::new();
+                while !self.at_list_end() {
+                    args.push(self.parse_expr());
+                    if self.current() == Token::Comma {
+                        self.advance();
+                    }
+                }
+                self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
+                ExprKind::Call("panic".to_string(), args)
             }
             Token::OffsetOf => {
                 self.advance();
                 self.expect(Token::LeftParen, vec![Token::RightParen]);
-                let struct_name = if let Token::Identifier(name) = self.current() {
-                    self.advance();
-                    name
-                } else {
-                    self.advance();
-                    "error".to_string()
-                };
+                let struct_name = self.expect_identifier("a struct name");
                 self.expect(Token::Comma, vec![Token::RightParen]);
-                let field_name = if let Token::Identifier(name) = self.current() {
-                    self.advance();
-                    name
-                } else {
-                    self.advance();
-                    "error".to_string()
-                };
+                let field_name = self.expect_identifier("a field name");
                 self.expect(Token::RightParen, vec![Token::Semicolon]);
-                Expr::OffsetOf {
+                ExprKind::OffsetOf {
                     struct_type: struct_name,
                     field: field_name
                 }
@@ -450,14 +570,39 @@ impl Parser {
                 self.expect(Token::LeftParen, vec![Token::RightParen]);
                 let target_type = self.parse_type();
                 self.expect(Token::RightParen, vec![Token::Semicolon]);
-                Expr::AlignOf(target_type)
+                ExprKind::AlignOf(target_type)
             }
             Token::TypeOf => {
                 self.advance();
                 self.expect(Token::LeftParen, vec![Token::RightParen]);
                 let expr = self.parse_expr();
                 self.expect(Token::RightParen, vec![Token::Semicolon]);
-                Expr::TypeOf(Box::new(expr))
+                ExprKind::TypeOf(Box::new(expr))
+            }
+            Token::Func if self.peek(1) == Token::LeftParen => {
+                self.advance();
+                self.expect(Token::LeftParen, vec![Token::RightParen]);
+                let mut params = Vec::new();
+                while !matches!(self.current(), Token::RightParen | Token::EOF) {
+                    let name = self.expect_identifier("a parameter name");
+                    self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
+                    params.push((name, self.parse_type()));
+                    if self.current() == Token::Comma {
+                        self.advance();
+                    } else {
+                        break;
+                    }
+                }
+                self.expect(Token::RightParen, vec![Token::Colon, Token::FatArrow]);
+                let return_type = if self.current() == Token::Colon {
+                    self.advance();
+                    self.parse_type()
+                } else {
+                    Type::Void
+                };
+                self.expect(Token::FatArrow, vec![]);
+                let body = self.parse_expr();
+                ExprKind::Lambda { params, return_type, body: Box::new(body) }
             }
             Token::Selfish | Token::Identifier(_) => {
                 let var_name = match self.current() {
@@ -466,6 +611,7 @@ impl Parser {
                     _ => unreachable!(),
                 };
                 self.advance();
+                let var_expr = self.expr_at(start, ExprKind::Var(var_name.clone()));
                 
                 if self.current() == Token::Dot {
                     self.advance();
@@ -473,10 +619,14 @@ impl Parser {
                         let method_name = member.clone();
                         self.advance();
                         
-                        if self.current() == Token::LeftParen {
+                        if self.current() == Token::LeftParen && matches!(self.peek(1), Token::Identifier(_)) && self.peek(2) == Token::Equals {
+                            self.advance();
+                            let named_args = self.parse_named_args();
+                            ExprKind::MethodCallNamed(Box::new(var_expr), method_name, named_args)
+                        } else if self.current() == Token::LeftParen {
                             self.advance();
                             let mut args = Vec::new();
-                            while !matches!(self.current(), Token::RightParen | Token::EOF) {
+                            while !self.at_list_end() {
                                 args.push(self.parse_expr());
                                 if self.current() == Token::Comma {
                                     self.advance();
@@ -484,72 +634,57 @@ impl Parser {
                             }
                             self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
                             
-                            Expr::MethodCall(Box::new(Expr::Var(var_name)), method_name, args)
+                            ExprKind::MethodCall(Box::new(var_expr), method_name, args)
                         } else {
-                            let obj = Expr::Var(var_name);
-                            Expr::MemberAccess(Box::new(obj), member)
+                            ExprKind::MemberAccess(Box::new(var_expr), member)
                         }
                     } else {
+                        self.unexpected("a member name");
                         self.advance();
-                        Expr::ModuleAccess(var_name, "error".to_string())
+                        ExprKind::ModuleAccess(var_name, "error".to_string())
                     }
                 } else if self.current() == Token::LeftParen {
                     self.advance();
                     if matches!(self.current(), Token::Identifier(_)) && self.peek(1) == Token::Equals {
-                        let mut named_args = Vec::new();
-                        while !matches!(self.current(), Token::RightParen | Token::EOF) {
-                            if let Token::Identifier(arg_name) = self.current() {
-                                self.advance();
-                                self.expect(Token::Equals, vec![Token::Comma, Token::RightParen]);
-                                let arg_expr = self.parse_expr();
-                                named_args.push((arg_name, arg_expr));
-                                if self.current() == Token::Comma {
-                                    self.advance();
-                                }
-                            } else {
-                                self.advance();
-                                break;
-                            }
-                        }
-                        self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
-                        Expr::CallNamed(var_name, named_args)
+                        let named_args = self.parse_named_args();
+                        ExprKind::CallNamed(var_name, named_args)
                     } else {
                         let mut args = Vec::new();
-                        while !matches!(self.current(), Token::RightParen | Token::EOF) {
+                        while !self.at_list_end() {
                             args.push(self.parse_expr());
                             if self.current() == Token::Comma {
                                 self.advance();
                             }
                         }
                         self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
-                        Expr::Call(var_name, args)
+                        ExprKind::Call(var_name, args)
                     }
                 } else {
-                    Expr::Var(var_name)
-                }
-            }
-            Token::None => {self.advance();Expr::None}
-            Token::Number(n) => { self.advance(); Expr::Number(n) }
-            Token::Float(f) => { self.advance(); Expr::Float(f.into_inner() as f32) }
-            Token::HexNumber(n) => { self.advance(); Expr::HexNumber(n as i32) }
-            Token::BinaryNumber(n) => { self.advance(); Expr::BinaryNumber(n as i32) }
-            Token::OctalNumber(n) => { self.advance(); Expr::OctalNumber(n as i32) }
-            Token::String(s) => { self.advance(); Expr::String(s) }
-            Token::True => { self.advance(); Expr::Bool(true) }
-            Token::False => { self.advance(); Expr::Bool(false) }
+                    ExprKind::Var(var_name)
+                }
+            }
+            Token::None => {self.advance();ExprKind::None}
+            Token::Number(n) => { self.advance(); ExprKind::Number(n) }
+            Token::Float(f) => { self.advance(); ExprKind::Float(f.into_inner() as f32) }
+            Token::HexNumber(n) => { self.advance(); ExprKind::HexNumber(n as i32) }
+            Token::BinaryNumber(n) => { self.advance(); ExprKind::BinaryNumber(n as i32) }
+            Token::OctalNumber(n) => { self.advance(); ExprKind::OctalNumber(n as i32) }
+            Token::String(s) => { self.advance(); ExprKind::String(s) }
+            Token::True => { self.advance(); ExprKind::Bool(true) }
+            Token::False => { self.advance(); ExprKind::Bool(false) }
             Token::Some => {
                 self.advance();
                 self.expect(Token::LeftParen, vec![Token::RightParen]);
                 let value = self.parse_expr();
                 self.expect(Token::RightParen, vec![Token::Semicolon]);
-                Expr::Some(Box::new(value))
+                ExprKind::Some(Box::new(value))
             }
             Token::Ok => {
                 self.advance();
                 self.expect(Token::LeftParen, vec![Token::RightParen]);
                 let value = self.parse_expr();
                 self.expect(Token::RightParen, vec![Token::Semicolon]);
-                Expr::ResultOk(Box::new(value))
+                ExprKind::ResultOk(Box::new(value))
             }
             
             Token::Err => {
@@ -557,14 +692,14 @@ impl Parser {
                 self.expect(Token::LeftParen, vec![Token::RightParen]);
                 let value = self.parse_expr();
                 self.expect(Token::RightParen, vec![Token::Semicolon]);
-                Expr::ResultErr(Box::new(value))
+                ExprKind::ResultErr(Box::new(value))
             }
 
             Token::LeftParen => {
                 self.advance();
                 if self.current() == Token::RightParen {
                     self.advance();
-                    return Expr::Tuple(vec![]);
+                    return self.expr_at(start, ExprKind::Tuple(vec![]));
                 }
                 let first_expr = self.parse_expr();
                 if self.current() == Token::Comma {
@@ -575,39 +710,44 @@ impl Parser {
                         elements.push(self.parse_expr());
                     }
                     self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
-                    Expr::Tuple(elements)
+                    ExprKind::Tuple(elements)
                 } else {
                     self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
-                    first_expr
+                    first_expr.kind
                 }
             }
             Token::LeftBracket => {
                 self.advance();
                 let mut elements = Vec::new();
-                while !matches!(self.current(), Token::RightBracket | Token::EOF) {
+                while !self.at_list_end() {
                     elements.push(self.parse_expr());
                     if self.current() == Token::Comma {
                         self.advance();
                     }
                 }
                 self.expect(Token::RightBracket, vec![Token::Semicolon, Token::End]);
-                Expr::Array(elements)
+                ExprKind::Array(elements)
             }
 
             _ => {
-                self.advance();
-                Expr::Number(0)
+                self.unexpected("an expression");
+                // What closes the surrounding list or block is left for it to consume.
+                if !self.at_list_end() {
+                    self.advance();
+                }
+                ExprKind::Number(0)
             }
         };
-        expr
+        self.expr_at(start, kind)
     }
 
     pub fn parse_unary(&mut self) -> Expr {
-        match self.current() {
+        let start = self.pos;
+        let kind = match self.current() {
             Token::Not => {
                 self.advance();
                 let expr = self.parse_unary();
-                Expr::Not(Box::new(expr))
+                ExprKind::Not(Box::new(expr))
             }
             Token::Ampersand => {
                 self.advance();
@@ -615,13 +755,13 @@ impl Parser {
                     self.advance();
                     if self.current() == Token::LeftParen {
                         self.advance();
-                        Expr::Number(0)
+                        ExprKind::Number(0)
                     } else {
-                        Expr::FuncAddr(name)
+                        ExprKind::FuncAddr(name)
                     }
                 } else {
                     let expr = self.parse_unary();
-                    Expr::UnOp("&".to_string(), Box::new(expr))
+                    ExprKind::UnOp("&".to_string(), Box::new(expr))
                 }
             }
             Token::Minus => {
@@ -631,10 +771,11 @@ impl Parser {
                 };
                 self.advance();
                 let expr = self.parse_unary();
-                Expr::UnOp(op, Box::new(expr))
+                ExprKind::UnOp(op, Box::new(expr))
             }
-            _ => self.parse_primary()
-        }
+            _ => return self.parse_primary(),
+        };
+        self.expr_at(start, kind)
     }
 
     fn is_type_token(&self, token: Token) -> bool {
@@ -643,7 +784,7 @@ impl Parser {
             Token::Str | Token::Ampersand| Token::BitwiseAnd | Token::TripleDot |
             Token::Tilde | Token::Mut | Token::LeftParen | Token::LeftBracket |
             Token::Identifier(_) | Token::Option | Token::Result | Token::Selfish |
-            Token::Trait | Token::Caret
+            Token::Caret | Token::Func
         )
     }
 
@@ -652,60 +793,66 @@ impl Parser {
     }
 
     fn parse_logic_or(&mut self) -> Expr {
+        let start = self.pos;
         let mut node = self.parse_logic_and();
 
         while self.current() == Token::Or { 
             self.advance();
             let right = self.parse_logic_and();
-            node = Expr::BinOp("||".to_string(), Box::new(node), Box::new(right));
+            node = self.expr_at(start, ExprKind::BinOp("||".to_string(), Box::new(node), Box::new(right)));
         }
 
         node
     }
 
     fn parse_logic_and(&mut self) -> Expr {
+        let start = self.pos;
         let mut node = self.parse_bitwise_or();
 
         while self.current() == Token::And {
             self.advance();
             let right = self.parse_bitwise_or();
-            node = Expr::BinOp("&&".to_string(), Box::new(node), Box::new(right));
+            node = self.expr_at(start, ExprKind::BinOp("&&".to_string(), Box::new(node), Box::new(right)));
         }
 
         node
     }
 
     fn parse_bitwise_or(&mut self) -> Expr {
+        let start = self.pos;
         let mut node = self.parse_bitwise_xor();
         while self.current() == Token::Pipe {
              self.advance();
              let right = self.parse_bitwise_xor();
-             node = Expr::BinOp("|".to_string(), Box::new(node), Box::new(right));
+             node = self.expr_at(start, ExprKind::BinOp("|".to_string(), Box::new(node), Box::new(right)));
         }
         node
     }
 
     fn parse_bitwise_xor(&mut self) -> Expr {
+        let start = self.pos;
         let mut node = self.parse_bitwise_and();
         while self.current() == Token::Caret {
              self.advance();
              let right = self.parse_bitwise_and();
-             node = Expr::BinOp("^".to_string(), Box::new(node), Box::new(right));
+             node = self.expr_at(start, ExprKind::BinOp("^".to_string(), Box::new(node), Box::new(right)));
         }
         node
     }
 
     fn parse_bitwise_and(&mut self) -> Expr {
+        let start = self.pos;
         let mut node = self.parse_equality();
         while self.current() == Token::Ampersand {
              self.advance();
              let right = self.parse_equality();
-             node = Expr::BinOp("&".to_string(), Box::new(node), Box::new(right));
+             node = self.expr_at(start, ExprKind::BinOp("&".to_string(), Box::new(node), Box::new(right)));
         }
         node
     }
 
     fn parse_equality(&mut self) -> Expr {
+        let start = self.pos;
         let mut node = self.parse_comparison();
 
         while matches!(self.current(), Token::EqualsEquals | Token::NotEquals) {
@@ -716,13 +863,14 @@ impl Parser {
             };
             self.advance();
             let right = self.parse_comparison();
-            node = Expr::BinOp(op, Box::new(node), Box::new(right));
+            node = self.expr_at(start, ExprKind::BinOp(op, Box::new(node), Box::new(right)));
         }
 
         node
     }
 
     fn parse_comparison(&mut self) -> Expr {
+        let start = self.pos;
         let mut node = self.parse_shift();
 
         while matches!(self.current(), Token::Less | Token::LessEquals | Token::Greater | Token::GreaterEquals) {
@@ -735,13 +883,14 @@ impl Parser {
             };
             self.advance();
             let right = self.parse_shift();
-            node = Expr::BinOp(op, Box::new(node), Box::new(right));
+            node = self.expr_at(start, ExprKind::BinOp(op, Box::new(node), Box::new(right)));
         }
 
         node
     }
 
     fn parse_shift(&mut self) -> Expr {
+        let start = self.pos;
         let mut node = self.parse_term();
         while matches!(self.current(), Token::LeftShift | Token::RightShift) {
              let op = match self.current() {
@@ -751,12 +900,13 @@ impl Parser {
             };
              self.advance();
              let right = self.parse_term();
-             node = Expr::BinOp(op, Box::new(node), Box::new(right));
+             node = self.expr_at(start, ExprKind::BinOp(op, Box::new(node), Box::new(right)));
         }
         node
     }
 
     fn parse_term(&mut self) -> Expr {
+        let start = self.pos;
         let mut node = self.parse_factor();
 
         while matches!(self.current(), Token::Plus | Token::Minus) {
@@ -767,13 +917,14 @@ impl Parser {
             };
             self.advance();
             let right = self.parse_factor();
-            node = Expr::BinOp(op, Box::new(node), Box::new(right));
+            node = self.expr_at(start, ExprKind::BinOp(op, Box::new(node), Box::new(right)));
         }
 
         node
     }
 
     fn parse_factor(&mut self) -> Expr {
+        let start = self.pos;
         let mut node = self.parse_unary();
 
         while matches!(self.current(), Token::Star | Token::Slash | Token::Percent) {
@@ -785,13 +936,32 @@ impl Parser {
             };
             self.advance();
             let right = self.parse_unary();
-            node = Expr::BinOp(op, Box::new(node), Box::new(right));
+            node = self.expr_at(start, ExprKind::BinOp(op, Box::new(node), Box::new(right)));
         }
 
         node
     }
 
-    fn parse_stmt(&mut self) -> Stmt {
+    fn parse_stmt(&mut self) -> SpannedStmt {
+        // Brackets left over from an error in the previous statement go with it, but an error in
+        // this statement is reported.
+        if self.recovering {
+            while matches!(self.current(), Token::RightParen | Token::RightBracket) {
+                self.advance();
+            }
+        }
+        self.recovering = false;
+        let start = self.pos;
+        let stmt = self.parse_stmt_kind();
+        if self.pos == start && !self.at_block_end() {
+            // A stray `)` or `]` would otherwise stop the block from moving on.
+            self.advance();
+        }
+        let (span, location) = self.span_from(start);
+        SpannedStmt { stmt, span, location }
+    }
+
+    fn parse_stmt_kind(&mut self) -> Stmt {
         match self.current() {
         Token::Identifier(name) if self.peek(1) == Token::Colon => {
                 let var_name = name.clone();
@@ -817,7 +987,7 @@ impl Parser {
                 self.expect(Token::Colon, vec![Token::End]);
                
                 let mut body = Vec::new();
-                while !matches!(self.current(), Token::End | Token::EOF) {
+                while !self.at_block_end() {
                     body.push(self.parse_stmt());
                     if self.current() == Token::Semicolon {
                         self.advance();
@@ -835,7 +1005,7 @@ impl Parser {
                 self.expect(Token::Colon, vec![Token::End]);
                
                 let mut body = Vec::new();
-                while !matches!(self.current(), Token::End | Token::EOF) {
+                while !self.at_block_end() {
                     body.push(self.parse_stmt());
                     if self.current() == Token::Semicolon {
                         self.advance();
@@ -884,12 +1054,12 @@ impl Parser {
                 let mut cases = Vec::new();
                 let mut default_body = None;
                
-                while !matches!(self.current(), Token::End | Token::EOF) {
+                while !self.at_block_end() {
                     if self.current() == Token::Default {
                         self.advance();
                         self.expect(Token::Colon, vec![Token::End]);
                         let mut stmts = Vec::new();
-                        while !matches!(self.current(), Token::End | Token::EOF) {
+                        while !self.at_block_end() {
                             stmts.push(self.parse_stmt());
                             if self.current() == Token::Semicolon {
                                 self.advance();
@@ -902,11 +1072,17 @@ impl Parser {
                             self.advance();
                         }
                        
-                        let case_value = self.parse_expr();
+                        let pattern = self.parse_pattern();
+                        let guard = if self.current() == Token::If {
+                            self.advance();
+                            Some(self.parse_expr())
+                        } else {
+                            None
+                        };
                         self.expect(Token::Colon, vec![Token::End, Token::Case, Token::Default]);
                        
                         let mut case_stmts = Vec::new();
-                        while !matches!(self.current(), Token::Case | Token::Default | Token::End | Token::EOF) {
+                        while !matches!(self.current(), Token::Case | Token::Default) && !self.at_block_end() {
                             case_stmts.push(self.parse_stmt());
                             if self.current() == Token::Semicolon {
                                 self.advance();
@@ -914,7 +1090,8 @@ impl Parser {
                         }
                        
                         cases.push(MatchCase {
-                            value: case_value,
+                            pattern,
+                            guard,
                             body: case_stmts,
                         });
                     }
@@ -953,7 +1130,7 @@ impl Parser {
                     self.advance();
                     self.expect(Token::Colon, vec![Token::End]);
                     
-                    while !matches!(self.current(), Token::End | Token::EOF) {
+                    while !self.at_block_end() {
                         stmts.push(self.parse_stmt());
                         if self.current() == Token::Semicolon {
                             self.advance();
@@ -969,7 +1146,7 @@ impl Parser {
                     self.advance()
                 }
 
-                while !matches!(self.current(), Token::Else | Token::End | Token::EOF) {
+                while self.current() != Token::Else && !self.at_block_end() {
                     then_body.push(self.parse_stmt());
 
                     if self.current() == Token::Semicolon {
@@ -995,7 +1172,7 @@ impl Parser {
                 }
 
                 let mut body = Vec::new();
-                while !matches!(self.current(), Token::End | Token::EOF) {
+                while !self.at_block_end() {
                     body.push(self.parse_stmt());
                     if self.current() == Token::Semicolon {
                         self.advance();
@@ -1011,31 +1188,20 @@ impl Parser {
             Token::For => {
                 self.advance();
 
-                let var_name = if let Token::Identifier(name) = self.current() {
-                    self.advance();
-                    name
-                } else {
-                    self.advance();
-                    "error".to_string()
-                };
+                let var_name = self.expect_identifier("a variable name");
                 
                 self.expect(Token::In, vec![Token::Do, Token::End]);
                 let iter_expr = self.parse_expr();
-                println!("[DEBUG] parse_for: iter_expr={:?}", iter_expr);
                 self.expect(Token::Do, vec![Token::End]);
                 
                 let mut body = Vec::new();
-                while !matches!(self.current(), Token::End | Token::EOF) {
-                    println!("[DEBUG] parse_for: about to parse stmt at {:?}", self.current());
-                    let s = self.parse_stmt();
-                    println!("[DEBUG] parse_for: parsed stmt: {:?}", s);
-                    body.push(s);
+                while !self.at_block_end() {
+                    body.push(self.parse_stmt());
                     if self.current() == Token::Semicolon {
                         self.advance();
                     }
                 }
 
-                println!("[DEBUG] parse_for: loop body length={}", body.len());
                 if self.current() == Token::End {
                     self.advance();
                 }
@@ -1068,15 +1234,16 @@ impl Parser {
                     self.advance();
                     let value = self.parse_expr();
                    
-                    match expr {
-                        Expr::Var(name) => Stmt::Assign(name, value),
-                        Expr::Index(obj, indices) => Stmt::IndexAssign(obj, indices, value),
-                        Expr::MemberAccess(obj, field) => Stmt::MemberAssign(obj, field, value),
-                        Expr::ModuleAccess(module, member) => Stmt::ModuleAssign(module, member, value),
-                        _ => Stmt::Expr(expr)
+                    match expr.kind {
+                        ExprKind::Var(name) => Stmt::Assign(name, value),
+                        ExprKind::Index(obj, indices) => Stmt::IndexAssign(obj, indices, value),
+                        ExprKind::MemberAccess(obj, field) => Stmt::MemberAssign(obj, field, value),
+                        ExprKind::ModuleAccess(module, member) => Stmt::ModuleAssign(module, member, value),
+                        kind => Stmt::Expr(Expr { kind, ..expr })
                     }
                 } else if self.current() == Token::Colon {
-                    if let Expr::Var(name) = expr {
+                    if let ExprKind::Var(name) = &expr.kind {
+                        let name = name.clone();
                         self.advance();
 
                         let ty = self.parse_type();
@@ -1085,7 +1252,7 @@ impl Parser {
                             self.parse_expr()
 
                                 } else {
-                            Expr::Number(0)
+                            ExprKind::Number(0).into()
                         };
 
                         Stmt::TypedDeclaration {
@@ -1100,16 +1267,16 @@ impl Parser {
                 } else if let Some(op) = self.parse_compound_op() {
                     self.advance();
                    
-                    match expr {
-                        Expr::Var(name) => Stmt::CompoundAssign(name, op, self.parse_expr()),
-                        Expr::ModuleAccess(module, member) => Stmt::ModuleCompoundAssign(module, member, op, self.parse_expr()),
-                        _ => Stmt::Expr(expr)
+                    match expr.kind {
+                        ExprKind::Var(name) => Stmt::CompoundAssign(name, op, self.parse_expr()),
+                        ExprKind::ModuleAccess(module, member) => Stmt::ModuleCompoundAssign(module, member, op, self.parse_expr()),
+                        kind => Stmt::Expr(Expr { kind, ..expr })
                     }
                 } else {
-                    match expr {
-                        Expr::Call(func, args) => Stmt::Call(func, args),
-                        Expr::ModuleCall(module, func, args) => Stmt::ModuleCall(module, func, args),
-                        _ => Stmt::Expr(expr)
+                    match expr.kind {
+                        ExprKind::Call(func, args) => Stmt::Call(func, args),
+                        ExprKind::ModuleCall(module, func, args) => Stmt::ModuleCall(module, func, args),
+                        kind => Stmt::Expr(Expr { kind, ..expr })
                     }
                 }
             }
@@ -1117,19 +1284,16 @@ impl Parser {
     }
     
     fn parse_struct(&mut self, is_public: bool) -> StructDef {
+        let (_, location) = self.span_from(self.pos);
         self.expect(Token::Struct, vec![Token::Colon, Token::End]);
         
         let mut fields = Vec::new();
-        let name = if let Token::Identifier(name) = self.current() {
-            self.advance();
-            name
-        } else {
-            self.advance();
-            "error".to_string()
-        };
+        let name = self.expect_identifier("a name");
+        let type_params = self.parse_type_params();
         self.expect(Token::Colon, vec![Token::End]);
         
         while !matches!(self.current(), Token::End | Token::EOF) {
+            let (_, location) = self.span_from(self.pos);
             let is_public = 
                 if self.current() == Token::Pub {
                     self.advance();
@@ -1146,14 +1310,7 @@ impl Parser {
                     false
                 };
             
-            let field_name = 
-                if let Token::Identifier(field_name) = self.current() {
-                    self.advance();
-                    field_name
-                } else {
-                    self.advance();
-                    "error".to_string()
-                };
+            let field_name = self.expect_identifier("a field name");
             
             self.expect(Token::Equals, vec![Token::Comma, Token::End]);
 
@@ -1162,27 +1319,24 @@ impl Parser {
                 ty: self.parse_type(),
                 is_public,
                 is_mutable,
+                location,
             });
         }
         
         if self.current() == Token::End {
             self.advance();
         }
+        self.type_params.clear();
         
-        StructDef { name, fields, is_public }
+        StructDef { name, fields, type_params, is_public, location }
     }
 
     fn parse_enum(&mut self, is_public: bool) -> EnumDef {
+        let (_, location) = self.span_from(self.pos);
         self.expect(Token::Enum, vec![Token::Colon, Token::End]);
                 
         let mut variants = Vec::new();
-        let name = if let Token::Identifier(name) = self.current() {
-            self.advance();
-            name
-                } else {
-             self.advance();
-            "error".to_string()
-        };
+        let name = self.expect_identifier("a name");
         
         self.expect(Token::Colon, vec![Token::End]);
 
@@ -1199,6 +1353,7 @@ impl Parser {
                      if is_struct_like {
                          let mut fields = Vec::new();
                          while self.current() != Token::RightParen && self.current() != Token::EOF {
+                            let (_, location) = self.span_from(self.pos);
                             let fname = 
                                 if let Token::Identifier(n) =
                                     self.current() { 
@@ -1214,7 +1369,8 @@ impl Parser {
                                 name: fname, 
                                 ty: self.parse_type(), 
                                 is_public: true, 
-                                is_mutable: true 
+                                is_mutable: true,
+                                location,
                             });
 
                             if self.current() == Token::Comma { 
@@ -1246,9 +1402,161 @@ impl Parser {
             self.advance();
         }
         
-        EnumDef { name, variants, is_public }
+        EnumDef { name, variants, is_public, location }
+    }
+
+
+    /// The optional `[T, U: Trait]` after a generic function or struct name. The names stay in
+    /// scope as types until the item ends.
+    fn parse_type_params(&mut self) -> Vec<(String, Option<String>)> {
+        let mut type_params = Vec::new();
+        if self.current() != Token::LeftBracket {
+            return type_params;
+        }
+        self.advance();
+        while !matches!(self.current(), Token::RightBracket | Token::EOF) {
+            let name = self.expect_identifier("a type parameter");
+            let bound = if self.current() == Token::Colon {
+                self.advance();
+                Some(self.expect_identifier("a trait name"))
+            } else {
+                None
+            };
+            self.type_params.push(name.clone());
+            type_params.push((name, bound));
+            if self.current() == Token::Comma {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        self.expect(Token::RightBracket, vec![Token::LeftParen, Token::Colon]);
+        type_params
     }
 
+    /// `name = value` arguments up to and including the closing `)`.
+    fn parse_named_args(&mut self) -> Vec<(String, Expr)> {
+        let mut named_args = Vec::new();
+        while !self.at_list_end() {
+            if let Token::Identifier(arg_name) = self.current() {
+                self.advance();
+                self.expect(Token::Equals, vec![Token::Comma, Token::RightParen]);
+                let arg_expr = self.parse_expr();
+                named_args.push((arg_name, arg_expr));
+                if self.current() == Token::Comma {
+                    self.advance();
+                }
+            } else {
+                self.advance();
+                break;
+            }
+        }
+        self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
+        named_args
+    }
+
+    /// A `case` pattern: one or more alternatives separated by `|`.
+    fn parse_pattern(&mut self) -> Pattern {
+        let start = self.pos;
+        let first = self.parse_single_pattern();
+        if self.current() != Token::Pipe {
+            return first;
+        }
+        let mut alternatives = vec![first];
+        while self.current() == Token::Pipe {
+            self.advance();
+            alternatives.push(self.parse_single_pattern());
+        }
+        self.pattern_at(start, PatternKind::Or(alternatives))
+    }
+
+    fn pattern_at(&self, start: usize, kind: PatternKind) -> Pattern {
+        let (_, location) = self.span_from(start);
+        Pattern { kind, location }
+    }
+
+    /// The parenthesized sub-patterns after a variant or inside a tuple pattern.
+    /// Also returns whether the list had a trailing comma.
+    fn parse_pattern_list(&mut self) -> (Vec<Pattern>, bool) {
+        let mut patterns = Vec::new();
+        let mut trailing_comma = false;
+        self.advance();
+        while !matches!(self.current(), Token::RightParen | Token::Colon | Token::EOF) {
+            patterns.push(self.parse_pattern());
+            trailing_comma = self.current() == Token::Comma;
+            if !trailing_comma {
+                break;
+            }
+            self.advance();
+        }
+        self.expect(Token::RightParen, vec![Token::Colon]);
+        (patterns, trailing_comma)
+    }
+
+    fn parse_variant_pattern(&mut self, enum_name: Option<String>, variant: String) -> PatternKind {
+        let fields = if self.current() == Token::LeftParen {
+            self.parse_pattern_list().0
+        } else {
+            Vec::new()
+        };
+        PatternKind::Variant { enum_name, variant, fields }
+    }
+
+    fn parse_single_pattern(&mut self) -> Pattern {
+        let start = self.pos;
+        let kind = match self.current() {
+            Token::Identifier(name) if name == "_" => {
+                self.advance();
+                PatternKind::Wildcard
+            }
+            Token::Identifier(name) if self.peek(1) == Token::Dot => {
+                self.advance();
+                self.advance();
+                let variant = self.expect_identifier("a variant name");
+                self.parse_variant_pattern(Some(name), variant)
+            }
+            Token::Identifier(name) => {
+                self.advance();
+                PatternKind::Binding(name)
+            }
+            Token::Some | Token::None | Token::Ok | Token::Err => {
+                let variant = match self.current() {
+                    Token::Some => "Some",
+                    Token::None => "None",
+                    Token::Ok => "Ok",
+                    _ => "Err",
+                };
+                self.advance();
+                self.parse_variant_pattern(None, variant.to_string())
+            }
+            Token::LeftParen => {
+                let (mut elements, trailing_comma) = self.parse_pattern_list();
+                if elements.len() == 1 && !trailing_comma {
+                    return elements.remove(0);
+                }
+                PatternKind::Tuple(elements)
+            }
+            Token::Number(_) | Token::Float(_) | Token::HexNumber(_) | Token::BinaryNumber(_)
+            | Token::OctalNumber(_) | Token::String(_) | Token::True | Token::False | Token::Minus => {
+                let literal = self.parse_unary();
+                match self.current() {
+                    Token::DotDot | Token::DotDotEquals => {
+                        let inclusive = self.current() == Token::DotDotEquals;
+                        self.advance();
+                        let end = self.parse_unary();
+                        PatternKind::Range { start: literal, end, inclusive }
+                    }
+                    _ => PatternKind::Literal(literal),
+                }
+            }
+            _ => {
+                self.unexpected("a pattern");
+                self.advance();
+                PatternKind::Wildcard
+            }
+        };
+        self.pattern_at(start, kind)
+    }
 
     fn parse_function(&mut self, is_module: bool) -> Function {
         self.parse_function_with_visibility(is_module, false)
@@ -1256,15 +1564,11 @@ impl Parser {
    
     
     fn parse_function_with_visibility(&mut self, is_module: bool, is_public: bool) -> Function {
+        let (_, location) = self.span_from(self.pos);
         self.expect(Token::Func, vec![Token::Colon, Token::End]);
         
-        let name = if let Token::Identifier(name) = self.current() {
-            self.advance();
-            name
-        } else {
-            self.advance();
-            "error".to_string()
-        };
+        let name = self.expect_identifier("a name");
+        let type_params = self.parse_type_params();
         
         self.expect(Token::LeftParen, vec![Token::RightParen, Token::Arrow, Token::Colon]);
         
@@ -1296,7 +1600,7 @@ impl Parser {
             }
         }
         
-        while self.current() != Token::RightParen {
+        while !matches!(self.current(), Token::RightParen | Token::EOF) {
             let modifier = match self.current() {
                 Token::Mutable => {
                     self.advance();
@@ -1314,20 +1618,12 @@ impl Parser {
                 _ => ParamModifier::Immutable
             };
             
-            let pname = if let Token::Identifier(pname) = self.current() {
-                self.advance();
-                pname
-            } else {
-                self.advance();
-                "error".to_string()
-            };
-            
-            self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
-            let ptype = self.parse_type();
+            let pname = self.expect_identifier("a parameter name");
+            let ptype = self.parse_param_type();
             params.push((pname, ptype, modifier));
             
-            if self.current() == Token::Comma {
-                self.advance();
+            if !self.param_separator() {
+                break;
             }
         }
         self.expect(Token::RightParen, vec![Token::Colon]);
@@ -1355,7 +1651,8 @@ impl Parser {
             self.advance();
         }
 
-        while !matches!(self.current(), Token::End | Token::EOF) {
+        // An item cannot start a statement, so one here means the `end` is missing.
+        while !self.at_block_end() {
             body.push(self.parse_stmt());
             if self.current() == Token::Semicolon {
                 self.advance();
@@ -1364,14 +1661,182 @@ impl Parser {
        
         if self.current() == Token::End {
             self.advance();
+        } else {
+            self.missing_end(&format!("function '{}'", name), location.clone());
         }
+        self.type_params.clear();
        
         Function {
             name,
             params,
             return_type,
             body,
+            type_params,
+            is_public,
+            is_test: false,
+            location,
+        }
+    }
+
+    /// The `: Type` after a parameter name. `name Type` is reported but keeps its type.
+    fn parse_param_type(&mut self) -> Type {
+        if self.current() == Token::Colon || !self.is_type_token(self.current()) {
+            self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
+        } else {
+            self.unexpected("`:`");
+        }
+        if matches!(self.current(), Token::Comma | Token::RightParen) {
+            return Type::Any;
+        }
+        self.parse_type()
+    }
+
+    /// Consumes the `,` after a parameter. Returns false when the list ends here; anything
+    /// other than `)` is reported and the rest of the list skipped.
+    fn param_separator(&mut self) -> bool {
+        match self.current() {
+            Token::Comma => {
+                self.advance();
+                true
+            }
+            Token::RightParen => false,
+            _ => {
+                self.unexpected("`,` or `)`");
+                while !matches!(self.current(), Token::RightParen | Token::EOF) && !Self::is_sync_token(&self.current()) {
+                    self.advance();
+                }
+                false
+            }
+        }
+    }
+
+    /// `func name(self, params): Type`, up to where an impl method's body starts. A trait
+    /// declares its methods with just this.
+    fn parse_method_signature(&mut self) -> TraitMethod {
+        self.recovering = false;
+        let (_, location) = self.span_from(self.pos);
+        self.advance();
+        
+        let method_name = self.expect_identifier("a method name");
+        
+        self.expect(Token::LeftParen, vec![Token::RightParen, Token::Colon]);
+        
+        let mut params = Vec::new();
+        let mut self_modifier = None;
+        
+        if self.current() == Token::Selfish {
+            self.advance();
+            self_modifier = Some(SelfModifier::Immutable);
+            if self.current() == Token::Comma {
+                self.advance();
+            }
+        } else if (self.current() == Token::Mutable || self.current() == Token::Mut) && self.peek(1) == Token::Selfish {
+            self.advance();
+            self.advance();
+            self_modifier = Some(SelfModifier::Mutable);
+            if self.current() == Token::Comma {
+                self.advance();
+            }
+        } else if (self.current() == Token::Reference || self.current() == Token::Ampersand) && self.peek(1) == Token::Selfish {
+            self.advance();
+            self.advance();
+            self_modifier = Some(SelfModifier::Reference);
+            if self.current() == Token::Comma {
+                self.advance();
+            }
+        } else if (self.current() == Token::Reference || self.current() == Token::Ampersand) && (self.peek(1) == Token::Mutable || self.peek(1) == Token::Mut) && self.peek(2) == Token::Selfish {
+            self.advance();
+            self.advance();
+            self.advance();
+            
+            self_modifier = Some(SelfModifier::Reference);
+
+            if self.current() == Token::Comma {
+                self.advance();
+            }
+        } else if let Token::Identifier(s) = self.current() {
+            if s == "brw" && self.peek(1) == Token::Selfish {
+                self.advance();
+                self.advance();
+
+                self_modifier = Some(SelfModifier::Borrow);
+                if self.current() == Token::Comma {
+                    self.advance();
+                }
+            }
+        }
+                
+        while self.current() != Token::RightParen && self.current() != Token::EOF {
+            let modifier = match self.current() {
+                Token::Mutable => {
+                    self.advance();
+                    ParamModifier::Mutable
+                }
+                Token::Reference => {
+                    self.advance();
+                    ParamModifier::Reference
+                }
+                _ => ParamModifier::Immutable
+            };
+            
+            let pname = self.expect_identifier("a parameter name");
+            let ptype = self.parse_param_type();
+            params.push((pname, ptype, modifier));
+            
+            if !self.param_separator() {
+                break;
+            }
+        }
+        
+        self.expect(Token::RightParen, vec![Token::Colon]);
+        
+        let return_type = if self.current() == Token::Arrow || self.current() == Token::Colon {
+            self.advance();
+            if self.is_type_token(self.current()) {
+                self.parse_type()
+            } else {
+                Type::Void
+            }
+        } else {
+            Type::Void
+        };
+
+        TraitMethod {
+            name: method_name,
+            params,
+            return_type,
+            self_modifier,
+            location,
+        }
+    }
+
+    fn parse_trait(&mut self, is_public: bool) -> TraitDef {
+        let (_, location) = self.span_from(self.pos);
+        self.expect(Token::Trait, vec![Token::Colon, Token::End]);
+        let name = self.expect_identifier("a trait name");
+        self.expect(Token::Colon, vec![Token::Func, Token::End]);
+
+        let mut methods = Vec::new();
+        while !matches!(self.current(), Token::End | Token::EOF | Token::Struct | Token::Impl | Token::Enum | Token::Trait) {
+            if self.current() == Token::Func {
+                methods.push(self.parse_method_signature());
+            } else {
+                self.unexpected("a method signature or `end`");
+                self.advance();
+            }
+        }
+
+        if self.current() == Token::End {
+            self.advance();
+        } else {
+            self.missing_end(&format!("trait '{}'", name), location.clone());
+        }
+
+        TraitDef {
+            name,
+            methods,
             is_public,
+            location,
         }
     }
 
@@ -1384,7 +1849,7 @@ impl Parser {
             if self.current() == Token::From {
                 self.advance();
                 
-                if let Token::Identifier(from) = self.current() {
+                if let Token::Identifier(from) | Token::String(from) = self.current() {
                     self.advance();
                     return ImportDecl::FileImport { name, from };
                 }
@@ -1395,7 +1860,7 @@ impl Parser {
         
         ImportDecl::LibraryImport { name: "unknown".to_string() }
     }
- pub fn parse(mut self) -> (Program, Vec<StructDef>, Vec<EnumDef>, Vec<ExternDecl>, Vec<ModuleImport>, Vec<ModuleUse>, Vec<ClassDef>, Vec<ImplBlock>, Vec<TraitDef>, UndefinedFunctions, Vec<ImportDecl>) {
+ pub fn parse(mut self) -> (Program, Vec<StructDef>, Vec<EnumDef>, Vec<ExternDecl>, Vec<ModuleImport>, Vec<ModuleUse>, Vec<ClassDef>, Vec<ImplBlock>, Vec<TraitDef>, UndefinedFunctions, Vec<ImportDecl>, Vec<Diagnostic>) {
         let mut functions = Vec::new();
         let mut structs = Vec::new();
         let mut enums = Vec::new();
@@ -1404,7 +1869,7 @@ impl Parser {
         let mut uses = Vec::new();
         let classes = Vec::new();
         let mut impls = Vec::new();
-        let traits = Vec::new();
+        let mut traits = Vec::new();
         let mut import_decls = Vec::new();
        
          
@@ -1420,14 +1885,17 @@ impl Parser {
         let import_context = Self::build_import_context(&import_decls);
         
          
-        self.pos = import_decls.len();
-        
-         
         while self.current() != Token::EOF {
+            self.recovering = false;
             match self.current() {
                 Token::Import => {
-                     
-                    self.advance();
+                    self.error_at(
+                        self.pos,
+                        &codes::E0102,
+                        "Imports must come before any other item",
+                        Some("Move this import to the top of the file.".to_string()),
+                    );
+                    self.parse_import();
                 }
             
                 Token::Enum => {
@@ -1439,15 +1907,24 @@ impl Parser {
                     functions.push(func);
                 }
 
+                Token::Identifier(_) if self.at_test_func() => {
+                    self.advance();
+                    let mut func = self.parse_function_with_visibility(false, false);
+                    func.is_test = true;
+                    functions.push(func);
+                }
+
                 Token::Impl => {
+                    let (_, location) = self.span_from(self.pos);
                     self.advance();
                     
-                    let struct_name = if let Token::Identifier(name) = self.current() {
+                    // `impl Trait for Struct:` implements a trait, `impl Struct(params):` adds methods and a constructor.
+                    let first_name = self.expect_identifier("a struct or trait name");
+                    let (struct_name, trait_name) = if self.current() == Token::For {
                         self.advance();
-                        name
+                        (self.expect_identifier("a struct name"), Some(first_name))
                     } else {
-                        self.advance();
-                        "error".to_string()
+                        (first_name, None)
                     };
                     
                     let mut constructor_params = Vec::new();
@@ -1455,13 +1932,7 @@ impl Parser {
                         self.advance();
                         
                         while self.current() != Token::RightParen && self.current() != Token::EOF {
-                            let pname = if let Token::Identifier(pname) = self.current() {
-                                self.advance();
-                                pname
-                            } else {
-                                self.advance();
-                                "error".to_string()
-                            };
+                            let pname = self.expect_identifier("a parameter name");
                             
                             self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
                             let ptype = self.parse_type();
@@ -1472,22 +1943,9 @@ impl Parser {
                             }
                         }
                         
-                        self.expect(Token::RightParen, vec![Token::For, Token::Colon]);
+                        self.expect(Token::RightParen, vec![Token::Colon]);
                     }
                     
-                    let trait_name = if self.current() == Token::For {
-                        self.advance();
-                        if let Token::Identifier(trait_name) = self.current() {
-                            self.advance();
-                            Some(trait_name)
-                        } else {
-                            self.advance();
-                            None
-                        }
-                    } else {
-                        None
-                    };
-                    
                     self.expect(Token::Colon, vec![Token::End]);
                     
                     let mut methods = Vec::new();
@@ -1502,7 +1960,7 @@ impl Parser {
                                 self.advance();
                                 
 
-                                while !matches!(self.current(), Token::RightParen | Token::EOF) {
+                                while !self.at_list_end() {
                                     if let Token::Identifier(field_name) = self.current() {
                                         self.advance();
                                         self.expect(Token::Equals, vec![Token::Comma, Token::RightParen]);
@@ -1532,116 +1990,10 @@ impl Parser {
                         };
 
                         if self.current() == Token::Func {
-                            self.advance();
-                            
-                            let method_name = if let Token::Identifier(name) = self.current() {
-                                self.advance();
-                                name
-                            } else {
-                                self.advance();
-                                "error".to_string()
-                            };
-                            
-                            self.expect(Token::LeftParen, vec![Token::RightParen, Token::Colon]);
-                            
-                            let mut params = Vec::new();
-                            let mut self_modifier = None;
-                            
-                            if self.current() == Token::Selfish {
-                                self.advance();
-                                self_modifier = Some(SelfModifier::Immutable);
-                                if self.current() == Token::Comma {
-                                    self.advance();
-                                }
-                            } else if (self.current() == Token::Mutable || self.current() == Token::Mut) && self.peek(1) == Token::Selfish {
-                                self.advance();
-                                self.advance();
-                                self_modifier = Some(SelfModifier::Mutable);
-                                if self.current() == Token::Comma {
-                                    self.advance();
-                                }
-                            } else if (self.current() == Token::Reference || self.current() == Token::Ampersand) && self.peek(1) == Token::Selfish {
-                                self.advance();
-                                self.advance();
-                                self_modifier = Some(SelfModifier::Reference);
-                                if self.current() == Token::Comma {
-                                    self.advance();
-                                }
-                            } else if (self.current() == Token::Reference || self.current() == Token::Ampersand) && (self.peek(1) == Token::Mutable || self.peek(1) == Token::Mut) && self.peek(2) == Token::Selfish {
-                                self.advance();
-                                self.advance();
-                                self.advance();
-                                
-                                self_modifier = Some(SelfModifier::Reference);
-
-                                if self.current() == Token::Comma {
-                                    self.advance();
-                                }
-                            } else if let Token::Identifier(s) = self.current() {
-                                if s == "brw" && self.peek(1) == Token::Selfish {
-                                    self.advance();
-                                    self.advance();
-
-                                    self_modifier = Some(SelfModifier::Borrow);
-                                    if self.current() == Token::Comma {
-                                        self.advance();
-                                    }
-                                }
-                            }
-                                    
-                            while self.current() != Token::RightParen && self.current() != Token::EOF {
-                                let modifier = match self.current() {
-                                    Token::Mutable => {
-                                        self.advance();
-                                        ParamModifier::Mutable
-                                    }
-                                    Token::Reference => {
-                                        self.advance();
-                                        ParamModifier::Reference
-                                    }
-                                    _ => ParamModifier::Immutable
-                                };
-                                
-                                let pname = if let Token::Identifier(pname) = self.current() {
-                                    self.advance();
-                                    pname
-                                } else {
-                                    self.advance();
-                                    "error".to_string()
-                                };
-                                
-                                self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
-                                let ptype = self.parse_type();
-                                params.push((pname, ptype, modifier));
-                                
-                                if self.current() == Token::Comma {
-                                    self.advance();
-                                }
-                            }
-                            
-                            self.expect(Token::RightParen, vec![Token::Colon]);
-                            
+                            let signature = self.parse_method_signature();
                             
                             let mut body = Vec::new();
-                            let return_type = if self.current() == Token::Arrow || self.current() == Token::Colon {
-                                self.advance();
-                                if self.is_type_token(self.current()) {
-                                    self.parse_type()
-                                } else {
-                                    Type::Void
-                                }
-                            } else {
-                                Type::Void
-                            };
-                            
-                            while !matches!(self.current(), Token::End | Token::Func | Token::EOF) {
-                            
-                                if matches!(self.current(), Token::End) {
-                                    break;
-                                } else if self.current() == Token::Func {
-                                    break;
-                                }
-                                
+                            while !self.at_block_end() {
                                 body.push(self.parse_stmt());
                                 if self.current() == Token::Semicolon {
                                     self.advance();
@@ -1653,12 +2005,13 @@ impl Parser {
                             }
                             
                             methods.push(ImplMethod {
-                                name: method_name,
-                                params,
-                                return_type,
+                                name: signature.name,
+                                params: signature.params,
+                                return_type: signature.return_type,
                                 body,
-                                self_modifier,
+                                self_modifier: signature.self_modifier,
                                 is_public,
+                                location: signature.location,
                             });
                         } else if self.current() == Token::End {
                             break;
@@ -1677,6 +2030,7 @@ impl Parser {
                         constructor_params,
                         constructor_body,
                         methods,
+                        location,
                     });
                 }
 
@@ -1729,13 +2083,7 @@ impl Parser {
                             if self.current() == Token::Func {
                                 self.advance();
                                 
-                                let name = if let Token::Identifier(name) = self.current() {
-                                    self.advance();
-                                    name
-                                } else {
-                                    self.advance();
-                                    "error".to_string()
-                                };
+                                let name = self.expect_identifier("a name");
                                 
                                 self.expect(Token::LeftParen, vec![Token::RightParen, Token::Colon]);
                                 
@@ -1750,13 +2098,7 @@ impl Parser {
                                         continue;
                                     }
                                     
-                                    let pname = if let Token::Identifier(pname) = self.current() {
-                                        self.advance();
-                                        pname
-                                    } else {
-                                        self.advance();
-                                        "error".to_string()
-                                    };
+                                    let pname = self.expect_identifier("a parameter name");
                                     
                                     self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
                                     let ptype = self.parse_type();
@@ -1847,25 +2189,13 @@ impl Parser {
                                     if self.current() == Token::Func {
                                         self.advance();
                                         
-                                        let name = if let Token::Identifier(name) = self.current() {
-                                            self.advance();
-                                            name
-                                        } else {
-                                            self.advance();
-                                            "error".to_string()
-                                        };
+                                        let name = self.expect_identifier("a name");
                                         
                                         self.expect(Token::LeftParen, vec![Token::RightParen, Token::Colon]);
                                         
                                         let mut params = Vec::new();
                                         while self.current() != Token::RightParen && self.current() != Token::EOF {
-                                            let pname = if let Token::Identifier(pname) = self.current() {
-                                                self.advance();
-                                                pname
-                                            } else {
-                                                self.advance();
-                                                "error".to_string()
-                                            };
+                                            let pname = self.expect_identifier("a parameter name");
                                             
                                             self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
                                             let ptype = self.parse_type();
@@ -1918,25 +2248,13 @@ impl Parser {
                             } else {
                                 self.expect(Token::Func, vec![Token::Colon, Token::End]);
                                 
-                                let name = if let Token::Identifier(name) = self.current() {
-                                    self.advance();
-                                    name
-                                } else {
-                                    self.advance();
-                                    "error".to_string()
-                                };
+                                let name = self.expect_identifier("a name");
                                 
                                 self.expect(Token::LeftParen, vec![Token::RightParen, Token::Colon]);
                                 
                                 let mut params = Vec::new();
                                 while self.current() != Token::RightParen && self.current() != Token::EOF {
-                                    let pname = if let Token::Identifier(pname) = self.current() {
-                                        self.advance();
-                                        pname
-                                    } else {
-                                        self.advance();
-                                        "error".to_string()
-                                    };
+                                    let pname = self.expect_identifier("a parameter name");
                                     
                                     self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
                                     let ptype = self.parse_type();
@@ -1967,7 +2285,7 @@ impl Parser {
                                     self.advance();
                                 }
                                 
-                                while !matches!(self.current(), Token::End | Token::EOF) {
+                                while !self.at_block_end() {
                                     body.push(self.parse_stmt());
                                     if self.current() == Token::Semicolon {
                                         self.advance();
@@ -2012,6 +2330,9 @@ impl Parser {
                         Token::Enum => {
                             enums.push(self.parse_enum(true));
                         }
+                        Token::Trait => {
+                            traits.push(self.parse_trait(true));
+                        }
                         _ => {
                             self.advance();
                         }
@@ -2031,30 +2352,31 @@ impl Parser {
                 
                 Token::Type => {
                     self.advance();
-                    let _name = if let Token::Identifier(name) = self.current() { self.advance(); name } else { self.advance(); "error".to_string() };
+                    let _name = self.expect_identifier("a name");
                     self.expect(Token::Equals, vec![Token::End]);
                     let _ty = self.parse_type();
                 }
 
                 Token::Trait => {
-                    self.advance();
-                    let _name = if let Token::Identifier(name) = self.current() { self.advance(); name } else { self.advance(); "error".to_string() };
-                    self.expect(Token::Colon, vec![Token::End]);
-                    while !matches!(self.current(), Token::End | Token::EOF) {
-                        self.advance(); 
-                    }
-                    if self.current() == Token::End { self.advance(); }
+                    traits.push(self.parse_trait(false));
                 }
 
-                _ => {
+                found => {
+                    self.error_at(
+                        self.pos,
+                        &codes::E0101,
+                        &format!("Expected an item, found {}", found),
+                        Some("Only declarations (func, struct, enum, impl, trait, type, extern, import) may appear at the top level.".to_string()),
+                    );
                     self.advance();
+                    self.skip_to_item();
                 }
             }
         }
         
         let undefined = self.find_undefined_functions(&functions, &externs, &import_context);
             
-        (Program { functions }, structs, enums, externs, imports, uses, classes, impls, traits, undefined, import_decls)
+        (Program { functions }, structs, enums, externs, imports, uses, classes, impls, traits, undefined, import_decls, self.diags)
     }
     fn find_undefined_functions(
         &self, 
@@ -2097,13 +2419,13 @@ impl Parser {
     
     fn stmt_calls(
         &self,
-        stmts: &[Stmt],
+        stmts: &[SpannedStmt],
         defined: &HashSet<String>,
         undefined: &mut Vec<UndefinedFunction>,
         import_context: &ImportContext
     ) {
-        for stmt in stmts {
-            match stmt {
+        for spanned in stmts {
+            match &spanned.stmt {
                 Stmt::Call(name, args) => {
                      
                     if import_context.is_library_function(name) {
@@ -2115,7 +2437,7 @@ impl Parser {
                         if !undefined.iter().any(|u| u.name == *name) {
                             undefined.push(UndefinedFunction {
                                 name: name.clone(),
-                                call_location: SourceSpan::from(0..0), 
+                                call_location: spanned.span, 
                                 args_count: args.len(),
                             });
                         }
@@ -2150,7 +2472,9 @@ impl Parser {
                 Stmt::Match(expr, cases, default) => {
                     self.expr_calls(expr, defined, undefined, import_context);
                     for case in cases {
-                        self.expr_calls(&case.value, defined, undefined, import_context);
+                        if let Some(guard) = &case.guard {
+                            self.expr_calls(guard, defined, undefined, import_context);
+                        }
                         self.stmt_calls(&case.body, defined, undefined, import_context);
                     }
                     if let Some(default_body) = default {
@@ -2175,8 +2499,8 @@ impl Parser {
         undefined: &mut Vec<UndefinedFunction>,
         import_context: &ImportContext
     ) {
-        match expr {
-            Expr::Call(name, args) => {
+        match &expr.kind {
+            ExprKind::Call(name, args) => {
                 if import_context.is_library_function(name) {
                      
                     return;
@@ -2186,7 +2510,7 @@ impl Parser {
                     if !undefined.iter().any(|u| u.name == *name) {
                         undefined.push(UndefinedFunction {
                             name: name.clone(),
-                            call_location: SourceSpan::from(0..0),
+                            call_location: expr.span,
                             args_count: args.len(),
                         });
                     }
@@ -2195,7 +2519,7 @@ impl Parser {
                     self.expr_calls(arg, defined, undefined, import_context);
                 }
             }
-            Expr::ModuleCall(module, _func, args) => {
+            ExprKind::ModuleCall(module, _func, args) => {
                 if import_context.is_imported_symbol(module) {
                      
                     return;
@@ -2205,14 +2529,14 @@ impl Parser {
                     self.expr_calls(arg, defined, undefined, import_context);
                 }
             }
-            Expr::BinOp(_, left, right) => {
+            ExprKind::BinOp(_, left, right) => {
                 self.expr_calls(left, defined, undefined, import_context);
                 self.expr_calls(right, defined, undefined, import_context);
             }
-            Expr::UnOp(_, expr) => {
+            ExprKind::UnOp(_, expr) => {
                 self.expr_calls(expr, defined, undefined, import_context);
             }
-            Expr::Tuple(exprs) | Expr::Array(exprs) => {
+            ExprKind::Tuple(exprs) | ExprKind::Array(exprs) => {
                 for e in exprs {
                     self.expr_calls(e, defined, undefined, import_context);
                 }
@@ -1,5 +1,6 @@
 use crate::import::*;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 
 pub struct TypeChecker {
     handler: DiagnosticHandler,
@@ -8,10 +9,45 @@ pub struct TypeChecker {
     imported_functions: HashMap<String, FunctionSignature>,
     structs: HashMap<String, StructInfo>,
     enums: HashMap<String, EnumInfo>,
-    variables: Vec<HashMap<String, Type>>,
+    traits: HashMap<String, TraitDef>,
+    /// (struct, trait) for every `impl Trait for Struct`, so the struct can be used as a `dyn Trait`.
+    trait_impls: HashSet<(String, String)>,
+    /// Type parameters of each generic function, with their optional trait bounds.
+    generic_functions: HashMap<String, Vec<(String, Option<String>)>>,
+    /// Type parameters of the function being checked, which its types may refer to.
+    current_type_params: Vec<(String, Option<String>)>,
+    variables: Vec<HashMap<String, Variable>>,
     current_function_return_type: Option<Type>,
     borrow_tracker: BorrowTracker,
-    current_line: usize,
+    current_location: SourceLocation,
+    file_name: String,
+    /// Names imported from other local modules, checked for use by the lint pass.
+    local_imports: Vec<String>,
+    /// Every expression checked so far with the type it was given, for hovers in the language server.
+    expr_types: RefCell<Vec<(SourceLocation, Type)>>,
+    /// Every variable and parameter brought into scope, with where that happened.
+    bindings: Vec<(String, Type, SourceLocation)>,
+    /// Errors found while inferring a type, which cannot report directly; emitted after the statement.
+    inference_errors: RefCell<Vec<Diagnostic>>,
+    /// Lambdas met while inferring a type, whose bodies are checked after the statement.
+    pending_lambdas: RefCell<Vec<Expr>>,
+}
+
+/// Calls the code generator implements itself, with no signature to check against.
+const INTRINSICS: &[&str] = &[
+    "chars", "str", "string", "have", "contain", "contains", "has", "is_not_empty", "collect", "contain_all", "index",
+    "index_of", "some", "none", "ok", "err", "unwrap", "unwrap_or", "is_some", "is_none", "array_get", "wait", "tuple",
+    "is_empty", "filter", "Array", "vector", "Vector", "buffer", "Buffer", "hashMap", "range", "random",
+];
+
+#[derive(Debug, Clone)]
+struct Variable {
+    ty: Type,
+    /// For a variable holding a capturing closure, the scope whose block holds its environment.
+    closure_scope: Option<usize>,
+    is_mutable: bool,
+    /// Where `mut ` goes to make a local declaration mutable.
+    declaration: Option<SourceLocation>,
 }
 
 #[derive(Debug, Clone)]
@@ -24,13 +60,18 @@ struct FunctionSignature {
 
 #[derive(Debug, Clone)]
 struct StructInfo {
-    fields: HashMap<String, (Type, bool, bool)>,
+    fields: HashMap<String, (Type, bool, bool, SourceLocation)>,
+    /// Field names in declaration order, which positional constructor arguments follow.
+    field_order: Vec<String>,
+    type_params: Vec<(String, Option<String>)>,
     location: SourceLocation,
 }
 
 #[derive(Debug, Clone)]
 struct EnumInfo {
     variants: HashMap<String, EnumVariantInfo>,
+    /// Variant names in declaration order, the order their tags follow.
+    variant_order: Vec<String>,
     location: SourceLocation,
 }
 
@@ -38,7 +79,18 @@ struct EnumInfo {
 enum EnumVariantInfo {
     Simple,
     Tuple(Vec<Type>),
-    Struct(HashMap<String, Type>),
+    Struct(Vec<(String, Type)>),
+}
+
+impl EnumVariantInfo {
+    /// Payload types in the order a pattern lists them.
+    fn field_types(&self) -> Vec<Type> {
+        match self {
+            EnumVariantInfo::Simple => vec![],
+            EnumVariantInfo::Tuple(types) => types.clone(),
+            EnumVariantInfo::Struct(fields) => fields.iter().map(|(_, ty)| ty.clone()).collect(),
+        }
+    }
 }
 
 struct BorrowTracker {
@@ -90,16 +142,63 @@ impl TypeChecker {
             imported_functions: HashMap::new(),
             structs: HashMap::new(),
             enums: HashMap::new(),
+            traits: HashMap::new(),
+            trait_impls: HashSet::new(),
+            generic_functions: HashMap::new(),
+            current_type_params: Vec::new(),
             variables: vec![HashMap::new()],
             current_function_return_type: None,
             borrow_tracker: BorrowTracker::new(),
-            current_line: 1,
+            current_location: default_location(),
+            file_name: "input".to_string(),
+            local_imports: Vec::new(),
+            expr_types: RefCell::new(Vec::new()),
+            bindings: Vec::new(),
+            inference_errors: RefCell::new(Vec::new()),
+            pending_lambdas: RefCell::new(Vec::new()),
         };
         
         checker.register_builtin_functions();
         checker
     }
 
+    pub fn with_file(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = file_name.into();
+        self
+    }
+
+    pub fn with_options(mut self, options: DiagnosticOptions) -> Self {
+        self.handler.options = options;
+        self
+    }
+
+    /// Makes items imported from other local modules visible without checking their bodies.
+    /// `impls` are the trait impls of imported structs, wherever they are written.
+    pub fn register_module_imports(&mut self, functions: &[Function], structs: &[StructDef], enums: &[EnumDef], traits: &[TraitDef], impls: &[ImplBlock]) {
+        self.register_structs(structs);
+        self.register_enums(enums);
+        self.register_traits(traits);
+        self.register_trait_impls(impls);
+        self.local_imports.extend(structs.iter().map(|s| s.name.clone()));
+        self.local_imports.extend(enums.iter().map(|e| e.name.clone()));
+        self.local_imports.extend(traits.iter().map(|t| t.name.clone()));
+        self.local_imports.extend(functions.iter().map(|f| f.name.clone()));
+        for func in functions {
+            if !func.type_params.is_empty() {
+                self.generic_functions.insert(func.name.clone(), func.type_params.clone());
+            }
+            self.functions.insert(
+                func.name.clone(),
+                FunctionSignature {
+                    params: func.params.clone(),
+                    return_type: func.return_type.clone(),
+                    location: func.location.clone(),
+                    is_builtin: false,
+                },
+            );
+        }
+    }
+
     fn register_builtin_functions(&mut self) {
         let builtin_loc = self.make_location(0, 0, 0);
         
@@ -141,31 +240,101 @@ impl TypeChecker {
     }
 
     fn add_variable(&mut self, name: String, ty: Type) {
+        self.bindings.push((name.clone(), ty.clone(), self.here(name.chars().count())));
         if let Some(scope) = self.variables.last_mut() {
-            scope.insert(name, ty);
+            scope.insert(name, Variable { ty, closure_scope: None, is_mutable: false, declaration: None });
         }
     }
 
+    /// The variable most recently added to the innermost scope under `name`.
+    fn last_variable_mut(&mut self, name: &str) -> Option<&mut Variable> {
+        self.variables.last_mut().and_then(|scope| scope.get_mut(name))
+    }
+
+    fn immutable_assignment_error(&mut self, name: &str) {
+        let Some(declaration) = self.get_variable(name).filter(|variable| !variable.is_mutable).map(|variable| variable.declaration.clone()) else {
+            return;
+        };
+        let fix = declaration.clone().map(|location| Fix::insert(format!("Declare '{}' as `mut`", name), location, "mut "));
+        self.handler.emit(
+            Diagnostic::error(
+                &codes::E0384,
+                &format!("Cannot assign to immutable variable '{}'", name),
+                ErrorContext {
+                    primary_location: self.here(name.len()),
+                    secondary_locations: declaration.into_iter().map(|location| (location, "declared here".to_string())).collect(),
+                    help_message: Some(format!("Variable '{}' is not declared `mut` and cannot be reassigned.", name)),
+                    suggestions: vec![],
+                },
+            )
+            .with_fixes(fix),
+        );
+    }
+
     fn get_variable_type(&self, name: &str) -> Option<Type> {
-        for scope in self.variables.iter().rev() {
-            if let Some(ty) = scope.get(name) {
-                return Some(ty.clone());
-            }
+        self.get_variable(name).map(|variable| variable.ty.clone())
+    }
+
+    fn get_variable(&self, name: &str) -> Option<&Variable> {
+        self.variables.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// The depth of the scope `name` is declared in.
+    fn variable_scope(&self, name: &str) -> Option<usize> {
+        self.variables.iter().rposition(|scope| scope.contains_key(name))
+    }
+
+    /// A fix renaming `name` at the current location to the closest known name, if one is close enough.
+    /// Every short name is one edit away from many others, so those renames are only suggested.
+    fn rename_fix<'a>(&self, name: &str, location: SourceLocation, known: impl Iterator<Item = &'a String>) -> Option<Fix> {
+        let max_distance = (name.chars().count() / 3).max(1);
+        let (_, candidate) = known
+            .map(|candidate| (levenshtein(name, candidate), candidate))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min()?;
+        let fix = Fix::replace(format!("A similar name exists: '{}'", candidate), location, candidate.clone());
+        if name.chars().count().min(candidate.chars().count()) <= 2 {
+            return Some(fix.maybe_incorrect());
         }
-        None
+        Some(fix)
+    }
+
+    /// Assigning to a misspelled name may just as well be meant to declare it, so the rename
+    /// is only suggested.
+    fn assignment_rename_fix(&self, name: &str) -> Option<Fix> {
+        self.rename_fix(name, self.here(name.chars().count()), self.variables.iter().flat_map(|scope| scope.keys())).map(Fix::maybe_incorrect)
     }
 
     fn make_location(&self, line: usize, col: usize, len: usize) -> SourceLocation {
         SourceLocation {
-            file: "input".to_string(),
+            file: self.file_name.clone(),
             line,
             column: col,
             length: len,
         }
     }
     
-    fn set_line(&mut self, line: usize) {
-        self.current_line = line;
+    fn set_location(&mut self, location: &SourceLocation) {
+        self.current_location = location.clone();
+    }
+
+    /// Location of the statement or function being checked, `len` characters long.
+    fn here(&self, len: usize) -> SourceLocation {
+        if self.current_location.line == 0 {
+            return self.make_location(1, 1, len);
+        }
+        SourceLocation {
+            length: len.max(1),
+            ..self.current_location.clone()
+        }
+    }
+
+    fn expr_location(&self, expr: &Expr) -> SourceLocation {
+        if expr.location.line == 0 {
+            self.here(1)
+        } else {
+            expr.location()
+        }
     }
 
     pub fn check_program(
@@ -175,14 +344,22 @@ impl TypeChecker {
         enums: &[EnumDef],
         externs: &[ExternDecl],
         impls: &[ImplBlock],
+        traits: &[TraitDef],
         imports: &[ImportDecl],
     ) -> bool {
         self.register_structs(structs);
         self.register_enums(enums);
+        self.register_traits(traits);
         self.register_externs(externs);
         self.register_imports(imports);
         self.register_functions(&program.functions);
         self.register_impl_methods(impls);
+        self.register_trait_impls(impls);
+
+        for s in structs {
+            let field_types: Vec<Type> = s.fields.iter().map(|f| f.ty.clone()).collect();
+            self.check_type_params(&s.type_params, &field_types, &s.location);
+        }
 
         for func in &program.functions {
             self.check_function(func);
@@ -192,10 +369,36 @@ impl TypeChecker {
             self.check_impl_block(impl_block);
         }
 
+        Linter::new(&mut self.handler).run(program, impls, &self.local_imports, &self.file_name);
+
         self.handler.print_summary();
         !self.handler.has_errors()
     }
 
+    /// Everything reported so far, including fixes for `vix fix`.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        self.handler.diagnostics()
+    }
+
+    /// The types inferred for the expressions of the program, in the order they were checked.
+    pub fn expr_types(&self) -> Vec<(SourceLocation, Type)> {
+        self.expr_types.borrow().clone()
+    }
+
+    pub fn bindings(&self) -> &[(String, Type, SourceLocation)] {
+        &self.bindings
+    }
+
+    pub fn resolved_functions(&self) -> HashMap<String, (Vec<(String, Type)>, Type)> {
+        self.functions
+            .iter()
+            .map(|(name, sig)| {
+                let params = sig.params.iter().map(|(n, t, _)| (n.clone(), t.clone())).collect();
+                (name.replace('.', "_"), (params, sig.return_type.clone()))
+            })
+            .collect()
+    }
+
     fn register_imports(&mut self, imports: &[ImportDecl]) {
         for import in imports {
             match import {
@@ -250,14 +453,16 @@ impl TypeChecker {
             for field in &s.fields {
                 fields.insert(
                     field.name.clone(),
-                    (field.ty.clone(), field.is_public, field.is_mutable),
+                    (field.ty.clone(), field.is_public, field.is_mutable, field.location.clone()),
                 );
             }
             self.structs.insert(
                 s.name.clone(),
                 StructInfo {
                     fields,
-                    location: self.make_location(1, 1, s.name.len()),
+                    field_order: s.fields.iter().map(|f| f.name.clone()).collect(),
+                    type_params: s.type_params.clone(),
+                    location: s.location.clone(),
                 },
             );
         }
@@ -266,6 +471,7 @@ impl TypeChecker {
     fn register_enums(&mut self, enums: &[EnumDef]) {
         for e in enums {
             let mut variants = HashMap::new();
+            let mut variant_order = Vec::new();
             for variant in &e.variants {
                 let info = match variant {
                     EnumVariant::Simple(name) => (name.clone(), EnumVariantInfo::Simple),
@@ -273,20 +479,19 @@ impl TypeChecker {
                         (name.clone(), EnumVariantInfo::Tuple(types.clone()))
                     }
                     EnumVariant::Struct(name, fields) => {
-                        let mut field_map = HashMap::new();
-                        for field in fields {
-                            field_map.insert(field.name.clone(), field.ty.clone());
-                        }
-                        (name.clone(), EnumVariantInfo::Struct(field_map))
+                        let fields = fields.iter().map(|field| (field.name.clone(), field.ty.clone())).collect();
+                        (name.clone(), EnumVariantInfo::Struct(fields))
                     }
                 };
+                variant_order.push(info.0.clone());
                 variants.insert(info.0, info.1);
             }
             self.enums.insert(
                 e.name.clone(),
                 EnumInfo {
                     variants,
-                    location: self.make_location(1, 1, e.name.len()),
+                    variant_order,
+                    location: e.location.clone(),
                 },
             );
         }
@@ -336,12 +541,12 @@ impl TypeChecker {
 
     fn register_functions(&mut self, functions: &[Function]) {
         for func in functions {
-            let loc = self.make_location(1, 1, func.name.len());
+            let loc = func.location.clone();
             
              
             if self.builtin_functions.contains_key(&func.name) {
                 self.handler.error(
-                    "E0428",
+                    &codes::E0428,
                     &format!("Function '{}' conflicts with built-in function", func.name),
                     ErrorContext {
                         primary_location: loc.clone(),
@@ -362,7 +567,7 @@ impl TypeChecker {
              
             if let Some(imported_sig) = self.imported_functions.get(&func.name) {
                 self.handler.error(
-                    "E0428",
+                    &codes::E0428,
                     &format!("Function '{}' conflicts with imported function", func.name),
                     ErrorContext {
                         primary_location: loc.clone(),
@@ -382,6 +587,9 @@ impl TypeChecker {
                 );
             }
             
+            if !func.type_params.is_empty() {
+                self.generic_functions.insert(func.name.clone(), func.type_params.clone());
+            }
             self.functions.insert(
                 func.name.clone(),
                 FunctionSignature {
@@ -394,6 +602,20 @@ impl TypeChecker {
         }
     }
 
+    fn register_traits(&mut self, traits: &[TraitDef]) {
+        for trait_def in traits {
+            self.traits.insert(trait_def.name.clone(), trait_def.clone());
+        }
+    }
+
+    fn register_trait_impls(&mut self, impls: &[ImplBlock]) {
+        for impl_block in impls {
+            if let Some(trait_name) = &impl_block.trait_name {
+                self.trait_impls.insert((impl_block.struct_name.clone(), trait_name.clone()));
+            }
+        }
+    }
+
     fn register_impl_methods(&mut self, impls: &[ImplBlock]) {
         for impl_block in impls {
             for method in &impl_block.methods {
@@ -403,7 +625,7 @@ impl TypeChecker {
                     FunctionSignature {
                         params: method.params.clone(),
                         return_type: method.return_type.clone(),
-                        location: self.make_location(1, 1, method.name.len()),
+                        location: method.location.clone(),
                         is_builtin: false,
                     },
                 );
@@ -414,33 +636,49 @@ impl TypeChecker {
     fn check_function(&mut self, func: &Function) {
         self.enter_scope();
         self.current_function_return_type = Some(func.return_type.clone());
-        self.set_line(1);
+        self.current_type_params = func.type_params.clone();
+        self.set_location(&func.location);
+        let param_types: Vec<Type> = func.params.iter().map(|(_, ty, _)| ty.clone()).collect();
+        self.check_type_params(&func.type_params, &param_types, &func.location);
+
+        if func.is_test && (!func.params.is_empty() || func.return_type != Type::Void) {
+            self.handler.error(
+                &codes::E0580,
+                &format!("Test function '{}' must take no parameters and return nothing", func.name),
+                ErrorContext {
+                    primary_location: func.location.clone(),
+                    secondary_locations: vec![],
+                    help_message: Some("The test harness calls tests without arguments; check results with `assert`.".to_string()),
+                    suggestions: vec![],
+                },
+            );
+        }
 
         for (name, ty, modifier) in &func.params {
             if self.is_void_type(ty) {
                 self.handler.error(
-                    "E0001",
+                    &codes::E0001,
                     &format!("Parameter '{}' cannot have void type", name),
-                    void_variable_error(name, self.make_location(1, 1, name.len())),
+                    void_variable_error(name, self.here(name.len())),
                 );
             }
             
             match modifier {
                 ParamModifier::MutableReference => {
-                    if let Some(conflict) = self.borrow_tracker.add_mutable_borrow(name.clone(), self.make_location(1, 1, name.len())) {
+                    if let Some(conflict) = self.borrow_tracker.add_mutable_borrow(name.clone(), self.here(name.len())) {
                         self.handler.error(
-                            "E0502",
+                            &codes::E0502,
                             &format!("Cannot borrow '{}' as mutable more than once", name),
-                            borrow_conflict_error(name, self.make_location(1, 1, name.len()), conflict),
+                            borrow_conflict_error(name, self.here(name.len()), conflict),
                         );
                     }
                 }
                 ParamModifier::Reference => {
-                    if let Some(conflict) = self.borrow_tracker.add_immutable_borrow(name.clone(), self.make_location(1, 1, name.len())) {
+                    if let Some(conflict) = self.borrow_tracker.add_immutable_borrow(name.clone(), self.here(name.len())) {
                         self.handler.error(
-                            "E0502",
+                            &codes::E0502,
                             &format!("Cannot borrow '{}' as immutable while mutable borrow exists", name),
-                            borrow_conflict_error(name, self.make_location(1, 1, name.len()), conflict),
+                            borrow_conflict_error(name, self.here(name.len()), conflict),
                         );
                     }
                 }
@@ -448,6 +686,9 @@ impl TypeChecker {
             }
 
             self.add_variable(name.clone(), ty.clone());
+            if let Some(variable) = self.last_variable_mut(name) {
+                variable.is_mutable = matches!(modifier, ParamModifier::Mutable | ParamModifier::MutableReference);
+            }
         }
 
         for stmt in &func.body {
@@ -455,6 +696,7 @@ impl TypeChecker {
         }
 
         self.current_function_return_type = None;
+        self.current_type_params.clear();
         self.exit_scope();
     }
 
@@ -462,10 +704,10 @@ impl TypeChecker {
          
         if !self.structs.contains_key(&impl_block.struct_name) {
             self.handler.error(
-                "E0412",
+                &codes::E0412,
                 &format!("Cannot find struct '{}' for impl block", impl_block.struct_name),
                 ErrorContext {
-                    primary_location: self.make_location(1, 1, impl_block.struct_name.len()),
+                    primary_location: self.here(impl_block.struct_name.len()),
                     secondary_locations: vec![],
                     help_message: Some(format!(
                         "Struct '{}' must be defined before implementing methods for it.",
@@ -478,6 +720,10 @@ impl TypeChecker {
             );
         }
 
+        if let Some(trait_name) = &impl_block.trait_name {
+            self.check_trait_impl(impl_block, trait_name);
+        }
+
         for method in &impl_block.methods {
              
             if method.name == impl_block.struct_name {
@@ -488,28 +734,32 @@ impl TypeChecker {
 
             self.enter_scope();
             self.current_function_return_type = Some(method.return_type.clone());
+            self.set_location(&method.location);
 
              
             if let Some(self_mod) = &method.self_modifier {
                 let self_type = Type::Struct { name: impl_block.struct_name.clone() };
                 self.add_variable("self".to_string(), self_type);
+                if let Some(variable) = self.last_variable_mut("self") {
+                    variable.is_mutable = matches!(self_mod, SelfModifier::Mutable);
+                }
 
                 match self_mod {
                     SelfModifier::Mutable => {
-                        if let Some(conflict) = self.borrow_tracker.add_mutable_borrow("self".to_string(), self.make_location(1, 1, 4)) {
+                        if let Some(conflict) = self.borrow_tracker.add_mutable_borrow("self".to_string(), self.here(4)) {
                             self.handler.error(
-                                "E0502",
+                                &codes::E0502,
                                 "Cannot borrow 'self' as mutable more than once",
-                                borrow_conflict_error("self", self.make_location(1, 1, 4), conflict),
+                                borrow_conflict_error("self", self.here(4), conflict),
                             );
                         }
                     }
                     SelfModifier::Reference | SelfModifier::Borrow => {
-                        if let Some(conflict) = self.borrow_tracker.add_immutable_borrow("self".to_string(), self.make_location(1, 1, 4)) {
+                        if let Some(conflict) = self.borrow_tracker.add_immutable_borrow("self".to_string(), self.here(4)) {
                             self.handler.error(
-                                "E0502",
+                                &codes::E0502,
                                 "Cannot borrow 'self' as immutable while mutable borrow exists",
-                                borrow_conflict_error("self", self.make_location(1, 1, 4), conflict),
+                                borrow_conflict_error("self", self.here(4), conflict),
                             );
                         }
                     }
@@ -520,28 +770,28 @@ impl TypeChecker {
             for (name, ty, modifier) in &method.params {
                 if self.is_void_type(ty) {
                     self.handler.error(
-                        "E0001",
+                        &codes::E0001,
                         &format!("Parameter '{}' cannot have void type", name),
-                        void_variable_error(name, self.make_location(1, 1, name.len())),
+                        void_variable_error(name, self.here(name.len())),
                     );
                 }
 
                 match modifier {
                     ParamModifier::MutableReference => {
-                        if let Some(conflict) = self.borrow_tracker.add_mutable_borrow(name.clone(), self.make_location(1, 1, name.len())) {
+                        if let Some(conflict) = self.borrow_tracker.add_mutable_borrow(name.clone(), self.here(name.len())) {
                             self.handler.error(
-                                "E0502",
+                                &codes::E0502,
                                 &format!("Cannot borrow '{}' as mutable more than once", name),
-                                borrow_conflict_error(name, self.make_location(1, 1, name.len()), conflict),
+                                borrow_conflict_error(name, self.here(name.len()), conflict),
                             );
                         }
                     }
                     ParamModifier::Reference => {
-                        if let Some(conflict) = self.borrow_tracker.add_immutable_borrow(name.clone(), self.make_location(1, 1, name.len())) {
+                        if let Some(conflict) = self.borrow_tracker.add_immutable_borrow(name.clone(), self.here(name.len())) {
                             self.handler.error(
-                                "E0502",
+                                &codes::E0502,
                                 &format!("Cannot borrow '{}' as immutable while mutable borrow exists", name),
-                                borrow_conflict_error(name, self.make_location(1, 1, name.len()), conflict),
+                                borrow_conflict_error(name, self.here(name.len()), conflict),
                             );
                         }
                     }
@@ -549,6 +799,9 @@ impl TypeChecker {
                 }
 
                 self.add_variable(name.clone(), ty.clone());
+                if let Some(variable) = self.last_variable_mut(name) {
+                    variable.is_mutable = matches!(modifier, ParamModifier::Mutable | ParamModifier::MutableReference);
+                }
             }
 
             for stmt in &method.body {
@@ -559,98 +812,944 @@ impl TypeChecker {
             self.exit_scope();
         }
     }
-    fn check_statement(&mut self, stmt: &Stmt, expected_return_type: &Type) {
-         
+    /// An `impl Trait for Struct` defines exactly the trait's methods, each with the trait's signature.
+    fn check_trait_impl(&mut self, impl_block: &ImplBlock, trait_name: &str) {
+        let Some(trait_def) = self.traits.get(trait_name).cloned() else {
+            self.handler.error(
+                &codes::E0405,
+                &format!("Cannot find trait '{}'", trait_name),
+                ErrorContext {
+                    primary_location: impl_block.location.clone(),
+                    secondary_locations: vec![],
+                    help_message: Some(format!("Trait '{}' must be defined or imported before it can be implemented.", trait_name)),
+                    suggestions: vec![format!("Define `trait {}:` with the methods it requires", trait_name)],
+                },
+            );
+            return;
+        };
+
+        for required in &trait_def.methods {
+            match impl_block.methods.iter().find(|method| method.name == required.name) {
+                None => self.handler.error(
+                    &codes::E0046,
+                    &format!("Missing method '{}' in the implementation of '{}' for '{}'", required.name, trait_name, impl_block.struct_name),
+                    ErrorContext {
+                        primary_location: impl_block.location.clone(),
+                        secondary_locations: vec![(required.location.clone(), "declared in the trait here".to_string())],
+                        help_message: Some(format!("Add `{}` to this impl block.", self.signature_to_string(required))),
+                        suggestions: vec![],
+                    },
+                ),
+                Some(method) if !Self::signature_matches(required, method) => self.handler.error(
+                    &codes::E0053,
+                    &format!("Method '{}' does not match its declaration in trait '{}'", method.name, trait_name),
+                    ErrorContext {
+                        primary_location: method.location.clone(),
+                        secondary_locations: vec![(required.location.clone(), "declared in the trait here".to_string())],
+                        help_message: Some(format!("The trait expects `{}`.", self.signature_to_string(required))),
+                        suggestions: vec![],
+                    },
+                ),
+                Some(_) => {}
+            }
+        }
+
+        for method in &impl_block.methods {
+            if !trait_def.methods.iter().any(|required| required.name == method.name) {
+                self.handler.error(
+                    &codes::E0407,
+                    &format!("Method '{}' is not a member of trait '{}'", method.name, trait_name),
+                    ErrorContext {
+                        primary_location: method.location.clone(),
+                        secondary_locations: vec![(trait_def.location.clone(), "trait defined here".to_string())],
+                        help_message: Some(format!("Move '{}' to a plain `impl {}` block.", method.name, impl_block.struct_name)),
+                        suggestions: vec![],
+                    },
+                );
+            }
+        }
+    }
+
+    fn signature_matches(required: &TraitMethod, method: &ImplMethod) -> bool {
+        required.self_modifier.is_some() == method.self_modifier.is_some()
+            && required.params.len() == method.params.len()
+            && required.params.iter().zip(&method.params).all(|((_, expected, _), (_, got, _))| expected == got)
+            && required.return_type == method.return_type
+    }
+
+    fn signature_to_string(&self, method: &TraitMethod) -> String {
+        let mut params: Vec<String> = method.self_modifier.iter().map(|_| "self".to_string()).collect();
+        params.extend(method.params.iter().map(|(name, ty, _)| format!("{}: {}", name, self.type_to_string(ty))));
+        let mut signature = format!("func {}({})", method.name, params.join(", "));
+        if !self.is_void_type(&method.return_type) {
+            signature.push_str(&format!(": {}", self.type_to_string(&method.return_type)));
+        }
+        signature
+    }
+
+    /// Every bound must name a known trait, and every type parameter must appear in a parameter
+    /// (or field) type, since that is the only place its type argument can be inferred from.
+    fn check_type_params(&mut self, type_params: &[(String, Option<String>)], inferred_from: &[Type], location: &SourceLocation) {
+        for (param, bound) in type_params {
+            if let Some(bound) = bound.as_ref().filter(|bound| !self.traits.contains_key(*bound)) {
+                self.handler.error(
+                    &codes::E0405,
+                    &format!("Cannot find trait '{}'", bound),
+                    ErrorContext {
+                        primary_location: location.clone(),
+                        secondary_locations: vec![],
+                        help_message: Some(format!("The bound on type parameter '{}' must be a trait defined or imported here.", param)),
+                        suggestions: vec![],
+                    },
+                );
+            }
+
+            let target = Type::Param { name: param.clone() };
+            let mut mentioned = false;
+            for ty in inferred_from {
+                ty.map(&mut |part| {
+                    mentioned |= part == &target;
+                    None
+                });
+            }
+            if !mentioned {
+                self.handler.error(
+                    &codes::E0282,
+                    &format!("Cannot infer type parameter '{}'", param),
+                    ErrorContext {
+                        primary_location: location.clone(),
+                        secondary_locations: vec![],
+                        help_message: Some(format!("'{}' is inferred from the types of the arguments, but no parameter uses it.", param)),
+                        suggestions: vec![format!("Remove '{}' or use it in a parameter type", param)],
+                    },
+                );
+            }
+        }
+    }
+
+    /// The trait a type parameter of the current function is bounded by.
+    fn bound_of(&self, param: &str) -> Option<&String> {
+        self.current_type_params.iter().find(|(name, _)| name == param).and_then(|(_, bound)| bound.as_ref())
+    }
+
+    /// E0425 for a call to `name`, with a rename to the closest known function.
+    fn undefined_function(&self, name: &str, arg_count: usize, location: SourceLocation) -> Diagnostic {
+        let known = self.functions.keys().chain(self.builtin_functions.keys()).chain(self.imported_functions.keys());
+        let fix = self.rename_fix(name, location.clone(), known.filter(|name| !name.contains('.')));
+        Diagnostic::error(
+            &codes::E0425,
+            &format!("Cannot find function '{}' in this scope", name),
+            undefined_function_error(name, arg_count, location),
+        )
+        .with_fixes(fix)
+    }
+
+    fn defer_error(&self, diagnostic: Diagnostic) {
+        let mut errors = self.inference_errors.borrow_mut();
+        let location = &diagnostic.context.primary_location;
+        let seen = errors.iter().any(|d| {
+            d.message == diagnostic.message
+                && d.context.primary_location.line == location.line
+                && d.context.primary_location.column == location.column
+        });
+        if !seen {
+            errors.push(diagnostic);
+        }
+    }
+
+    fn conflicting_binding_error(&self, owner: &str, param: &str, bindings: &HashMap<String, Type>, got: &Type, location: SourceLocation) {
+        let earlier = bindings.get(param).map_or_else(|| "any".to_string(), |ty| self.type_to_string(ty));
+        self.defer_error(Diagnostic::error(
+            &codes::E0308,
+            &format!("Conflicting types for type parameter '{}' of '{}'", param, owner),
+            ErrorContext {
+                primary_location: location,
+                secondary_locations: vec![],
+                help_message: Some(format!(
+                    "'{}' is '{}' from an earlier argument, but this argument has type '{}'.",
+                    param,
+                    earlier,
+                    self.type_to_string(got)
+                )),
+                suggestions: vec![format!("Convert the argument to '{}'", earlier)],
+            },
+        ));
+    }
+
+    fn check_bounds(&self, owner: &str, type_params: &[(String, Option<String>)], bindings: &HashMap<String, Type>, location: &SourceLocation) {
+        for (param, bound) in type_params {
+            let (Some(bound), Some(ty)) = (bound, bindings.get(param)) else {
+                continue;
+            };
+            let satisfied = match ty {
+                Type::Struct { name } => self.trait_impls.contains(&(name.clone(), bound.clone())),
+                Type::Trait { name } => name == bound,
+                Type::Param { name } => self.bound_of(name) == Some(bound),
+                Type::Any => true,
+                _ => false,
+            };
+            if !satisfied {
+                self.defer_error(Diagnostic::error(
+                    &codes::E0283,
+                    &format!("Type '{}' does not implement trait '{}'", self.type_to_string(ty), bound),
+                    ErrorContext {
+                        primary_location: location.clone(),
+                        secondary_locations: vec![],
+                        help_message: Some(format!("Type parameter '{}' of '{}' is declared as `{}: {}`.", param, owner, param, bound)),
+                        suggestions: vec![format!("Add `impl {} for {}:`", bound, self.type_to_string(ty))],
+                    },
+                ));
+            }
+        }
+    }
+
+    /// The return type of a call to a generic function, with its type parameters inferred by
+    /// unifying the parameter types with the argument types.
+    fn infer_generic_call(
+        &self,
+        func_name: &str,
+        sig: &FunctionSignature,
+        type_params: &[(String, Option<String>)],
+        args: &[Expr],
+        location: SourceLocation,
+    ) -> Type {
+        let mut bindings = HashMap::new();
+        for (i, (arg, (_, param_ty, _))) in args.iter().zip(&sig.params).enumerate() {
+            let arg_ty = self.infer_expr_type(arg);
+            if let Err(param) = param_ty.unify(&arg_ty, &mut bindings) {
+                self.conflicting_binding_error(func_name, &param, &bindings, &arg_ty, location);
+                return Type::Any;
+            }
+            let expected = param_ty.substitute(&bindings);
+            if !self.types_compatible(&expected, &arg_ty) {
+                self.defer_error(Diagnostic::error(
+                    &codes::E0308,
+                    &format!("Type mismatch in argument {} of function '{}'", i + 1, func_name),
+                    type_mismatch_error(
+                        &self.type_to_string(&expected),
+                        &self.type_to_string(&arg_ty),
+                        location.clone(),
+                        sig.location.clone(),
+                    ),
+                ));
+            }
+        }
+        self.check_bounds(func_name, type_params, &bindings, &location);
+
+        for (param, _) in type_params {
+            bindings.entry(param.clone()).or_insert(Type::Any);
+        }
+        sig.return_type.substitute(&bindings)
+    }
+
+    /// A generic struct built from its fields, with the type arguments they imply. Positional
+    /// arguments follow the declaration order of the fields.
+    fn infer_generic_struct(&self, name: &str, info: &StructInfo, args: Vec<(Option<String>, &Expr)>, location: SourceLocation) -> Type {
+        let mut bindings = HashMap::new();
+        for (i, (field, arg)) in args.into_iter().enumerate() {
+            let field = field.or_else(|| info.field_order.get(i).cloned());
+            let arg_ty = self.infer_expr_type(arg);
+            let Some((field_ty, _, _, _)) = field.and_then(|f| info.fields.get(&f)) else {
+                continue;
+            };
+            if let Err(param) = field_ty.unify(&arg_ty, &mut bindings) {
+                self.conflicting_binding_error(name, &param, &bindings, &arg_ty, location);
+                return Type::Any;
+            }
+        }
+        self.check_bounds(name, &info.type_params, &bindings, &location);
+
+        Type::Generic {
+            name: name.to_string(),
+            args: info.type_params.iter().map(|(param, _)| bindings.get(param).cloned().unwrap_or(Type::Any)).collect(),
+        }
+    }
+
+    /// Inside a generic function, the methods of a type parameter are those of its bound.
+    fn infer_bounded_method(&self, param: &str, method: &str, location: SourceLocation) -> Type {
+        let bound = self.bound_of(param);
+        let declared = bound
+            .and_then(|bound| self.traits.get(bound))
+            .and_then(|trait_def| trait_def.methods.iter().find(|m| m.name == method));
+        if let Some(declared) = declared {
+            return declared.return_type.clone();
+        }
+
+        let help = match bound {
+            Some(bound) => format!("Trait '{}' declares no method '{}'.", bound, method),
+            None => format!("Add a trait bound such as `{}: Trait` to call the trait's methods on it.", param),
+        };
+        self.defer_error(Diagnostic::error(
+            &codes::E0599,
+            &format!("No method '{}' on type parameter '{}'", method, param),
+            ErrorContext {
+                primary_location: location,
+                secondary_locations: vec![],
+                help_message: Some(help),
+                suggestions: vec![],
+            },
+        ));
+        Type::Any
+    }
+
+    /// Checks the arms of a `match` on a value of type `ty`: each pattern against the type, then
+    /// that every arm can run and that together they cover every value.
+    fn check_match(&mut self, scrutinee: &Expr, ty: &Type, cases: &[MatchCase], default: &Option<Vec<SpannedStmt>>, expected_return_type: &Type) {
+        let mut rows = Vec::new();
+        let mut complete = true;
+        for case in cases {
+            self.enter_scope();
+            let mut bindings = Vec::new();
+            let pat = self.check_pattern(&case.pattern, ty, &mut bindings);
+            for (name, binding_type) in bindings {
+                self.add_variable(name, binding_type);
+            }
+            if let Some(guard) = &case.guard {
+                let guard_type = self.infer_expr_type(guard);
+                if !self.types_compatible(&Type::Bool, &guard_type) {
+                    self.handler.error(
+                        &codes::E0308,
+                        "Match guard must be a bool",
+                        type_mismatch_error("bool", &self.type_to_string(&guard_type), self.expr_location(guard), case.pattern.location.clone()),
+                    );
+                }
+            }
+
+            match pat {
+                Some(pat) => {
+                    if complete && useful(self, &rows, std::slice::from_ref(&pat), std::slice::from_ref(ty)).is_none() {
+                        self.handler.warning(
+                            &codes::W0108,
+                            "Unreachable pattern",
+                            ErrorContext {
+                                primary_location: case.pattern.location.clone(),
+                                secondary_locations: vec![],
+                                help_message: Some("The arms above already match every value this pattern does.".to_string()),
+                                suggestions: vec!["Remove the arm, or move it before the arm that covers it".to_string()],
+                            },
+                        );
+                    }
+                    if case.guard.is_none() {
+                        rows.push(vec![pat]);
+                    }
+                }
+                None => complete = false,
+            }
+
+            for stmt in &case.body {
+                self.check_statement(stmt, expected_return_type);
+            }
+            self.exit_scope();
+        }
+
+        // A pattern with an error, or a value of unknown type, says nothing about what is covered.
+        if complete && !matches!(ty, Type::Any) {
+            let missing = useful(self, &rows, &[Pat::Any], std::slice::from_ref(ty));
+            match (missing, default) {
+                (Some(witness), None) => {
+                    let witness = witness[0].to_string();
+                    self.handler.error(
+                        &codes::E0004,
+                        &format!("Non-exhaustive match: `{}` is not covered", witness),
+                        ErrorContext {
+                            primary_location: self.expr_location(scrutinee),
+                            secondary_locations: vec![],
+                            help_message: Some(format!(
+                                "The matched value has type '{}', and no arm matches `{}`.",
+                                self.type_to_string(ty),
+                                witness
+                            )),
+                            suggestions: vec![format!("Add `case {}:`", witness), "Add a `default:` arm".to_string()],
+                        },
+                    );
+                }
+                (None, Some(_)) => {
+                    self.handler.warning(
+                        &codes::W0108,
+                        "Unreachable `default` arm",
+                        ErrorContext {
+                            primary_location: self.expr_location(scrutinee),
+                            secondary_locations: vec![],
+                            help_message: Some("The cases of this match already cover every value.".to_string()),
+                            suggestions: vec!["Remove the `default:` arm".to_string()],
+                        },
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(default_body) = default {
+            self.enter_scope();
+            for stmt in default_body {
+                self.check_statement(stmt, expected_return_type);
+            }
+            self.exit_scope();
+        }
+    }
+
+    /// Checks `pattern` against the type of the value it matches and collects the names it binds.
+    /// Returns the pattern as the exhaustiveness check sees it, or `None` if it has an error.
+    fn check_pattern(&mut self, pattern: &Pattern, ty: &Type, bindings: &mut Vec<(String, Type)>) -> Option<Pat> {
+        let location = pattern.location.clone();
+        match &pattern.kind {
+            PatternKind::Wildcard => Some(Pat::Any),
+            PatternKind::Binding(name) => {
+                bindings.push((name.clone(), ty.clone()));
+                Some(Pat::Any)
+            }
+            PatternKind::Literal(literal) => {
+                let literal_type = self.infer_expr_type(literal);
+                if !self.types_compatible(ty, &literal_type) {
+                    let found = self.type_to_string(&literal_type);
+                    self.pattern_mismatch(ty, &found, location);
+                    return None;
+                }
+                Some(Pat::Ctor(literal_key(literal), vec![]))
+            }
+            PatternKind::Range { start, end, inclusive } => {
+                let start_type = self.infer_expr_type(start);
+                let end_type = self.infer_expr_type(end);
+                let numeric = matches!(ty, Type::Int { .. } | Type::Float { .. } | Type::Char { .. } | Type::Any);
+                if !numeric || !self.types_compatible(ty, &start_type) || !self.types_compatible(ty, &end_type) {
+                    let found = format!("a range of {}", self.type_to_string(&start_type));
+                    self.pattern_mismatch(ty, &found, location);
+                    return None;
+                }
+                let operator = if *inclusive { "..=" } else { ".." };
+                Some(Pat::Ctor(format!("{}{}{}", literal_key(start), operator, literal_key(end)), vec![]))
+            }
+            PatternKind::Tuple(elements) => {
+                let field_types = match ty {
+                    Type::Tuple { fields } if fields.len() == elements.len() => fields.clone(),
+                    Type::Any => vec![Type::Any; elements.len()],
+                    _ => {
+                        self.pattern_mismatch(ty, &format!("a tuple of {} elements", elements.len()), location);
+                        bind_any(elements, bindings);
+                        return None;
+                    }
+                };
+                let fields = self.check_patterns(elements, &field_types, bindings)?;
+                Some(Pat::Ctor(String::new(), fields))
+            }
+            PatternKind::Variant { enum_name, variant, fields } => {
+                let checked = self.variant_fields(enum_name.as_deref(), variant, fields.len(), ty, &location);
+                let Some((ctor, field_types)) = checked else {
+                    bind_any(fields, bindings);
+                    return None;
+                };
+                let fields = self.check_patterns(fields, &field_types, bindings)?;
+                Some(Pat::Ctor(ctor, fields))
+            }
+            PatternKind::Or(alternatives) => {
+                let mut first: Option<Vec<(String, Type)>> = None;
+                let mut pats = Some(Vec::new());
+                for alternative in alternatives {
+                    let mut alternative_bindings = Vec::new();
+                    let pat = self.check_pattern(alternative, ty, &mut alternative_bindings);
+                    pats = pats.zip(pat).map(|(mut pats, pat)| {
+                        pats.push(pat);
+                        pats
+                    });
+                    match &first {
+                        None => first = Some(alternative_bindings),
+                        Some(expected) => {
+                            if !self.check_alternative_bindings(expected, &alternative_bindings, &alternative.location) {
+                                pats = None;
+                            }
+                        }
+                    }
+                }
+                bindings.extend(first.unwrap_or_default());
+                pats.map(Pat::Or)
+            }
+        }
+    }
+
+    /// Checks each of `patterns` against the matching type; `None` if any has an error.
+    fn check_patterns(&mut self, patterns: &[Pattern], types: &[Type], bindings: &mut Vec<(String, Type)>) -> Option<Vec<Pat>> {
+        let checked: Vec<Option<Pat>> = patterns.iter().zip(types).map(|(pattern, ty)| self.check_pattern(pattern, ty, bindings)).collect();
+        checked.into_iter().collect()
+    }
+
+    /// The constructor a variant pattern names and the types of its fields, after checking that it
+    /// belongs to the matched type and lists `arity` fields.
+    fn variant_fields(&mut self, enum_name: Option<&str>, variant: &str, arity: usize, ty: &Type, location: &SourceLocation) -> Option<(String, Vec<Type>)> {
+        let (ctor, field_types) = match (enum_name, ty) {
+            (None, Type::Option { inner }) if variant == "Some" => (variant.to_string(), vec![(**inner).clone()]),
+            (None, Type::Option { .. }) if variant == "None" => (variant.to_string(), vec![]),
+            (None, Type::Result { ok, .. }) if variant == "Ok" => (variant.to_string(), vec![(**ok).clone()]),
+            (None, Type::Result { err, .. }) if variant == "Err" => (variant.to_string(), vec![(**err).clone()]),
+            (None, Type::Any) => (variant.to_string(), vec![Type::Any; arity]),
+            (Some(name), _) => {
+                let Some(info) = self.enums.get(name).cloned() else {
+                    self.handler.error(
+                        &codes::E0412,
+                        &format!("Cannot find enum '{}'", name),
+                        ErrorContext {
+                            primary_location: location.clone(),
+                            secondary_locations: vec![],
+                            help_message: Some(format!("No enum named '{}' is declared or imported.", name)),
+                            suggestions: vec![],
+                        },
+                    );
+                    return None;
+                };
+                if !matches!(ty, Type::Any) && !matches!(ty, Type::Struct { name: matched } if matched == name) {
+                    self.pattern_mismatch(ty, &format!("enum '{}'", name), location.clone());
+                    return None;
+                }
+                let Some(variant_info) = info.variants.get(variant) else {
+                    self.handler.error(
+                        &codes::E0599,
+                        &format!("No variant '{}' on enum '{}'", variant, name),
+                        ErrorContext {
+                            primary_location: location.clone(),
+                            secondary_locations: vec![(info.location.clone(), format!("enum '{}' declared here", name))],
+                            help_message: Some(format!("'{}' has the variants {}.", name, info.variant_order.join(", "))),
+                            suggestions: vec![],
+                        },
+                    );
+                    return None;
+                };
+                (format!("{}.{}", name, variant), variant_info.field_types())
+            }
+            (None, _) => {
+                self.pattern_mismatch(ty, &format!("`{}`", variant), location.clone());
+                return None;
+            }
+        };
+
+        if field_types.len() != arity {
+            self.handler.error(
+                &codes::E0023,
+                &format!("Pattern for '{}' lists {} field(s), but the variant has {}", ctor, arity, field_types.len()),
+                ErrorContext {
+                    primary_location: location.clone(),
+                    secondary_locations: vec![],
+                    help_message: Some("List one pattern per field, in declaration order; `_` matches any value.".to_string()),
+                    suggestions: vec![],
+                },
+            );
+            return None;
+        }
+        Some((ctor, field_types))
+    }
+
+    /// Every alternative of an or-pattern must bind the same names with the same types as the first.
+    fn check_alternative_bindings(&mut self, expected: &[(String, Type)], got: &[(String, Type)], location: &SourceLocation) -> bool {
+        let missing = expected
+            .iter()
+            .find(|(name, _)| !got.iter().any(|(other, _)| other == name))
+            .or_else(|| got.iter().find(|(name, _)| !expected.iter().any(|(other, _)| other == name)));
+        let message = match missing {
+            Some((name, _)) => format!("Variable '{}' is not bound in every alternative", name),
+            None => match expected.iter().find(|(name, ty)| got.iter().any(|(other, other_ty)| other == name && !self.types_compatible(ty, other_ty))) {
+                Some((name, _)) => format!("Variable '{}' has a different type in each alternative", name),
+                None => return true,
+            },
+        };
+        self.handler.error(
+            &codes::E0408,
+            &message,
+            ErrorContext {
+                primary_location: location.clone(),
+                secondary_locations: vec![],
+                help_message: Some("The arm's body may run after any of the alternatives matched, so each must bind the same variables.".to_string()),
+                suggestions: vec![],
+            },
+        );
+        false
+    }
+
+    fn pattern_mismatch(&mut self, ty: &Type, found: &str, location: SourceLocation) {
+        self.handler.error(
+            &codes::E0308,
+            "Mismatched types in pattern",
+            ErrorContext {
+                primary_location: location,
+                secondary_locations: vec![],
+                help_message: Some(format!("The matched value has type '{}', but this pattern matches {}.", self.type_to_string(ty), found)),
+                suggestions: vec![],
+            },
+        );
+    }
+
+    /// The type of `Enum.Variant` used as a value, when `obj` names an enum rather than a variable.
+    fn enum_value_type(&self, obj: &Expr, variant: &str, location: SourceLocation) -> Option<Type> {
+        let ExprKind::Var(name) = &obj.kind else {
+            return None;
+        };
+        if self.get_variable_type(name).is_some() {
+            return None;
+        }
+        let info = self.enums.get(name)?;
+        match info.variants.get(variant) {
+            None => self.unknown_variant_error(name, info, variant, location),
+            Some(EnumVariantInfo::Simple) => {}
+            Some(payload) => self.variant_arity_error(name, info, variant, payload.field_types().len(), 0, location),
+        }
+        Some(Type::Struct { name: name.clone() })
+    }
+
+    /// The type of `Enum.Variant(args)`, a payload variant built from positional or named
+    /// fields, or `None` when `obj` does not name an enum.
+    fn enum_constructor_type(&self, obj: &Expr, variant: &str, args: Vec<(Option<String>, &Expr)>, location: SourceLocation) -> Option<Type> {
+        let ExprKind::Var(name) = &obj.kind else {
+            return None;
+        };
+        if self.get_variable_type(name).is_some() {
+            return None;
+        }
+        let info = self.enums.get(name)?;
+        let enum_type = Some(Type::Struct { name: name.clone() });
+        let Some(payload) = info.variants.get(variant) else {
+            self.unknown_variant_error(name, info, variant, location);
+            return enum_type;
+        };
+        let fields: Vec<(Option<&String>, &Type)> = match payload {
+            EnumVariantInfo::Simple => vec![],
+            EnumVariantInfo::Tuple(types) => types.iter().map(|ty| (None, ty)).collect(),
+            EnumVariantInfo::Struct(fields) => fields.iter().map(|(field, ty)| (Some(field), ty)).collect(),
+        };
+        if fields.len() != args.len() {
+            self.variant_arity_error(name, info, variant, fields.len(), args.len(), location);
+            return enum_type;
+        }
+
+        let mut given = HashSet::new();
+        for (i, (field, arg)) in args.into_iter().enumerate() {
+            if let Some(field) = &field
+                && !given.insert(field.clone())
+            {
+                self.defer_error(Diagnostic::error(
+                    &codes::E0062,
+                    &format!("Field '{}' of variant '{}.{}' is given more than once", field, name, variant),
+                    ErrorContext {
+                        primary_location: self.expr_location(arg),
+                        secondary_locations: vec![],
+                        help_message: Some(format!("Remove the second `{} = ...`.", field)),
+                        suggestions: vec![],
+                    },
+                ));
+                continue;
+            }
+            let expected = match &field {
+                Some(field) => match fields.iter().find(|(declared, _)| declared == &Some(field)) {
+                    Some((_, ty)) => *ty,
+                    None => {
+                        let help = match payload {
+                            EnumVariantInfo::Struct(fields) => format!(
+                                "'{}.{}' has the fields {}.",
+                                name,
+                                variant,
+                                fields.iter().map(|(f, _)| f.as_str()).collect::<Vec<_>>().join(", ")
+                            ),
+                            _ => format!("The fields of '{}.{}' have no names; pass them in order.", name, variant),
+                        };
+                        self.defer_error(Diagnostic::error(
+                            &codes::E0609,
+                            &format!("No field '{}' on variant '{}.{}'", field, name, variant),
+                            ErrorContext {
+                                primary_location: self.expr_location(arg),
+                                secondary_locations: vec![(info.location.clone(), format!("enum '{}' declared here", name))],
+                                help_message: Some(help),
+                                suggestions: vec![],
+                            },
+                        ));
+                        continue;
+                    }
+                },
+                None => fields[i].1,
+            };
+            let arg_type = self.infer_expr_type(arg);
+            if !self.types_compatible(expected, &arg_type) {
+                let what = field.map_or_else(|| format!("field {}", i + 1), |field| format!("field '{}'", field));
+                self.defer_error(Diagnostic::error(
+                    &codes::E0308,
+                    &format!("Type mismatch in {} of variant '{}.{}'", what, name, variant),
+                    type_mismatch_error(&self.type_to_string(expected), &self.type_to_string(&arg_type), self.expr_location(arg), info.location.clone()),
+                ));
+            }
+        }
+        enum_type
+    }
+
+    fn unknown_variant_error(&self, name: &str, info: &EnumInfo, variant: &str, location: SourceLocation) {
+        self.defer_error(Diagnostic::error(
+            &codes::E0599,
+            &format!("No variant '{}' on enum '{}'", variant, name),
+            ErrorContext {
+                primary_location: location,
+                secondary_locations: vec![(info.location.clone(), format!("enum '{}' declared here", name))],
+                help_message: Some(format!("'{}' has the variants {}.", name, info.variant_order.join(", "))),
+                suggestions: vec![],
+            },
+        ));
+    }
+
+    fn variant_arity_error(&self, name: &str, info: &EnumInfo, variant: &str, expected: usize, got: usize, location: SourceLocation) {
+        self.defer_error(Diagnostic::error(
+            &codes::E0061,
+            &format!("Variant '{}.{}' has {} field(s), got {}", name, variant, expected, got),
+            ErrorContext {
+                primary_location: location,
+                secondary_locations: vec![(info.location.clone(), format!("enum '{}' declared here", name))],
+                help_message: Some(if expected == 0 {
+                    format!("Write `{}.{}` without arguments.", name, variant)
+                } else {
+                    format!("Pass all {} field(s) to construct `{}.{}`.", expected, name, variant)
+                }),
+                suggestions: vec![],
+            },
+        ));
+    }
+
+    fn check_statement(&mut self, spanned: &SpannedStmt, expected_return_type: &Type) {
+        self.check_statement_kind(spanned, expected_return_type);
+        for diagnostic in self.inference_errors.take() {
+            self.handler.emit(diagnostic);
+        }
+        loop {
+            let lambda = self.pending_lambdas.borrow_mut().pop();
+            match lambda {
+                Some(lambda) => self.check_lambda(&lambda),
+                None => break,
+            }
+        }
+    }
+
+    /// Checks a lambda's body against its return type, with its parameters in scope.
+    fn check_lambda(&mut self, lambda: &Expr) {
+        let ExprKind::Lambda { params, return_type, body } = &lambda.kind else {
+            return;
+        };
+        self.enter_scope();
+        for (name, ty) in params {
+            self.add_variable(name.clone(), ty.clone());
+        }
+        let body_type = self.infer_expr_type(body);
+        if !self.is_void_type(return_type) && !self.types_compatible(return_type, &body_type) {
+            self.handler.error(
+                &codes::E0308,
+                "Mismatched return type",
+                return_type_mismatch_error(
+                    &self.type_to_string(return_type),
+                    &self.type_to_string(&body_type),
+                    self.expr_location(lambda),
+                    self.expr_location(body),
+                ),
+            );
+        }
+        for diagnostic in self.inference_errors.take() {
+            self.handler.emit(diagnostic);
+        }
+        self.exit_scope();
+    }
+
+    /// The variables in scope that a lambda uses, which it has to capture.
+    fn lambda_captures(&self, params: &[(String, Type)], body: &Expr) -> Vec<String> {
+        free_variables(params, body).into_iter().filter(|name| self.get_variable_type(name).is_some()).collect()
+    }
+
+    fn is_capturing_lambda(&self, expr: &Expr) -> bool {
+        matches!(&expr.kind, ExprKind::Lambda { params, body, .. } if !self.lambda_captures(params, body).is_empty())
+    }
+
+    /// The scope whose block holds the environment of the closure `expr` evaluates to. A closure
+    /// must not be used once that block has ended.
+    fn closure_scope_of(&self, expr: &Expr) -> Option<usize> {
+        match &expr.kind {
+            ExprKind::Lambda { .. } if self.is_capturing_lambda(expr) => Some(self.variables.len() - 1),
+            ExprKind::Var(name) => self.get_variable(name).and_then(|variable| variable.closure_scope),
+            _ => None,
+        }
+    }
+
+    fn closure_escape_error(&mut self, message: &str, expr: &Expr) {
+        self.handler.error(
+            &codes::E0373,
+            message,
+            ErrorContext {
+                primary_location: self.expr_location(expr),
+                secondary_locations: vec![],
+                help_message: Some("A closure's captured variables live in the block that creates it, which is gone once that block ends.".to_string()),
+                suggestions: vec!["Pass the captured values as parameters instead".to_string()],
+            },
+        );
+    }
+
+    /// Reports a lambda that captures variables given where a bare function pointer is expected.
+    fn function_pointer_error(&self, expected: &Type, value: &Expr) -> bool {
+        if !matches!(expected, Type::FnPtr { .. }) || !self.is_capturing_lambda(value) {
+            return false;
+        }
+        let ExprKind::Lambda { params, body, .. } = &value.kind else {
+            return false;
+        };
+        let captures = self.lambda_captures(params, body);
+        self.defer_error(Diagnostic::error(
+            &codes::E0434,
+            &format!("Expected '{}', found a closure that captures '{}'", self.type_to_string(expected), captures.join("', '")),
+            ErrorContext {
+                primary_location: self.expr_location(value),
+                secondary_locations: vec![],
+                help_message: Some("A function pointer cannot carry captured variables.".to_string()),
+                suggestions: vec![format!("Pass '{}' as a parameter instead of capturing it", captures[0])],
+            },
+        ));
+        true
+    }
+
+    /// The result of calling a variable that holds a function, checking the arguments against its type.
+    fn indirect_call_type(&self, name: &str, args: &[Expr], location: SourceLocation) -> Option<Type> {
+        let (Type::FnPtr { params, return_type } | Type::Closure { params, return_type }) = self.get_variable_type(name)? else {
+            return None;
+        };
+        if params.len() != args.len() {
+            self.defer_error(Diagnostic::error(
+                &codes::E0061,
+                &format!("'{}' expects {} arguments, got {}", name, params.len(), args.len()),
+                ErrorContext {
+                    primary_location: location,
+                    secondary_locations: vec![],
+                    help_message: Some(format!("'{}' has type '{}'.", name, self.type_to_string(&Type::Closure { params, return_type }))),
+                    suggestions: vec![],
+                },
+            ));
+            return Some(Type::Any);
+        }
+        for (param, arg) in params.iter().zip(args) {
+            let arg_type = self.infer_expr_type(arg);
+            if !self.function_pointer_error(param, arg) && !self.types_compatible(param, &arg_type) {
+                self.defer_error(Diagnostic::error(
+                    &codes::E0308,
+                    &format!("Type mismatch in argument of '{}'", name),
+                    type_mismatch_error(&self.type_to_string(param), &self.type_to_string(&arg_type), location.clone(), self.expr_location(arg)),
+                ));
+            }
+        }
+        Some(*return_type)
+    }
+
+    fn check_statement_kind(&mut self, spanned: &SpannedStmt, expected_return_type: &Type) {
+        let stmt = &spanned.stmt;
+        self.set_location(&spanned.location);
         
         match stmt {
             Stmt::TypedDeclaration { name, ty, value, is_mutable } => {
                 if self.is_void_type(ty) {
                     self.handler.error(
-                        "E0001",
+                        &codes::E0001,
                         &format!("Variable '{}' cannot have void type", name),
-                        void_variable_error(name, self.make_location(1, 1, name.len())),
+                        void_variable_error(name, self.here(name.len())),
                     );
                 }
 
                 let value_type = self.infer_expr_type(value);
-                if !self.types_compatible(ty, &value_type) {
+                if !self.function_pointer_error(ty, value) && !self.types_compatible(ty, &value_type) {
                     self.handler.error(
-                        "E0308",
+                        &codes::E0308,
                         &format!("Type mismatch in variable declaration '{}'", name),
                         type_mismatch_error(
                             &self.type_to_string(ty),
                             &self.type_to_string(&value_type),
-                            self.make_location(1, 5, name.len()),
-                            self.make_location(1, 5, 1),
+                            self.here(name.len()),
+                            self.expr_location(value),
                         ),
                     );
                 }
 
                 if *is_mutable {
-                    if let Some(conflict) = self.borrow_tracker.add_mutable_borrow(name.clone(), self.make_location(1, 1, name.len())) {
+                    if let Some(conflict) = self.borrow_tracker.add_mutable_borrow(name.clone(), self.here(name.len())) {
                         self.handler.error(
-                            "E0502",
+                            &codes::E0502,
                             &format!("Cannot borrow '{}' as mutable more than once", name),
-                            borrow_conflict_error(name, self.make_location(1, 1, name.len()), conflict),
+                            borrow_conflict_error(name, self.here(name.len()), conflict),
                         );
                     }
                 }
 
+                let closure_scope = self.closure_scope_of(value);
+                let declaration = self.here(name.len());
                 self.add_variable(name.clone(), ty.clone());
+                if let Some(variable) = self.last_variable_mut(name) {
+                    variable.closure_scope = closure_scope;
+                    variable.is_mutable = *is_mutable;
+                    variable.declaration = (!*is_mutable).then_some(declaration);
+                }
             }
 
             Stmt::Assign(name, value) => {
                 if let Some(var_type) = self.get_variable_type(name) {
+                    self.immutable_assignment_error(name);
                     let value_type = self.infer_expr_type(value);
                     if !self.types_compatible(&var_type, &value_type) {
                         self.handler.error(
-                            "E0308",
+                            &codes::E0308,
                             &format!("Type mismatch in assignment to '{}'", name),
                             type_mismatch_error(
                                 &self.type_to_string(&var_type),
                                 &self.type_to_string(&value_type),
-                                self.make_location(1, 1, name.len()),
-                                self.make_location(1, 1, 1),
+                                self.here(name.len()),
+                                self.expr_location(value),
                             ),
                         );
                     }
+
+                    let closure_scope = self.closure_scope_of(value);
+                    let scope = self.variable_scope(name).unwrap_or(0);
+                    if closure_scope.is_some_and(|closure_scope| closure_scope > scope) {
+                        self.closure_escape_error(&format!("Closure assigned to '{}' outlives the block that creates it", name), value);
+                    } else if let Some(variable) = self.variables[scope].get_mut(name) {
+                        variable.closure_scope = closure_scope;
+                    }
                 } else {
-                    self.handler.error(
-                        "E0425",
-                        &format!("Cannot find variable '{}' in this scope", name),
-                        undefined_variable_error(name, self.make_location(1, 1, name.len())),
+                    let fix = self.assignment_rename_fix(name);
+                    self.handler.emit(
+                        Diagnostic::error(
+                            &codes::E0425,
+                            &format!("Cannot find variable '{}' in this scope", name),
+                            undefined_variable_error(name, self.here(name.len())),
+                        )
+                        .with_fixes(fix),
                     );
                 }
             }
 
             Stmt::CompoundAssign(name, op, value) => {
                 if let Some(var_type) = self.get_variable_type(name) {
+                    self.immutable_assignment_error(name);
                     if self.is_void_type(&var_type) {
                         self.handler.error(
-                            "E0277",
+                            &codes::E0277,
                             &format!("Cannot perform operation '{}' on void type", op),
-                            void_operation_error(op, self.make_location(1, 1, name.len())),
+                            void_operation_error(op, self.here(name.len())),
                         );
                     }
 
                     let value_type = self.infer_expr_type(value);
                     if !self.types_compatible(&var_type, &value_type) {
                         self.handler.error(
-                            "E0308",
+                            &codes::E0308,
                             &format!("Type mismatch in compound assignment to '{}'", name),
                             type_mismatch_error(
                                 &self.type_to_string(&var_type),
                                 &self.type_to_string(&value_type),
-                                self.make_location(1, 1, name.len()),
-                                self.make_location(1, 1, 1),
+                                self.here(name.len()),
+                                self.expr_location(value),
                             ),
                         );
                     }
                 } else {
-                    self.handler.error(
-                        "E0425",
-                        &format!("Cannot find variable '{}' in this scope", name),
-                        undefined_variable_error(name, self.make_location(1, 1, name.len())),
+                    let fix = self.assignment_rename_fix(name);
+                    self.handler.emit(
+                        Diagnostic::error(
+                            &codes::E0425,
+                            &format!("Cannot find variable '{}' in this scope", name),
+                            undefined_variable_error(name, self.here(name.len())),
+                        )
+                        .with_fixes(fix),
                     );
                 }
             }
@@ -662,15 +1761,19 @@ impl TypeChecker {
                     Type::Void
                 };
 
-                if !self.types_compatible(expected_return_type, &return_type) {
+                if let Some(expr) = expr_opt.as_ref().filter(|expr| self.closure_scope_of(expr).is_some()) {
+                    self.closure_escape_error("Closure may outlive the variables it captures", expr);
+                } else if !expr_opt.as_ref().is_some_and(|expr| self.function_pointer_error(expected_return_type, expr))
+                    && !self.types_compatible(expected_return_type, &return_type)
+                {
                     self.handler.error(
-                        "E0308",
+                        &codes::E0308,
                         "Mismatched return type",
                         return_type_mismatch_error(
                             &self.type_to_string(expected_return_type),
                             &self.type_to_string(&return_type),
-                            self.make_location(1, 1, 6),
-                            self.make_location(1, 1, 1),
+                            self.here(6),
+                            expr_opt.as_ref().map_or_else(|| self.here(1), |expr| self.expr_location(expr)),
                         ),
                     );
                 }
@@ -680,10 +1783,10 @@ impl TypeChecker {
                 let cond_type = self.infer_expr_type(cond);
                 if !matches!(cond_type, Type::Bool) && !matches!(cond_type, Type::Any) {
                     self.handler.error(
-                        "E0308",
+                        &codes::E0308,
                         "Condition must be boolean",
                         ErrorContext {
-                            primary_location: self.make_location(1, 1, 2),
+                            primary_location: self.here(2),
                             secondary_locations: vec![],
                             help_message: Some(format!(
                                 "Expected boolean type in condition, found '{}'",
@@ -716,10 +1819,10 @@ impl TypeChecker {
                 let cond_type = self.infer_expr_type(cond);
                 if !matches!(cond_type, Type::Bool) && !matches!(cond_type, Type::Any) {
                     self.handler.error(
-                        "E0308",
+                        &codes::E0308,
                         "Loop condition must be boolean",
                         ErrorContext {
-                            primary_location: self.make_location(1, 1, 5),
+                            primary_location: self.here(5),
                             secondary_locations: vec![],
                             help_message: Some(format!(
                                 "Expected boolean type in loop condition, found '{}'",
@@ -749,10 +1852,10 @@ impl TypeChecker {
                     }
                     _ => {
                         self.handler.error(
-                            "E0277",
+                            &codes::E0277,
                             "For loop requires iterable type",
                             ErrorContext {
-                                primary_location: self.make_location(1, 1, 3),
+                                primary_location: self.here(3),
                                 secondary_locations: vec![],
                                 help_message: Some(format!(
                                     "Cannot iterate over type '{}'. Expected array or iterable type.",
@@ -776,47 +1879,18 @@ impl TypeChecker {
 
             Stmt::Match(expr, cases, default) => {
                 let expr_type = self.infer_expr_type(expr);
-
-                for case in cases {
-                    let case_type = self.infer_expr_type(&case.value);
-                    if !self.types_compatible(&expr_type, &case_type) {
-                        self.handler.error(
-                            "E0308",
-                            "Match arm type mismatch",
-                            type_mismatch_error(
-                                &self.type_to_string(&expr_type),
-                                &self.type_to_string(&case_type),
-                                self.make_location(1, 1, 5),
-                                self.make_location(1, 1, 1),
-                            ),
-                        );
-                    }
-
-                    self.enter_scope();
-                    for stmt in &case.body {
-                        self.check_statement(stmt, expected_return_type);
-                    }
-                    self.exit_scope();
-                }
-
-                if let Some(default_body) = default {
-                    self.enter_scope();
-                    for stmt in default_body {
-                        self.check_statement(stmt, expected_return_type);
-                    }
-                    self.exit_scope();
-                }
+                self.check_match(expr, &expr_type, cases, default, expected_return_type);
             }
 
             Stmt::Call(func_name, args) | Stmt::ModuleCall(_, func_name, args) => {
-                 
-                if self.builtin_functions.contains_key(func_name) {
-                     
+                if self.indirect_call_type(func_name, args, self.here(func_name.len())).is_some() {
                     return;
                 }
-                
-                 
-                if self.imported_functions.contains_key(func_name) {
+
+                if self.builtin_functions.contains_key(func_name) || self.imported_functions.contains_key(func_name) {
+                    for arg in args {
+                        self.infer_expr_type(arg);
+                    }
                     return;
                 }
                 
@@ -824,10 +1898,10 @@ impl TypeChecker {
                 if let Some(sig) = self.functions.get(func_name) {
                     if args.len() != sig.params.len() {
                         self.handler.error(
-                            "E0061",
+                            &codes::E0061,
                             &format!("Function '{}' expects {} arguments, got {}", func_name, sig.params.len(), args.len()),
                             ErrorContext {
-                                primary_location: self.make_location(1, 1, func_name.len()),
+                                primary_location: self.here(func_name.len()),
                                 secondary_locations: vec![
                                     (sig.location.clone(), format!("defined here with {} parameters", sig.params.len()))
                                 ],
@@ -844,18 +1918,20 @@ impl TypeChecker {
                                 ],
                             },
                         );
+                    } else if let Some(type_params) = self.generic_functions.get(func_name) {
+                        self.infer_generic_call(func_name, sig, type_params, args, self.here(func_name.len()));
                     } else {
                         for (i, arg) in args.iter().enumerate() {
                             if let Some((_, expected_type, _)) = sig.params.get(i) {
                                 let arg_type = self.infer_expr_type(arg);
-                                if !self.types_compatible(expected_type, &arg_type) {
+                                if !self.function_pointer_error(expected_type, arg) && !self.types_compatible(expected_type, &arg_type) {
                                     self.handler.error(
-                                        "E0308",
+                                        &codes::E0308,
                                         &format!("Type mismatch in argument {} of function '{}'", i + 1, func_name),
                                         type_mismatch_error(
                                             &self.type_to_string(expected_type),
                                             &self.type_to_string(&arg_type),
-                                            self.make_location(1, 1, func_name.len()),
+                                            self.here(func_name.len()),
                                             sig.location.clone(),
                                         ),
                                     );
@@ -864,11 +1940,8 @@ impl TypeChecker {
                         }
                     }
                 } else {
-                    self.handler.error(
-                        "E0425",
-                        &format!("Cannot find function '{}' in this scope", func_name),
-                        undefined_function_error(func_name, args.len(), self.make_location(1, 1, func_name.len())),
-                    );
+                    let diagnostic = self.undefined_function(func_name, args.len(), self.here(func_name.len()));
+                    self.handler.emit(diagnostic);
                 }
             }
 
@@ -891,13 +1964,13 @@ impl TypeChecker {
                     Type::Array { element, .. } => {
                         if !self.types_compatible(element, &value_type) {
                             self.handler.error(
-                                "E0308",
+                                &codes::E0308,
                                 "Array element type mismatch",
                                 type_mismatch_error(
                                     &self.type_to_string(element),
                                     &self.type_to_string(&value_type),
-                                    self.make_location(1, 1, 1),
-                                    self.make_location(1, 1, 1),
+                                    self.expr_location(obj),
+                                    self.expr_location(value),
                                 ),
                             );
                         }
@@ -906,10 +1979,10 @@ impl TypeChecker {
                             let idx_type = self.infer_expr_type(idx);
                             if !self.is_integer_type(&idx_type) {
                                 self.handler.error(
-                                    "E0308",
+                                    &codes::E0308,
                                     "Array index must be integer",
                                     ErrorContext {
-                                        primary_location: self.make_location(1, 1, 1),
+                                        primary_location: self.expr_location(idx),
                                         secondary_locations: vec![],
                                         help_message: Some(format!(
                                             "Expected integer type for array index, found '{}'",
@@ -926,10 +1999,10 @@ impl TypeChecker {
                     }
                     _ => {
                         self.handler.error(
-                            "E0277",
+                            &codes::E0277,
                             "Cannot index non-array type",
                             ErrorContext {
-                                primary_location: self.make_location(1, 1, 1),
+                                primary_location: self.expr_location(obj),
                                 secondary_locations: vec![],
                                 help_message: Some(format!(
                                     "Cannot index into type '{}'. Only arrays support indexing.",
@@ -948,45 +2021,47 @@ impl TypeChecker {
                 let obj_type = self.infer_expr_type(obj);
                 let value_type = self.infer_expr_type(value);
 
-                if let Expr::Var(struct_name) = obj.as_ref() {
+                if let ExprKind::Var(struct_name) = &obj.kind {
                     if let Some(struct_info) = self.structs.get(struct_name) {
-                        if let Some((field_type, _, is_mutable)) = struct_info.fields.get(field) {
+                        if let Some((field_type, _, is_mutable, field_location)) = struct_info.fields.get(field) {
                             if !is_mutable {
-                                self.handler.error(
-                                    "E0594",
-                                    &format!("Cannot assign to immutable field '{}'", field),
-                                    ErrorContext {
-                                        primary_location: self.make_location(1, 1, field.len()),
-                                        secondary_locations: vec![],
-                                        help_message: Some(format!(
-                                            "Field '{}' is declared as immutable and cannot be modified.",
-                                            field
-                                        )),
-                                        suggestions: vec![
-                                            format!("Mark field '{}' as mutable in struct definition", field),
-                                        ],
-                                    },
+                                let fix = Fix::insert(format!("Declare field '{}' as `mut`", field), field_location.clone(), "mut ");
+                                self.handler.emit(
+                                    Diagnostic::error(
+                                        &codes::E0594,
+                                        &format!("Cannot assign to immutable field '{}'", field),
+                                        ErrorContext {
+                                            primary_location: self.here(field.len()),
+                                            secondary_locations: vec![(field_location.clone(), "field declared here".to_string())],
+                                            help_message: Some(format!(
+                                                "Field '{}' is declared as immutable and cannot be modified.",
+                                                field
+                                            )),
+                                            suggestions: vec![],
+                                        },
+                                    )
+                                    .with_fixes(Some(fix)),
                                 );
                             }
 
                             if !self.types_compatible(field_type, &value_type) {
                                 self.handler.error(
-                                    "E0308",
+                                    &codes::E0308,
                                     &format!("Type mismatch in field '{}' assignment", field),
                                     type_mismatch_error(
                                         &self.type_to_string(field_type),
                                         &self.type_to_string(&value_type),
-                                        self.make_location(1, 1, field.len()),
-                                        self.make_location(1, 1, 1),
+                                        self.here(field.len()),
+                                        self.expr_location(value),
                                     ),
                                 );
                             }
                         } else {
                             self.handler.error(
-                                "E0609",
+                                &codes::E0609,
                                 &format!("No field '{}' on type '{}'", field, struct_name),
                                 ErrorContext {
-                                    primary_location: self.make_location(1, 1, field.len()),
+                                    primary_location: self.here(field.len()),
                                     secondary_locations: vec![],
                                     help_message: Some(format!(
                                         "Struct '{}' does not have a field named '{}'.",
@@ -1006,16 +2081,16 @@ impl TypeChecker {
                     match &obj_type {
                         Type::Struct { name } => {
                             if let Some(struct_info) = self.structs.get(name) {
-                                if let Some((field_type, _, _)) = struct_info.fields.get(field) {
+                                if let Some((field_type, _, _, _)) = struct_info.fields.get(field) {
                                     if !self.types_compatible(field_type, &value_type) {
                                         self.handler.error(
-                                            "E0308",
+                                            &codes::E0308,
                                             &format!("Type mismatch in field '{}' assignment", field),
                                             type_mismatch_error(
                                                 &self.type_to_string(field_type),
                                                 &self.type_to_string(&value_type),
-                                                self.make_location(1, 1, field.len()),
-                                                self.make_location(1, 1, 1),
+                                                self.here(field.len()),
+                                                self.expr_location(value),
                                             ),
                                         );
                                     }
@@ -1032,13 +2107,21 @@ impl TypeChecker {
     }
 
     fn infer_expr_type(&self, expr: &Expr) -> Type {
-        match expr {
-            Expr::Number(_) | Expr::HexNumber(_) | Expr::BinaryNumber(_) | Expr::OctalNumber(_) => Type::i32(),
-            Expr::Float(_) => Type::f32(),
-            Expr::String(_) => Type::Str { len_type: Box::new(Type::i64()) },
-            Expr::Bool(_) => Type::Bool,
-            Expr::None => Type::Option { inner: Box::new(Type::Any) },
-            Expr::Var(name) => {
+        let ty = self.infer_kind_type(expr);
+        if expr.location.line > 0 {
+            self.expr_types.borrow_mut().push((expr.location(), ty.clone()));
+        }
+        ty
+    }
+
+    fn infer_kind_type(&self, expr: &Expr) -> Type {
+        match &expr.kind {
+            ExprKind::Number(_) | ExprKind::HexNumber(_) | ExprKind::BinaryNumber(_) | ExprKind::OctalNumber(_) => Type::i32(),
+            ExprKind::Float(_) => Type::f32(),
+            ExprKind::String(_) => Type::Str { len_type: Box::new(Type::i64()) },
+            ExprKind::Bool(_) => Type::Bool,
+            ExprKind::None => Type::Option { inner: Box::new(Type::Any) },
+            ExprKind::Var(name) => {
                 if name == "self" {
                     Type::SelfType
                 } else {
@@ -1046,14 +2129,15 @@ impl TypeChecker {
                 }
             }
              
-            Expr::CallNamed(struct_name, _) => {
-                if self.structs.contains_key(struct_name) {
-                    Type::Struct { name: struct_name.clone() }
-                } else {
-                    Type::Any
+            ExprKind::CallNamed(struct_name, args) => match self.structs.get(struct_name) {
+                Some(info) if !info.type_params.is_empty() => {
+                    let args = args.iter().map(|(field, arg)| (Some(field.clone()), arg)).collect();
+                    self.infer_generic_struct(struct_name, info, args, self.expr_location(expr))
                 }
-            }
-            Expr::BinOp(op, left, right) => {
+                Some(_) => Type::Struct { name: struct_name.clone() },
+                None => Type::Any,
+            },
+            ExprKind::BinOp(op, left, right) => {
                 let left_type = self.infer_expr_type(left);
                 let right_type = self.infer_expr_type(right);
 
@@ -1074,7 +2158,7 @@ impl TypeChecker {
                     }
                 }
             }
-            Expr::UnOp(op, operand) => {
+            ExprKind::UnOp(op, operand) => {
                 let operand_type = self.infer_expr_type(operand);
                 if self.is_void_type(&operand_type) {
                     Type::Void
@@ -1086,23 +2170,47 @@ impl TypeChecker {
                     }
                 }
             }
-            Expr::Call(func_name, _) => {
-                 
-                if self.structs.contains_key(func_name) {
+            ExprKind::Call(func_name, args) => {
+                if let Some(ty) = self.indirect_call_type(func_name, args, self.expr_location(expr)) {
+                    return ty;
+                }
+
+                if let Some(info) = self.structs.get(func_name) {
+                    if !info.type_params.is_empty() {
+                        let args = args.iter().map(|arg| (None, arg)).collect();
+                        return self.infer_generic_struct(func_name, info, args, self.expr_location(expr));
+                    }
                     return Type::Struct { name: func_name.clone() };
                 }
                 
-                if let Some(sig) = self.functions.get(func_name) {
-                    sig.return_type.clone()
-                } else if let Some(sig) = self.builtin_functions.get(func_name) {
-                    sig.return_type.clone()
-                } else if let Some(sig) = self.imported_functions.get(func_name) {
+                if let (Some(sig), Some(type_params)) = (self.functions.get(func_name), self.generic_functions.get(func_name)) {
+                    if sig.params.len() == args.len() {
+                        return self.infer_generic_call(func_name, sig, type_params, args, self.expr_location(expr));
+                    }
+                    Type::Any
+                } else if let Some(sig) = self.functions.get(func_name) {
+                    for ((_, param, _), arg) in sig.params.iter().zip(args) {
+                        self.function_pointer_error(param, arg);
+                    }
                     sig.return_type.clone()
+                } else if let Some(sig) = self.builtin_functions.get(func_name).or_else(|| self.imported_functions.get(func_name)) {
+                    let return_type = sig.return_type.clone();
+                    for arg in args {
+                        self.infer_expr_type(arg);
+                    }
+                    return_type
                 } else {
+                    for arg in args {
+                        self.infer_expr_type(arg);
+                    }
+                    if !INTRINSICS.contains(&func_name.as_str()) {
+                        let location = SourceLocation { length: func_name.chars().count(), ..self.expr_location(expr) };
+                        self.defer_error(self.undefined_function(func_name, args.len(), location));
+                    }
                     Type::Any
                 }
             }
-            Expr::Array(elements) => {
+            ExprKind::Array(elements) => {
                 if elements.is_empty() {
                     Type::Array {
                         element: Box::new(Type::Any),
@@ -1116,23 +2224,26 @@ impl TypeChecker {
                     }
                 }
             }
-            Expr::Tuple(elements) => {
+            ExprKind::Tuple(elements) => {
                 let fields = elements.iter().map(|e| self.infer_expr_type(e)).collect();
                 Type::Tuple { fields }
             }
-            Expr::Index(obj, _) => {
+            ExprKind::Index(obj, _) => {
                 let obj_type = self.infer_expr_type(obj);
                 match obj_type {
                     Type::Array { element, .. } => (*element).clone(),
                     _ => Type::Any,
                 }
             }
-            Expr::MemberAccess(obj, field) => {
+            ExprKind::MemberAccess(obj, field) => {
+                if let Some(ty) = self.enum_value_type(obj, field, self.expr_location(expr)) {
+                    return ty;
+                }
                 let obj_type = self.infer_expr_type(obj);
                 match obj_type {
                     Type::Struct { name } => {
                         if let Some(struct_info) = self.structs.get(&name) {
-                            if let Some((field_type, _, _)) = struct_info.fields.get(field) {
+                            if let Some((field_type, _, _, _)) = struct_info.fields.get(field) {
                                 field_type.clone()
                             } else {
                                 Type::Any
@@ -1141,22 +2252,73 @@ impl TypeChecker {
                             Type::Any
                         }
                     }
+                    Type::Generic { name, args } => match self.structs.get(&name) {
+                        Some(info) => {
+                            let bindings = info.type_params.iter().map(|(p, _)| p.clone()).zip(args).collect();
+                            info.fields.get(field).map_or(Type::Any, |(field_type, _, _, _)| field_type.substitute(&bindings))
+                        }
+                        None => Type::Any,
+                    },
                     _ => Type::Any,
                 }
             }
-            Expr::Some(inner) => Type::Option { inner: Box::new(self.infer_expr_type(inner)) },
-            Expr::ResultOk(inner) => Type::Result {
+            ExprKind::MethodCall(obj, method, args) => {
+                let ctor_args = args.iter().map(|arg| (None, arg)).collect();
+                if let Some(ty) = self.enum_constructor_type(obj, method, ctor_args, self.expr_location(expr)) {
+                    return ty;
+                }
+                match self.infer_expr_type(obj) {
+                    Type::Struct { name } => self
+                        .functions
+                        .get(&format!("{}.{}", name, method))
+                        .map_or(Type::Any, |sig| sig.return_type.clone()),
+                    Type::Trait { name } => self
+                        .traits
+                        .get(&name)
+                        .and_then(|trait_def| trait_def.methods.iter().find(|m| &m.name == method))
+                        .map_or(Type::Any, |m| m.return_type.clone()),
+                    Type::Param { name } => self.infer_bounded_method(&name, method, self.expr_location(expr)),
+                    _ => Type::Any,
+                }
+            }
+            ExprKind::MethodCallNamed(obj, method, args) => {
+                let ctor_args = args.iter().map(|(field, arg)| (Some(field.clone()), arg)).collect();
+                self.enum_constructor_type(obj, method, ctor_args, self.expr_location(expr)).unwrap_or(Type::Any)
+            }
+            ExprKind::Some(inner) => Type::Option { inner: Box::new(self.infer_expr_type(inner)) },
+            ExprKind::ResultOk(inner) => Type::Result {
                 ok: Box::new(self.infer_expr_type(inner)),
                 err: Box::new(Type::Any),
             },
-            Expr::ResultErr(inner) => Type::Result {
+            ExprKind::ResultErr(inner) => Type::Result {
                 ok: Box::new(Type::Any),
                 err: Box::new(self.infer_expr_type(inner)),
             },
-            Expr::Not(inner) => {
+            ExprKind::Not(inner) => {
                 let _ = self.infer_expr_type(inner);
                 Type::Bool
             }
+            ExprKind::Lambda { params, return_type, body } => {
+                let mut pending = self.pending_lambdas.borrow_mut();
+                if !pending.iter().any(|lambda| lambda.location == expr.location) {
+                    pending.push(expr.clone());
+                }
+                let captures = self.lambda_captures(params, body);
+                let params = params.iter().map(|(_, ty)| ty.clone()).collect();
+                let return_type = Box::new(return_type.clone());
+                if captures.is_empty() {
+                    Type::FnPtr { params, return_type }
+                } else {
+                    Type::Closure { params, return_type }
+                }
+            }
+            ExprKind::FuncAddr(name) => match self.functions.get(name) {
+                Some(sig) if !self.generic_functions.contains_key(name) => Type::FnPtr {
+                    params: sig.params.iter().map(|(_, ty, _)| ty.clone()).collect(),
+                    return_type: Box::new(sig.return_type.clone()),
+                },
+                _ => Type::Any,
+            },
             _ => Type::Any,
         }
     }
@@ -1182,6 +2344,7 @@ impl TypeChecker {
             (Type::Float { bits: b1 }, Type::Float { bits: b2 }) => b1 == b2,
             (Type::Bool, Type::Bool) => true,
             (Type::Void, Type::Void) => true,
+            (Type::Str { .. }, Type::Str { .. }) => true,
             (Type::ConstStr, Type::Str { .. }) => true,
             (Type::Str { .. }, Type::ConstStr) => true,
             (Type::Option { inner: exp }, Type::Option { inner: got }) => self.types_compatible(exp, got),
@@ -1200,6 +2363,21 @@ impl TypeChecker {
             }
 
             (Type::Struct { name: n1 }, Type::Struct { name: n2 }) => n1 == n2,
+            (Type::Trait { name: n1 }, Type::Trait { name: n2 }) => n1 == n2,
+            (Type::Trait { name: trait_name }, Type::Struct { name: struct_name }) => {
+                self.trait_impls.contains(&(struct_name.clone(), trait_name.clone()))
+            }
+            (Type::Trait { name: trait_name }, Type::Param { name }) => self.bound_of(name) == Some(trait_name),
+            (Type::Param { name: n1 }, Type::Param { name: n2 }) => n1 == n2,
+            (Type::Generic { name: n1, args: a1 }, Type::Generic { name: n2, args: a2 }) => {
+                n1 == n2 && a1.len() == a2.len() && a1.iter().zip(a2).all(|(e, g)| self.types_compatible(e, g))
+            }
+
+            // A function pointer converts to a closure with an empty environment.
+            (Type::Closure { params: p1, return_type: r1 }, Type::Closure { params: p2, return_type: r2 } | Type::FnPtr { params: p2, return_type: r2 })
+            | (Type::FnPtr { params: p1, return_type: r1 }, Type::FnPtr { params: p2, return_type: r2 }) => {
+                p1.len() == p2.len() && p1.iter().zip(p2).all(|(e, g)| self.types_compatible(e, g)) && self.types_compatible(r1, r2)
+            }
 
             (Type::Ref(exp), Type::Ref(got)) | 
             (Type::MutRef(exp), Type::MutRef(got)) |
@@ -1209,7 +2387,7 @@ impl TypeChecker {
         }
     }
 
-    fn type_to_string(&self, ty: &Type) -> String {
+    pub fn type_to_string(&self, ty: &Type) -> String {
         match ty {
             Type::Int { bits: 8, signed: true } => "int8".to_string(),
             Type::Int { bits: 16, signed: true } => "int16".to_string(),
@@ -1230,7 +2408,12 @@ impl TypeChecker {
             Type::ConstStr => "const str".to_string(),
             Type::Any => "any".to_string(),
             Type::SelfType => "Self".to_string(),
-            Type::Trait => "trait".to_string(),
+            Type::Trait { name } => format!("dyn {}", name),
+            Type::Param { name } => name.clone(),
+            Type::Generic { name, args } => {
+                let arg_strs: Vec<_> = args.iter().map(|a| self.type_to_string(a)).collect();
+                format!("{}[{}]", name, arg_strs.join(", "))
+            }
             Type::Option { inner } => format!("Option<{}>", self.type_to_string(inner)),
             Type::Result { ok, err } => format!("Result<{}, {}>", self.type_to_string(ok), self.type_to_string(err)),
             Type::Array { element, size } => {
@@ -1269,10 +2452,36 @@ impl TypeChecker {
             Type::Char { bits: 32, .. } => "char32".to_string(),
             Type::Char { bits, .. } => format!("char{}", bits),
             Type::StrSlice { .. } => "str".to_string(),
-            Type::FnPtr { params, return_type } => {
+            Type::FnPtr { params, return_type } => format!("^{}", self.type_to_string(&Type::Closure { params: params.clone(), return_type: return_type.clone() })),
+            Type::Closure { params, return_type } => {
                 let param_strs: Vec<_> = params.iter().map(|p| self.type_to_string(p)).collect();
-                format!("fn({}) -> {}", param_strs.join(", "), self.type_to_string(return_type))
+                format!("func({}): {}", param_strs.join(", "), self.type_to_string(return_type))
             }
         }
     }
 }
+
+impl Constructors for TypeChecker {
+    fn constructors(&self, ty: &Type) -> Option<Vec<(String, Vec<Type>)>> {
+        match ty {
+            Type::Bool => Some(vec![("true".to_string(), vec![]), ("false".to_string(), vec![])]),
+            Type::Option { inner } => Some(vec![("Some".to_string(), vec![(**inner).clone()]), ("None".to_string(), vec![])]),
+            Type::Result { ok, err } => Some(vec![("Ok".to_string(), vec![(**ok).clone()]), ("Err".to_string(), vec![(**err).clone()])]),
+            Type::Tuple { fields } => Some(vec![(String::new(), fields.clone())]),
+            Type::Struct { name } => self.enums.get(name).map(|info| {
+                info.variant_order
+                    .iter()
+                    .map(|variant| (format!("{}.{}", name, variant), info.variants[variant].field_types()))
+                    .collect()
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Binds the names in patterns that could not be checked, so the arm's body still sees them.
+fn bind_any(patterns: &[Pattern], bindings: &mut Vec<(String, Type)>) {
+    for pattern in patterns {
+        bindings.extend(pattern.bindings().into_iter().map(|(name, _)| (name.to_string(), Type::Any)));
+    }
+}
@@ -11,13 +11,13 @@ pub use regex::Regex;
 pub use libloading::{Library, Symbol};
 pub use std::sync::Arc;
 pub use levenshtein::levenshtein;
-pub use miette::{Diagnostic, NamedSource, Report, SourceSpan};
+pub use miette::{NamedSource, Report, SourceSpan};
 pub use ordered_float::OrderedFloat;
 pub use crate::Library::manager::FunctionSignature;
 pub use crate::Token::Storge::Token::Token;
-pub use crate::Token::Storge::AST::{Stmt, Function, ExternDecl, ExternFunction, ExternFunctionWithBody, CodegenConfig, CompilationMode, OptimizationLevel,
+pub use crate::Token::Storge::AST::{Stmt, SpannedStmt, Function, ExternDecl, ExternFunction, ExternFunctionWithBody, CodegenConfig, CompilationMode, CodegenUnit, OptimizationLevel,
     StructDef, StructField, TraitDef, TraitMethod, ImplBlock, ImplMethod, ExternFunctionMap,
-    ModuleImport, ModuleUse, ImportDecl, MatchCase, CastTarget, Codegen,
+    ModuleImport, ModuleUse, ImportDecl, MatchCase, Pattern, PatternKind, CastTarget, Codegen,
     ParamModifier, SelfModifier, Program, UndefinedFunction, UndefinedFunctions, ClassDef, Parser, EnumDef, EnumVariant
 };
 pub use crate::Gen::codegen::ErrorCheck;
@@ -25,10 +25,25 @@ pub use crate::Gen::config::ArchConfig;
 pub use crate::Token::Storge::AST::Type; 
 pub use crate::Gen::API::Clang::{Clang, TargetOS};
 pub use crate::Token::Lexer::*;
+pub use crate::Token::typechecker::TypeChecker;
+pub use crate::Token::lint::Linter;
+pub use crate::Token::exhaustive::{literal_key, useful, Constructors, Pat};
+pub use crate::Token::format::format_source;
 pub use crate::Token::Storge::AST::IR;
-pub use crate::Gen::Type::{EnumDefinition, StructDefinition, TypeRegistry};
+pub use crate::Gen::Type::{EnumDefinition, StructDefinition, TypeRegistry, VariantDefinition};
+pub use crate::Gen::API::codes::{self, ErrorCode, LintLevel};
 pub use crate::Gen::API::error::*;
-pub use crate::Token::Storge::Expr::Expr;
+pub use crate::Token::Storge::Expr::{default_location, Expr, ExprKind};
+pub use crate::Token::Helper::visit::free_variables;
 pub use crate::Token::Storge::AST::StructInfo;
 pub use crate::Library::manager::{DependencyInfo, PackageInfo, PackageInformation, PackageJson, FootprintPack, LibraryError, LibraryMetadata};
 pub use crate::Library::manager::LibraryManager;
+pub use crate::Driver::cli::{BuildArgs, CheckArgs, CleanArgs, Cli, Commands, FmtArgs, MessageFormat, OptLevel, TestArgs};
+pub use crate::Driver::analysis::Analysis;
+pub use crate::Driver::cache::{BuildCache, Fingerprint};
+pub use crate::Driver::fix::apply_fixes;
+pub use crate::Driver::layout::{BuildLayout, DEFAULT_TARGET_DIR};
+pub use crate::Driver::lsp::run_server;
+pub use crate::Driver::manifest::{Manifest, ManifestError, ManifestProfile, MANIFEST_FILE};
+pub use crate::Driver::module::{LinkedProgram, Module, ModuleError, ModuleGraph};
+pub use crate::Driver::profile::Profile;
@@ -5,3 +5,4 @@ pub mod import;
 pub mod Token;
 pub mod Gen;
 pub mod Library;
+pub mod Driver;
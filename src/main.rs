@@ -1,152 +1,450 @@
 use Vix::import::*;
+use clap::{CommandFactory, Parser as ClapParser};
 use std::env;
 use std::fs;
 use std::path::Path;
+use std::process::Command;
 
-fn print_help() {
-    println!("{}", "Vix Compiler".bright_cyan().bold());
-    println!("Usage: vix [command] [options]");
-    println!();
-    println!("Commands:");
-    println!("  run [--target OS]     Compile and run the program");
-    println!("  build [--target OS]   Compile the program without running");
-    println!("  path                  Show the Vix installation directory");
-    println!("  help                  Show this help message");
-    println!();
-    println!("Options:");
-    println!("  --debug               Enable debug output");
-    println!("  --target <OS>         Target operating system (windows, linux, macos, freebsd)");
-    println!("  --output <name>       Output executable name (default: program)");
-    println!();
-    println!("Examples:");
-    println!("  vix run                      # Compile and run for current OS");
-    println!("  vix run --target windows     # Compile for Windows");
-    println!("  vix build --target linux     # Build for Linux");
-    println!("  vix run --debug              # Run with debug output");
-    println!("  vix path                     # Show installation directory");
+fn print_install_path() {
+    match env::current_exe() {
+        Ok(exe_path) => {
+            // Get parent directory (remove the executable name)
+            if let Some(parent) = exe_path.parent() {
+                // Get the parent of bin directory (if in bin/)
+                if parent.file_name().and_then(|n| n.to_str()) == Some("bin") {
+                    if let Some(vix_root) = parent.parent() {
+                        println!("{}", vix_root.display());
+                    } else {
+                        println!("{}", parent.display());
+                    }
+                } else {
+                    println!("{}", parent.display());
+                }
+            } else {
+                eprintln!("Error: Could not determine parent directory");
+                std::process::exit(1);
+            }
+        }
+        Err(e) => {
+            eprintln!("Error: Could not get executable path: {}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+fn absolute_path(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir().map(|cwd| cwd.join(path)).unwrap_or_else(|_| path.to_path_buf())
+    }
+}
+
+/// Finds and loads `Vix.toml` (or falls back to the legacy layout) and moves into the project root.
+fn load_manifest(manifest_path: Option<PathBuf>, src_dir: Option<&Path>) -> Manifest {
+    let explicit_src_dir = src_dir.map(absolute_path);
+    let manifest_path = manifest_path.or_else(|| env::current_dir().ok().and_then(|cwd| Manifest::find(&cwd)));
+
+    let mut manifest = match manifest_path {
+        Some(path) => match Manifest::load(&path) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                eprintln!("{} {}", "Error:".red(), e);
+                std::process::exit(1);
+            }
+        },
+        None => Manifest::legacy(explicit_src_dir.clone().unwrap_or_else(|| PathBuf::from("src"))),
+    };
 
-    let command = if args.len() > 1 {
-        args[1].as_str()
+    if let Some(dir) = explicit_src_dir {
+        manifest.build.sources = vec![dir];
+    }
+
+    // Every relative path from here on is resolved against the project root.
+    if let Err(e) = env::set_current_dir(&manifest.root) {
+        eprintln!("{} Could not enter project directory {}: {}", "Error:".red(), manifest.root.display(), e);
+        std::process::exit(1);
+    }
+
+    manifest
+}
+
+/// `vix fix`: writes the machine-applicable fixes back to the sources. Fails only when
+/// errors remain that had nothing to apply.
+fn run_fixes(diagnostics: &[Diagnostic], quiet: bool) -> ! {
+    let applied = match apply_fixes(diagnostics) {
+        Ok(applied) => applied,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red(), e);
+            std::process::exit(1);
+        }
+    };
+    if !quiet {
+        for (file, count) in &applied {
+            println!("   {} Fixed {} issue(s) in {}", "success:".bright_green(), count, file);
+        }
+        if applied.is_empty() {
+            println!("   {} Nothing to fix", "→".bright_black());
+        }
+    }
+    let has_errors = diagnostics.iter().any(Diagnostic::is_error);
+    std::process::exit(if has_errors && applied.is_empty() { 1 } else { 0 });
+}
+
+/// `vix fmt`: formats the given files and directories, or the project's sources. With `--check`
+/// nothing is written and unformatted files are listed instead.
+fn run_fmt(args: &FmtArgs, manifest_path: Option<PathBuf>, quiet: bool) -> ! {
+    let mut files = Vec::new();
+    let listed = if args.paths.is_empty() {
+        load_manifest(manifest_path, None).source_files().map(|found| files.extend(found))
     } else {
-        "run"
+        args.paths.iter().try_for_each(|path| {
+            if path.is_dir() {
+                let mut manifest = Manifest::legacy(absolute_path(path));
+                manifest.build.extensions.push("vix".to_string());
+                manifest.source_files().map(|found| files.extend(found))
+            } else {
+                files.push(path.clone());
+                Ok(())
+            }
+        })
     };
+    if let Err(e) = listed {
+        eprintln!("{} {}", "Error:".red(), e);
+        std::process::exit(1);
+    }
 
-    if command == "help" || command == "--help" || command == "-h" {
-        print_help();
-        return;
+    let mut failed = false;
+    let mut unformatted = 0;
+    for path in &files {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("{} Failed to read {}: {}", "Error:".red(), path.display(), e);
+                failed = true;
+                continue;
+            }
+        };
+        let formatted = match format_source(&source, &path.display().to_string()) {
+            Ok(formatted) => formatted,
+            Err(diags) => {
+                for diag in diags {
+                    eprintln!("{}", diag.with_source(&source).render());
+                }
+                failed = true;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+        unformatted += 1;
+
+        if args.check {
+            let line = source.lines().zip(formatted.lines()).position(|(a, b)| a != b).unwrap_or_else(|| source.lines().count().min(formatted.lines().count()));
+            println!("   {} {}:{} is not formatted", "→".bright_black(), path.display(), line + 1);
+        } else if let Err(e) = fs::write(path, &formatted) {
+            eprintln!("{} Failed to write {}: {}", "Error:".red(), path.display(), e);
+            failed = true;
+        } else if !quiet {
+            println!("   {} Formatted {}", "success:".bright_green(), path.display());
+        }
     }
 
-    // Handle path command
-    if command == "path" {
-        match env::current_exe() {
-            Ok(exe_path) => {
-                // Get parent directory (remove the executable name)
-                if let Some(parent) = exe_path.parent() {
-                    // Get the parent of bin directory (if in bin/)
-                    if parent.file_name().and_then(|n| n.to_str()) == Some("bin") {
-                        if let Some(vix_root) = parent.parent() {
-                            println!("{}", vix_root.display());
-                        } else {
-                            println!("{}", parent.display());
-                        }
-                    } else {
-                        println!("{}", parent.display());
+    if !quiet && unformatted == 0 && !failed {
+        println!("   {} {} file(s) already formatted", "→".bright_black(), files.len());
+    }
+    std::process::exit(if failed || (args.check && unformatted > 0) { 1 } else { 0 });
+}
+
+/// `vix test`: runs each selected test in its own process, so that a failed assertion, a panic
+/// or a crash fails only that test.
+fn run_tests(exe_path: &Path, filter: Option<&str>, target: TargetOS, current_os: TargetOS, quiet: bool) -> ! {
+    if target != current_os {
+        eprintln!("{} Cannot run tests compiled for {} on {}", "Error:".red(), target.display_name(), current_os.display_name());
+        std::process::exit(1);
+    }
+    let listing = match Command::new(exe_path).output() {
+        Ok(listing) => listing,
+        Err(e) => {
+            eprintln!("{} Could not run {}: {}", "Error:".red(), exe_path.display(), e);
+            std::process::exit(1);
+        }
+    };
+    let names: Vec<String> = String::from_utf8_lossy(&listing.stdout).lines().map(str::to_string).collect();
+    let selected: Vec<&String> = names.iter().filter(|name| filter.is_none_or(|filter| name.contains(filter))).collect();
+
+    println!("\nrunning {} test(s)", selected.len());
+    let mut failures = Vec::new();
+    for name in &selected {
+        let passed = match Command::new(exe_path).arg(name.as_str()).output() {
+            Ok(output) => {
+                let passed = output.status.success();
+                if !passed {
+                    let mut log = String::from_utf8_lossy(&output.stdout).into_owned();
+                    log.push_str(&String::from_utf8_lossy(&output.stderr));
+                    if output.status.code().is_none() {
+                        log.push_str("the test was terminated by a signal\n");
                     }
-                } else {
-                    eprintln!("Error: Could not determine parent directory");
-                    std::process::exit(1);
+                    failures.push((name, log));
                 }
+                passed
             }
             Err(e) => {
-                eprintln!("Error: Could not get executable path: {}", e);
-                std::process::exit(1);
+                failures.push((name, format!("could not run the test: {}\n", e)));
+                false
             }
+        };
+        if !passed {
+            println!("test {} ... {}", name, "FAILED".red());
+        } else if !quiet {
+            println!("test {} ... {}", name, "ok".bright_green());
         }
-        return;
     }
 
-    let debug_mode = args.contains(&"--debug".to_string());
-    let should_run = command == "run";
+    if !failures.is_empty() {
+        println!("\nfailures:");
+        for (name, log) in &failures {
+            println!("\n---- {} ----\n{}", name, log.trim_end());
+        }
+    }
+    let result = if failures.is_empty() { "ok".bright_green() } else { "FAILED".red() };
+    println!(
+        "\ntest result: {}. {} passed; {} failed; {} filtered out",
+        result,
+        selected.len() - failures.len(),
+        failures.len(),
+        names.len() - selected.len()
+    );
+    std::process::exit(if failures.is_empty() { 0 } else { 1 });
+}
+
+fn run_program(exe_path: &Path, target: TargetOS, current_os: TargetOS) {
+    if target != current_os {
+        println!("\n{} Cannot run executable compiled for {} on {}", "Warning:".yellow(), target.display_name(), current_os.display_name());
+    } else if let Err(e) = Clang::run_executable(exe_path) {
+        eprintln!("\n{} Runtime error: {}", "Error:".red(), e);
+        std::process::exit(1);
+    }
+}
+
+/// Everything the final executable depends on. `inputs` are extra files (library sources)
+/// discovered while building.
+fn build_fingerprint(manifest: &Manifest, source_files: &[PathBuf], settings: &[(&str, String)], inputs: &[PathBuf]) -> String {
+    let mut fingerprint = Fingerprint::new();
+    for (label, value) in settings {
+        fingerprint.add_str(label, value);
+    }
+    fingerprint.add_file(&manifest.root.join(MANIFEST_FILE));
+    for file in source_files.iter().chain(inputs) {
+        fingerprint.add_file(file);
+    }
+    fingerprint.finish()
+}
+
+/// What the translation unit of module `index` depends on: its own source, the sources of the
+/// modules it depends on, and what every unit depends on apart from the other sources.
+fn unit_fingerprint(manifest: &Manifest, graph: &ModuleGraph, index: usize, settings: &[(&str, String)], inputs: &[PathBuf]) -> String {
+    let mut fingerprint = Fingerprint::new();
+    for (label, value) in settings {
+        fingerprint.add_str(label, value);
+    }
+    fingerprint.add_str("module", &graph.modules[index].name);
+    fingerprint.add_file(&manifest.root.join(MANIFEST_FILE));
+    for module in std::iter::once(index).chain(graph.dependencies(index)) {
+        fingerprint.add_file(&graph.modules[module].path);
+    }
+    for input in inputs {
+        fingerprint.add_file(input);
+    }
+    fingerprint.finish()
+}
+
+/// Lint levels and output format, in a stable order so they can be fingerprinted.
+fn diagnostic_settings(options: &DiagnosticOptions) -> String {
+    let mut allow: Vec<&String> = options.allow.iter().collect();
+    let mut deny: Vec<&String> = options.deny.iter().collect();
+    allow.sort();
+    deny.sort();
+    format!("{:?} allow={:?} deny={:?} deny_warnings={}", options.format, allow, deny, options.deny_warnings)
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let format = DiagnosticFormat::from(cli.message_format);
+    // JSON output is for tools, which read nothing but the diagnostics.
+    let quiet = cli.quiet || format == DiagnosticFormat::Json;
+    let human = format == DiagnosticFormat::Human;
 
-    let target_os = if let Some(pos) = args.iter().position(|arg| arg == "--target") {
-        if let Some(os_str) = args.get(pos + 1) {
-            match TargetOS::from_string(os_str) {
-                Some(os) => Some(os),
+    let (args, should_run, check_only, fix_mode, tests) = match cli.command {
+        None => (BuildArgs::default(), true, false, false, None),
+        Some(Commands::Run(args)) => (args, true, false, false, None),
+        Some(Commands::Build(args)) => (args, false, false, false, None),
+        Some(Commands::Check(check)) => (BuildArgs { src_dir: check.src_dir, ..BuildArgs::default() }, false, true, false, None),
+        Some(Commands::Fix(check)) => (BuildArgs { src_dir: check.src_dir, ..BuildArgs::default() }, false, true, true, None),
+        Some(Commands::Test(test)) => {
+            let args = BuildArgs { release: test.release, src_dir: test.src_dir.clone(), target_dir: test.target_dir.clone(), ..BuildArgs::default() };
+            (args, false, false, false, Some(test))
+        }
+        Some(Commands::Fmt(fmt)) => run_fmt(&fmt, cli.manifest_path.clone(), quiet),
+        Some(Commands::Clean(clean)) => {
+            let explicit_target_dir = clean.target_dir.as_deref().map(absolute_path);
+            let manifest = load_manifest(cli.manifest_path.clone(), None);
+            let target_dir = manifest.target_dir(explicit_target_dir.as_deref());
+            match BuildLayout::clean(&target_dir) {
+                Ok(true) => println!("   {} Removed {}", "success:".bright_green(), target_dir.display()),
+                Ok(false) => println!("   {} Nothing to clean", "→".bright_black()),
+                Err(e) => {
+                    eprintln!("{} Could not remove {}: {}", "Error:".red(), target_dir.display(), e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Commands::Path) => {
+            print_install_path();
+            return;
+        }
+        Some(Commands::Lsp) => {
+            if let Err(e) = run_server() {
+                eprintln!("{} Language server failed: {}", "Error:".red(), e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::Explain { code }) => {
+            match codes::lookup(&code) {
+                Some(entry) => print!("{}", entry.explain()),
                 None => {
-                    eprintln!("Error: Unknown target OS '{}'. Valid options: windows, linux, macos, freebsd", os_str);
+                    eprintln!("{} Unknown diagnostic code '{}'", "Error:".red(), code);
                     std::process::exit(1);
                 }
             }
-        } else {
-            eprintln!("Error: --target flag requires an OS name");
-            std::process::exit(1);
+            return;
+        }
+        Some(Commands::Completions { shell }) => {
+            clap_complete::generate(shell, &mut Cli::command(), "vix", &mut std::io::stdout());
+            return;
         }
-    } else {
-        None
     };
 
-    let output_name = if let Some(pos) = args.iter().position(|arg| arg == "--output") {
-        args.get(pos + 1).map(|s| s.as_str()).unwrap_or("program")
-    } else {
-        "program"
-    };
+    let debug_mode = cli.verbose > 0;
+
+    let explicit_target_dir = args.target_dir.as_deref().map(absolute_path);
+    let manifest = load_manifest(cli.manifest_path.clone(), args.src_dir.as_deref());
+
+    if debug_mode {
+        println!("   {} Project {} v{} at {}", "→".bright_black(), manifest.information.name, manifest.information.version, manifest.root.display());
+    }
+
+    let mut output_name = args.output.clone().unwrap_or_else(|| manifest.output_name());
+    if tests.is_some() {
+        output_name.push_str("-test");
+    }
+    let output_name = output_name.as_str();
+    // The test harness is cached separately so that it does not replace the program.
+    let cache_entry = if tests.is_some() { "test" } else { "build" };
 
     let current_os = TargetOS::current();
-    let target = target_os.unwrap_or(current_os);
-    let src_dir = Path::new("src");
-    
-    if !src_dir.exists() {
-        eprintln!("{} src/ directory not found!", "Error:".red());
+    let target = args.target.or(manifest.target()).unwrap_or(current_os);
+
+    let source_files = match manifest.source_files() {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red(), e);
+            std::process::exit(1);
+        }
+    };
+
+    if source_files.is_empty() {
+        eprintln!("{} No source files with extension(s) {} found", "Error:".red(), manifest.build.extensions.iter().map(|ext| format!(".{}", ext)).collect::<Vec<_>>().join(", "));
         std::process::exit(1);
     }
 
-    let mut source_files = Vec::new();
-    if let Ok(entries) = fs::read_dir(src_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("x") {
-                source_files.push(path);
+    let mut diagnostic_options = manifest.diagnostic_options(format);
+    let overrides = [(&cli.allow, LintLevel::Allow), (&cli.warn, LintLevel::Warn), (&cli.deny, LintLevel::Deny)];
+    for (names, level) in overrides {
+        for name in names {
+            match codes::warning_code(name) {
+                Some(code) => diagnostic_options.set_level(code, level),
+                None => {
+                    eprintln!("{} Unknown lint or warning code '{}'", "Error:".red(), name);
+                    std::process::exit(1);
+                }
             }
         }
     }
-
-    if source_files.is_empty() {
-        eprintln!("{} No .x files found in src/", "Error:".red());
-        std::process::exit(1);
+    diagnostic_options.deny_warnings = cli.deny_warnings;
+    let mut profile = manifest.profile(args.release);
+    if let Some(level) = args.opt_level {
+        profile.opt_level = level.into();
     }
 
-    let mut all_import_decls = Vec::new();
-    for source_file in &source_files {
-        let source_code = match fs::read_to_string(source_file) {
-            Ok(code) => code,
-            Err(e) => {
-                eprintln!("   {} Failed to read {}: {}", "Error:".red(), source_file.display(), e);
-                std::process::exit(1);
+    let layout = BuildLayout::new(&manifest.target_dir(explicit_target_dir.as_deref()), &profile.name, target);
+    let cache = BuildCache::new(&layout);
+    let exe_path = layout.executable(output_name);
+    let clang_version = if check_only { String::new() } else { Clang::version() };
+    let build_settings = [
+        ("target", target.dir_name().to_string()),
+        ("profile", format!("{:?}", profile)),
+        ("output", output_name.to_string()),
+        ("clang", clang_version.clone()),
+        ("diagnostics", diagnostic_settings(&diagnostic_options)),
+    ];
+
+    if !check_only {
+        let previous_inputs = cache.recorded_inputs(cache_entry);
+        let key = build_fingerprint(&manifest, &source_files, &build_settings, &previous_inputs);
+        if cache.is_fresh(cache_entry, &key, &exe_path) {
+            for warning in cache.recorded_warnings(cache_entry) {
+                eprintln!("{}", warning);
             }
-        };
+            if !quiet {
+                println!("   {} {} is up to date: {}", "success:".bright_green(), output_name, exe_path.display());
+            }
+            if should_run {
+                run_program(&exe_path, target, current_os);
+            }
+            if let Some(test) = &tests {
+                run_tests(&exe_path, test.filter.as_deref(), target, current_os, quiet);
+            }
+            return;
+        }
+    }
 
-        let mut lexer = Lexer::new(&source_code);
-        let tokens = lexer.tokenize();
+    let module_files: Vec<(String, PathBuf)> = source_files
+        .iter()
+        .map(|path| (manifest.module_name(path), path.clone()))
+        .collect();
 
-        if !lexer.errors.is_empty() {
-            for err in &lexer.errors {
-                eprintln!("   {} in {}: {}", "Error:".red(), source_file.display(), err.message);
+    let graph = match ModuleGraph::load(&module_files, &manifest.root) {
+        Ok(graph) => graph,
+        Err(errors) => {
+            for e in &errors {
+                match (e, format) {
+                    (_, DiagnosticFormat::Json) => {
+                        for diag in e.to_diagnostics() {
+                            eprintln!("{}", diag.to_json());
+                        }
+                    }
+                    (ModuleError::Syntax(_), _) => eprintln!("{}", e),
+                    _ => eprintln!("   {} {}", "Error:".red(), e),
+                }
+            }
+            if fix_mode {
+                let diagnostics: Vec<Diagnostic> = errors.iter().flat_map(ModuleError::to_diagnostics).collect();
+                run_fixes(&diagnostics, quiet);
             }
             std::process::exit(1);
         }
+    };
 
-        let parser = Parser::new(tokens, source_code, lexer.spans);
-        let (_, _, _, _, _, _, _, _, _, _, import_decls) = parser.parse();
-        all_import_decls.extend(import_decls);
-    }
+    let mut all_import_decls = graph.library_imports();
+    all_import_decls.extend(manifest.dependency_imports());
 
      
-    let footprint_packs = if !all_import_decls.is_empty() {
-        match LibraryManager::process_imports_from_decls(&all_import_decls, Some(target)) {
+    let footprint_packs = if !all_import_decls.is_empty() && !check_only {
+        match LibraryManager::process_imports_from_decls(&all_import_decls, Some(target), &profile) {
             Ok(packs) => {
                  
                 if let Err(e) = LibraryManager::validate_imports(&all_import_decls, &packs) {
@@ -167,25 +465,137 @@ fn main() {
      
     let arch = ArchConfig::x86_64();
 
-    let mut combined_source = String::new();
-    let main_filename = source_files.first().map(|p| p.display().to_string()).unwrap_or_else(|| "main.x".to_string());
+    let mut type_errors = false;
+    let mut resolved_functions = HashMap::new();
+    let mut reported = Vec::new();
+    let mut warnings = Vec::new();
+    for (index, module) in graph.modules.iter().enumerate() {
+        let mut parser_warnings = DiagnosticHandler::new(module.source.to_string());
+        parser_warnings.options = diagnostic_options.clone();
+        for warning in &module.warnings {
+            parser_warnings.emit(warning.clone());
+        }
+        type_errors |= parser_warnings.has_errors();
+        if fix_mode {
+            reported.extend(parser_warnings.diagnostics().iter().cloned());
+        }
+        warnings.extend(parser_warnings.diagnostics().iter().filter(|d| !d.is_error()).map(|d| d.format(format)));
+
+        let mut type_checker = TypeChecker::new(module.source.to_string())
+            .with_file(module.path.display().to_string())
+            .with_options(diagnostic_options.clone());
+        type_checker.register_module_imports(
+            &graph.visible_functions(index),
+            &graph.visible_structs(index),
+            &graph.visible_enums(index),
+            &graph.visible_traits(index),
+            &graph.visible_trait_impls(index),
+        );
+
+        if !type_checker.check_program(
+            &module.program,
+            &module.structs,
+            &module.enums,
+            &module.externs,
+            &module.impls,
+            &module.traits,
+            &module.library_imports,
+        ) {
+            type_errors = true;
+        }
+        if fix_mode {
+            reported.extend(type_checker.diagnostics().iter().cloned());
+        }
+        warnings.extend(type_checker.diagnostics().iter().filter(|d| !d.is_error()).map(|d| d.format(format)));
+
+        // What a module only imported is left to the module that defines it, whose names win.
+        for (name, signature) in type_checker.resolved_functions() {
+            match module.emitted_signature(&name, &signature) {
+                Some((emitted, signature)) => {
+                    resolved_functions.insert(emitted, signature);
+                }
+                None => {
+                    resolved_functions.entry(name).or_insert(signature);
+                }
+            }
+        }
+    }
 
-    let mut combined_source_code = String::new();
-    for source_file in &source_files {
-        let source_code = fs::read_to_string(source_file).unwrap();
-        combined_source_code.push_str(&source_code);
-        combined_source_code.push_str("\n\n");
+    if type_errors && fix_mode {
+        run_fixes(&reported, quiet);
+    }
+    if type_errors {
+        if human {
+            eprintln!("{} Type checking failed, aborting before code generation", "Error:".red());
+        }
+        std::process::exit(1);
     }
 
-    let mut lexer = Lexer::new(&combined_source_code);
-    let tokens = lexer.tokenize();
-    let parser = Parser::new(tokens, combined_source_code.clone(), lexer.spans.clone());
-    let (program, all_structs, all_enums, all_externs, _, _, _, all_impls, _, _, _) = parser.parse();
+    let has_main = |module: &Module| module.program.functions.iter().any(|f| f.name == "main");
+    let entry = match &manifest.build.entry {
+        Some(entry) => {
+            let entry_path = manifest.root.join(entry);
+            match graph.modules.iter().position(|m| m.path == entry_path) {
+                Some(index) if has_main(&graph.modules[index]) => index,
+                _ => {
+                    eprintln!("{} Entry file {} has no `main` function", "Error:".red(), entry_path.display());
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => graph.modules.iter().position(has_main).unwrap_or(0),
+    };
+    let main_filename = graph.modules[entry].path.display().to_string();
+    let entry_source = graph.modules[entry].source.to_string();
+    let module_sources: Vec<(String, String)> = graph
+        .modules
+        .iter()
+        .map(|m| (m.path.display().to_string(), m.source.to_string()))
+        .collect();
+    // Tests are listed by their source name, qualified with the module when there are several.
+    let test_cases: Vec<(String, String)> = graph
+        .modules
+        .iter()
+        .flat_map(|module| {
+            let qualify = graph.modules.len() > 1;
+            module.program.functions.iter().filter(|func| func.is_test).map(move |func| {
+                let name = if qualify { format!("{}::{}", module.name, func.name) } else { func.name.clone() };
+                (name, module.renames.get(&func.name).cloned().unwrap_or_else(|| func.name.clone()))
+            })
+        })
+        .collect();
+
+    // Each module is its own translation unit, keyed by what its code depends on, so that one
+    // whose key is unchanged keeps its generated C and object.
+    let library_inputs: Vec<PathBuf> = footprint_packs
+        .iter()
+        .flat_map(|pack| pack.inputs.iter().map(PathBuf::from))
+        .collect();
+    let unit_settings: Vec<(&str, String)> = build_settings.iter().cloned().chain([("unit", cache_entry.to_string())]).collect();
+    let unit_keys: Vec<String> = (0..graph.modules.len())
+        .map(|index| unit_fingerprint(&manifest, &graph, index, &unit_settings, &library_inputs))
+        .collect();
+    let unit_modules: Vec<(String, String)> = graph.modules.iter().map(|m| (m.name.clone(), m.path.display().to_string())).collect();
+    let mut first_impl = 0;
+    let units: Vec<CodegenUnit> = graph
+        .modules
+        .iter()
+        .zip(&unit_keys)
+        .map(|(module, key)| {
+            let impls = first_impl..first_impl + module.impls.len();
+            first_impl = impls.end;
+            CodegenUnit {
+                functions: module.renames.values().cloned().collect(),
+                impls,
+                cached: !fix_mode && cache.unit_source(key).exists(),
+                code: String::new(),
+            }
+        })
+        .collect();
 
-    let mut all_functions = program.functions;
-    let mut combined_source = combined_source_code;
+    let LinkedProgram { program, structs: all_structs, enums: all_enums, externs: all_externs, impls: all_impls, traits: all_traits } = graph.link();
 
-    if all_functions.is_empty() {
+    if program.functions.is_empty() {
         eprintln!("{} No functions found to compile", "Error:".red());
         std::process::exit(1);
     }
@@ -208,7 +618,19 @@ fn main() {
         }
     }
 
-    let mut codegen = Codegen::new(arch, combined_source, main_filename);
+    let mut codegen = Codegen::new(arch, entry_source, main_filename);
+    codegen.diagnostics.options = diagnostic_options;
+    for (file, source) in module_sources {
+        codegen.diagnostics.add_source(file, source);
+    }
+    codegen.user_functions.extend(resolved_functions);
+    if tests.is_some() {
+        codegen.compilation_mode = CompilationMode::Test { tests: test_cases };
+    }
+    if !check_only {
+        codegen.units = units;
+    }
+    profile.apply(&mut codegen.config);
 
     for func_sig in &all_library_functions {
         let params_str = if func_sig.parameters.is_empty() {
@@ -231,49 +653,170 @@ fn main() {
             println!("   {} Added forward declaration: {}", "success:".bright_green(), decl);
         }
     }
-    
-    let program = Program { functions: all_functions };
 
     let c_code = match codegen.codegen_program_full(
         &program, 
         &all_structs, 
         &all_enums, 
         &all_impls, 
+        &all_traits,
         &all_externs, 
         &all_library_includes,
         &all_library_functions
     ) {
         Ok(code) => code,
         Err(_) => {
-            eprintln!("{} Code generation failed", "Error:".red());
+            if human {
+                eprintln!("{} Code generation failed", "Error:".red());
+            }
             std::process::exit(1);
         }
     };
 
+    if fix_mode {
+        reported.extend(codegen.diagnostics.diagnostics().iter().cloned());
+        run_fixes(&reported, quiet);
+    }
+
+    warnings.extend(codegen.diagnostics.diagnostics().iter().filter(|d| !d.is_error()).map(|d| d.format(format)));
+    for (unit, (key, (_, path))) in codegen.units.iter().zip(unit_keys.iter().zip(&unit_modules)) {
+        if unit.cached {
+            for warning in cache.unit_warnings(key) {
+                eprintln!("{}", warning);
+                warnings.push(warning);
+            }
+        } else if !codegen.diagnostics.has_errors() {
+            let unit_warnings: Vec<String> = codegen
+                .diagnostics
+                .diagnostics()
+                .iter()
+                .filter(|d| !d.is_error() && d.context.primary_location.file == *path)
+                .map(|d| d.format(format))
+                .collect();
+            if let Err(e) = cache.record_unit_warnings(key, &unit_warnings) {
+                eprintln!("{} Could not write build cache: {}", "Warning:".yellow(), e);
+            }
+        }
+    }
+
+    if codegen.diagnostics.has_errors() {
+        if human {
+            let stage = if check_only { "Check" } else { "Build" };
+            eprintln!("{} {} failed with {} error(s)", "Error:".red(), stage, codegen.diagnostics.error_count);
+        }
+        std::process::exit(1);
+    }
+
+    if check_only {
+        if !quiet {
+            println!("   {} No errors found in {} file(s)", "success:".bright_green(), source_files.len());
+        }
+        return;
+    }
+
     let linked_libs = codegen.get_linked_libraries();
     if debug_mode && !linked_libs.is_empty() {
         println!("   {} Libraries to link: {:?}", "success:".bright_green(), linked_libs);
     }
 
-     
-    println!("   {} Compiling main program to object file", "success:".bright_green());
-    
-     
-    let main_obj = Path::new("main.o");
-    match Clang::compile_to_object(&c_code, main_obj, Some(target)) {
-        Ok(_) => println!("   {} Main object file created: {}", "success:".green(), main_obj.display()),
-        Err(e) => {
-            eprintln!("{} Main compilation failed: {}", "Error:".red().bold(), e);
+    if let Err(e) = layout.create() {
+        eprintln!("{} Could not create {}: {}", "Error:".red(), layout.dir.display(), e);
+        std::process::exit(1);
+    }
+
+    let mut object_key = Fingerprint::new();
+    object_key.add_str("c", &c_code);
+    for (label, value) in &build_settings {
+        object_key.add_str(label, value);
+    }
+    let main_obj_path = cache.object(&object_key.finish(), target);
+    let main_obj = main_obj_path.as_path();
+
+    if main_obj.exists() {
+        if debug_mode {
+            println!("   {} Reusing cached object {}", "→".bright_black(), main_obj.display());
+        }
+    } else {
+        if !quiet {
+            println!("   {} Compiling main program to object file", "success:".bright_green());
+        }
+
+        let built_obj = layout.object("main");
+        match Clang::compile_to_object(&c_code, &built_obj, Some(target), &profile) {
+            Ok(_) => {
+                if !quiet {
+                    println!("   {} Main object file created: {}", "success:".green(), built_obj.display());
+                }
+            }
+            Err(e) => {
+                eprintln!("{} Main compilation failed: {}", "Error:".red().bold(), e);
+                std::process::exit(1);
+            }
+        }
+
+        if let Err(e) = cache.store(&built_obj, main_obj) {
+            eprintln!("{} Could not cache {}: {}", "Error:".red(), built_obj.display(), e);
             std::process::exit(1);
         }
+
+        // Debug info refers to the generated C for code that has no Vix source line.
+        if debug_mode || profile.debug {
+            if debug_mode {
+                println!("   {} Kept C source in {}", "→".bright_black(), layout.intermediate_dir().display());
+            }
+        } else {
+            let _ = fs::remove_file(built_obj.with_extension("c"));
+        }
+    }
+
+    let mut object_files: Vec<PathBuf> = vec![main_obj.to_path_buf()];
+    for (unit, (key, (name, _))) in codegen.units.iter().zip(unit_keys.iter().zip(&unit_modules)) {
+        let unit_obj = cache.unit_object(key, target);
+        if unit_obj.exists() {
+            if debug_mode {
+                println!("   {} Reusing cached object {} for module {}", "→".bright_black(), unit_obj.display(), name);
+            }
+            object_files.push(unit_obj);
+            continue;
+        }
+
+        // The C is written where the cache keeps it, so the `#line`s that point into it stay valid.
+        let unit_c = cache.unit_source(key);
+        if !unit.cached {
+            let written = unit_c
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .map_err(|e| e.to_string())
+                .and_then(|_| Clang::write_c_source(&codegen.unit_source(unit), &unit_c));
+            if let Err(e) = written {
+                eprintln!("{} Could not write C for module {}: {}", "Error:".red(), name, e);
+                let _ = fs::remove_file(&unit_c);
+                std::process::exit(1);
+            }
+        }
+        if !quiet {
+            println!("   {} Compiling module {}", "success:".bright_green(), name);
+        }
+        let built_obj = layout.object(&format!("module-{}", name));
+        if let Err(e) = Clang::compile_c_file(&unit_c, &built_obj, target, &profile) {
+            eprintln!("{} Compilation of module {} failed: {}", "Error:".red().bold(), name, e);
+            // C that does not compile is not reused by the next build.
+            let _ = fs::remove_file(&unit_c);
+            let _ = fs::remove_file(&built_obj);
+            std::process::exit(1);
+        }
+        if let Err(e) = cache.store(&built_obj, &unit_obj) {
+            eprintln!("{} Could not cache {}: {}", "Error:".red(), built_obj.display(), e);
+            let _ = fs::remove_file(&built_obj);
+            std::process::exit(1);
+        }
+        object_files.push(unit_obj);
     }
 
-     
-    let mut object_files: Vec<&Path> = vec![main_obj];
     for pack in &footprint_packs {
         let lib_path = Path::new(&pack.source_library);
         if lib_path.exists() {
-            object_files.push(lib_path);
+            object_files.push(lib_path.to_path_buf());
             if debug_mode {
                 println!("   {} Linking library: {}", "→".bright_black(), lib_path.display());
             }
@@ -282,18 +825,25 @@ fn main() {
         }
     }
 
-    println!("   {} Linking executable with {} object file(s)", "→".bright_cyan(), object_files.len());
-    match Clang::link_executable(&object_files, output_name, linked_libs, Some(target)) {
+    if !quiet {
+        println!("   {} Linking executable with {} object file(s)", "→".bright_cyan(), object_files.len());
+    }
+    let object_files: Vec<&Path> = object_files.iter().map(PathBuf::as_path).collect();
+    match Clang::link_executable(&object_files, &exe_path, linked_libs, Some(target), &profile) {
         Ok(_) => {
+            if !quiet {
+                println!("   {} Executable linked: {}", "success:".green(), exe_path.display());
+            }
+            let key = build_fingerprint(&manifest, &source_files, &build_settings, &library_inputs);
+            if let Err(e) = cache.record(cache_entry, &key, &library_inputs, &warnings) {
+                eprintln!("{} Could not write build cache: {}", "Warning:".yellow(), e);
+            }
+
             if should_run {
-                if target != current_os {
-                    println!("\n{} Cannot run executable compiled for {} on {}", "Warning:".yellow(), target.display_name(), current_os.display_name());
-                } else {
-                    if let Err(e) = Clang::run_executable(output_name, Some(target)) {
-                        eprintln!("\n{} Runtime error: {}", "Error:".red(), e);
-                        std::process::exit(1);
-                    }
-                }
+                run_program(&exe_path, target, current_os);
+            }
+            if let Some(test) = &tests {
+                run_tests(&exe_path, test.filter.as_deref(), target, current_os, quiet);
             }
         }
         Err(e) => {
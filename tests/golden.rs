@@ -0,0 +1,253 @@
+//! Golden-file tests for the compiler. Every `tests/golden/*.x` fixture goes through the Lexer,
+//! Parser, TypeChecker and Codegen, and each stage's output is compared with a snapshot next to it:
+//!
+//! - `name.tokens`: one token per line with its line and column
+//! - `name.ast`: the parsed items
+//! - `name.c`: the emitted C, for fixtures without errors
+//! - `name.stdout`: what the program prints, checked only where clang is installed
+//!
+//! Diagnostics are not snapshotted. A fixture marks each one it expects with a comment on the
+//! line it points at, `//~ ERROR <code or message text>` or `//~ WARNING ...`; `//~^` points one
+//! line up. Any diagnostic without an annotation, or annotation without a diagnostic, fails.
+//! A `//@ warn: <lint>` line turns on a lint that is allowed by default, and files a fixture
+//! imports are read from `tests/golden/modules`, which holds no fixtures itself.
+//!
+//! Run with `VIX_BLESS=1` to write the snapshots from the current output instead of comparing.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use Vix::import::*;
+
+struct Expected {
+    line: usize,
+    error: bool,
+    text: String,
+}
+
+#[derive(Default)]
+struct Outcome {
+    tokens: String,
+    ast: String,
+    c_code: Option<String>,
+    libraries: Vec<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[test]
+fn golden() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden");
+    let bless = env::var_os("VIX_BLESS").is_some();
+    let clang = Command::new("clang").arg("--version").output().is_ok_and(|output| output.status.success());
+
+    let mut fixtures: Vec<PathBuf> = fs::read_dir(&dir)
+        .expect("tests/golden is missing")
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "x"))
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "no fixtures in {}", dir.display());
+
+    let mut failures = Vec::new();
+    for fixture in &fixtures {
+        let name = fixture.file_stem().unwrap().to_string_lossy().into_owned();
+        let source = fs::read_to_string(fixture).unwrap().replace("\r\n", "\n");
+        let outcome = compile(&source, &format!("tests/golden/{}.x", name));
+
+        let mut snapshots = vec![("tokens", Some(outcome.tokens.clone())), ("ast", Some(outcome.ast.clone()))];
+        snapshots.push(("c", outcome.c_code.clone()));
+        if let Some(c_code) = &outcome.c_code {
+            if c_code.contains("_error_") {
+                failures.push(format!("{}: the emitted C contains `_error_`", name));
+            }
+            if clang {
+                match run(c_code, &outcome.libraries, &name) {
+                    Ok(stdout) => snapshots.push(("stdout", Some(stdout))),
+                    Err(e) => failures.push(format!("{}: {}", name, e)),
+                }
+            }
+        }
+
+        for (extension, actual) in snapshots {
+            let path = fixture.with_extension(extension);
+            let Some(actual) = actual else {
+                if path.exists() {
+                    failures.push(format!("{}: has a .{} snapshot but produced none", name, extension));
+                }
+                continue;
+            };
+            if bless {
+                fs::write(&path, &actual).unwrap();
+                continue;
+            }
+            match fs::read_to_string(&path) {
+                Ok(expected) if expected.replace("\r\n", "\n") == actual => {}
+                Ok(expected) => failures.push(format!("{}.{} differs:\n{}", name, extension, diff(&expected.replace("\r\n", "\n"), &actual))),
+                Err(_) => failures.push(format!("{}.{} is missing; run with VIX_BLESS=1 to create it", name, extension)),
+            }
+        }
+
+        failures.extend(check_annotations(&name, &source, &outcome.diagnostics));
+    }
+
+    assert!(failures.is_empty(), "{} golden failure(s):\n\n{}", failures.len(), failures.join("\n\n"));
+}
+
+/// Runs the pipeline as `vix build` does for a single file, stopping at the first stage with errors.
+fn compile(source: &str, file: &str) -> Outcome {
+    let mut outcome = Outcome::default();
+
+    let mut lexer = Lexer::new(source).with_file(file);
+    let tokens = lexer.tokenize();
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.chars().enumerate().filter(|(_, c)| *c == '\n').map(|(i, _)| i + 1))
+        .collect();
+    for (token, span) in tokens.iter().zip(&lexer.spans) {
+        let line = line_starts.partition_point(|&start| start <= span.offset());
+        let column = span.offset() - line_starts[line - 1] + 1;
+        outcome.tokens.push_str(&format!("{}:{} {:?}\n", line, column, token));
+    }
+    if !lexer.errors.is_empty() {
+        outcome.diagnostics = lexer.errors;
+        return outcome;
+    }
+
+    let parser = Parser::new(tokens, source.to_string(), lexer.spans).with_file(file);
+    let (program, structs, enums, externs, _, _, _, impls, traits, _, imports, diagnostics) = parser.parse();
+    outcome.ast = format!("{:#?}\n{:#?}\n{:#?}\n{:#?}\n{:#?}\n{:#?}\n", program, structs, enums, externs, impls, traits);
+    outcome.diagnostics = diagnostics;
+    if outcome.diagnostics.iter().any(Diagnostic::is_error) {
+        return outcome;
+    }
+
+    let mut options = DiagnosticOptions::default();
+    for lint in source.lines().filter_map(|line| line.trim().strip_prefix("//@ warn:")) {
+        let code = codes::warning_code(lint.trim()).unwrap_or_else(|| panic!("{}: no lint named `{}`", file, lint.trim()));
+        options.set_level(code, LintLevel::Warn);
+    }
+    let mut type_checker = TypeChecker::new(source.to_string()).with_file(file).with_options(options);
+
+    let (mut all_program, mut all_structs, mut all_enums, mut all_traits) = (program.clone(), structs.clone(), enums.clone(), traits.clone());
+    let mut library_imports = Vec::new();
+    for import in imports {
+        let ImportDecl::FileImport { name, from } = &import else {
+            library_imports.push(import);
+            continue;
+        };
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(from);
+        let text = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: cannot read {}: {}", file, path.display(), e));
+        let Ok((module, _)) = Module::parse_source(from.clone(), &path, text) else {
+            panic!("{}: {} does not parse", file, path.display());
+        };
+        let functions: Vec<Function> = module.program.functions.into_iter().filter(|f| &f.name == name).collect();
+        let structs: Vec<StructDef> = module.structs.into_iter().filter(|s| &s.name == name).collect();
+        let enums: Vec<EnumDef> = module.enums.into_iter().filter(|e| &e.name == name).collect();
+        let traits: Vec<TraitDef> = module.traits.into_iter().filter(|t| &t.name == name).collect();
+        type_checker.register_module_imports(&functions, &structs, &enums, &traits, &[]);
+        all_program.functions.extend(functions);
+        all_structs.extend(structs);
+        all_enums.extend(enums);
+        all_traits.extend(traits);
+    }
+
+    let checked = type_checker.check_program(&program, &structs, &enums, &externs, &impls, &traits, &library_imports);
+    outcome.diagnostics.extend(type_checker.diagnostics().iter().cloned());
+    if !checked {
+        return outcome;
+    }
+
+    let mut codegen = Codegen::new(ArchConfig::x86_64(), source.to_string(), file.to_string());
+    codegen.user_functions.extend(type_checker.resolved_functions());
+    let c_code = codegen.codegen_program_full(&all_program, &all_structs, &all_enums, &impls, &all_traits, &externs, &[], &[]);
+    outcome.diagnostics.extend(codegen.diagnostics.diagnostics().iter().cloned());
+    if !codegen.diagnostics.has_errors() {
+        outcome.c_code = c_code.ok();
+        outcome.libraries = codegen.get_linked_libraries().to_vec();
+    }
+    outcome
+}
+
+/// Builds the emitted C with clang and returns what the program printed.
+fn run(c_code: &str, libraries: &[String], name: &str) -> Result<String, String> {
+    let dir = env::temp_dir().join(format!("vix-golden-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let object = dir.join(format!("{}.o", name));
+    let exe = dir.join(name);
+    let profile = Profile::dev();
+
+    let built = Clang::compile_to_object(c_code, &object, None, &profile)
+        .and_then(|_| Clang::link_executable(&[object.as_path()], &exe, libraries, None, &profile));
+    let output = built.map_err(|e| format!("clang rejected the emitted C: {}", e)).and_then(|_| {
+        Command::new(&exe)
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+            .map_err(|e| format!("could not run the program: {}", e))
+    });
+    let _ = fs::remove_dir_all(&dir);
+    output
+}
+
+fn check_annotations(name: &str, source: &str, diagnostics: &[Diagnostic]) -> Vec<String> {
+    let mut expected: Vec<Expected> = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let Some(at) = line.find("//~") else { continue };
+        let rest = &line[at + 3..];
+        let up = rest.chars().take_while(|&c| c == '^').count();
+        let rest = rest[up..].trim();
+        let (error, text) = if let Some(text) = rest.strip_prefix("ERROR") {
+            (true, text)
+        } else if let Some(text) = rest.strip_prefix("WARNING") {
+            (false, text)
+        } else {
+            return vec![format!("{}:{}: annotations start with `//~ ERROR` or `//~ WARNING`", name, index + 1)];
+        };
+        expected.push(Expected { line: index + 1 - up, error, text: text.trim().to_string() });
+    }
+
+    let mut failures = Vec::new();
+    let mut unmatched: Vec<&Diagnostic> = diagnostics.iter().collect();
+    for annotation in &expected {
+        let found = unmatched.iter().position(|diag| {
+            diag.context.primary_location.line == annotation.line
+                && diag.is_error() == annotation.error
+                && (diag.code == annotation.text || diag.message.contains(&annotation.text))
+        });
+        match found {
+            Some(index) => {
+                unmatched.remove(index);
+            }
+            None => {
+                let level = if annotation.error { "error" } else { "warning" };
+                failures.push(format!("{}:{}: expected {} `{}` was not reported", name, annotation.line, level, annotation.text));
+            }
+        }
+    }
+    for diag in unmatched {
+        let level = if diag.is_error() { "error" } else { "warning" };
+        failures.push(format!(
+            "{}:{}: unexpected {} {}: {}",
+            name, diag.context.primary_location.line, level, diag.code, diag.message
+        ));
+    }
+    failures
+}
+
+/// The first few lines that differ, for the failure message.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut lines = Vec::new();
+    for index in 0..expected.len().max(actual.len()) {
+        let (old, new) = (expected.get(index), actual.get(index));
+        if old != new {
+            lines.push(format!("  line {}:\n  - {}\n  + {}", index + 1, old.unwrap_or(&""), new.unwrap_or(&"")));
+        }
+        if lines.len() == 5 {
+            lines.push("  ...".to_string());
+            break;
+        }
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,36 @@
+extern "C" from "c":
+    func atexit(callback: ^func(): void): int32
+end
+
+public func adder(n: int32): func(int32): int32
+    return func(x: int32): int32 => x + n //~ ERROR E0373
+end
+
+public func callbacks()
+    k: int32 = 3
+    atexit(func(): void => print(k)) //~ ERROR E0434
+    _pointer: ^func(int32): int32 = func(x: int32): int32 => x + k //~ ERROR E0434
+    f: func(int32): int32 = func(x: int32): int32 => x * k
+    _extra: int32 = f(1, 2) //~ ERROR E0061
+    _mismatch: int32 = f(true) //~ ERROR E0308
+    _body: func(int32): bool = func(x: int32): bool => x //~ ERROR E0308
+end
+
+public func escapes(flag: bool): int32
+    k: int32 = 2
+    mut outer: func(int32): int32 = func(x: int32): int32 => x
+    while flag do
+        inner: func(int32): int32 = func(x: int32): int32 => x * k
+        outer = inner //~ ERROR E0373
+        outer = func(x: int32): int32 => x + k //~ ERROR E0373
+    end
+    outer = func(x: int32): int32 => x - k
+    local: func(int32): int32 = outer
+    return local(1) + 0
+end
+
+public func returns_variable(): func(int32): int32
+    k: int32 = 2
+    f: func(int32): int32 = func(x: int32): int32 => x * k
+    return f //~ ERROR E0373
+end
@@ -0,0 +1,165 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <stdbool.h>
+#include <string.h>
+#include <time.h>
+typedef struct { char* ptr; int64_t len; } String;
+
+typedef int32_t (*FnPtr_int32_int32)(int32_t);
+static int32_t FnPtr_int32_int32_call(void* f, int32_t a0) { return ((FnPtr_int32_int32)f)(a0); }
+
+typedef struct {
+    int32_t (*call)(void*, int32_t);
+    void* env;
+} Closure_int32_int32;
+
+int32_t apply(Closure_int32_int32 var_f, int32_t var_x);
+int32_t twice(int32_t var_x);
+int32_t vix_main();
+typedef void (*FnPtr_void)(void);
+static void FnPtr_void_call(void* f) { ((FnPtr_void)f)(); }
+
+extern int32_t atexit(FnPtr_void callback);
+typedef struct {
+    int32_t var_k;
+} vix_lambda_0_env;
+
+typedef int32_t (*FnPtr_int32)(void);
+static int32_t FnPtr_int32_call(void* f) { return ((FnPtr_int32)f)(); }
+
+typedef struct {
+    int32_t (*call)(void*);
+    void* env;
+} Closure_int32;
+
+typedef struct {
+    int32_t* var_count;
+} vix_lambda_1_env;
+
+typedef struct {
+    int32_t var_k;
+} vix_lambda_2_env;
+
+
+static String vix_string_concat(String s1, String s2) {
+    String res;
+    res.len = s1.len + s2.len;
+    res.ptr = (char*)malloc(res.len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, s1.ptr, s1.len);
+        memcpy(res.ptr + s1.len, s2.ptr, s2.len);
+        res.ptr[res.len] = '\0';
+    }
+    return res;
+}
+
+static String vix_int_to_str(int64_t val) {
+    char buf[32];
+    int len = snprintf(buf, sizeof(buf), "%lld", (long long)val);
+    String res;
+    res.len = len;
+    res.ptr = (char*)malloc(len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, buf, len);
+        res.ptr[len] = '\0';
+    }
+    return res;
+}
+
+static String vix_string_from_const(const char* s) {
+    String res;
+    res.ptr = (char*)s;
+    res.len = s ? strlen(s) : 0;
+    return res;
+}
+
+
+int32_t apply(Closure_int32_int32 var_f, int32_t var_x) {
+int32_t t0 = var_f.call(var_f.env, var_x);
+return t0;
+}
+
+int32_t twice(int32_t var_x) {
+int32_t t0 = 2;
+int32_t t1 = var_x * t0;
+return t1;
+}
+
+static int32_t vix_lambda_0(void* vix_env_ptr, int32_t var_x) {
+vix_lambda_0_env* vix_env = vix_env_ptr;
+int32_t t1 = var_x * vix_env->var_k;
+return t1;
+}
+
+static int32_t vix_lambda_1(void* vix_env_ptr) {
+vix_lambda_1_env* vix_env = vix_env_ptr;
+return (*vix_env->var_count);
+}
+
+
+static void* x_array_init() {
+    struct { void* ptr; size_t len; size_t capacity; }* arr = malloc(sizeof(*arr));
+    arr->ptr = malloc(16 * sizeof(void*));
+    arr->len = 0;
+    arr->capacity = 16;
+    return arr;
+}
+
+static void* x_hashmap_init() { return malloc(1024); }
+static void* x_vector_init() { return malloc(1024); }
+static void* x_buffer_init() { return malloc(1024); }
+
+static int x_random(int max) {
+    if (max <= 0) return 0;
+    static int seeded = 0;
+    if (!seeded) { srand((unsigned)time(NULL)); seeded = 1; }
+    return rand() % max;
+}
+static int32_t vix_lambda_2(void* vix_env_ptr, int32_t var_x) {
+vix_lambda_2_env* vix_env = vix_env_ptr;
+int32_t t16 = var_x - vix_env->var_k;
+return t16;
+}
+
+static void vix_lambda_3(void) {
+String t22 = { .ptr = "bye", .len = 3 };
+int32_t t23 = printf("%s\n", t22.ptr);
+}
+
+int32_t vix_main() {
+int32_t t0 = 3;
+const int32_t var_k = t0;
+vix_lambda_0_env t2 = { .var_k = var_k };
+Closure_int32_int32 t3 = { vix_lambda_0, &t2 };
+const Closure_int32_int32 var_times_k = t3;
+int32_t t4 = 5;
+int32_t t5 = var_times_k.call(var_times_k.env, t4);
+int32_t t6 = printf("%d\n", t5);
+int32_t t7 = 10;
+int32_t var_count = t7;
+vix_lambda_1_env t8 = { .var_count = &var_count };
+Closure_int32 t9 = { vix_lambda_1, &t8 };
+const Closure_int32 var_read_count = t9;
+int32_t t10 = 42;
+var_count = t10;
+int32_t t11 = var_read_count.call(var_read_count.env);
+int32_t t12 = printf("%d\n", t11);
+int32_t t13 = 21;
+int32_t t14 = apply((Closure_int32_int32){ FnPtr_int32_int32_call, (void*)twice }, t13);
+int32_t t15 = printf("%d\n", t14);
+vix_lambda_2_env t17 = { .var_k = var_k };
+Closure_int32_int32 t18 = { vix_lambda_2, &t17 };
+int32_t t19 = 10;
+int32_t t20 = apply(t18, t19);
+int32_t t21 = printf("%d\n", t20);
+atexit(vix_lambda_3);
+int32_t t24 = 0;
+return t24;
+}
+
+
+int main() {
+    vix_main();
+    return 0;
+}
@@ -0,0 +1,28 @@
+extern "C" from "c":
+    func atexit(callback: ^func(): void): int32
+end
+
+func apply(f: func(int32): int32, x: int32): int32
+    return f(x)
+end
+
+func twice(x: int32): int32
+    return x * 2
+end
+
+func main(): int32
+    k: int32 = 3
+    times_k: func(int32): int32 = func(x: int32): int32 => x * k
+    print(times_k(5))
+
+    mut count: int32 = 10
+    read_count: func(): int32 = func(): int32 => count
+    count = 42
+    print(read_count())
+
+    print(apply(&twice, 21))
+    print(apply(func(x: int32): int32 => x - k, 10))
+
+    atexit(func(): void => print("bye"))
+    return 0
+end
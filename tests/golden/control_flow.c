@@ -0,0 +1,69 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <stdbool.h>
+#include <string.h>
+#include <time.h>
+int32_t classify(int32_t var_n);
+int32_t vix_main();
+
+int32_t classify(int32_t var_n) {
+int32_t t0 = 10;
+bool t1 = var_n > t0;
+if (t1) {
+int32_t t2 = 2;
+return t2;
+}
+int32_t t3 = 0;
+bool t4 = var_n > t3;
+if (t4) {
+int32_t t5 = 1;
+return t5;
+}
+int32_t t6 = 0;
+return t6;
+}
+
+
+static void* x_array_init() {
+    struct { void* ptr; size_t len; size_t capacity; }* arr = malloc(sizeof(*arr));
+    arr->ptr = malloc(16 * sizeof(void*));
+    arr->len = 0;
+    arr->capacity = 16;
+    return arr;
+}
+
+static void* x_hashmap_init() { return malloc(1024); }
+static void* x_vector_init() { return malloc(1024); }
+static void* x_buffer_init() { return malloc(1024); }
+
+static int x_random(int max) {
+    if (max <= 0) return 0;
+    static int seeded = 0;
+    if (!seeded) { srand((unsigned)time(NULL)); seeded = 1; }
+    return rand() % max;
+}
+int32_t vix_main() {
+int32_t t0 = 0;
+int32_t var_count = t0;
+label_0:
+int32_t t1 = 3;
+bool t2 = var_count < t1;
+if (!t2) goto label_1;
+int32_t t3 = printf("%d\n", var_count);
+int32_t t4 = 1;
+var_count += t4;
+goto label_0;
+label_1:
+int32_t t5 = 42;
+int32_t t6 = classify(t5);
+int32_t t7 = printf("%d\n", t6);
+int32_t t8 = 0;
+return t8;
+}
+
+
+int main() {
+    vix_main();
+    return 0;
+}
@@ -0,0 +1,19 @@
+func classify(n: int32): int32
+    if n > 10 then
+        return 2
+    end
+    if n > 0 then
+        return 1
+    end
+    return 0
+end
+
+func main(): int32
+    mut count: int32 = 0
+    while count < 3 do
+        print(count)
+        count += 1
+    end
+    print(classify(42))
+    return 0
+end
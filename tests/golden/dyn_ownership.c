@@ -0,0 +1,333 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <stdbool.h>
+#include <string.h>
+#include <time.h>
+typedef struct { char* ptr; int64_t len; } String;
+
+typedef struct Shape_vtable Shape_vtable;
+typedef struct Shape_dyn {
+    void* self;
+    const Shape_vtable* vtable;
+} Shape_dyn;
+
+
+static String vix_string_concat(String s1, String s2) {
+    String res;
+    res.len = s1.len + s2.len;
+    res.ptr = (char*)malloc(res.len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, s1.ptr, s1.len);
+        memcpy(res.ptr + s1.len, s2.ptr, s2.len);
+        res.ptr[res.len] = '\0';
+    }
+    return res;
+}
+
+static String vix_int_to_str(int64_t val) {
+    char buf[32];
+    int len = snprintf(buf, sizeof(buf), "%lld", (long long)val);
+    String res;
+    res.len = len;
+    res.ptr = (char*)malloc(len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, buf, len);
+        res.ptr[len] = '\0';
+    }
+    return res;
+}
+
+static String vix_string_from_const(const char* s) {
+    String res;
+    res.ptr = (char*)s;
+    res.len = s ? strlen(s) : 0;
+    return res;
+}
+
+typedef struct Square {
+    int32_t side;
+} Square;
+
+Square Square_new(int32_t side);
+typedef struct Holder {
+    Shape_dyn shape;
+    int32_t count;
+} Holder;
+
+Holder Holder_new(Shape_dyn shape, int32_t count);
+struct Shape_vtable {
+    size_t vix_size;
+    int32_t (*area)(void* self);
+};
+
+static Shape_dyn Shape_clone(Shape_dyn value) {
+    void* boxed = malloc(value.vtable->vix_size);
+    memcpy(boxed, value.self, value.vtable->vix_size);
+    return (Shape_dyn){ boxed, value.vtable };
+}
+
+Shape_dyn make(int32_t var_n);
+Shape_dyn pass(Shape_dyn var_shape);
+int32_t total(Shape_dyn var_shape);
+int32_t held(Holder var_holder);
+int32_t vix_main();
+int32_t Square_area(Square* self);
+extern const Shape_vtable Square_Shape_vtable;
+Shape_dyn Square_as_Shape(Square value);
+
+Square Square_new(int32_t side) {
+    Square instance;
+    instance.side = side;
+    return instance;
+}
+
+Holder Holder_new(Shape_dyn shape, int32_t count) {
+    Holder instance;
+    instance.shape = shape;
+    instance.count = count;
+    return instance;
+}
+
+Shape_dyn make(int32_t var_n) {
+Square t0 = Square_new(var_n);
+const Square var_s = t0;
+return Square_as_Shape(var_s);
+}
+
+Shape_dyn pass(Shape_dyn var_shape) {
+return Shape_clone(var_shape);
+}
+
+int32_t total(Shape_dyn var_shape) {
+int32_t t0 = var_shape.vtable->area(var_shape.self);
+return t0;
+}
+
+
+static void* x_array_init() {
+    struct { void* ptr; size_t len; size_t capacity; }* arr = malloc(sizeof(*arr));
+    arr->ptr = malloc(16 * sizeof(void*));
+    arr->len = 0;
+    arr->capacity = 16;
+    return arr;
+}
+
+static void* x_hashmap_init() { return malloc(1024); }
+static void* x_vector_init() { return malloc(1024); }
+static void* x_buffer_init() { return malloc(1024); }
+
+static int x_random(int max) {
+    if (max <= 0) return 0;
+    static int seeded = 0;
+    if (!seeded) { srand((unsigned)time(NULL)); seeded = 1; }
+    return rand() % max;
+}
+int32_t held(Holder var_holder) {
+Shape_dyn t0 = var_holder.shape;
+int32_t t1 = total(t0);
+return t1;
+}
+
+int32_t vix_main() {
+void* t2 = NULL;
+void* t7 = NULL;
+void* t11 = NULL;
+void* t27 = NULL;
+void* t34 = NULL;
+void* t40 = NULL;
+void* t48 = NULL;
+void* t53 = NULL;
+void* t58 = NULL;
+void* t63 = NULL;
+void* t74 = NULL;
+int32_t t0 = 1;
+Shape_dyn t1 = make(t0);
+Shape_dyn var_best = t1;
+free(t2);
+t2 = var_best.self;
+int32_t t3 = 0;
+int32_t var_i = t3;
+label_0:
+int32_t t4 = 5;
+bool t5 = var_i < t4;
+if (!t5) goto label_1;
+Shape_dyn t6 = make(var_i);
+const Shape_dyn var_c = t6;
+free(t7);
+t7 = var_c.self;
+int32_t t8 = var_c.vtable->area(var_c.self);
+int32_t t9 = var_best.vtable->area(var_best.self);
+bool t10 = t8 > t9;
+if (t10) {
+var_best = Shape_clone(var_c);
+free(t11);
+t11 = var_best.self;
+}
+Shape_dyn t12 = make(var_i);
+int32_t t13 = total(t12);
+int32_t t14 = printf("%d\n", t13);
+free(t12.self);
+Shape_dyn t15 = pass(var_c);
+int32_t t16 = total(t15);
+int32_t t17 = printf("%d\n", t16);
+free(t15.self);
+int32_t t18 = 2;
+Shape_dyn t19 = make(t18);
+free(t19.self);
+int32_t t20 = 1;
+var_i += t20;
+goto label_0;
+label_1:
+int32_t t21 = var_best.vtable->area(var_best.self);
+int32_t t22 = printf("%d\n", t21);
+int32_t t23 = 7;
+Square t24 = Square_new(t23);
+const Square var_sq = t24;
+Square t25 = var_sq;
+Shape_dyn t26 = pass((Shape_dyn){ &t25, &Square_Shape_vtable });
+const Shape_dyn var_p = t26;
+free(t27);
+t27 = var_p.self;
+int32_t t28 = var_p.vtable->area(var_p.self);
+int32_t t29 = printf("%d\n", t28);
+int32_t t30 = 6;
+Shape_dyn t31 = make(t30);
+int32_t t32 = 1;
+Holder t33 = Holder_new(t31, t32);
+Holder var_h = t33;
+free(t34);
+t34 = var_h.shape.self;
+Shape_dyn t35 = var_h.shape;
+int32_t t36 = total(t35);
+int32_t t37 = printf("%d\n", t36);
+int32_t t38 = 2;
+Holder t39 = Holder_new(Square_as_Shape(var_sq), t38);
+const Holder var_boxed = t39;
+free(t40);
+t40 = var_boxed.shape.self;
+Shape_dyn t41 = var_boxed.shape;
+int32_t t42 = total(t41);
+int32_t t43 = printf("%d\n", t42);
+int32_t t44 = 3;
+int32_t t45 = 2;
+Shape_dyn t46 = make(t45);
+Holder t47 = Holder_new(t46, t44);
+const Holder var_named = t47;
+free(t48);
+t48 = var_named.shape.self;
+Shape_dyn t49 = var_named.shape;
+int32_t t50 = total(t49);
+int32_t t51 = printf("%d\n", t50);
+Shape_dyn t52 = var_h.shape;
+const Shape_dyn var_s2 = Shape_clone(t52);
+free(t53);
+t53 = var_s2.self;
+int32_t t54 = 5;
+Shape_dyn t55 = make(t54);
+int32_t t56 = 4;
+Holder t57 = Holder_new(t55, t56);
+var_h = t57;
+free(t58);
+t58 = var_h.shape.self;
+int32_t t59 = var_s2.vtable->area(var_s2.self);
+int32_t t60 = printf("%d\n", t59);
+int32_t t61 = 4;
+Shape_dyn t62 = make(t61);
+var_h.shape = t62;
+free(t63);
+t63 = var_h.shape.self;
+Shape_dyn t64 = var_h.shape;
+int32_t t65 = total(t64);
+int32_t t66 = printf("%d\n", t65);
+int32_t t67 = 3;
+Shape_dyn t68 = make(t67);
+int32_t t69 = 5;
+Holder t70 = Holder_new(t68, t69);
+int32_t t71 = held(t70);
+int32_t t72 = printf("%d\n", t71);
+free(t70.shape.self);
+Holder t73 = var_h;
+t73.shape = Shape_clone(t73.shape);
+const Holder var_copy = t73;
+free(t74);
+t74 = var_copy.shape.self;
+int32_t t75 = held(var_copy);
+int32_t t76 = printf("%d\n", t75);
+int32_t t77 = 3;
+Shape_dyn t78 = make(t77);
+int32_t t79 = total(t78);
+int32_t t80 = 5;
+bool t81 = t79 > t80;
+if (t81) {
+int32_t t82 = 0;
+int32_t t83 = t82;
+free(t78.self);
+free(t2);
+free(t7);
+free(t11);
+free(t27);
+free(t34);
+free(t40);
+free(t48);
+free(t53);
+free(t58);
+free(t63);
+free(t74);
+return t83;
+}
+free(t78.self);
+int32_t t84 = 1;
+int32_t t85 = t84;
+free(t2);
+free(t7);
+free(t11);
+free(t27);
+free(t34);
+free(t40);
+free(t48);
+free(t53);
+free(t58);
+free(t63);
+free(t74);
+return t85;
+free(t2);
+free(t7);
+free(t11);
+free(t27);
+free(t34);
+free(t40);
+free(t48);
+free(t53);
+free(t58);
+free(t63);
+free(t74);
+}
+
+int32_t Square_area(Square* self) {
+int32_t t86 = self->side;
+int32_t t87 = self->side;
+int32_t t88 = t86 * t87;
+return t88;
+}
+
+static int32_t Square_Shape_area(void* self) {
+    return Square_area((Square*)self);
+}
+
+const Shape_vtable Square_Shape_vtable = {
+    .vix_size = sizeof(Square),
+    .area = Square_Shape_area,
+};
+
+Shape_dyn Square_as_Shape(Square value) {
+    Square* boxed = malloc(sizeof(Square));
+    *boxed = value;
+    return (Shape_dyn){ boxed, &Square_Shape_vtable };
+}
+
+
+int main() {
+    vix_main();
+    return 0;
+}
@@ -0,0 +1,72 @@
+trait Shape:
+    func area(self): int32
+end
+
+struct Square:
+    side = int32
+end
+
+impl Shape for Square:
+    func area(self): int32
+        return self.side * self.side
+    end
+end
+
+struct Holder:
+    shape = dyn Shape
+    count = int32
+end
+
+func make(n: int32): dyn Shape
+    s: Square = Square(n)
+    return s
+end
+
+func pass(shape: dyn Shape): dyn Shape
+    return shape
+end
+
+func total(shape: dyn Shape): int32
+    return shape.area()
+end
+
+func held(holder: Holder): int32
+    return total(holder.shape)
+end
+
+func main(): int32
+    mut best: dyn Shape = make(1)
+    mut i: int32 = 0
+    while i < 5 do
+        c: dyn Shape = make(i)
+        if c.area() > best.area() then
+            best = c
+        end
+        print(total(make(i)))
+        print(total(pass(c)))
+        make(2)
+        i += 1
+    end
+    print(best.area())
+    sq: Square = Square(7)
+    p: dyn Shape = pass(sq)
+    print(p.area())
+    mut h: Holder = Holder(make(6), 1)
+    print(total(h.shape))
+    boxed: Holder = Holder(sq, 2)
+    print(total(boxed.shape))
+    named: Holder = Holder(count = 3, shape = make(2))
+    print(total(named.shape))
+    s2: dyn Shape = h.shape
+    h = Holder(make(5), 4)
+    print(s2.area())
+    h.shape = make(4)
+    print(total(h.shape))
+    print(held(Holder(make(3), 5)))
+    copy: Holder = h
+    print(held(copy))
+    if total(make(3)) > 5 then
+        return 0
+    end
+    return 1
+end
@@ -0,0 +1,16 @@
+enum Action:
+    Join(game: str, seat: int32),
+    Move(int32, int32),
+    Quit
+end
+
+public func build(): Action
+    _short: Action = Action.Join(game = "chess") //~ ERROR E0061
+    _unknown: Action = Action.Join(game = "chess", table = 2) //~ ERROR E0609
+    _twice: Action = Action.Join(game = "chess", game = "go") //~ ERROR E0062
+    _mismatch: Action = Action.Move(1, "two") //~ ERROR E0308
+    _bare: Action = Action.Move //~ ERROR E0061
+    _extra: Action = Action.Quit(1) //~ ERROR E0061
+    _missing: Action = Action.Fly(1) //~ ERROR E0599
+    return Action.Quit
+end
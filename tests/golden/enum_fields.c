@@ -0,0 +1,177 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <stdbool.h>
+#include <string.h>
+#include <time.h>
+typedef struct { char* ptr; int64_t len; } String;
+
+
+static String vix_string_concat(String s1, String s2) {
+    String res;
+    res.len = s1.len + s2.len;
+    res.ptr = (char*)malloc(res.len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, s1.ptr, s1.len);
+        memcpy(res.ptr + s1.len, s2.ptr, s2.len);
+        res.ptr[res.len] = '\0';
+    }
+    return res;
+}
+
+static String vix_int_to_str(int64_t val) {
+    char buf[32];
+    int len = snprintf(buf, sizeof(buf), "%lld", (long long)val);
+    String res;
+    res.len = len;
+    res.ptr = (char*)malloc(len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, buf, len);
+        res.ptr[len] = '\0';
+    }
+    return res;
+}
+
+static String vix_string_from_const(const char* s) {
+    String res;
+    res.ptr = (char*)s;
+    res.len = s ? strlen(s) : 0;
+    return res;
+}
+
+typedef struct Point {
+    int32_t x;
+    int32_t y;
+} Point;
+
+Point Point_new(int32_t x, int32_t y);
+typedef enum {
+    Shape__Dot = 0,
+    Shape__Circle = 1,
+    Shape__Empty = 2,
+} Shape_Tag;
+
+typedef struct {
+    uint8_t tag;
+    union {
+        struct {
+            Point field_0;
+        } Dot;
+        struct {
+            Point center;
+            int32_t radius;
+        } Circle;
+    } data;
+} Shape;
+typedef struct Drawing {
+    Shape shape;
+    int32_t layer;
+} Drawing;
+
+Drawing Drawing_new(Shape shape, int32_t layer);
+int32_t size(Shape var_shape);
+int32_t vix_main();
+
+Point Point_new(int32_t x, int32_t y) {
+    Point instance;
+    instance.x = x;
+    instance.y = y;
+    return instance;
+}
+
+Drawing Drawing_new(Shape shape, int32_t layer) {
+    Drawing instance;
+    instance.shape = shape;
+    instance.layer = layer;
+    return instance;
+}
+
+int32_t size(Shape var_shape) {
+switch (var_shape.tag) {
+case Shape__Dot: {
+if (var_shape.tag == Shape__Dot) {
+Point var_point = var_shape.data.Dot.field_0;
+int32_t t0 = var_point.x;
+return t0;
+goto label_0;
+}
+break;
+}
+case Shape__Circle: {
+if (var_shape.tag == Shape__Circle) {
+Point var_center = var_shape.data.Circle.center;
+int32_t var_radius = var_shape.data.Circle.radius;
+int32_t t1 = var_center.y;
+int32_t t2 = t1 * var_radius;
+return t2;
+goto label_0;
+}
+break;
+}
+case Shape__Empty: {
+if (var_shape.tag == Shape__Empty) {
+int32_t t3 = 0;
+return t3;
+goto label_0;
+}
+break;
+}
+}
+label_0:;
+int32_t t4 = 0;
+return t4;
+}
+
+
+static void* x_array_init() {
+    struct { void* ptr; size_t len; size_t capacity; }* arr = malloc(sizeof(*arr));
+    arr->ptr = malloc(16 * sizeof(void*));
+    arr->len = 0;
+    arr->capacity = 16;
+    return arr;
+}
+
+static void* x_hashmap_init() { return malloc(1024); }
+static void* x_vector_init() { return malloc(1024); }
+static void* x_buffer_init() { return malloc(1024); }
+
+static int x_random(int max) {
+    if (max <= 0) return 0;
+    static int seeded = 0;
+    if (!seeded) { srand((unsigned)time(NULL)); seeded = 1; }
+    return rand() % max;
+}
+int32_t vix_main() {
+int32_t t0 = 1;
+int32_t t1 = 2;
+Point t2 = Point_new(t0, t1);
+int32_t t3 = 3;
+Shape t4 = (Shape){ .tag = Shape__Circle, .data.Circle = { .center = t2, .radius = t3 } };
+int32_t t5 = 1;
+Drawing t6 = Drawing_new(t4, t5);
+const Drawing var_drawing = t6;
+int32_t t7 = 7;
+int32_t t8 = 8;
+Point t9 = Point_new(t7, t8);
+Shape t10 = (Shape){ .tag = Shape__Dot, .data.Dot = { .field_0 = t9 } };
+int32_t t11 = 2;
+Drawing t12 = Drawing_new(t10, t11);
+const Drawing var_dot = t12;
+Shape t13 = var_drawing.shape;
+int32_t t14 = size(t13);
+int32_t t15 = printf("%d\n", t14);
+Shape t16 = var_dot.shape;
+int32_t t17 = size(t16);
+int32_t t18 = printf("%d\n", t17);
+Shape t19 = (Shape){ .tag = Shape__Empty };
+int32_t t20 = size(t19);
+int32_t t21 = printf("%d\n", t20);
+int32_t t22 = 0;
+return t22;
+}
+
+
+int main() {
+    vix_main();
+    return 0;
+}
@@ -0,0 +1,36 @@
+struct Point:
+    x = int32
+    y = int32
+end
+
+enum Shape:
+    Dot(Point),
+    Circle(center: Point, radius: int32),
+    Empty
+end
+
+struct Drawing:
+    shape = Shape
+    layer = int32
+end
+
+func size(shape: Shape): int32
+    match shape:
+        case Shape.Dot(point):
+            return point.x
+        case Shape.Circle(center, radius):
+            return center.y * radius
+        case Shape.Empty:
+            return 0
+    end
+    return 0
+end
+
+func main(): int32
+    drawing: Drawing = Drawing(Shape.Circle(center = Point(1, 2), radius = 3), 1)
+    dot: Drawing = Drawing(shape = Shape.Dot(Point(7, 8)), layer = 2)
+    print(size(drawing.shape))
+    print(size(dot.shape))
+    print(size(Shape.Empty))
+    return 0
+end
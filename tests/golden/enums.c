@@ -0,0 +1,151 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <stdbool.h>
+#include <string.h>
+#include <time.h>
+typedef struct { char* ptr; int64_t len; } String;
+
+typedef enum {
+    Action__Join = 0,
+    Action__Move = 1,
+    Action__Quit = 2,
+} Action_Tag;
+
+typedef struct {
+    uint8_t tag;
+    union {
+        struct {
+            String game;
+            int32_t seat;
+        } Join;
+        struct {
+            int32_t field_0;
+            int32_t field_1;
+        } Move;
+    } data;
+} Action;
+int32_t describe(Action var_action);
+int32_t vix_main();
+
+static String vix_string_concat(String s1, String s2) {
+    String res;
+    res.len = s1.len + s2.len;
+    res.ptr = (char*)malloc(res.len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, s1.ptr, s1.len);
+        memcpy(res.ptr + s1.len, s2.ptr, s2.len);
+        res.ptr[res.len] = '\0';
+    }
+    return res;
+}
+
+static String vix_int_to_str(int64_t val) {
+    char buf[32];
+    int len = snprintf(buf, sizeof(buf), "%lld", (long long)val);
+    String res;
+    res.len = len;
+    res.ptr = (char*)malloc(len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, buf, len);
+        res.ptr[len] = '\0';
+    }
+    return res;
+}
+
+static String vix_string_from_const(const char* s) {
+    String res;
+    res.ptr = (char*)s;
+    res.len = s ? strlen(s) : 0;
+    return res;
+}
+
+
+int32_t describe(Action var_action) {
+switch (var_action.tag) {
+case Action__Join: {
+if (var_action.tag == Action__Join) {
+String var_game = var_action.data.Join.game;
+int32_t var_seat = var_action.data.Join.seat;
+int32_t t0 = printf("%s\n", var_game.ptr);
+return var_seat;
+goto label_0;
+}
+break;
+}
+case Action__Move: {
+if (var_action.tag == Action__Move) {
+int32_t var_x = var_action.data.Move.field_0;
+int32_t var_y = var_action.data.Move.field_1;
+int32_t t1 = printf("%d\n", var_x);
+return var_y;
+goto label_0;
+}
+break;
+}
+case Action__Quit: {
+if (var_action.tag == Action__Quit) {
+int32_t t2 = 1;
+int32_t t3 = -t2;
+return t3;
+goto label_0;
+}
+break;
+}
+}
+label_0:;
+int32_t t4 = 0;
+return t4;
+}
+
+
+static void* x_array_init() {
+    struct { void* ptr; size_t len; size_t capacity; }* arr = malloc(sizeof(*arr));
+    arr->ptr = malloc(16 * sizeof(void*));
+    arr->len = 0;
+    arr->capacity = 16;
+    return arr;
+}
+
+static void* x_hashmap_init() { return malloc(1024); }
+static void* x_vector_init() { return malloc(1024); }
+static void* x_buffer_init() { return malloc(1024); }
+
+static int x_random(int max) {
+    if (max <= 0) return 0;
+    static int seeded = 0;
+    if (!seeded) { srand((unsigned)time(NULL)); seeded = 1; }
+    return rand() % max;
+}
+int32_t vix_main() {
+String t0 = { .ptr = "chess", .len = 5 };
+int32_t t1 = 2;
+Action t2 = (Action){ .tag = Action__Join, .data.Join = { .game = t0, .seat = t1 } };
+const Action var_join = t2;
+int32_t t3 = 5;
+String t4 = { .ptr = "go", .len = 2 };
+Action t5 = (Action){ .tag = Action__Join, .data.Join = { .seat = t3, .game = t4 } };
+const Action var_swapped = t5;
+int32_t t6 = 3;
+int32_t t7 = 4;
+Action t8 = (Action){ .tag = Action__Move, .data.Move = { .field_0 = t6, .field_1 = t7 } };
+const Action var_step = t8;
+Action t9 = (Action){ .tag = Action__Quit };
+const Action var_quit = t9;
+int32_t t10 = describe(var_join);
+int32_t t11 = printf("%d\n", t10);
+int32_t t12 = describe(var_swapped);
+int32_t t13 = printf("%d\n", t12);
+int32_t t14 = describe(var_step);
+int32_t t15 = printf("%d\n", t14);
+int32_t t16 = describe(var_quit);
+int32_t t17 = printf("%d\n", t16);
+int32_t t18 = 0;
+return t18;
+}
+
+
+int main() {
+    vix_main();
+    return 0;
+}
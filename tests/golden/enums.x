@@ -0,0 +1,31 @@
+enum Action:
+    Join(game: str, seat: int32),
+    Move(int32, int32),
+    Quit
+end
+
+func describe(action: Action): int32
+    match action:
+        case Action.Join(game, seat):
+            print(game)
+            return seat
+        case Action.Move(x, y):
+            print(x)
+            return y
+        case Action.Quit:
+            return -1
+    end
+    return 0
+end
+
+func main(): int32
+    join: Action = Action.Join(game = "chess", seat = 2)
+    swapped: Action = Action.Join(seat = 5, game = "go")
+    step: Action = Action.Move(3, 4)
+    quit: Action = Action.Quit
+    print(describe(join))
+    print(describe(swapped))
+    print(describe(step))
+    print(describe(quit))
+    return 0
+end
@@ -0,0 +1,10 @@
+// A broken expression is recovered from inside its statement, so the functions after it parse.
+func a(): int32
+    print(1 +) //~ ERROR E0104
+    p: ^int32 = null //~ ERROR E0104
+    return 0
+end
+
+func main(): int32
+    return a()
+end
@@ -0,0 +1,39 @@
+trait Shape:
+    func area(self): int32
+end
+
+struct Square:
+    side = int32
+end
+
+impl Shape for Square:
+    func area(self): int32
+        return self.side * self.side
+    end
+end
+
+func same[T](a: T, b: T): T
+    return a
+end
+
+func measure[T: Shape](shape: T): int32
+    return shape.area()
+end
+
+public func zero[T](): int32 //~ ERROR E0282
+    return 0
+end
+
+public func draw[T: Drawable](item: T) //~ ERROR E0405
+end
+
+public func size[T](item: T): int32
+    return item.area() //~ ERROR E0599
+end
+
+func main(): int32
+    same(1, "one") //~ ERROR E0308
+    measure(5) //~ ERROR E0283
+    print(measure(Square(2)))
+    return 0
+end
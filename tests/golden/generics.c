@@ -0,0 +1,218 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <stdbool.h>
+#include <string.h>
+#include <time.h>
+typedef struct { char* ptr; int64_t len; } String;
+
+typedef struct Shape_vtable Shape_vtable;
+typedef struct Shape_dyn {
+    void* self;
+    const Shape_vtable* vtable;
+} Shape_dyn;
+
+
+static String vix_string_concat(String s1, String s2) {
+    String res;
+    res.len = s1.len + s2.len;
+    res.ptr = (char*)malloc(res.len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, s1.ptr, s1.len);
+        memcpy(res.ptr + s1.len, s2.ptr, s2.len);
+        res.ptr[res.len] = '\0';
+    }
+    return res;
+}
+
+static String vix_int_to_str(int64_t val) {
+    char buf[32];
+    int len = snprintf(buf, sizeof(buf), "%lld", (long long)val);
+    String res;
+    res.len = len;
+    res.ptr = (char*)malloc(len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, buf, len);
+        res.ptr[len] = '\0';
+    }
+    return res;
+}
+
+static String vix_string_from_const(const char* s) {
+    String res;
+    res.ptr = (char*)s;
+    res.len = s ? strlen(s) : 0;
+    return res;
+}
+
+typedef struct Square {
+    int32_t side;
+} Square;
+
+Square Square_new(int32_t side);
+typedef struct Pair_int32_str {
+    int32_t first;
+    String second;
+} Pair_int32_str;
+
+static inline Pair_int32_str Pair_int32_str_new(int32_t first, String second) {
+    Pair_int32_str instance;
+    instance.first = first;
+    instance.second = second;
+    return instance;
+}
+
+typedef struct Labeled {
+    Pair_int32_str pair;
+} Labeled;
+
+Labeled Labeled_new(Pair_int32_str pair);
+struct Shape_vtable {
+    size_t vix_size;
+    int32_t (*area)(void* self);
+};
+
+static Shape_dyn Shape_clone(Shape_dyn value) {
+    void* boxed = malloc(value.vtable->vix_size);
+    memcpy(boxed, value.self, value.vtable->vix_size);
+    return (Shape_dyn){ boxed, value.vtable };
+}
+
+typedef struct Pair_str_int32 {
+    String first;
+    int32_t second;
+} Pair_str_int32;
+
+static inline Pair_str_int32 Pair_str_int32_new(String first, int32_t second) {
+    Pair_str_int32 instance;
+    instance.first = first;
+    instance.second = second;
+    return instance;
+}
+
+int32_t vix_main();
+int32_t Square_area(Square* self);
+extern const Shape_vtable Square_Shape_vtable;
+Shape_dyn Square_as_Shape(Square value);
+static int32_t max_int32(int32_t var_a, int32_t var_b);
+static float max_float32(float var_a, float var_b);
+static Pair_str_int32 swap_int32_str(Pair_int32_str var_p);
+static String first_of_str_int32(Pair_str_int32 var_p);
+static int32_t larger_area_Square(Square var_a, Square var_b);
+
+Square Square_new(int32_t side) {
+    Square instance;
+    instance.side = side;
+    return instance;
+}
+
+Labeled Labeled_new(Pair_int32_str pair) {
+    Labeled instance;
+    instance.pair = pair;
+    return instance;
+}
+
+int32_t vix_main() {
+int32_t t0 = 3;
+int32_t t1 = 9;
+int32_t t2 = max_int32(t0, t1);
+int32_t t3 = printf("%d\n", t2);
+float t4 = 2.5;
+float t5 = 1.5;
+float t6 = max_float32(t4, t5);
+int32_t t7 = printf("%f\n", t6);
+int32_t t8 = 7;
+String t9 = { .ptr = "seven", .len = 5 };
+Pair_int32_str t10 = Pair_int32_str_new(t8, t9);
+const Pair_int32_str var_p = t10;
+int32_t t11 = var_p.first;
+String t12 = var_p.second;
+int32_t t13 = printf("%d %s\n", t11, t12.ptr);
+Pair_str_int32 t14 = swap_int32_str(var_p);
+const Pair_str_int32 var_q = t14;
+String t15 = var_q.first;
+int32_t t16 = var_q.second;
+int32_t t17 = printf("%s %d\n", t15.ptr, t16);
+String t18 = first_of_str_int32(var_q);
+int32_t t19 = printf("%s\n", t18.ptr);
+int32_t t20 = 2;
+Square t21 = Square_new(t20);
+int32_t t22 = 3;
+Square t23 = Square_new(t22);
+int32_t t24 = larger_area_Square(t21, t23);
+int32_t t25 = printf("%d\n", t24);
+int32_t t26 = 1;
+String t27 = { .ptr = "one", .len = 3 };
+Pair_int32_str t28 = Pair_int32_str_new(t26, t27);
+Labeled t29 = Labeled_new(t28);
+const Labeled var_l = t29;
+Pair_int32_str t30 = var_l.pair;
+const Pair_int32_str var_lp = t30;
+String t31 = var_lp.second;
+int32_t t32 = printf("%s\n", t31.ptr);
+int32_t t33 = 0;
+return t33;
+}
+
+int32_t Square_area(Square* self) {
+int32_t t34 = self->side;
+int32_t t35 = self->side;
+int32_t t36 = t34 * t35;
+return t36;
+}
+
+static int32_t Square_Shape_area(void* self) {
+    return Square_area((Square*)self);
+}
+
+const Shape_vtable Square_Shape_vtable = {
+    .vix_size = sizeof(Square),
+    .area = Square_Shape_area,
+};
+
+Shape_dyn Square_as_Shape(Square value) {
+    Square* boxed = malloc(sizeof(Square));
+    *boxed = value;
+    return (Shape_dyn){ boxed, &Square_Shape_vtable };
+}
+
+static int32_t larger_area_Square(Square var_a, Square var_b) {
+int32_t t0 = Square_area(&var_a);
+int32_t t1 = Square_area(&var_b);
+int32_t t2 = max_int32(t0, t1);
+return t2;
+}
+
+static String first_of_str_int32(Pair_str_int32 var_p) {
+String t0 = var_p.first;
+return t0;
+}
+
+static Pair_str_int32 swap_int32_str(Pair_int32_str var_p) {
+String t0 = var_p.second;
+int32_t t1 = var_p.first;
+Pair_str_int32 t2 = Pair_str_int32_new(t0, t1);
+return t2;
+}
+
+static float max_float32(float var_a, float var_b) {
+bool t0 = var_a > var_b;
+if (t0) {
+return var_a;
+}
+return var_b;
+}
+
+static int32_t max_int32(int32_t var_a, int32_t var_b) {
+bool t0 = var_a > var_b;
+if (t0) {
+return var_a;
+}
+return var_b;
+}
+
+
+int main() {
+    vix_main();
+    return 0;
+}
@@ -0,0 +1,56 @@
+trait Shape:
+    func area(self): int32
+end
+
+struct Square:
+    side = int32
+end
+
+impl Shape for Square:
+    func area(self): int32
+        return self.side * self.side
+    end
+end
+
+struct Pair[A, B]:
+    first = A
+    second = B
+end
+
+struct Labeled:
+    pair = Pair[int32, str]
+end
+
+func max[T](a: T, b: T): T
+    if a > b then
+        return a
+    end
+    return b
+end
+
+func swap[A, B](p: Pair[A, B]): Pair[B, A]
+    return Pair(p.second, p.first)
+end
+
+func first_of[A, B](p: Pair[A, B]): A
+    return p.first
+end
+
+func larger_area[T: Shape](a: T, b: T): int32
+    return max(a.area(), b.area())
+end
+
+func main(): int32
+    print(max(3, 9))
+    print(max(2.5, 1.5))
+    p: Pair[int32, str] = Pair(first = 7, second = "seven")
+    print(p.first, p.second)
+    q: Pair[str, int32] = swap(p)
+    print(q.first, q.second)
+    print(first_of(q))
+    print(larger_area(Square(2), Square(3)))
+    l: Labeled = Labeled(Pair(1, "one"))
+    lp: Pair[int32, str] = l.pair
+    print(lp.second)
+    return 0
+end
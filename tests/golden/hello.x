@@ -0,0 +1,5 @@
+// The smallest program: one call to print.
+func main(): int32
+    print("Hello, Vix")
+    return 0
+end
@@ -0,0 +1,115 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <stdbool.h>
+#include <string.h>
+#include <time.h>
+typedef struct { char* ptr; int64_t len; } String;
+
+typedef enum {
+    Pair__Both = 0,
+} Pair_Tag;
+
+typedef struct {
+    uint8_t tag;
+    union {
+        struct {
+            int32_t field_0;
+            int32_t field_1;
+        } Both;
+    } data;
+} Pair;
+int32_t helper();
+int32_t vix_main();
+int32_t twice(int32_t var_n);
+
+static String vix_string_concat(String s1, String s2) {
+    String res;
+    res.len = s1.len + s2.len;
+    res.ptr = (char*)malloc(res.len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, s1.ptr, s1.len);
+        memcpy(res.ptr + s1.len, s2.ptr, s2.len);
+        res.ptr[res.len] = '\0';
+    }
+    return res;
+}
+
+static String vix_int_to_str(int64_t val) {
+    char buf[32];
+    int len = snprintf(buf, sizeof(buf), "%lld", (long long)val);
+    String res;
+    res.len = len;
+    res.ptr = (char*)malloc(len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, buf, len);
+        res.ptr[len] = '\0';
+    }
+    return res;
+}
+
+static String vix_string_from_const(const char* s) {
+    String res;
+    res.ptr = (char*)s;
+    res.len = s ? strlen(s) : 0;
+    return res;
+}
+
+
+int32_t helper() {
+int32_t t0 = 1;
+return t0;
+}
+
+int32_t vix_main() {
+int32_t t0 = 5;
+const int32_t var_unused = t0;
+int32_t t1 = 10;
+int32_t var_limit = t1;
+int32_t t2 = printf("%d\n", var_limit);
+int32_t t3 = 2;
+const int32_t var_count = t3;
+int32_t t4 = 1;
+bool t5 = var_count > t4;
+if (t5) {
+int32_t t6 = 3;
+const int32_t var_count = t6;
+int32_t t7 = printf("%d\n", var_count);
+}
+bool t8 = var_count == var_count;
+if (t8) {
+int32_t t9 = printf("%d\n", var_count);
+}
+int32_t t10 = 1;
+int32_t t11 = 2;
+Pair t12 = (Pair){ .tag = Pair__Both, .data.Both = { .field_0 = t10, .field_1 = t11 } };
+const Pair var_pair = t12;
+switch (var_pair.tag) {
+case Pair__Both: {
+if (var_pair.tag == Pair__Both) {
+int32_t var_first = var_pair.data.Both.field_0;
+int32_t var_second = var_pair.data.Both.field_1;
+int32_t t13 = printf("%d\n", var_first);
+goto label_0;
+}
+break;
+}
+}
+label_0:;
+int32_t t14 = 0;
+return t14;
+String t15 = { .ptr = "never", .len = 5 };
+int32_t t16 = printf("%s\n", t15.ptr);
+}
+
+int32_t twice(int32_t var_n) {
+int32_t t0 = 2;
+int32_t t1 = var_n * t0;
+return t1;
+}
+
+
+int main() {
+    vix_main();
+    return 0;
+}
@@ -0,0 +1,33 @@
+//@ warn: shadowing
+import twice from "modules/helpers.x" //~ WARNING W0101
+
+enum Pair:
+    Both(int32, int32)
+end
+
+func helper(): int32 //~ WARNING W0102
+    return 1
+end
+
+func main(): int32
+    unused: int32 = 5 //~ WARNING W0100
+    mut limit: int32 = 10 //~ WARNING W0104
+    print(limit)
+    count: int32 = 2
+    if count > 1 then
+        count: int32 = 3 //~ WARNING W0105
+        print(count)
+    end
+    if count == count then //~ WARNING W0106
+        print(count)
+    end
+    unsafe: //~ WARNING W0107
+    end
+    pair: Pair = Pair.Both(1, 2)
+    match pair:
+        case Pair.Both(first, second): //~ WARNING W0100
+            print(first)
+    end
+    return 0
+    print("never") //~ WARNING W0103
+end
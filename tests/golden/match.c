@@ -0,0 +1,360 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <stdbool.h>
+#include <string.h>
+#include <time.h>
+typedef struct { char* ptr; int64_t len; } String;
+
+typedef enum {
+    Light__Red = 0,
+    Light__Yellow = 1,
+    Light__Green = 2,
+} Light_Tag;
+
+typedef struct {
+    uint8_t tag;
+} Light;
+String describe(Light var_light);
+int32_t size(int32_t var_n);
+typedef struct {
+    uint8_t tag;
+    int32_t value;
+} Option_int32;
+
+Option_int32 find(int32_t var_n);
+typedef struct {
+    uint8_t tag;
+    union {
+        int32_t ok;
+        String err;
+    } data;
+} Result_int32_str;
+
+Result_int32_str parse(int32_t var_n);
+int32_t value_or(int32_t var_n, int32_t var_fallback);
+int32_t check(int32_t var_n);
+typedef struct {
+    int32_t field_0;
+    int32_t field_1;
+} Tuple_int32_int32;
+
+int32_t quadrant(Tuple_int32_int32 var_point);
+int32_t name(String var_word);
+int32_t vix_main();
+
+static String vix_string_concat(String s1, String s2) {
+    String res;
+    res.len = s1.len + s2.len;
+    res.ptr = (char*)malloc(res.len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, s1.ptr, s1.len);
+        memcpy(res.ptr + s1.len, s2.ptr, s2.len);
+        res.ptr[res.len] = '\0';
+    }
+    return res;
+}
+
+static String vix_int_to_str(int64_t val) {
+    char buf[32];
+    int len = snprintf(buf, sizeof(buf), "%lld", (long long)val);
+    String res;
+    res.len = len;
+    res.ptr = (char*)malloc(len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, buf, len);
+        res.ptr[len] = '\0';
+    }
+    return res;
+}
+
+static String vix_string_from_const(const char* s) {
+    String res;
+    res.ptr = (char*)s;
+    res.len = s ? strlen(s) : 0;
+    return res;
+}
+
+
+String describe(Light var_light) {
+switch (var_light.tag) {
+case Light__Red: {
+if (var_light.tag == Light__Red) {
+String t0 = { .ptr = "stop", .len = 4 };
+return t0;
+goto label_0;
+}
+break;
+}
+case Light__Yellow: {
+if ((var_light.tag == Light__Yellow) || (var_light.tag == Light__Green)) {
+String t1 = { .ptr = "go", .len = 2 };
+return t1;
+goto label_0;
+}
+break;
+}
+case Light__Green: {
+if ((var_light.tag == Light__Yellow) || (var_light.tag == Light__Green)) {
+String t2 = { .ptr = "go", .len = 2 };
+return t2;
+goto label_0;
+}
+break;
+}
+}
+label_0:;
+String t3 = { .ptr = "?", .len = 1 };
+return t3;
+}
+
+int32_t size(int32_t var_n) {
+int32_t t0 = 0;
+if (var_n == t0) {
+int32_t t1 = 0;
+return t1;
+goto label_1;
+}
+int32_t t2 = 1;
+int32_t t3 = 10;
+if (var_n >= t2 && var_n < t3) {
+int32_t t4 = 1;
+return t4;
+goto label_1;
+}
+int32_t t5 = 10;
+int32_t t6 = 99;
+if (var_n >= t5 && var_n <= t6) {
+int32_t t7 = 2;
+return t7;
+goto label_1;
+}
+if (1) {
+int32_t var_x = var_n;
+int32_t t8 = 0;
+bool t9 = var_x < t8;
+if (t9) {
+int32_t t10 = 1;
+int32_t t11 = -t10;
+return t11;
+goto label_1;
+}
+}
+if (1) {
+int32_t t12 = 3;
+return t12;
+goto label_1;
+}
+label_1:;
+int32_t t13 = 9;
+return t13;
+}
+
+Option_int32 find(int32_t var_n) {
+int32_t t0 = 0;
+bool t1 = var_n > t0;
+if (t1) {
+Option_int32 t2 = { .tag = 1, .value = var_n };
+return t2;
+}
+Option_int32 t3 = { .tag = 0 };
+return t3;
+}
+
+Result_int32_str parse(int32_t var_n) {
+int32_t t0 = 0;
+bool t1 = var_n > t0;
+if (t1) {
+Result_int32_str t2 = { .tag = 0, .data.ok = var_n };
+return t2;
+}
+String t3 = { .ptr = "negative", .len = 8 };
+Result_int32_str t4 = { .tag = 1, .data.err = t3 };
+return t4;
+}
+
+
+static void* x_array_init() {
+    struct { void* ptr; size_t len; size_t capacity; }* arr = malloc(sizeof(*arr));
+    arr->ptr = malloc(16 * sizeof(void*));
+    arr->len = 0;
+    arr->capacity = 16;
+    return arr;
+}
+
+static void* x_hashmap_init() { return malloc(1024); }
+static void* x_vector_init() { return malloc(1024); }
+static void* x_buffer_init() { return malloc(1024); }
+
+static int x_random(int max) {
+    if (max <= 0) return 0;
+    static int seeded = 0;
+    if (!seeded) { srand((unsigned)time(NULL)); seeded = 1; }
+    return rand() % max;
+}
+int32_t value_or(int32_t var_n, int32_t var_fallback) {
+Option_int32 t0 = find(var_n);
+if (t0.tag == 1) {
+int32_t var_x = t0.value;
+return var_x;
+goto label_2;
+}
+if (t0.tag == 0) {
+return var_fallback;
+goto label_2;
+}
+label_2:;
+return var_fallback;
+}
+
+int32_t check(int32_t var_n) {
+Result_int32_str t0 = parse(var_n);
+if (t0.tag == 0) {
+int32_t var_v = t0.data.ok;
+int32_t t1 = 100;
+bool t2 = var_v > t1;
+if (t2) {
+int32_t t3 = 100;
+return t3;
+goto label_3;
+}
+}
+if (t0.tag == 0) {
+int32_t var_v = t0.data.ok;
+return var_v;
+goto label_3;
+}
+if (t0.tag == 1) {
+String var_message = t0.data.err;
+int32_t t4 = printf("%s\n", var_message.ptr);
+int32_t t5 = 1;
+int32_t t6 = -t5;
+return t6;
+goto label_3;
+}
+label_3:;
+int32_t t7 = 0;
+return t7;
+}
+
+int32_t quadrant(Tuple_int32_int32 var_point) {
+int32_t t0 = 0;
+int32_t t1 = 0;
+if (((var_point.field_0 == t0)) || ((var_point.field_1 == t1))) {
+int32_t t2 = 0;
+return t2;
+goto label_4;
+}
+if (1) {
+int32_t var_x = var_point.field_0;
+int32_t t3 = 0;
+bool t4 = var_x > t3;
+if (t4) {
+int32_t t5 = 1;
+return t5;
+goto label_4;
+}
+}
+if (1) {
+int32_t t6 = 2;
+return t6;
+goto label_4;
+}
+label_4:;
+int32_t t7 = 9;
+return t7;
+}
+
+int32_t name(String var_word) {
+String t0 = { .ptr = "one", .len = 3 };
+if ((var_word.len == t0.len && memcmp(var_word.ptr, t0.ptr, var_word.len) == 0)) {
+int32_t t1 = 1;
+return t1;
+goto label_5;
+}
+String t2 = { .ptr = "two", .len = 3 };
+if ((var_word.len == t2.len && memcmp(var_word.ptr, t2.ptr, var_word.len) == 0)) {
+int32_t t3 = 2;
+return t3;
+goto label_5;
+}
+int32_t t4 = 0;
+return t4;
+label_5:;
+int32_t t5 = 0;
+return t5;
+}
+
+int32_t vix_main() {
+Light t0 = (Light){ .tag = Light__Red };
+String t1 = describe(t0);
+int32_t t2 = printf("%s\n", t1.ptr);
+Light t3 = (Light){ .tag = Light__Green };
+String t4 = describe(t3);
+int32_t t5 = printf("%s\n", t4.ptr);
+int32_t t6 = 0;
+int32_t t7 = size(t6);
+int32_t t8 = printf("%d\n", t7);
+int32_t t9 = 5;
+int32_t t10 = size(t9);
+int32_t t11 = printf("%d\n", t10);
+int32_t t12 = 42;
+int32_t t13 = size(t12);
+int32_t t14 = printf("%d\n", t13);
+int32_t t15 = 4;
+int32_t t16 = -t15;
+int32_t t17 = size(t16);
+int32_t t18 = printf("%d\n", t17);
+int32_t t19 = 500;
+int32_t t20 = size(t19);
+int32_t t21 = printf("%d\n", t20);
+int32_t t22 = 7;
+int32_t t23 = 0;
+int32_t t24 = value_or(t22, t23);
+int32_t t25 = printf("%d\n", t24);
+int32_t t26 = 1;
+int32_t t27 = -t26;
+int32_t t28 = 3;
+int32_t t29 = value_or(t27, t28);
+int32_t t30 = printf("%d\n", t29);
+int32_t t31 = 5;
+int32_t t32 = check(t31);
+int32_t t33 = printf("%d\n", t32);
+int32_t t34 = 500;
+int32_t t35 = check(t34);
+int32_t t36 = printf("%d\n", t35);
+int32_t t37 = 5;
+int32_t t38 = -t37;
+int32_t t39 = check(t38);
+int32_t t40 = printf("%d\n", t39);
+int32_t t41 = 0;
+int32_t t42 = 4;
+Tuple_int32_int32 t43 = { .field_0 = t41,  .field_1 = t42 };
+int32_t t44 = quadrant(t43);
+int32_t t45 = printf("%d\n", t44);
+int32_t t46 = 2;
+int32_t t47 = 4;
+Tuple_int32_int32 t48 = { .field_0 = t46,  .field_1 = t47 };
+int32_t t49 = quadrant(t48);
+int32_t t50 = printf("%d\n", t49);
+int32_t t51 = 2;
+int32_t t52 = -t51;
+int32_t t53 = 4;
+Tuple_int32_int32 t54 = { .field_0 = t52,  .field_1 = t53 };
+int32_t t55 = quadrant(t54);
+int32_t t56 = printf("%d\n", t55);
+String t57 = { .ptr = "two", .len = 3 };
+int32_t t58 = name(t57);
+int32_t t59 = printf("%d\n", t58);
+String t60 = { .ptr = "six", .len = 3 };
+int32_t t61 = name(t60);
+int32_t t62 = printf("%d\n", t61);
+int32_t t63 = 0;
+return t63;
+}
+
+
+int main() {
+    vix_main();
+    return 0;
+}
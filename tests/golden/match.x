@@ -0,0 +1,113 @@
+enum Light:
+    Red,
+    Yellow,
+    Green
+end
+
+func describe(light: Light): str
+    match light:
+        case Light.Red:
+            return "stop"
+        case Light.Yellow | Light.Green:
+            return "go"
+    end
+    return "?"
+end
+
+func size(n: int32): int32
+    match n:
+        case 0:
+            return 0
+        case 1..10:
+            return 1
+        case 10..=99:
+            return 2
+        case x if x < 0:
+            return -1
+        case _:
+            return 3
+    end
+    return 9
+end
+
+func find(n: int32): Option[int32]
+    if n > 0 then
+        return Some(n)
+    end
+    return None
+end
+
+func parse(n: int32): Result[int32, str]
+    if n > 0 then
+        return Ok(n)
+    end
+    return Err("negative")
+end
+
+func value_or(n: int32, fallback: int32): int32
+    match find(n):
+        case Some(x):
+            return x
+        case None:
+            return fallback
+    end
+    return fallback
+end
+
+func check(n: int32): int32
+    match parse(n):
+        case Ok(v) if v > 100:
+            return 100
+        case Ok(v):
+            return v
+        case Err(message):
+            print(message)
+            return -1
+    end
+    return 0
+end
+
+func quadrant(point: (int32, int32)): int32
+    match point:
+        case (0, _) | (_, 0):
+            return 0
+        case (x, _) if x > 0:
+            return 1
+        case _:
+            return 2
+    end
+    return 9
+end
+
+func name(word: str): int32
+    match word:
+        case "one":
+            return 1
+        case "two":
+            return 2
+        default:
+            return 0
+    end
+    return 0
+end
+
+func main(): int32
+    print(describe(Light.Red))
+    print(describe(Light.Green))
+    print(size(0))
+    print(size(5))
+    print(size(42))
+    print(size(-4))
+    print(size(500))
+    print(value_or(7, 0))
+    print(value_or(-1, 3))
+    print(check(5))
+    print(check(500))
+    print(check(-5))
+    print(quadrant((0, 4)))
+    print(quadrant((2, 4)))
+    print(quadrant((-2, 4)))
+    print(name("two"))
+    print(name("six"))
+    return 0
+end
@@ -0,0 +1,85 @@
+enum Shape:
+    Circle(float32),
+    Rect(float32, float32),
+    Empty
+end
+
+public func area(shape: Shape): int32
+    match shape: //~ ERROR E0004
+        case Shape.Circle(_):
+            return 1
+        case Shape.Rect(_, _):
+            return 2
+    end
+    return 0
+end
+
+public func sides(shape: Shape): int32
+    match shape:
+        case Shape.Rect(_w): //~ ERROR E0023
+            return 4
+        case Shape.Square: //~ ERROR E0599
+            return 4
+        case _:
+            return 0
+    end
+    return 0
+end
+
+public func maybe(value: Option[int32]): int32
+    match value: //~ ERROR `None` is not covered
+        case Some(x) if x > 0:
+            return x
+        case Some(_):
+            return 0
+    end
+    return 0
+end
+
+public func flags(pair: (bool, bool)): int32
+    match pair: //~ ERROR `(false, false)` is not covered
+        case (true, _):
+            return 1
+        case (_, true):
+            return 2
+    end
+    return 0
+end
+
+public func count(n: int32): int32
+    match n:
+        case "one": //~ ERROR E0308
+            return 1
+        case _x | 0: //~ ERROR E0408
+            return 2
+        case y if y: //~ ERROR E0308
+            return 3
+    end
+    return 0
+end
+
+public func order(n: int32): int32
+    match n:
+        case _:
+            return 0
+        case 1: //~ WARNING W0108
+            return 1
+    end
+    return 0
+end
+
+public func light(on: bool): int32
+    match on: //~ WARNING W0108
+        case true:
+            return 1
+        case false:
+            return 0
+        default:
+            return 2
+    end
+    return 0
+end
+
+func main(): int32
+    return 0
+end
@@ -0,0 +1,3 @@
+public func twice(n: int32): int32
+    return n * 2
+end
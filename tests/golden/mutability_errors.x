@@ -0,0 +1,13 @@
+func bump(n: int32): int32
+    n = n + 1 //~ ERROR E0384
+    return n
+end
+
+func main(): int32
+    count: int32 = 0
+    count += 1 //~ ERROR E0384
+    mut total: int32 = 1
+    total = 2
+    print(bump(count + total))
+    return 0
+end
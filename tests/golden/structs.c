@@ -0,0 +1,80 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <stdbool.h>
+#include <string.h>
+#include <time.h>
+typedef struct { char* ptr; int64_t len; } String;
+
+
+static String vix_string_concat(String s1, String s2) {
+    String res;
+    res.len = s1.len + s2.len;
+    res.ptr = (char*)malloc(res.len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, s1.ptr, s1.len);
+        memcpy(res.ptr + s1.len, s2.ptr, s2.len);
+        res.ptr[res.len] = '\0';
+    }
+    return res;
+}
+
+static String vix_int_to_str(int64_t val) {
+    char buf[32];
+    int len = snprintf(buf, sizeof(buf), "%lld", (long long)val);
+    String res;
+    res.len = len;
+    res.ptr = (char*)malloc(len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, buf, len);
+        res.ptr[len] = '\0';
+    }
+    return res;
+}
+
+static String vix_string_from_const(const char* s) {
+    String res;
+    res.ptr = (char*)s;
+    res.len = s ? strlen(s) : 0;
+    return res;
+}
+
+typedef struct Point {
+    int32_t x;
+    int32_t y;
+} Point;
+
+Point Point_new(int32_t x, int32_t y);
+int32_t vix_main();
+int32_t Point_sum(Point* self);
+
+Point Point_new(int32_t x, int32_t y) {
+    Point instance;
+    instance.x = x;
+    instance.y = y;
+    return instance;
+}
+
+int32_t vix_main() {
+int32_t t0 = 3;
+int32_t t1 = 4;
+Point t2 = Point_new(t0, t1);
+const Point var_p = t2;
+int32_t t3 = var_p.x;
+int32_t t4 = printf("%d\n", t3);
+int32_t t5 = var_p.y;
+int32_t t6 = printf("%d\n", t5);
+int32_t t7 = 0;
+return t7;
+}
+
+int32_t Point_sum(Point* self) {
+int32_t t8 = self->x;
+return t8;
+}
+
+
+int main() {
+    vix_main();
+    return 0;
+}
@@ -0,0 +1,17 @@
+struct Point:
+    x = int32
+    y = int32
+end
+
+impl Point:
+    func sum(self): int32
+        return self.x
+    end
+end
+
+func main(): int32
+    p: Point = Point(x = 3, y = 4)
+    print(p.x)
+    print(p.y)
+    return 0
+end
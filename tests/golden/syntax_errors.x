@@ -0,0 +1,15 @@
+// The parser recovers after each error, so both are reported.
+func first(): int32
+    x: int32 = = 1 //~ ERROR E0104
+    return 0
+end
+
+func second(): int32
+    return 0
+end
+
+func third(a: int32 b: int32): int32 //~ ERROR E0104
+    return a
+end
+
+42 //~ ERROR E0101
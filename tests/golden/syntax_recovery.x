@@ -0,0 +1,10 @@
+// An error in one function does not hide a separate error in the next one.
+func a(x int32): int32 //~ ERROR E0104
+    return 1
+end
+
+func main(): int32
+    y: int32 = = 2 //~ ERROR E0104
+    print(y)
+    return y
+end
@@ -0,0 +1,47 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <stdbool.h>
+#include <string.h>
+#include <time.h>
+int32_t twice(int32_t var_x);
+int32_t vix_main();
+
+int32_t twice(int32_t var_x) {
+int32_t t0 = 2;
+int32_t t1 = var_x * t0;
+return t1;
+}
+
+
+static void* x_array_init() {
+    struct { void* ptr; size_t len; size_t capacity; }* arr = malloc(sizeof(*arr));
+    arr->ptr = malloc(16 * sizeof(void*));
+    arr->len = 0;
+    arr->capacity = 16;
+    return arr;
+}
+
+static void* x_hashmap_init() { return malloc(1024); }
+static void* x_vector_init() { return malloc(1024); }
+static void* x_buffer_init() { return malloc(1024); }
+
+static int x_random(int max) {
+    if (max <= 0) return 0;
+    static int seeded = 0;
+    if (!seeded) { srand((unsigned)time(NULL)); seeded = 1; }
+    return rand() % max;
+}
+int32_t vix_main() {
+int32_t t0 = 21;
+int32_t t1 = twice(t0);
+int32_t t2 = printf("%d\n", t1);
+int32_t t3 = 0;
+return t3;
+}
+
+
+int main() {
+    vix_main();
+    return 0;
+}
@@ -0,0 +1,13 @@
+func twice(x: int32): int32
+    return x * 2
+end
+
+func main(): int32
+    print(twice(21))
+    return 0
+end
+
+// Test functions are left out of normal builds.
+test func doubles()
+    assert(twice(2) == 4)
+end
@@ -0,0 +1,36 @@
+trait Shape:
+    func area(self): int32
+    func name(self): str
+end
+
+struct Square:
+    side = int32
+end
+
+struct Circle:
+    r = int32
+end
+
+impl Shape for Square: //~ ERROR E0046
+    func area(self): float32 //~ ERROR E0053
+        return 1.0
+    end
+
+    func grow(self) //~ ERROR E0407
+    end
+end
+
+impl Drawable for Circle: //~ ERROR E0405
+    func draw(self)
+    end
+end
+
+func show(shape: dyn Shape)
+    print(shape.area())
+end
+
+func main(): int32
+    c: Circle = Circle(2)
+    show(c) //~ ERROR E0308
+    return 0
+end
@@ -0,0 +1,235 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <stdbool.h>
+#include <string.h>
+#include <time.h>
+typedef struct { char* ptr; int64_t len; } String;
+
+typedef struct Shape_vtable Shape_vtable;
+typedef struct Shape_dyn {
+    void* self;
+    const Shape_vtable* vtable;
+} Shape_dyn;
+
+
+static String vix_string_concat(String s1, String s2) {
+    String res;
+    res.len = s1.len + s2.len;
+    res.ptr = (char*)malloc(res.len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, s1.ptr, s1.len);
+        memcpy(res.ptr + s1.len, s2.ptr, s2.len);
+        res.ptr[res.len] = '\0';
+    }
+    return res;
+}
+
+static String vix_int_to_str(int64_t val) {
+    char buf[32];
+    int len = snprintf(buf, sizeof(buf), "%lld", (long long)val);
+    String res;
+    res.len = len;
+    res.ptr = (char*)malloc(len + 1);
+    if (res.ptr) {
+        memcpy(res.ptr, buf, len);
+        res.ptr[len] = '\0';
+    }
+    return res;
+}
+
+static String vix_string_from_const(const char* s) {
+    String res;
+    res.ptr = (char*)s;
+    res.len = s ? strlen(s) : 0;
+    return res;
+}
+
+typedef struct Square {
+    int32_t side;
+} Square;
+
+Square Square_new(int32_t side);
+typedef struct Rect {
+    int32_t width;
+    int32_t height;
+} Rect;
+
+Rect Rect_new(int32_t width, int32_t height);
+struct Shape_vtable {
+    size_t vix_size;
+    int32_t (*area)(void* self);
+    void (*describe)(void* self, String);
+};
+
+static Shape_dyn Shape_clone(Shape_dyn value) {
+    void* boxed = malloc(value.vtable->vix_size);
+    memcpy(boxed, value.self, value.vtable->vix_size);
+    return (Shape_dyn){ boxed, value.vtable };
+}
+
+void show(Shape_dyn var_shape);
+Shape_dyn biggest();
+int32_t vix_main();
+int32_t Square_area(Square* self);
+void Square_describe(Square* self, String prefix);
+extern const Shape_vtable Square_Shape_vtable;
+Shape_dyn Square_as_Shape(Square value);
+int32_t Rect_area(Rect* self);
+void Rect_describe(Rect* self, String prefix);
+extern const Shape_vtable Rect_Shape_vtable;
+Shape_dyn Rect_as_Shape(Rect value);
+
+Square Square_new(int32_t side) {
+    Square instance;
+    instance.side = side;
+    return instance;
+}
+
+Rect Rect_new(int32_t width, int32_t height) {
+    Rect instance;
+    instance.width = width;
+    instance.height = height;
+    return instance;
+}
+
+void show(Shape_dyn var_shape) {
+String t0 = { .ptr = "shape:", .len = 6 };
+var_shape.vtable->describe(var_shape.self, t0);
+int32_t t1 = var_shape.vtable->area(var_shape.self);
+int32_t t2 = printf("%d\n", t1);
+    return;
+}
+
+Shape_dyn biggest() {
+int32_t t0 = 4;
+int32_t t1 = 5;
+Rect t2 = Rect_new(t0, t1);
+const Rect var_r = t2;
+return Rect_as_Shape(var_r);
+}
+
+
+static void* x_array_init() {
+    struct { void* ptr; size_t len; size_t capacity; }* arr = malloc(sizeof(*arr));
+    arr->ptr = malloc(16 * sizeof(void*));
+    arr->len = 0;
+    arr->capacity = 16;
+    return arr;
+}
+
+static void* x_hashmap_init() { return malloc(1024); }
+static void* x_vector_init() { return malloc(1024); }
+static void* x_buffer_init() { return malloc(1024); }
+
+static int x_random(int max) {
+    if (max <= 0) return 0;
+    static int seeded = 0;
+    if (!seeded) { srand((unsigned)time(NULL)); seeded = 1; }
+    return rand() % max;
+}
+int32_t vix_main() {
+void* t10 = NULL;
+int32_t t0 = 3;
+Square t1 = Square_new(t0);
+const Square var_s = t1;
+int32_t t2 = Square_area(&var_s);
+int32_t t3 = printf("%d\n", t2);
+Square t4 = var_s;
+show((Shape_dyn){ &t4, &Square_Shape_vtable });
+int32_t t5 = 2;
+int32_t t6 = 7;
+Rect t7 = Rect_new(t5, t6);
+Rect t8 = t7;
+show((Shape_dyn){ &t8, &Rect_Shape_vtable });
+Shape_dyn t9 = biggest();
+const Shape_dyn var_b = t9;
+free(t10);
+t10 = var_b.self;
+int32_t t11 = var_b.vtable->area(var_b.self);
+int32_t t12 = printf("%d\n", t11);
+Square t13 = var_s;
+const Shape_dyn var_d = (Shape_dyn){ &t13, &Square_Shape_vtable };
+String t14 = { .ptr = "dyn:", .len = 4 };
+var_d.vtable->describe(var_d.self, t14);
+int32_t t15 = 0;
+int32_t t16 = t15;
+free(t10);
+return t16;
+free(t10);
+}
+
+int32_t Square_area(Square* self) {
+int32_t t17 = self->side;
+int32_t t18 = self->side;
+int32_t t19 = t17 * t18;
+return t19;
+}
+
+void Square_describe(Square* self, String prefix) {
+String t20 = { .ptr = "square", .len = 6 };
+int32_t t21 = self->side;
+int32_t t22 = printf("%s %s %d\n", prefix.ptr, t20.ptr, t21);
+    return;
+}
+
+static int32_t Square_Shape_area(void* self) {
+    return Square_area((Square*)self);
+}
+
+static void Square_Shape_describe(void* self, String prefix) {
+    Square_describe((Square*)self, prefix);
+}
+
+const Shape_vtable Square_Shape_vtable = {
+    .vix_size = sizeof(Square),
+    .area = Square_Shape_area,
+    .describe = Square_Shape_describe,
+};
+
+Shape_dyn Square_as_Shape(Square value) {
+    Square* boxed = malloc(sizeof(Square));
+    *boxed = value;
+    return (Shape_dyn){ boxed, &Square_Shape_vtable };
+}
+
+int32_t Rect_area(Rect* self) {
+int32_t t23 = self->width;
+int32_t t24 = self->height;
+int32_t t25 = t23 * t24;
+return t25;
+}
+
+void Rect_describe(Rect* self, String prefix) {
+String t26 = { .ptr = "rect", .len = 4 };
+int32_t t27 = self->width;
+int32_t t28 = self->height;
+int32_t t29 = printf("%s %s %d %d\n", prefix.ptr, t26.ptr, t27, t28);
+    return;
+}
+
+static int32_t Rect_Shape_area(void* self) {
+    return Rect_area((Rect*)self);
+}
+
+static void Rect_Shape_describe(void* self, String prefix) {
+    Rect_describe((Rect*)self, prefix);
+}
+
+const Shape_vtable Rect_Shape_vtable = {
+    .vix_size = sizeof(Rect),
+    .area = Rect_Shape_area,
+    .describe = Rect_Shape_describe,
+};
+
+Shape_dyn Rect_as_Shape(Rect value) {
+    Rect* boxed = malloc(sizeof(Rect));
+    *boxed = value;
+    return (Shape_dyn){ boxed, &Rect_Shape_vtable };
+}
+
+
+int main() {
+    vix_main();
+    return 0;
+}
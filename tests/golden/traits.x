@@ -0,0 +1,55 @@
+trait Shape:
+    func area(self): int32
+    func describe(self, prefix: str)
+end
+
+struct Square:
+    side = int32
+end
+
+struct Rect:
+    width = int32
+    height = int32
+end
+
+impl Shape for Square:
+    func area(self): int32
+        return self.side * self.side
+    end
+
+    func describe(self, prefix: str)
+        print(prefix, "square", self.side)
+    end
+end
+
+impl Shape for Rect:
+    func area(self): int32
+        return self.width * self.height
+    end
+
+    func describe(self, prefix: str)
+        print(prefix, "rect", self.width, self.height)
+    end
+end
+
+func show(shape: dyn Shape)
+    shape.describe("shape:")
+    print(shape.area())
+end
+
+func biggest(): dyn Shape
+    r: Rect = Rect(4, 5)
+    return r
+end
+
+func main(): int32
+    s: Square = Square(3)
+    print(s.area())
+    show(s)
+    show(Rect(2, 7))
+    b: dyn Shape = biggest()
+    print(b.area())
+    d: dyn Shape = s
+    d.describe("dyn:")
+    return 0
+end
@@ -0,0 +1,9 @@
+func add(a: int32, b: int32): int32
+    return a
+end
+
+func main(): int32
+    total: int32 = "text" //~ ERROR E0308
+    print(add(total, 1))
+    return 0
+end
@@ -0,0 +1,10 @@
+func compute(x: int32): int32
+    return x * 2
+end
+
+func main(): int32
+    value: int32 = compte(2) //~ ERROR E0425
+    print(value)
+    print(undefined_fn(1)) //~ ERROR E0425
+    return compute(1)
+end
@@ -0,0 +1,4 @@
+// Undefined names get past the type checker and are reported by code generation.
+func main(): int32
+    return missing //~ ERROR E0425
+end
@@ -0,0 +1,202 @@
+//! Drives `vix lsp` over stdio the way an editor does: initialize, open a document, ask for a
+//! definition, hover, completions and the outline, edit it, then shut down. Each response is
+//! checked against the document below.
+
+use serde_json::{json, Value};
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+const SOURCE: &str = "\
+struct Point:
+    x = int32
+    y = int32
+end
+
+impl Point:
+    func sum(self): int32
+        return self.x
+    end
+end
+
+func add(a: int32, b: int32): int32
+    return a * b
+end
+
+func main(): int32
+    total: int32 = add(1, 2)
+    p: Point = Point(x = total, y = 4)
+    return p.sum()
+end
+";
+
+struct Client {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: i64,
+}
+
+impl Client {
+    fn start() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_Vix"))
+            .arg("lsp")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("could not start `vix lsp`");
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Self { child, stdin, stdout, next_id: 1 }
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            assert!(self.stdout.read_line(&mut header).unwrap() > 0, "the server closed its output");
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length: ") {
+                length = Some(value.parse::<usize>().unwrap());
+            }
+        }
+        let mut body = vec![0; length.expect("message without Content-Length")];
+        self.stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    /// Sends a request and returns its result, skipping the notifications that arrive first.
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        loop {
+            let message = self.receive();
+            if message["id"] == json!(id) {
+                assert!(message.get("error").is_none(), "{} failed: {}", method, message);
+                return message["result"].clone();
+            }
+        }
+    }
+
+    fn completion_labels(&mut self, document: &Value, line: u32, character: u32) -> Vec<String> {
+        let completion = self.request("textDocument/completion", json!({ "textDocument": document, "position": { "line": line, "character": character } }));
+        completion
+            .as_array()
+            .unwrap_or_else(|| panic!("no completions: {}", completion))
+            .iter()
+            .filter_map(|item| item["label"].as_str().map(str::to_string))
+            .collect()
+    }
+
+    /// The next notification with the given method.
+    fn notification(&mut self, method: &str) -> Value {
+        loop {
+            let message = self.receive();
+            if message["method"] == json!(method) {
+                return message["params"].clone();
+            }
+        }
+    }
+}
+
+#[test]
+fn lsp_session() {
+    let dir = env::temp_dir().join(format!("vix-lsp-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("main.x");
+    fs::write(&path, SOURCE).unwrap();
+    let uri = format!("file://{}", path.display());
+    let document = json!({ "uri": uri });
+
+    let mut client = Client::start();
+
+    let initialized = client.request("initialize", json!({ "processId": null, "rootUri": null, "capabilities": {} }));
+    let capabilities = &initialized["capabilities"];
+    assert_eq!(capabilities["definitionProvider"], json!(true), "{}", initialized);
+    assert_eq!(capabilities["hoverProvider"], json!(true), "{}", initialized);
+    assert!(capabilities["completionProvider"].is_object(), "{}", initialized);
+    client.notify("initialized", json!({}));
+
+    client.notify("textDocument/didOpen", json!({
+        "textDocument": { "uri": uri, "languageId": "vix", "version": 1, "text": SOURCE },
+    }));
+    let published = client.notification("textDocument/publishDiagnostics");
+    assert_eq!(published["uri"], json!(uri), "{}", published);
+    assert_eq!(published["diagnostics"], json!([]), "{}", published);
+
+    // `add` in `total: int32 = add(1, 2)` goes to its declaration.
+    let definition = client.request("textDocument/definition", json!({ "textDocument": document, "position": { "line": 16, "character": 20 } }));
+    let targets = definition.as_array().unwrap_or_else(|| panic!("no definition: {}", definition));
+    assert_eq!(targets.len(), 1, "{}", definition);
+    assert_eq!(targets[0]["uri"], json!(uri), "{}", definition);
+    assert_eq!(targets[0]["range"]["start"], json!({ "line": 11, "character": 5 }), "{}", definition);
+
+    let hover = client.request("textDocument/hover", json!({ "textDocument": document, "position": { "line": 16, "character": 20 } }));
+    let contents = hover["contents"]["value"].as_str().unwrap_or_else(|| panic!("no hover: {}", hover));
+    assert!(contents.contains("func add(a: int32, b: int32): int32"), "{}", contents);
+
+    // At the start of `return p.sum()`, the locals and both functions are in scope.
+    let labels = client.completion_labels(&document, 18, 4);
+    for expected in ["total", "p", "add", "main"] {
+        assert!(labels.contains(&expected.to_string()), "`{}` is not offered: {:?}", expected, labels);
+    }
+
+    // After `p.`, the fields and methods of `Point`.
+    let labels = client.completion_labels(&document, 18, 13);
+    for expected in ["x", "y", "sum"] {
+        assert!(labels.contains(&expected.to_string()), "`{}` is not offered after `p.`: {:?}", expected, labels);
+    }
+    assert!(!labels.contains(&"add".to_string()), "`add` is offered after `p.`: {:?}", labels);
+
+    let outline = client.request("textDocument/documentSymbol", json!({ "textDocument": document }));
+    let symbols = outline.as_array().unwrap_or_else(|| panic!("no symbols: {}", outline));
+    let names: Vec<&str> = symbols.iter().filter_map(|symbol| symbol["name"].as_str()).collect();
+    for expected in ["Point", "add", "main"] {
+        assert!(names.contains(&expected), "`{}` is not in the outline: {:?}", expected, names);
+    }
+    let point = symbols.iter().find(|symbol| symbol["name"] == json!("Point")).unwrap();
+    let fields: Vec<&str> = point["children"].as_array().into_iter().flatten().filter_map(|field| field["name"].as_str()).collect();
+    assert_eq!(fields, ["x", "y"], "{}", outline);
+
+    // An undefined name is only caught by code generation, which the server runs as `vix check` does.
+    let broken = SOURCE.replace("    return p.sum()", "    print(zz)\n    return p.sum()");
+    client.notify("textDocument/didChange", json!({
+        "textDocument": { "uri": uri, "version": 2 },
+        "contentChanges": [{ "text": broken }],
+    }));
+    let published = client.notification("textDocument/publishDiagnostics");
+    let diagnostics = published["diagnostics"].as_array().unwrap_or_else(|| panic!("no diagnostics: {}", published));
+    assert_eq!(diagnostics.len(), 1, "{}", published);
+    assert!(diagnostics[0]["message"].as_str().unwrap_or_default().contains("zz"), "{}", published);
+    assert_eq!(diagnostics[0]["range"]["start"]["line"], json!(18), "{}", published);
+
+    client.notify("textDocument/didChange", json!({
+        "textDocument": { "uri": uri, "version": 3 },
+        "contentChanges": [{ "text": SOURCE }],
+    }));
+    let published = client.notification("textDocument/publishDiagnostics");
+    assert_eq!(published["diagnostics"], json!([]), "{}", published);
+
+    assert_eq!(client.request("shutdown", Value::Null), Value::Null);
+    client.notify("exit", Value::Null);
+    let status = client.child.wait().unwrap();
+    assert!(status.success(), "`vix lsp` exited with {}", status);
+
+    let _ = fs::remove_dir_all(&dir);
+}